@@ -0,0 +1,332 @@
+//! Solving ACME `DNS-01` challenges (see [RFC 8555, section 8.4](https://www.rfc-editor.org/rfc/rfc8555#section-8.4))
+//! using any provider whose zones support record creation and deletion.
+//!
+//! [`Dns01Solver::present`] creates the `_acme-challenge` TXT record in the zone managing a domain and waits until all
+//! authoritative name servers of the zone serve it using a [`PropagationChecker`].
+//! The returned [`Dns01Challenge`] removes the record again, either explicitly using [`Dns01Challenge::cleanup`] or when dropped.
+//!
+//! The solver has to be used within a Tokio runtime with the I/O and time drivers enabled.
+//! This module is only available with the `acme` feature enabled.
+
+use std::{sync::Mutex, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::{
+    runtime::{Builder, Handle, RuntimeFlavor},
+    task::block_in_place,
+};
+
+use crate::{
+    propagation::{PropagationChecker, PropagationError},
+    CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName, Provider, Record,
+    RecordData, RetrieveZoneError, Zone,
+};
+
+/// The label prepended to a domain to form the name of its challenge record.
+pub const CHALLENGE_LABEL: &str = "_acme-challenge";
+
+const DEFAULT_TTL: u64 = 60;
+
+/// Returns the name of the challenge record for a domain, e.g. `_acme-challenge.example.com.` for `example.com`.
+///
+/// Wildcard domains use the challenge record of their base domain, e.g. `*.example.com` uses the one of `example.com`.
+pub fn challenge_name(domain: &DomainName) -> DomainName {
+    let labels = domain.labels().skip(domain.is_wildcard() as usize);
+    let name = std::iter::once(CHALLENGE_LABEL)
+        .chain(labels)
+        .collect::<Vec<&str>>()
+        .join(".");
+    DomainName::from_raw(&format!("{}.", name))
+}
+
+/// Returns the value of the challenge record for a key authorization, i.e. its base64url encoded SHA-256 digest.
+pub fn challenge_value(key_authorization: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(key_authorization.as_bytes()))
+}
+
+/// Represents an error that occured when presenting a challenge using [`Dns01Solver::present`].
+#[derive(Debug, Error)]
+pub enum AcmeError<P>
+where
+    P: Provider,
+    P::Zone: CreateRecord + DeleteRecord,
+{
+    /// Indicates that none of the provider's zones manages the domain.
+    #[error("no zone manages the domain")]
+    ZoneNotFound,
+
+    /// Indicates that the zones of the provider could not be retrieved.
+    #[error("failed to retrieve the zones: {0:?}")]
+    RetrieveZone(RetrieveZoneError<P::CustomRetrieveError>),
+
+    /// Indicates that the challenge record could not be created.
+    #[error("failed to create the challenge record: {0:?}")]
+    CreateRecord(CreateRecordError<<P::Zone as CreateRecord>::CustomCreateError>),
+
+    /// Indicates that the propagation of the challenge record could not be verified, e.g. because of a timeout.
+    ///
+    /// The challenge record has been removed again.
+    #[error(transparent)]
+    Propagation(PropagationError<<P::Zone as Zone>::CustomRetrieveError>),
+}
+
+/// Presents `DNS-01` challenges by creating TXT records using a provider.
+#[derive(Debug, Clone)]
+pub struct Dns01Solver<P> {
+    provider: P,
+    ttl: u64,
+    checker: PropagationChecker,
+}
+
+impl<P> Dns01Solver<P>
+where
+    P: Provider,
+    P::Zone: CreateRecord + DeleteRecord,
+{
+    /// Creates a solver using a TTL of 60 seconds and a [`PropagationChecker`] with its default settings.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            ttl: DEFAULT_TTL,
+            checker: PropagationChecker::new(),
+        }
+    }
+
+    /// Sets the TTL of created challenge records.
+    pub fn with_ttl(mut self, ttl: u64) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum time to wait for the challenge record to be served by all name servers.
+    pub fn with_propagation_timeout(mut self, propagation_timeout: Duration) -> Self {
+        self.checker = self.checker.with_timeout(propagation_timeout);
+        self
+    }
+
+    /// Sets the time to wait between checking the name servers.
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.checker = self.checker.with_polling_interval(polling_interval);
+        self
+    }
+
+    /// Sets the checker used to wait for the challenge record to propagate, e.g. to query fixed name servers.
+    pub fn with_propagation_checker(mut self, checker: PropagationChecker) -> Self {
+        self.checker = checker;
+        self
+    }
+
+    /// Creates the challenge record for a domain and waits until all authoritative name servers serve it.
+    ///
+    /// The zone managing the domain is the one with the longest domain the challenge record lies below.
+    /// If the record does not propagate in time, it is removed before returning [`AcmeError::Propagation`].
+    pub async fn present(
+        &self,
+        domain: &DomainName,
+        key_authorization: &str,
+    ) -> Result<Dns01Challenge<P::Zone>, AcmeError<P>> {
+        let name = challenge_name(domain);
+        let zone = self
+            .provider
+            .list_zones()
+            .await
+            .map_err(AcmeError::RetrieveZone)?
+            .into_iter()
+            .filter(|zone| name.is_subdomain_of(zone.domain()))
+            .max_by_key(|zone| zone.domain().label_count())
+            .ok_or(AcmeError::ZoneNotFound)?;
+
+        let data = RecordData::txt(challenge_value(key_authorization));
+        let record = zone
+            .create_record(&name, &data, self.ttl)
+            .await
+            .map_err(AcmeError::CreateRecord)?;
+        let challenge = Dns01Challenge {
+            zone,
+            record,
+            cleaned_up: false,
+        };
+
+        if let Err(err) = self.checker.wait(&challenge.zone, &name, &data).await {
+            // The original error is more relevant than a failed cleanup.
+            let _ = challenge.cleanup().await;
+            return Err(AcmeError::Propagation(err));
+        }
+        Ok(challenge)
+    }
+}
+
+/// Represents a presented challenge whose record is removed when the challenge is dropped.
+///
+/// As removing the record is asynchronous, dropping blocks until it has been removed if no Tokio runtime or a multi-threaded one
+/// is running; within a current-thread runtime, the record is left in place and reported by [`take_leaked_records`],
+/// as are records whose removal failed.
+/// Use [`Dns01Challenge::cleanup`] to remove the record explicitly and to handle errors.
+#[derive(Debug)]
+pub struct Dns01Challenge<Z: DeleteRecord> {
+    zone: Z,
+    record: Record,
+    cleaned_up: bool,
+}
+
+impl<Z: DeleteRecord> Dns01Challenge<Z> {
+    /// Returns the zone the challenge record was created in.
+    pub fn zone(&self) -> &Z {
+        &self.zone
+    }
+
+    /// Returns the challenge record.
+    pub fn record(&self) -> &Record {
+        &self.record
+    }
+
+    /// Removes the challenge record.
+    pub async fn cleanup(mut self) -> Result<(), DeleteRecordError<Z::CustomDeleteError>> {
+        self.cleaned_up = true;
+        self.zone.delete_record(&self.record.id).await
+    }
+}
+
+impl<Z: DeleteRecord> Drop for Dns01Challenge<Z> {
+    fn drop(&mut self) {
+        if self.cleaned_up {
+            return;
+        }
+
+        let delete = self.zone.delete_record(&self.record.id);
+        let deleted = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                block_in_place(|| handle.block_on(delete)).is_ok()
+            }
+            // Blocking would stall the only thread driving the runtime, possibly the very I/O the deletion depends on.
+            Ok(_) => false,
+            Err(_) => Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .is_ok_and(|runtime| runtime.block_on(delete).is_ok()),
+        };
+
+        if !deleted {
+            leak(LeakedRecord {
+                zone_id: self.zone.id().to_owned(),
+                zone: self.zone.domain().clone(),
+                record: self.record.clone(),
+            });
+        }
+    }
+}
+
+/// Represents a challenge record which was left in place when its [`Dns01Challenge`] was dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeakedRecord {
+    /// The ID of the zone containing the record.
+    pub zone_id: String,
+    /// The domain of the zone containing the record.
+    pub zone: DomainName,
+    pub record: Record,
+}
+
+static LEAKED_RECORDS: Mutex<Vec<LeakedRecord>> = Mutex::new(Vec::new());
+
+/// Returns and forgets the challenge records left in place by dropped [`Dns01Challenge`]s of this process.
+///
+/// Records are left in place if the challenge is dropped within a current-thread runtime or if removing them fails.
+/// They can be removed later on using [`Provider::get_zone`] and [`DeleteRecord::delete_record`].
+pub fn take_leaked_records() -> Vec<LeakedRecord> {
+    std::mem::take(&mut LEAKED_RECORDS.lock().unwrap_or_else(|err| err.into_inner()))
+}
+
+/// Records a challenge record left in place and logs it if the `tracing` feature is enabled.
+fn leak(leaked: LeakedRecord) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        zone_id = %leaked.zone_id,
+        record_id = %leaked.record.id,
+        "challenge record left in place"
+    );
+    LEAKED_RECORDS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(leaked);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::RetrieveRecordError;
+
+    struct TestZone {
+        domain: DomainName,
+        deleted: Arc<AtomicUsize>,
+    }
+
+    impl Zone for TestZone {
+        type CustomRetrieveError = ();
+
+        fn id(&self) -> &str {
+            "test"
+        }
+
+        fn domain(&self) -> &DomainName {
+            &self.domain
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<()>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_record(&self, _: &str) -> Result<Record, RetrieveRecordError<()>> {
+            Err(RetrieveRecordError::NotFound)
+        }
+    }
+
+    impl DeleteRecord for TestZone {
+        type CustomDeleteError = ();
+
+        async fn delete_record(&self, _: &str) -> Result<(), DeleteRecordError<()>> {
+            self.deleted.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn challenge(deleted: &Arc<AtomicUsize>) -> Dns01Challenge<TestZone> {
+        Dns01Challenge {
+            zone: TestZone {
+                domain: DomainName::new("example.com.").unwrap(),
+                deleted: deleted.clone(),
+            },
+            record: Record {
+                id: "1".to_owned(),
+                host: DomainName::new(CHALLENGE_LABEL).unwrap(),
+                data: RecordData::txt(challenge_value("token")),
+                ttl: DEFAULT_TTL,
+            },
+            cleaned_up: false,
+        }
+    }
+
+    #[test]
+    fn dropping_removes_or_reports_the_record() {
+        let deleted = Arc::new(AtomicUsize::new(0));
+
+        drop(challenge(&deleted));
+        assert_eq!(deleted.load(Ordering::SeqCst), 1);
+        assert!(take_leaked_records().is_empty());
+
+        let runtime = Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async { drop(challenge(&deleted)) });
+        assert_eq!(deleted.load(Ordering::SeqCst), 1);
+
+        let leaked = take_leaked_records();
+        assert_eq!(leaked.len(), 1);
+        assert_eq!(leaked[0].zone_id, "test");
+        assert_eq!(leaked[0].record.id, "1");
+    }
+}
@@ -0,0 +1,128 @@
+use std::{net::SocketAddr, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+use super::{tsig::TsigKey, AxfrError};
+use crate::{
+    wire::{self, Message, TYPE_AXFR, TYPE_SOA},
+    DomainName, RecordData,
+};
+
+/// The time to wait for connecting to the server and for every single response message.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Represents a record received from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferredRecord {
+    pub name: DomainName,
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    primary: SocketAddr,
+    tsig_key: Option<TsigKey>,
+}
+
+impl Client {
+    pub fn new(primary: SocketAddr, tsig_key: Option<TsigKey>) -> Self {
+        Self { primary, tsig_key }
+    }
+
+    /// Queries the SOA record of a zone, returning [`None`] if the server does not answer with one.
+    pub async fn retrieve_soa(&self, zone: &DomainName) -> Result<Option<RecordData>, AxfrError> {
+        let records = self.query(zone, TYPE_SOA).await?;
+        Ok(records
+            .into_iter()
+            .find(|record| record.name == *zone && record.data.get_type() == "SOA")
+            .map(|record| record.data))
+    }
+
+    /// Transfers all records of a zone, omitting the SOA record repeated at the end of the transfer.
+    pub async fn transfer(&self, zone: &DomainName) -> Result<Vec<TransferredRecord>, AxfrError> {
+        let mut records = self.query(zone, TYPE_AXFR).await?;
+        records.pop();
+        Ok(records)
+    }
+
+    /// Sends a query over TCP and collects the answers of all response messages.
+    ///
+    /// Zone transfers consist of multiple messages and end with the second occurrence of the zone's SOA record.
+    async fn query(
+        &self,
+        zone: &DomainName,
+        typ: u16,
+    ) -> Result<Vec<TransferredRecord>, AxfrError> {
+        let id = wire::random_id();
+        let mut query = wire::build_query(id, zone, typ);
+        let mut signer = self.tsig_key.as_ref().map(|key| key.sign_query(&mut query));
+
+        let mut stream = timeout(TIMEOUT, TcpStream::connect(self.primary))
+            .await
+            .map_err(|_| AxfrError::Timeout)??;
+        stream.write_u16(query.len() as u16).await?;
+        stream.write_all(&query).await?;
+
+        let mut records = Vec::new();
+        let mut soa_count = 0;
+        loop {
+            let message = timeout(TIMEOUT, read_message(&mut stream))
+                .await
+                .map_err(|_| AxfrError::Timeout)??;
+            let parsed = Message::parse(&message).ok_or(AxfrError::MalformedResponse)?;
+            if parsed.id != id {
+                return Err(AxfrError::MalformedResponse);
+            }
+
+            if parsed.rcode != 0 {
+                // Error responses are not necessarily signed, but a rejected signature is reported using TSIG.
+                if let Some(signer) = &mut signer {
+                    if let Err(err @ AxfrError::Tsig(_)) = signer.verify(&message, &parsed) {
+                        return Err(err);
+                    }
+                }
+                return Err(AxfrError::Rcode(parsed.rcode));
+            }
+            if let Some(signer) = &mut signer {
+                signer.verify(&message, &parsed)?;
+            }
+
+            for answer in &parsed.answers {
+                if answer.typ == TYPE_SOA {
+                    soa_count += 1;
+                } else if typ == TYPE_AXFR && soa_count == 0 {
+                    // The first record of a zone transfer has to be the SOA record.
+                    return Err(AxfrError::MalformedResponse);
+                }
+
+                records.push(TransferredRecord {
+                    name: answer.name.clone(),
+                    ttl: answer.ttl,
+                    data: wire::decode_record_data(&message, answer),
+                });
+            }
+
+            if typ != TYPE_AXFR || soa_count >= 2 {
+                break;
+            }
+        }
+
+        if let Some(signer) = &signer {
+            signer.finish()?;
+        }
+        Ok(records)
+    }
+}
+
+/// Reads a single message prefixed with its length.
+async fn read_message(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let length = stream.read_u16().await?;
+    let mut message = vec![0; length as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(message)
+}
@@ -0,0 +1,400 @@
+//! Read-only provider implementation retrieving zones using zone transfers (AXFR, see RFC 5936) from a primary name server.
+//!
+//! Transfers are performed over TCP and can be authenticated using TSIG (see RFC 8945), in which case the signatures of
+//! the responses are verified as well.
+//! Records are represented with [synthetic IDs](crate::rrset) as DNS does not assign IDs.
+//! Zone transfers cannot enumerate the zones of a server, so [`Provider::list_zones`] only returns the zones passed to
+//! [`AxfrProvider::with_zones`].
+//!
+//! The provider has to be used within a Tokio runtime with the I/O and time drivers enabled.
+
+use std::{io, net::SocketAddr, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{
+    rrset::{parse_record_id, record_id},
+    DomainName, Provider, Record, RetrieveRecordError, RetrieveZoneError, Zone,
+};
+
+mod client;
+mod tsig;
+
+pub use tsig::{TsigAlgorithm, TsigKey};
+
+/// The response code indicating that the name does not exist.
+const RCODE_NXDOMAIN: u8 = 3;
+
+/// The response code indicating that the server refused to answer, e.g. because transfers are not allowed.
+const RCODE_REFUSED: u8 = 5;
+
+/// The response code indicating that the server is not authoritative for the zone.
+const RCODE_NOTAUTH: u8 = 9;
+
+/// Represents an error that occured when querying the name server.
+#[derive(Debug, Error)]
+pub enum AxfrError {
+    /// Indicates that the connection to the server failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Indicates that the server did not respond in time.
+    #[error("the name server did not respond in time")]
+    Timeout,
+
+    /// Indicates that a response could not be parsed or does not belong to the query.
+    #[error("the name server sent a malformed response")]
+    MalformedResponse,
+
+    /// Indicates that the server responded with an error code.
+    #[error("the name server responded with error code {0}")]
+    Rcode(u8),
+
+    /// Indicates that the server rejected the TSIG signature of the query, e.g. because of an unknown key.
+    #[error("the name server rejected the TSIG signature with error code {0}")]
+    Tsig(u16),
+
+    /// Indicates that a response was not signed or its signature is invalid.
+    #[error("the response signature is missing or invalid")]
+    InvalidSignature,
+}
+
+#[derive(Debug, Clone)]
+pub struct AxfrProvider {
+    client: Arc<client::Client>,
+    zones: Vec<DomainName>,
+}
+
+impl AxfrProvider {
+    /// Creates a provider transferring zones from the given primary without authentication.
+    pub fn new(primary: SocketAddr) -> Self {
+        Self {
+            client: Arc::new(client::Client::new(primary, None)),
+            zones: Vec::new(),
+        }
+    }
+
+    /// Creates a provider transferring zones from the given primary, signing queries using the given TSIG key.
+    pub fn with_tsig(primary: SocketAddr, key: TsigKey) -> Self {
+        Self {
+            client: Arc::new(client::Client::new(primary, Some(key))),
+            zones: Vec::new(),
+        }
+    }
+
+    /// Sets the zones returned by [`Provider::list_zones`].
+    pub fn with_zones(mut self, zones: impl IntoIterator<Item = DomainName>) -> Self {
+        self.zones = zones.into_iter().map(|zone| zone.to_absolute()).collect();
+        self
+    }
+}
+
+impl Provider for AxfrProvider {
+    type Zone = AxfrZone;
+    type CustomRetrieveError = AxfrError;
+
+    /// Retrieves a zone by its domain, e.g. `example.com`, checking that the server answers authoritatively for it.
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domain = DomainName::new(zone_id.trim_end_matches('.'))
+            .map_err(|_| RetrieveZoneError::NotFound)?
+            .to_absolute();
+
+        self.client
+            .retrieve_soa(&domain)
+            .await
+            .map_err(|err| match err {
+                AxfrError::Rcode(RCODE_NXDOMAIN | RCODE_NOTAUTH) => RetrieveZoneError::NotFound,
+                AxfrError::Rcode(RCODE_REFUSED) | AxfrError::Tsig(_) => {
+                    RetrieveZoneError::Unauthorized
+                }
+                _ => RetrieveZoneError::Custom(err),
+            })?
+            .ok_or(RetrieveZoneError::NotFound)?;
+
+        Ok(AxfrZone::new(self.client.clone(), domain))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let mut zones = Vec::with_capacity(self.zones.len());
+        for domain in &self.zones {
+            zones.push(self.get_zone(domain.as_ascii()).await?);
+        }
+        Ok(zones)
+    }
+}
+
+/// Represents a zone retrieved using zone transfers.
+///
+/// The zone ID is the domain of the zone, e.g. `example.com`.
+/// Every call to [`Zone::list_records`] or [`Zone::get_record`] performs a full zone transfer.
+#[derive(Debug, Clone)]
+pub struct AxfrZone {
+    client: Arc<client::Client>,
+    id: String,
+    domain: DomainName,
+}
+
+impl AxfrZone {
+    fn new(client: Arc<client::Client>, domain: DomainName) -> Self {
+        Self {
+            client,
+            id: domain.as_ascii().to_owned(),
+            domain,
+        }
+    }
+}
+
+impl Zone for AxfrZone {
+    type CustomRetrieveError = AxfrError;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let records = self
+            .client
+            .transfer(&self.domain)
+            .await
+            .map_err(|err| match err {
+                AxfrError::Rcode(RCODE_NXDOMAIN | RCODE_NOTAUTH) => RetrieveRecordError::NotFound,
+                AxfrError::Rcode(RCODE_REFUSED) | AxfrError::Tsig(_) => {
+                    RetrieveRecordError::Unauthorized
+                }
+                _ => RetrieveRecordError::Custom(err),
+            })?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| Record {
+                id: record_id(&record.name, &record.data),
+                host: record
+                    .name
+                    .relative_to(&self.domain)
+                    .unwrap_or_else(|| record.name.clone()),
+                data: record.data,
+                ttl: record.ttl as u64,
+            })
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (name, _) = parse_record_id(record_id).ok_or(RetrieveRecordError::NotFound)?;
+        if name.relative_to(&self.domain).is_none() {
+            return Err(RetrieveRecordError::NotFound);
+        }
+
+        self.list_records()
+            .await?
+            .into_iter()
+            .find(|record| record.id == record_id)
+            .ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::{
+        wire::{build_response, encode_name, Message, TsigData, TYPE_AXFR, TYPE_SOA},
+        RecordData,
+    };
+
+    const TYPE_A: u16 = 1;
+    const TYPE_MX: u16 = 15;
+    const TYPE_TXT: u16 = 16;
+
+    fn name(name: &str) -> DomainName {
+        DomainName::from_raw(name)
+    }
+
+    fn soa() -> Vec<u8> {
+        let mut rdata = Vec::new();
+        encode_name(&name("ns1.example.com."), &mut rdata);
+        encode_name(&name("hostmaster.example.com."), &mut rdata);
+        for value in [2024010101u32, 7200, 3600, 1209600, 300] {
+            rdata.extend(value.to_be_bytes());
+        }
+        rdata
+    }
+
+    fn mx() -> Vec<u8> {
+        let mut rdata = 10u16.to_be_bytes().to_vec();
+        encode_name(&name("mail.example.com."), &mut rdata);
+        rdata
+    }
+
+    /// Starts a primary serving `example.com.` in two messages, signing every response if a key is given.
+    ///
+    /// Queries for other zones or signed with an unknown key are refused.
+    async fn start_primary(key: Option<TsigKey>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let length = stream.read_u16().await.unwrap();
+                let mut query = vec![0; length as usize];
+                stream.read_exact(&mut query).await.unwrap();
+
+                let parsed = Message::parse(&query).unwrap();
+                let query_mac = parsed
+                    .tsig
+                    .as_ref()
+                    .filter(|record| key.as_ref().is_some_and(|key| record.name == key.name))
+                    .map(|record| TsigData::parse(&query, record).unwrap().mac);
+                let question_end = 12 + name_length(&query[12..]);
+                let typ = u16::from_be_bytes([query[question_end], query[question_end + 1]]);
+                let zone = query[12..].starts_with(b"\x07example\x03com\x00");
+
+                let mut messages = match (zone, key.is_some() == query_mac.is_some(), typ) {
+                    (true, true, TYPE_SOA) => {
+                        vec![build_response(
+                            &query,
+                            0x0400,
+                            &[(name("example.com."), TYPE_SOA, soa())],
+                        )]
+                    }
+                    (true, true, TYPE_AXFR) => vec![
+                        build_response(
+                            &query,
+                            0x0400,
+                            &[
+                                (name("example.com."), TYPE_SOA, soa()),
+                                (name("www.example.com."), TYPE_A, vec![192, 0, 2, 1]),
+                                (name("example.com."), TYPE_MX, mx()),
+                            ],
+                        ),
+                        build_response(
+                            &query,
+                            0x0400,
+                            &[
+                                (name("example.com."), TYPE_TXT, b"\x05hello".to_vec()),
+                                (name("example.com."), TYPE_SOA, soa()),
+                            ],
+                        ),
+                    ],
+                    (true, false, _) => vec![build_response(&query, RCODE_REFUSED as u16, &[])],
+                    _ => vec![build_response(&query, RCODE_NOTAUTH as u16, &[])],
+                };
+
+                if let (Some(key), Some(mut prior_mac)) = (&key, query_mac) {
+                    for (i, message) in messages.iter_mut().enumerate() {
+                        prior_mac = key.sign_response(message, &prior_mac, &[], i == 0);
+                    }
+                }
+                for message in messages {
+                    stream.write_u16(message.len() as u16).await.unwrap();
+                    stream.write_all(&message).await.unwrap();
+                }
+            }
+        });
+
+        address
+    }
+
+    /// Returns the length of the uncompressed name at the start of the given data.
+    fn name_length(data: &[u8]) -> usize {
+        let mut length = 0;
+        while data[length] != 0 {
+            length += data[length] as usize + 1;
+        }
+        length + 1
+    }
+
+    fn key(secret: &[u8]) -> TsigKey {
+        TsigKey::new(
+            name("transfer-key"),
+            TsigAlgorithm::HmacSha256,
+            secret.to_vec(),
+        )
+    }
+
+    #[tokio::test]
+    async fn zones_are_transferred() {
+        let provider =
+            AxfrProvider::new(start_primary(None).await).with_zones([name("example.com")]);
+
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(zones.len(), 1);
+        let zone = &zones[0];
+        assert_eq!(zone.id(), "example.com");
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.host.to_string(), record.data.get_type(), record.ttl))
+                .collect::<Vec<_>>(),
+            [
+                ("@".to_owned(), "SOA", 3600),
+                ("www".to_owned(), "A", 3600),
+                ("@".to_owned(), "MX", 3600),
+                ("@".to_owned(), "TXT", 3600),
+            ]
+        );
+        assert_eq!(
+            records[2].data,
+            RecordData::MX {
+                priority: 10,
+                mail_server: name("mail.example.com."),
+            }
+        );
+
+        let record = zone.get_record(&records[1].id).await.unwrap();
+        assert_eq!(record.data, RecordData::A("192.0.2.1".parse().unwrap()));
+        assert!(matches!(
+            zone.get_record("www.example.org.|A|3139322e302e322e31")
+                .await,
+            Err(RetrieveRecordError::NotFound)
+        ));
+
+        assert!(matches!(
+            provider.get_zone("example.org").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn signed_zones_are_transferred() {
+        let address = start_primary(Some(key(b"secret"))).await;
+
+        let provider = AxfrProvider::with_tsig(address, key(b"secret"));
+        let zone = provider.get_zone("example.com.").await.unwrap();
+        assert_eq!(zone.list_records().await.unwrap().len(), 4);
+
+        // Responses signed with another secret are rejected.
+        let provider = AxfrProvider::with_tsig(address, key(b"other secret"));
+        assert!(matches!(
+            provider.get_zone("example.com").await,
+            Err(RetrieveZoneError::Custom(AxfrError::InvalidSignature))
+        ));
+
+        // Unsigned queries are refused.
+        let provider = AxfrProvider::new(address);
+        assert!(matches!(
+            provider.get_zone("example.com").await,
+            Err(RetrieveZoneError::Unauthorized)
+        ));
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{
+    digest::{core_api::BlockSizeUser, Digest},
+    Mac, SimpleHmac,
+};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::AxfrError;
+use crate::{
+    wire::{encode_name, Message, TsigData, CLASS_ANY, TYPE_TSIG},
+    DomainName,
+};
+
+/// The permitted difference between the time a message was signed and the time it was received, in seconds.
+const FUDGE: u16 = 300;
+
+/// Represents the HMAC algorithm of a TSIG key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TsigAlgorithm {
+    HmacSha1,
+    #[default]
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    /// Returns the name identifying the algorithm in TSIG records, e.g. `hmac-sha256.`.
+    pub fn name(&self) -> DomainName {
+        DomainName::from_raw(match self {
+            TsigAlgorithm::HmacSha1 => "hmac-sha1.",
+            TsigAlgorithm::HmacSha256 => "hmac-sha256.",
+            TsigAlgorithm::HmacSha384 => "hmac-sha384.",
+            TsigAlgorithm::HmacSha512 => "hmac-sha512.",
+        })
+    }
+
+    fn sign(&self, secret: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            TsigAlgorithm::HmacSha1 => hmac::<Sha1>(secret, data).finalize().into_bytes().to_vec(),
+            TsigAlgorithm::HmacSha256 => hmac::<Sha256>(secret, data)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+            TsigAlgorithm::HmacSha384 => hmac::<Sha384>(secret, data)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+            TsigAlgorithm::HmacSha512 => hmac::<Sha512>(secret, data)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+        }
+    }
+
+    /// Checks a MAC in constant time.
+    fn verify(&self, secret: &[u8], data: &[u8], mac: &[u8]) -> bool {
+        match self {
+            TsigAlgorithm::HmacSha1 => hmac::<Sha1>(secret, data).verify_slice(mac).is_ok(),
+            TsigAlgorithm::HmacSha256 => hmac::<Sha256>(secret, data).verify_slice(mac).is_ok(),
+            TsigAlgorithm::HmacSha384 => hmac::<Sha384>(secret, data).verify_slice(mac).is_ok(),
+            TsigAlgorithm::HmacSha512 => hmac::<Sha512>(secret, data).verify_slice(mac).is_ok(),
+        }
+    }
+}
+
+fn hmac<D: Digest + BlockSizeUser>(secret: &[u8], data: &[u8]) -> SimpleHmac<D> {
+    let mut mac = SimpleHmac::<D>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac
+}
+
+/// Represents a shared secret used to authenticate zone transfers using TSIG (see RFC 8945).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TsigKey {
+    /// The name of the key as configured on the name server, e.g. `transfer-key.`.
+    pub name: DomainName,
+    pub algorithm: TsigAlgorithm,
+    pub secret: Vec<u8>,
+}
+
+impl TsigKey {
+    pub fn new(name: DomainName, algorithm: TsigAlgorithm, secret: Vec<u8>) -> Self {
+        Self {
+            name: name.to_absolute(),
+            algorithm,
+            secret,
+        }
+    }
+
+    /// Creates a key from a base64 encoded secret as used in the configuration of name servers like BIND.
+    pub fn from_base64(
+        name: DomainName,
+        algorithm: TsigAlgorithm,
+        secret: &str,
+    ) -> Result<Self, base64::DecodeError> {
+        Ok(Self::new(name, algorithm, STANDARD.decode(secret.trim())?))
+    }
+
+    /// Appends a TSIG record to a query and returns the signer used to verify the responses to it.
+    pub(super) fn sign_query(&self, query: &mut Vec<u8>) -> Signer<'_> {
+        let mut tsig = TsigData {
+            algorithm: self.algorithm.name(),
+            time_signed: now(),
+            fudge: FUDGE,
+            mac: Vec::new(),
+            original_id: u16::from_be_bytes([query[0], query[1]]),
+            error: 0,
+            other: Vec::new(),
+        };
+
+        let mut data = query.clone();
+        self.encode_variables(&tsig, &mut data);
+        tsig.mac = self.algorithm.sign(&self.secret, &data);
+        self.append_record(&tsig, query);
+
+        Signer {
+            key: self,
+            prior_mac: tsig.mac,
+            unsigned: Vec::new(),
+            verified_any: false,
+        }
+    }
+
+    /// Appends a TSIG record to the additional section of a message.
+    fn append_record(&self, tsig: &TsigData, message: &mut Vec<u8>) {
+        encode_name(&self.name, message);
+        message.extend(TYPE_TSIG.to_be_bytes());
+        message.extend(CLASS_ANY.to_be_bytes());
+        message.extend(0u32.to_be_bytes());
+        let mut rdata = Vec::new();
+        tsig.encode(&mut rdata);
+        message.extend((rdata.len() as u16).to_be_bytes());
+        message.extend(rdata);
+        increment_additional_count(message, 1);
+    }
+
+    /// Signs a response the way a name server would and returns its MAC.
+    ///
+    /// The MAC covers the MAC of the previous signed message and the unsigned messages sent since. Only the first
+    /// response covers all TSIG variables, while subsequent ones only cover the timers (see RFC 8945, section 5.3.1).
+    #[cfg(test)]
+    pub(super) fn sign_response(
+        &self,
+        response: &mut Vec<u8>,
+        prior_mac: &[u8],
+        unsigned: &[u8],
+        first: bool,
+    ) -> Vec<u8> {
+        let mut tsig = TsigData {
+            algorithm: self.algorithm.name(),
+            time_signed: now(),
+            fudge: FUDGE,
+            mac: Vec::new(),
+            original_id: u16::from_be_bytes([response[0], response[1]]),
+            error: 0,
+            other: Vec::new(),
+        };
+
+        let mut data = Vec::new();
+        data.extend((prior_mac.len() as u16).to_be_bytes());
+        data.extend(prior_mac);
+        data.extend(unsigned);
+        data.extend(response.iter());
+        if first {
+            self.encode_variables(&tsig, &mut data);
+        } else {
+            data.extend(&tsig.time_signed.to_be_bytes()[2..]);
+            data.extend(tsig.fudge.to_be_bytes());
+        }
+        tsig.mac = self.algorithm.sign(&self.secret, &data);
+        self.append_record(&tsig, response);
+        tsig.mac
+    }
+
+    /// Encodes the TSIG variables covered by the MAC (see RFC 8945, section 4.3.3).
+    fn encode_variables(&self, tsig: &TsigData, buffer: &mut Vec<u8>) {
+        encode_name(&self.name, buffer);
+        buffer.extend(CLASS_ANY.to_be_bytes());
+        buffer.extend(0u32.to_be_bytes());
+        encode_name(&tsig.algorithm, buffer);
+        buffer.extend(&tsig.time_signed.to_be_bytes()[2..]);
+        buffer.extend(tsig.fudge.to_be_bytes());
+        buffer.extend(tsig.error.to_be_bytes());
+        buffer.extend((tsig.other.len() as u16).to_be_bytes());
+        buffer.extend(&tsig.other);
+    }
+}
+
+impl fmt::Debug for TsigKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TsigKey")
+            .field("name", &self.name)
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Verifies the signatures of the responses to a signed query.
+///
+/// The first response has to be signed, while subsequent messages of a zone transfer may be left unsigned
+/// as long as the following signed message covers them (see RFC 8945, section 5.3.1).
+pub(super) struct Signer<'a> {
+    key: &'a TsigKey,
+    prior_mac: Vec<u8>,
+    /// The unsigned messages received since the last signed one.
+    unsigned: Vec<u8>,
+    verified_any: bool,
+}
+
+impl Signer<'_> {
+    pub fn verify(&mut self, message: &[u8], parsed: &Message) -> Result<(), AxfrError> {
+        let Some(record) = &parsed.tsig else {
+            if !self.verified_any {
+                return Err(AxfrError::InvalidSignature);
+            }
+            self.unsigned.extend_from_slice(message);
+            return Ok(());
+        };
+
+        let tsig = TsigData::parse(message, record).ok_or(AxfrError::MalformedResponse)?;
+        if tsig.error != 0 {
+            return Err(AxfrError::Tsig(tsig.error));
+        }
+        if record.name != self.key.name || tsig.algorithm != self.key.algorithm.name() {
+            return Err(AxfrError::InvalidSignature);
+        }
+
+        // The MAC covers the message as it was before adding the TSIG record.
+        let mut stripped = message[..record.offset].to_vec();
+        stripped[..2].copy_from_slice(&tsig.original_id.to_be_bytes());
+        increment_additional_count(&mut stripped, -1);
+
+        let mut data = Vec::new();
+        data.extend((self.prior_mac.len() as u16).to_be_bytes());
+        data.extend(&self.prior_mac);
+        if self.verified_any {
+            data.extend(&self.unsigned);
+            data.extend(stripped);
+            data.extend(&tsig.time_signed.to_be_bytes()[2..]);
+            data.extend(tsig.fudge.to_be_bytes());
+        } else {
+            data.extend(stripped);
+            self.key.encode_variables(&tsig, &mut data);
+        }
+
+        if !self
+            .key
+            .algorithm
+            .verify(&self.key.secret, &data, &tsig.mac)
+            || now().abs_diff(tsig.time_signed) > tsig.fudge as u64
+        {
+            return Err(AxfrError::InvalidSignature);
+        }
+
+        self.prior_mac = tsig.mac;
+        self.unsigned.clear();
+        self.verified_any = true;
+        Ok(())
+    }
+
+    /// Ensures that the last message received was signed.
+    pub fn finish(&self) -> Result<(), AxfrError> {
+        if !self.verified_any || !self.unsigned.is_empty() {
+            return Err(AxfrError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn increment_additional_count(message: &mut [u8], delta: i32) {
+    let count = u16::from_be_bytes([message[10], message[11]]) as i32 + delta;
+    message[10..12].copy_from_slice(&(count as u16).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::{build_query, build_response};
+
+    const TYPE_A: u16 = 1;
+
+    fn key() -> TsigKey {
+        TsigKey::from_base64(
+            DomainName::from_raw("transfer-key"),
+            TsigAlgorithm::HmacSha256,
+            "c2VjcmV0LXNoYXJlZC13aXRoLXRoZS1wcmltYXJ5",
+        )
+        .unwrap()
+    }
+
+    fn verify(signer: &mut Signer<'_>, message: &[u8]) -> Result<(), AxfrError> {
+        signer.verify(message, &Message::parse(message).unwrap())
+    }
+
+    #[test]
+    fn algorithms() {
+        // Test case 2 of RFC 2202 and RFC 4231.
+        let (secret, data) = (b"Jefe", b"what do ya want for nothing?");
+        let cases = [
+            (TsigAlgorithm::HmacSha1, "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+            (
+                TsigAlgorithm::HmacSha256,
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                TsigAlgorithm::HmacSha384,
+                "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+            ),
+            (
+                TsigAlgorithm::HmacSha512,
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+        ];
+
+        for (algorithm, expected) in cases {
+            let mac = algorithm.sign(secret, data);
+            assert_eq!(crate::presentation::encode_hex(&mac), expected);
+            assert!(algorithm.verify(secret, data, &mac));
+            assert!(!algorithm.verify(b"Joe", data, &mac));
+        }
+        assert_eq!(TsigAlgorithm::default().name().to_string(), "hmac-sha256.");
+    }
+
+    #[test]
+    fn queries_are_signed() {
+        let key = key();
+        let unsigned = build_query(0x1234, &DomainName::from_raw("example.com."), TYPE_A);
+        let mut query = unsigned.clone();
+        let signer = key.sign_query(&mut query);
+
+        let parsed = Message::parse(&query).unwrap();
+        let record = parsed.tsig.unwrap();
+        assert_eq!(record.name, DomainName::from_raw("transfer-key."));
+        assert_eq!(record.offset, unsigned.len());
+        assert_eq!(query[10..12], [0, 1]);
+
+        let tsig = TsigData::parse(&query, &record).unwrap();
+        assert_eq!(tsig.algorithm, DomainName::from_raw("hmac-sha256."));
+        assert_eq!(tsig.original_id, 0x1234);
+        assert_eq!(tsig.fudge, FUDGE);
+        assert!(now().abs_diff(tsig.time_signed) <= 1);
+        assert_eq!(tsig.mac, signer.prior_mac);
+
+        // The name server verifies the MAC over the query without the TSIG record, followed by the TSIG variables.
+        let mut data = unsigned;
+        key.encode_variables(&tsig, &mut data);
+        assert!(key.algorithm.verify(&key.secret, &data, &tsig.mac));
+    }
+
+    #[test]
+    fn responses_are_verified() {
+        let key = key();
+        let mut query = build_query(0x1234, &DomainName::from_raw("example.com."), TYPE_A);
+        let mut signer = key.sign_query(&mut query);
+        let query_mac = signer.prior_mac.clone();
+
+        let mut first = build_response(&query, 0x0400, &[]);
+        let first_mac = key.sign_response(&mut first, &query_mac, &[], true);
+        verify(&mut signer, &first).unwrap();
+        signer.finish().unwrap();
+
+        // Intermediate messages of a zone transfer may be unsigned if the following signed message covers them.
+        let unsigned = build_response(&query, 0x0400, &[]);
+        verify(&mut signer, &unsigned).unwrap();
+        assert!(matches!(signer.finish(), Err(AxfrError::InvalidSignature)));
+
+        let mut last = build_response(&query, 0x0400, &[]);
+        key.sign_response(&mut last, &first_mac, &unsigned, false);
+        verify(&mut signer, &last).unwrap();
+        signer.finish().unwrap();
+    }
+
+    #[test]
+    fn invalid_signatures_are_rejected() {
+        let key = key();
+        let query = build_query(0x1234, &DomainName::from_raw("example.com."), TYPE_A);
+        let response = build_response(&query, 0x0400, &[]);
+        let rejected = |message: &[u8]| {
+            let mut signer = key.sign_query(&mut query.clone());
+            let result = verify(&mut signer, message);
+            (result, signer)
+        };
+
+        let (result, signer) = rejected(&response);
+        assert!(matches!(result, Err(AxfrError::InvalidSignature)));
+        assert!(matches!(signer.finish(), Err(AxfrError::InvalidSignature)));
+
+        // A MAC computed with another secret or covering another message does not match.
+        let other = TsigKey::new(key.name.clone(), key.algorithm, b"other secret".to_vec());
+        let mut signer = key.sign_query(&mut query.clone());
+        let mut signed = response.clone();
+        other.sign_response(&mut signed, &signer.prior_mac, &[], true);
+        assert!(matches!(
+            verify(&mut signer, &signed),
+            Err(AxfrError::InvalidSignature)
+        ));
+
+        let mut signer = key.sign_query(&mut query.clone());
+        let mut tampered = response.clone();
+        key.sign_response(&mut tampered, &signer.prior_mac, &[], true);
+        tampered[3] ^= 0x01;
+        assert!(matches!(
+            verify(&mut signer, &tampered),
+            Err(AxfrError::InvalidSignature)
+        ));
+        tampered[3] ^= 0x01;
+        verify(&mut signer, &tampered).unwrap();
+
+        // Name servers reject unknown keys with an unsigned TSIG record carrying the error code.
+        let mut refused = build_response(&query, 0x0009, &[]);
+        let tsig = TsigData {
+            algorithm: key.algorithm.name(),
+            time_signed: now(),
+            fudge: FUDGE,
+            mac: Vec::new(),
+            original_id: 0x1234,
+            error: 17,
+            other: Vec::new(),
+        };
+        key.append_record(&tsig, &mut refused);
+        assert!(matches!(rejected(&refused).0, Err(AxfrError::Tsig(17))));
+    }
+
+    #[test]
+    fn debug_omits_secret() {
+        let debug = format!("{:?}", key());
+        assert!(debug.contains("transfer-key"));
+        assert!(!debug.contains("secret"));
+    }
+}
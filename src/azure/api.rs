@@ -0,0 +1,387 @@
+use std::error::Error as StdErr;
+
+use reqwest::{Client as HttpClient, Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::auth::TokenSource;
+use crate::http::StatusError;
+#[cfg(feature = "retry")]
+use crate::layer::Transient;
+
+pub const AZURE_MANAGEMENT_URL: &str = "https://management.azure.com";
+pub const AZURE_AUTHORITY_URL: &str = "https://login.microsoftonline.com";
+
+const API_VERSION: &str = "2018-05-01";
+
+/// Represents an error returned by the Azure DNS API client.
+#[derive(Debug, Error)]
+pub enum AzureError {
+    /// Indicates that the HTTP request failed or Azure responded with an error status.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that no access token could be obtained for the client credentials.
+    #[error("could not obtain an access token: {0}")]
+    Authentication(reqwest::Error),
+
+    /// Indicates that the values of a record set could not be converted to the Azure representation,
+    /// e.g. when deleting a single value of a record set of an unsupported type.
+    #[error("the {0} record set can not be changed by this provider")]
+    UnsupportedRecordSet(String),
+}
+
+impl AzureError {
+    /// Returns the HTTP status code the Azure Resource Manager responded with.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            AzureError::Http(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the request was rejected because of missing or insufficient credentials.
+    pub fn is_unauthorized(&self) -> bool {
+        match self {
+            AzureError::Authentication(err) => {
+                err.status().is_some_and(|status| status.is_client_error())
+            }
+            _ => matches!(
+                self.status(),
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            ),
+        }
+    }
+}
+
+impl StatusError for AzureError {
+    /// Returns the status code Azure responded with, treating a rejected token request as `401 Unauthorized`.
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            AzureError::Authentication(_) if self.is_unauthorized() => {
+                Some(StatusCode::UNAUTHORIZED)
+            }
+            _ => AzureError::status(self),
+        }
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for AzureError {
+    fn is_transient(&self) -> bool {
+        match self {
+            AzureError::Http(err) | AzureError::Authentication(err) => err.is_transient(),
+            AzureError::UnsupportedRecordSet(_) => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    http_client: HttpClient,
+    zones_url: String,
+    token_source: TokenSource,
+}
+
+impl Client {
+    pub fn new(
+        management_url: &str,
+        subscription_id: &str,
+        resource_group: &str,
+        token_source: TokenSource,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let http_client = HttpClient::builder().build()?;
+        Ok(Self {
+            http_client,
+            zones_url: format!(
+                "{}/subscriptions/{}/resourceGroups/{}/providers/Microsoft.Network/dnsZones",
+                management_url.trim_end_matches('/'),
+                subscription_id,
+                resource_group
+            ),
+            token_source,
+        })
+    }
+
+    /// Prepares an authenticated request to `url`, which is either a path below the DNS zones of the resource group or a `nextLink` returned by Azure.
+    ///
+    /// An empty `url` refers to the DNS zones of the resource group themselves.
+    async fn request(&self, method: Method, url: &str) -> Result<RequestBuilder, AzureError> {
+        let url = if url.is_empty() || url.starts_with('/') {
+            format!("{}{}?api-version={}", self.zones_url, url, API_VERSION)
+        } else {
+            url.to_owned()
+        };
+
+        let token = self.token_source.token().await?;
+        Ok(self.http_client.request(method, url).bearer_auth(token))
+    }
+
+    pub async fn list_zones(
+        &self,
+        next_link: Option<&str>,
+    ) -> Result<ListResponse<Zone>, AzureError> {
+        Ok(self
+            .request(Method::GET, next_link.unwrap_or_default())
+            .await?
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn get_zone(&self, zone: &str) -> Result<Zone, AzureError> {
+        Ok(self
+            .request(Method::GET, &format!("/{}", zone))
+            .await?
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Creates a public zone, failing if it already exists instead of updating it.
+    pub async fn create_zone(&self, zone: &str) -> Result<Zone, AzureError> {
+        Ok(self
+            .request(Method::PUT, &format!("/{}", zone))
+            .await?
+            .header("If-None-Match", "*")
+            .json(&CreateZoneRequest { location: "global" })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn delete_zone(&self, zone: &str) -> Result<(), AzureError> {
+        self.request(Method::DELETE, &format!("/{}", zone))
+            .await?
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn list_record_sets(
+        &self,
+        zone: &str,
+        next_link: Option<&str>,
+    ) -> Result<ListResponse<RecordSet>, AzureError> {
+        let path = format!("/{}/recordsets", zone);
+        Ok(self
+            .request(Method::GET, next_link.unwrap_or(&path))
+            .await?
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Retrieves a record set, returning [`None`] if it does not exist.
+    pub async fn get_record_set(
+        &self,
+        zone: &str,
+        typ: &str,
+        name: &str,
+    ) -> Result<Option<RecordSet>, AzureError> {
+        let response = self
+            .request(Method::GET, &format!("/{}/{}/{}", zone, typ, name))
+            .await?
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    /// Creates or replaces a record set.
+    ///
+    /// If `etag` is given, the record set is only replaced if it was not modified in the meantime.
+    /// Otherwise it is only created if it does not exist yet.
+    pub async fn put_record_set(
+        &self,
+        zone: &str,
+        typ: &str,
+        name: &str,
+        etag: Option<&str>,
+        properties: &RecordSetProperties,
+    ) -> Result<(), AzureError> {
+        let request = self
+            .request(Method::PUT, &format!("/{}/{}/{}", zone, typ, name))
+            .await?;
+        let request = match etag {
+            Some(etag) => request.header("If-Match", etag),
+            None => request.header("If-None-Match", "*"),
+        };
+
+        request
+            .json(&RecordSetRequest { properties })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Deletes a record set if it was not modified since `etag` was retrieved.
+    pub async fn delete_record_set(
+        &self,
+        zone: &str,
+        typ: &str,
+        name: &str,
+        etag: Option<&str>,
+    ) -> Result<(), AzureError> {
+        let mut request = self
+            .request(Method::DELETE, &format!("/{}/{}/{}", zone, typ, name))
+            .await?;
+        if let Some(etag) = etag {
+            request = request.header("If-Match", etag);
+        }
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResponse<T> {
+    #[serde(default = "Vec::new")]
+    pub value: Vec<T>,
+    pub next_link: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+struct CreateZoneRequest {
+    location: &'static str,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Zone {
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+struct RecordSetRequest<'a> {
+    properties: &'a RecordSetProperties,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+pub struct RecordSet {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub etag: Option<String>,
+    pub properties: RecordSetProperties,
+}
+
+impl RecordSet {
+    /// Returns the short record type, e.g. `A` for `Microsoft.Network/dnszones/A`.
+    pub fn short_type(&self) -> &str {
+        self.typ.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct RecordSetProperties {
+    #[serde(rename = "TTL", skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+    #[serde(rename = "ARecords", skip_serializing_if = "Option::is_none")]
+    pub a_records: Option<Vec<ARecord>>,
+    #[serde(rename = "AAAARecords", skip_serializing_if = "Option::is_none")]
+    pub aaaa_records: Option<Vec<AaaaRecord>>,
+    #[serde(rename = "CAARecords", skip_serializing_if = "Option::is_none")]
+    pub caa_records: Option<Vec<CaaRecord>>,
+    #[serde(rename = "CNAMERecord", skip_serializing_if = "Option::is_none")]
+    pub cname_record: Option<CnameRecord>,
+    #[serde(rename = "MXRecords", skip_serializing_if = "Option::is_none")]
+    pub mx_records: Option<Vec<MxRecord>>,
+    #[serde(rename = "NSRecords", skip_serializing_if = "Option::is_none")]
+    pub ns_records: Option<Vec<NsRecord>>,
+    #[serde(rename = "PTRRecords", skip_serializing_if = "Option::is_none")]
+    pub ptr_records: Option<Vec<PtrRecord>>,
+    #[serde(rename = "SOARecord", skip_serializing_if = "Option::is_none")]
+    pub soa_record: Option<SoaRecord>,
+    #[serde(rename = "SRVRecords", skip_serializing_if = "Option::is_none")]
+    pub srv_records: Option<Vec<SrvRecord>>,
+    #[serde(rename = "TXTRecords", skip_serializing_if = "Option::is_none")]
+    pub txt_records: Option<Vec<TxtRecord>>,
+    #[serde(rename = "targetResource", skip_serializing_if = "Option::is_none")]
+    pub target_resource: Option<TargetResource>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ARecord {
+    pub ipv4_address: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AaaaRecord {
+    pub ipv6_address: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CaaRecord {
+    pub flags: u8,
+    pub tag: String,
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CnameRecord {
+    pub cname: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct MxRecord {
+    pub preference: u16,
+    pub exchange: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct NsRecord {
+    pub nsdname: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct PtrRecord {
+    pub ptrdname: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoaRecord {
+    pub host: String,
+    pub email: String,
+    pub serial_number: u32,
+    pub refresh_time: u32,
+    pub retry_time: u32,
+    pub expire_time: u32,
+    #[serde(rename = "minimumTTL")]
+    pub minimum_ttl: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct TxtRecord {
+    pub value: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct TargetResource {
+    pub id: Option<String>,
+}
@@ -0,0 +1,66 @@
+//! OAuth2 authentication of Microsoft Entra ID applications using the client credentials flow.
+//!
+//! See <https://learn.microsoft.com/en-us/entra/identity-platform/v2-oauth2-client-creds-grant-flow>.
+
+use std::error::Error as StdErr;
+
+use reqwest::Client as HttpClient;
+
+use super::{AzureError, ClientCredentials};
+use crate::oauth::{TokenCache, TokenResponse};
+
+/// Requests access tokens for the Azure Resource Manager and caches them until shortly before they expire.
+#[derive(Debug)]
+pub struct TokenSource {
+    http_client: HttpClient,
+    credentials: ClientCredentials,
+    token_url: String,
+    scope: String,
+    cache: TokenCache,
+}
+
+impl TokenSource {
+    /// Creates a token source requesting tokens from `<authority>/<tenant>/oauth2/v2.0/token` which are valid for `resource`.
+    pub fn new(
+        credentials: ClientCredentials,
+        authority: &str,
+        resource: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let token_url = format!(
+            "{}/{}/oauth2/v2.0/token",
+            authority.trim_end_matches('/'),
+            credentials.tenant_id
+        );
+
+        Ok(Self {
+            http_client: HttpClient::builder().build()?,
+            credentials,
+            token_url,
+            scope: format!("{}/.default", resource.trim_end_matches('/')),
+            cache: TokenCache::default(),
+        })
+    }
+
+    /// Returns a valid access token, requesting a new one if the cached one is missing or about to expire.
+    pub async fn token(&self) -> Result<String, AzureError> {
+        self.cache.get(|| self.request_token()).await
+    }
+
+    async fn request_token(&self) -> Result<TokenResponse, AzureError> {
+        self.http_client
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", &self.credentials.client_id),
+                ("client_secret", &self.credentials.client_secret),
+                ("scope", &self.scope),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(AzureError::Authentication)?
+            .json()
+            .await
+            .map_err(AzureError::Authentication)
+    }
+}
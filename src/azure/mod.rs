@@ -0,0 +1,752 @@
+//! Provider implementation for [Azure DNS](https://azure.microsoft.com/products/dns).
+//!
+//! The provider manages the public DNS zones of a single resource group using the Azure Resource Manager API.
+//! Requests are authenticated as a Microsoft Entra ID application using its client credentials (see [`ClientCredentials`]).
+//!
+//! Azure DNS groups all values of the same name and type into record sets,
+//! so every value is represented as a [`Record`] with a [synthetic ID](crate::rrset).
+//! Record sets are updated using their ETag, so concurrent modifications result in an error instead of being overwritten.
+//! Alias record sets pointing to Azure resources are skipped when listing records.
+
+use std::{env, error::Error as StdErr, fmt, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    presentation,
+    rrset::{self, parse_record_id, record_id},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+mod api;
+mod auth;
+
+pub use api::AzureError;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 9] =
+    &["A", "AAAA", "CAA", "CNAME", "MX", "NS", "PTR", "SRV", "TXT"];
+
+const PROVIDER_NAME: &str = "azure";
+
+/// Represents the client credentials of a Microsoft Entra ID application (service principal).
+///
+/// The client secret is omitted from the [`Debug`](fmt::Debug) output.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ClientCredentials {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl ClientCredentials {
+    pub fn new(tenant_id: &str, client_id: &str, client_secret: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_owned(),
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+        }
+    }
+
+    /// Reads the credentials from the `AZURE_TENANT_ID`, `AZURE_CLIENT_ID` and `AZURE_CLIENT_SECRET` environment variables.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            tenant_id: env::var("AZURE_TENANT_ID").ok()?,
+            client_id: env::var("AZURE_CLIENT_ID").ok()?,
+            client_secret: env::var("AZURE_CLIENT_SECRET").ok()?,
+        })
+    }
+}
+
+impl fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("tenant_id", &self.tenant_id)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl AzureProvider {
+    /// Creates a provider managing the DNS zones of a resource group in the given subscription.
+    pub fn new(
+        credentials: ClientCredentials,
+        subscription_id: &str,
+        resource_group: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_endpoints(
+            credentials,
+            subscription_id,
+            resource_group,
+            api::AZURE_AUTHORITY_URL,
+            api::AZURE_MANAGEMENT_URL,
+        )
+    }
+
+    /// Creates a provider using a custom authority and management endpoint, e.g. for sovereign clouds or a mock server.
+    ///
+    /// `authority_url` replaces `https://login.microsoftonline.com` and `management_url` replaces `https://management.azure.com`.
+    pub fn with_endpoints(
+        credentials: ClientCredentials,
+        subscription_id: &str,
+        resource_group: &str,
+        authority_url: &str,
+        management_url: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let token_source = auth::TokenSource::new(credentials, authority_url, management_url)?;
+        let api_client = api::Client::new(
+            management_url,
+            subscription_id,
+            resource_group,
+            token_source,
+        )?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for AzureProvider {
+    type Zone = AzureZone;
+    type CustomRetrieveError = AzureError;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self
+            .api_client
+            .get_zone(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(AzureZone::new(self.api_client.clone(), zone))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = collect_pages(PROVIDER_NAME, |next_link: Option<String>| async move {
+            self.api_client
+                .list_zones(next_link.as_deref())
+                .await
+                .map(|response| Page {
+                    items: response.value,
+                    next: response.next_link,
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(zones
+            .into_iter()
+            .map(|zone| AzureZone::new(self.api_client.clone(), zone))
+            .collect())
+    }
+}
+
+impl CreateZone for AzureProvider {
+    type CustomCreateError = AzureError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let zone = self
+            .api_client
+            .create_zone(domain.as_ascii())
+            .await
+            .map_err(CreateZoneError::from_status)?;
+
+        Ok(AzureZone::new(self.api_client.clone(), zone))
+    }
+}
+
+impl DeleteZone for AzureProvider {
+    type CustomDeleteError = AzureError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_zone(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+/// Represents an Azure DNS zone.
+///
+/// The zone ID is the name of the zone resource, which equals its domain, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct AzureZone {
+    api_client: Arc<api::Client>,
+    repr: api::Zone,
+    domain: DomainName,
+}
+
+impl AzureZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Zone) -> Self {
+        let domain = DomainName::from_raw(&repr.name).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+
+    /// Retrieves the record set containing the record with the given fully qualified name and type.
+    async fn find_record_set(
+        &self,
+        name: &DomainName,
+        typ: &str,
+    ) -> Result<Option<api::RecordSet>, AzureError> {
+        let Some(host) = name.relative_to(&self.domain) else {
+            return Ok(None);
+        };
+
+        self.api_client
+            .get_record_set(&self.repr.name, typ, &host.to_string())
+            .await
+    }
+
+    fn to_records(&self, set: api::RecordSet) -> Vec<Record> {
+        let host = DomainName::from_raw(&set.name);
+        let name = self.fqdn(&host);
+
+        rrset::to_records(
+            &name,
+            &host,
+            set.properties.to_record_data(set.short_type()),
+            set.properties.ttl.unwrap_or_default(),
+        )
+    }
+}
+
+impl Zone for AzureZone {
+    type CustomRetrieveError = AzureError;
+
+    fn id(&self) -> &str {
+        &self.repr.name
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let sets = collect_pages(PROVIDER_NAME, |next_link: Option<String>| async move {
+            self.api_client
+                .list_record_sets(&self.repr.name, next_link.as_deref())
+                .await
+                .map(|response| Page {
+                    items: response.value,
+                    next: response.next_link,
+                })
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(sets
+            .into_iter()
+            .filter(|set| !set.properties.is_alias())
+            .flat_map(|set| self.to_records(set))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(RetrieveRecordError::NotFound)?;
+
+        let set = self
+            .find_record_set(&name, data.get_type())
+            .await
+            .map_err(RetrieveRecordError::from_status)?
+            .ok_or(RetrieveRecordError::NotFound)?;
+
+        rrset::find_record(self.to_records(set), record_id).ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for AzureZone {
+    type CustomCreateError = AzureError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let name = self.fqdn(&host);
+
+        let existing = self
+            .find_record_set(&name, typ)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        let values = rrset::add_value(
+            existing
+                .as_ref()
+                .map(|set| set.properties.to_record_data(typ))
+                .unwrap_or_default(),
+            data,
+            data.clone(),
+            RecordData::clone,
+        );
+
+        let properties = api::RecordSetProperties::from_record_data(ttl, &values, &self.domain)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        self.api_client
+            .put_record_set(
+                &self.repr.name,
+                typ,
+                &host.to_string(),
+                existing.as_ref().and_then(|set| set.etag.as_deref()),
+                &properties,
+            )
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(Record {
+            id: record_id(&name, data),
+            host,
+            data: data.clone(),
+            ttl,
+        })
+    }
+}
+
+impl DeleteRecord for AzureZone {
+    type CustomDeleteError = AzureError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(DeleteRecordError::NotFound)?;
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(DeleteRecordError::Custom(AzureError::UnsupportedRecordSet(
+                typ.to_owned(),
+            )));
+        }
+
+        let existing = self
+            .find_record_set(&name, typ)
+            .await
+            .map_err(DeleteRecordError::from_status)?
+            .ok_or(DeleteRecordError::NotFound)?;
+        let host = DomainName::from_raw(&existing.name).to_string();
+
+        let remaining = rrset::remove_value(
+            existing.properties.to_record_data(typ),
+            &data,
+            RecordData::clone,
+        )
+        .ok_or(DeleteRecordError::NotFound)?;
+
+        if remaining.is_empty() {
+            return self
+                .api_client
+                .delete_record_set(&self.repr.name, typ, &host, existing.etag.as_deref())
+                .await
+                .map_err(DeleteRecordError::from_status);
+        }
+
+        let properties = api::RecordSetProperties::from_record_data(
+            existing.properties.ttl.unwrap_or_default(),
+            &remaining,
+            &self.domain,
+        )
+        .ok_or_else(|| {
+            DeleteRecordError::Custom(AzureError::UnsupportedRecordSet(typ.to_owned()))
+        })?;
+
+        self.api_client
+            .put_record_set(
+                &self.repr.name,
+                typ,
+                &host,
+                existing.etag.as_deref(),
+                &properties,
+            )
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::RecordSetProperties {
+    /// Returns whether the record set is an alias pointing to an Azure resource.
+    pub fn is_alias(&self) -> bool {
+        self.target_resource
+            .as_ref()
+            .is_some_and(|target| target.id.is_some())
+    }
+
+    /// Converts the typed values of a record set into [`RecordData`] values.
+    pub fn to_record_data(&self, typ: &str) -> Vec<RecordData> {
+        // Azure returns target names without a trailing dot.
+        let target = |name: &str| DomainName::from_raw(name).to_absolute();
+
+        match typ {
+            "A" => self
+                .a_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::from_raw(typ, &record.ipv4_address))
+                .collect(),
+            "AAAA" => self
+                .aaaa_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::from_raw(typ, &record.ipv6_address))
+                .collect(),
+            "CAA" => self
+                .caa_records
+                .iter()
+                .flatten()
+                .map(|record| {
+                    RecordData::from_raw(
+                        typ,
+                        &format!(
+                            "{} {} {}",
+                            record.flags,
+                            record.tag,
+                            presentation::quote(&record.value)
+                        ),
+                    )
+                })
+                .collect(),
+            "CNAME" => self
+                .cname_record
+                .iter()
+                .map(|record| RecordData::CNAME(target(&record.cname)))
+                .collect(),
+            "MX" => self
+                .mx_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::MX {
+                    priority: record.preference,
+                    mail_server: target(&record.exchange),
+                })
+                .collect(),
+            "NS" => self
+                .ns_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::NS(target(&record.nsdname)))
+                .collect(),
+            "PTR" => self
+                .ptr_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::PTR(target(&record.ptrdname)))
+                .collect(),
+            "SOA" => self
+                .soa_record
+                .iter()
+                .map(|record| {
+                    RecordData::from_raw(
+                        typ,
+                        &format!(
+                            "{} {} {} {} {} {} {}",
+                            target(&record.host),
+                            target(&record.email),
+                            record.serial_number,
+                            record.refresh_time,
+                            record.retry_time,
+                            record.expire_time,
+                            record.minimum_ttl
+                        ),
+                    )
+                })
+                .collect(),
+            "SRV" => self
+                .srv_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::SRV {
+                    priority: record.priority,
+                    weight: record.weight,
+                    port: record.port,
+                    target: target(&record.target),
+                })
+                .collect(),
+            "TXT" => self
+                .txt_records
+                .iter()
+                .flatten()
+                .map(|record| RecordData::TXT(record.value.clone()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Builds the properties of a record set containing the given values, which all have to be of the same type.
+    ///
+    /// Returns [`None`] if a value can not be represented by Azure DNS.
+    pub fn from_record_data(
+        ttl: u64,
+        values: &[RecordData],
+        zone_domain: &DomainName,
+    ) -> Option<Self> {
+        let target = |name: &DomainName| name.to_fqdn(zone_domain).as_ascii().to_owned();
+
+        let mut properties = Self {
+            ttl: Some(ttl),
+            ..Default::default()
+        };
+
+        for value in values {
+            match value {
+                RecordData::A(address) => {
+                    properties
+                        .a_records
+                        .get_or_insert_with(Vec::new)
+                        .push(api::ARecord {
+                            ipv4_address: address.to_string(),
+                        })
+                }
+                RecordData::AAAA(address) => properties
+                    .aaaa_records
+                    .get_or_insert_with(Vec::new)
+                    .push(api::AaaaRecord {
+                        ipv6_address: address.to_string(),
+                    }),
+                RecordData::Other { typ, value } if typ == "CAA" => {
+                    let tokens = presentation::tokenize(value)?;
+                    let [flags, tag, value] = tokens.as_slice() else {
+                        return None;
+                    };
+                    properties
+                        .caa_records
+                        .get_or_insert_with(Vec::new)
+                        .push(api::CaaRecord {
+                            flags: flags.parse().ok()?,
+                            tag: tag.clone(),
+                            value: value.clone(),
+                        });
+                }
+                RecordData::CNAME(name) => {
+                    // A CNAME record set can only hold a single value.
+                    if properties.cname_record.is_some() {
+                        return None;
+                    }
+                    properties.cname_record = Some(api::CnameRecord {
+                        cname: target(name),
+                    });
+                }
+                RecordData::MX {
+                    priority,
+                    mail_server,
+                } => properties
+                    .mx_records
+                    .get_or_insert_with(Vec::new)
+                    .push(api::MxRecord {
+                        preference: *priority,
+                        exchange: target(mail_server),
+                    }),
+                RecordData::NS(name) => {
+                    properties
+                        .ns_records
+                        .get_or_insert_with(Vec::new)
+                        .push(api::NsRecord {
+                            nsdname: target(name),
+                        })
+                }
+                RecordData::PTR(name) => {
+                    properties
+                        .ptr_records
+                        .get_or_insert_with(Vec::new)
+                        .push(api::PtrRecord {
+                            ptrdname: target(name),
+                        })
+                }
+                RecordData::SRV {
+                    priority,
+                    weight,
+                    port,
+                    target: name,
+                } => properties
+                    .srv_records
+                    .get_or_insert_with(Vec::new)
+                    .push(api::SrvRecord {
+                        priority: *priority,
+                        weight: *weight,
+                        port: *port,
+                        target: target(name),
+                    }),
+                RecordData::TXT(strings) => properties
+                    .txt_records
+                    .get_or_insert_with(Vec::new)
+                    .push(api::TxtRecord {
+                        value: strings.clone(),
+                    }),
+                _ => return None,
+            }
+        }
+
+        Some(properties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, OnceLock};
+
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    const ZONES_PATH: &str =
+        "/subscriptions/subscription/resourceGroups/group/providers/Microsoft.Network/dnsZones";
+
+    #[test]
+    fn debug_omits_secrets() {
+        let credentials = ClientCredentials::new("tenant", "client", "client-secret");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("tenant") && debug.contains("client"));
+        assert!(!debug.contains("client-secret"));
+    }
+
+    #[tokio::test]
+    async fn record_sets_are_paged_and_updated_using_etags() {
+        let url = Arc::new(OnceLock::<String>::new());
+        let server_url = url.clone();
+        let server = MockServer::start(move |request| {
+            let Some(path) = request.path.strip_prefix(ZONES_PATH) else {
+                return match (request.method.as_str(), request.path.as_str()) {
+                    ("POST", "/tenant/oauth2/v2.0/token") => Response::json(
+                        200,
+                        r#"{"access_token":"access-token","expires_in":3600,"token_type":"Bearer"}"#,
+                    ),
+                    ("GET", "/record-sets-2") => Response::json(
+                        200,
+                        r#"{"value":[{"name":"@","type":"Microsoft.Network/dnszones/CAA","etag":"2","properties":{"TTL":3600,"CAARecords":[{"flags":0,"tag":"issue","value":"letsencrypt.org"}]}}]}"#,
+                    ),
+                    _ => Response::json(404, r#"{"error":{"code":"NotFound"}}"#),
+                };
+            };
+            if request.header("authorization") != Some("Bearer access-token") {
+                return Response::json(401, r#"{"error":{"code":"AuthenticationFailed"}}"#);
+            }
+
+            match (request.method.as_str(), path) {
+                ("GET", "?api-version=2018-05-01") => {
+                    Response::json(200, r#"{"value":[{"name":"example.com"}]}"#)
+                }
+                ("GET", "/example.com/recordsets?api-version=2018-05-01") => Response::json(
+                    200,
+                    &format!(
+                        r#"{{"value":[{{"name":"www","type":"Microsoft.Network/dnszones/A","etag":"1","properties":{{"TTL":300,"ARecords":[{{"ipv4Address":"192.0.2.1"}},{{"ipv4Address":"192.0.2.2"}}]}}}},{{"name":"cdn","type":"Microsoft.Network/dnszones/A","etag":"3","properties":{{"TTL":300,"targetResource":{{"id":"/subscriptions/subscription/resourceGroups/group/providers/Microsoft.Network/publicIPAddresses/ip"}}}}}}],"nextLink":"{}/record-sets-2"}}"#,
+                        server_url.get().unwrap()
+                    ),
+                ),
+                ("GET", "/example.com/A/www?api-version=2018-05-01") => Response::json(
+                    200,
+                    r#"{"name":"www","type":"Microsoft.Network/dnszones/A","etag":"1","properties":{"TTL":300,"ARecords":[{"ipv4Address":"192.0.2.1"},{"ipv4Address":"192.0.2.2"}]}}"#,
+                ),
+                ("PUT", "/example.com/A/www?api-version=2018-05-01")
+                | ("PUT", "/example.com/TXT/txt?api-version=2018-05-01") => {
+                    Response::json(200, r#"{}"#)
+                }
+                _ => Response::json(404, r#"{"error":{"code":"NotFound"}}"#),
+            }
+        })
+        .await;
+        url.set(server.url().to_owned()).unwrap();
+
+        let credentials = ClientCredentials::new("tenant", "client", "client-secret");
+        let provider = AzureProvider::with_endpoints(
+            credentials,
+            "subscription",
+            "group",
+            server.url(),
+            server.url(),
+        )
+        .unwrap();
+
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(zones.len(), 1);
+        let zone = &zones[0];
+        assert_eq!(zone.domain(), &DomainName::from_raw("example.com."));
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.host.to_string(), &record.data))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "www".to_owned(),
+                    &RecordData::A("192.0.2.1".parse().unwrap())
+                ),
+                (
+                    "www".to_owned(),
+                    &RecordData::A("192.0.2.2".parse().unwrap())
+                ),
+                (
+                    "@".to_owned(),
+                    &RecordData::from_raw("CAA", r#"0 issue "letsencrypt.org""#)
+                ),
+            ]
+        );
+
+        let data = RecordData::TXT(vec!["hello".to_owned()]);
+        zone.create_record(&DomainName::from_raw("txt"), &data, 600)
+            .await
+            .unwrap();
+        zone.delete_record(&records[0].id).await.unwrap();
+
+        let requests = server.requests();
+        let token_requests = requests
+            .iter()
+            .filter(|request| request.path.ends_with("/token"))
+            .collect::<Vec<_>>();
+        assert_eq!(token_requests.len(), 1);
+        assert!(token_requests[0]
+            .body
+            .contains("client_secret=client-secret"));
+        assert!(token_requests[0].body.contains(&format!(
+            "scope={}%2F.default",
+            server.url().replace(':', "%3A").replace('/', "%2F")
+        )));
+
+        let puts = requests
+            .iter()
+            .filter(|request| request.method == "PUT")
+            .collect::<Vec<_>>();
+        assert_eq!(puts.len(), 2);
+        // New record sets must not exist yet, existing ones must not have been modified since they were read.
+        assert_eq!(puts[0].header("if-none-match"), Some("*"));
+        assert_eq!(
+            puts[0].body,
+            r#"{"properties":{"TTL":600,"TXTRecords":[{"value":["hello"]}]}}"#
+        );
+        assert_eq!(puts[1].header("if-match"), Some("1"));
+        assert_eq!(
+            puts[1].body,
+            r#"{"properties":{"TTL":300,"ARecords":[{"ipv4Address":"192.0.2.2"}]}}"#
+        );
+    }
+}
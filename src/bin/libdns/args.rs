@@ -0,0 +1,187 @@
+use std::collections::{HashMap, VecDeque};
+
+use thiserror::Error;
+
+/// Options taking a value, given either as `--name value` or `--name=value`.
+const VALUE_OPTIONS: &[&str] = &[
+    "config",
+    "provider",
+    "token",
+    "token-env",
+    "token-file",
+    "base-url",
+    "option",
+    "output",
+    "host",
+    "type",
+    "ttl",
+    "format",
+    "file",
+];
+
+/// Options not taking a value.
+const FLAGS: &[&str] = &["help", "prune"];
+
+/// Represents an invalid command line, which is reported together with a hint to `--help`.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct UsageError(pub String);
+
+/// Represents the parsed command line, with options separated from positional arguments.
+#[derive(Debug, Default)]
+pub struct Args {
+    positionals: VecDeque<String>,
+    options: HashMap<String, Vec<String>>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Parses the arguments, which may mix options and positional arguments. Everything after `--` is positional.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, UsageError> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positionals.extend(args.by_ref());
+                break;
+            }
+            let Some(option) = arg.strip_prefix("--") else {
+                parsed.positionals.push_back(arg);
+                continue;
+            };
+
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (option, None),
+            };
+            if FLAGS.contains(&name) && inline_value.is_none() {
+                parsed.flags.push(name.to_owned());
+            } else if VALUE_OPTIONS.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => args.next().ok_or_else(|| {
+                        UsageError(format!("the option --{} requires a value", name))
+                    })?,
+                };
+                parsed
+                    .options
+                    .entry(name.to_owned())
+                    .or_default()
+                    .push(value);
+            } else {
+                return Err(UsageError(format!("unknown option --{}", name)));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the next positional argument, failing with a message naming the missing argument.
+    pub fn next(&mut self, name: &str) -> Result<String, UsageError> {
+        self.positionals
+            .pop_front()
+            .ok_or_else(|| UsageError(format!("missing argument <{}>", name)))
+    }
+
+    /// Returns all remaining positional arguments.
+    pub fn rest(&mut self) -> Vec<String> {
+        self.positionals.drain(..).collect()
+    }
+
+    /// Fails if there are unused positional arguments.
+    pub fn finish(&self) -> Result<(), UsageError> {
+        match self.positionals.front() {
+            Some(arg) => Err(UsageError(format!("unexpected argument {}", arg))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the last value of an option.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    /// Returns all values of a repeatable option.
+    pub fn options(&self, name: &str) -> &[String] {
+        self.options.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, UsageError> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn options_and_positionals() {
+        let mut args = parse(&[
+            "--provider",
+            "hetzner",
+            "records",
+            "--option=a=1",
+            "create",
+            "--option",
+            "b=2",
+            "--prune",
+            "--ttl=60",
+            "example.com",
+            "--ttl",
+            "120",
+            "--",
+            "--host",
+            "value",
+        ])
+        .unwrap();
+
+        assert_eq!(args.option("provider"), Some("hetzner"));
+        assert_eq!(args.option("ttl"), Some("120"));
+        assert_eq!(args.options("ttl"), ["60", "120"]);
+        assert_eq!(args.options("option"), ["a=1", "b=2"]);
+        assert_eq!(args.option("host"), None);
+        assert!(args.options("host").is_empty());
+        assert!(args.flag("prune"));
+        assert!(!args.flag("help"));
+
+        assert_eq!(args.next("COMMAND").unwrap(), "records");
+        assert_eq!(args.next("SUBCOMMAND").unwrap(), "create");
+        assert_eq!(args.next("ZONE").unwrap(), "example.com");
+        assert_eq!(
+            args.finish().unwrap_err().to_string(),
+            "unexpected argument --host"
+        );
+        assert_eq!(args.rest(), ["--host", "value"]);
+        assert!(args.finish().is_ok());
+        assert_eq!(
+            args.next("VALUE").unwrap_err().to_string(),
+            "missing argument <VALUE>"
+        );
+    }
+
+    #[test]
+    fn invalid_options() {
+        for (args, message) in [
+            (&["--ttl"][..], "the option --ttl requires a value"),
+            (&["zones", "--verbose"], "unknown option --verbose"),
+            (&["--prune=yes"], "unknown option --prune"),
+            (&["--help=1"], "unknown option --help"),
+        ] {
+            assert_eq!(parse(args).unwrap_err().to_string(), message);
+        }
+
+        // Values starting with dashes are taken as they are.
+        let args = parse(&["--token", "--secret", "--host="]).unwrap();
+        assert_eq!(args.option("token"), Some("--secret"));
+        assert_eq!(args.option("host"), Some(""));
+    }
+}
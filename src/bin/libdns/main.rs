@@ -0,0 +1,451 @@
+//! Command-line interface for managing zones and records of any provider enabled at compile time.
+//!
+//! Run `libdns --help` for usage. This binary is only built with the `cli` feature enabled.
+
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use libdns::{
+    dynamic::{DynProvider, DynZone},
+    registry::{ProviderConfig, Registry, RegistryError},
+    CreateRecord, CreateZone, DeleteRecord, DeleteZone, DomainName, Provider, Record, RecordData,
+    Zone,
+};
+
+use args::{Args, UsageError};
+use output::Output;
+use transfer::Format;
+
+mod args;
+mod output;
+mod transfer;
+
+const DEFAULT_TTL: u64 = 3600;
+
+const USAGE: &str = "\
+Usage: libdns [OPTIONS] <COMMAND>
+
+Commands:
+  providers                                    List the available providers
+  zones list                                   List all zones
+  zones get <ZONE>                             Show a zone
+  zones create <DOMAIN>                        Create a zone
+  zones delete <ZONE>                          Delete a zone
+  records list <ZONE> [--host H] [--type T]    List the records of a zone
+  records get <ZONE> <RECORD_ID>               Show a record
+  records create <ZONE> <HOST> <TYPE> <VALUE>  Create a record
+  records delete <ZONE> <RECORD_ID>            Delete a record
+  records upsert <ZONE> <HOST> <TYPE> <VALUE>...
+                                               Replace all records of a host and type by the given values
+  export <ZONE> [--format F] [--file PATH]     Write the records of a zone to a file or stdout
+  import <ZONE> [--format F] [--file PATH] [--prune]
+                                               Create the records read from a file or stdin, optionally deleting
+                                               all other records; the SOA record and apex NS records are skipped
+
+Zones are given by their ID or domain. Hosts are relative to the zone (`@` for the apex) or fully qualified.
+
+The options of the providers are described in the documentation of the `libdns::registry` module.
+
+Options:
+  --config <PATH>        Read the provider configuration from a JSON file with the fields provider,
+                         token, token_env, token_file, base_url and options
+  --provider <NAME>      The provider to use
+  --token <TOKEN>        The token or secret used to authenticate
+  --token-env <VAR>      Read the token from an environment variable
+  --token-file <PATH>    Read the token from a file
+  --base-url <URL>       Use a custom API base URL, e.g. a mock server
+  --option <KEY=VALUE>   Set a provider-specific option, may be repeated
+  --output <MODE>        Print results as `table` (default) or `json`
+  --ttl <SECONDS>        The TTL of created records, 3600 by default
+  --format <FORMAT>      The file format of export and import, `zone` (default) or `json`
+  --help                 Print this help
+";
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => return usage_error(&err),
+    };
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("error: failed to start the runtime: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match runtime.block_on(run(args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => match err.downcast_ref::<UsageError>() {
+            Some(err) => usage_error(err),
+            None => {
+                eprintln!("error: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+fn usage_error(err: &UsageError) -> ExitCode {
+    eprintln!("error: {}\n\nRun `libdns --help` for usage.", err);
+    ExitCode::from(2)
+}
+
+async fn run(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let output = Output::parse(args.option("output"))?;
+    let command = args.next("COMMAND")?;
+    if command == "providers" {
+        args.finish()?;
+        for name in Registry::new().names() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+
+    let config = provider_config(&args)?;
+    let provider = Registry::new().create(&config).map_err(|err| match err {
+        RegistryError::UnknownProvider(_) => Box::new(UsageError(err.to_string())),
+        _ => Box::<dyn Error>::from(err),
+    })?;
+
+    match command.as_str() {
+        "zones" => zones(&provider, args, output).await,
+        "records" => records(&provider, args, output).await,
+        "export" => export(&provider, args).await,
+        "import" => import(&provider, args, output).await,
+        _ => Err(UsageError(format!("unknown command {}", command)).into()),
+    }
+}
+
+async fn zones(
+    provider: &DynProvider,
+    mut args: Args,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let subcommand = args.next("SUBCOMMAND")?;
+    match subcommand.as_str() {
+        "list" => {
+            args.finish()?;
+            output::print_zones(output, &provider.list_zones().await?);
+        }
+        "get" => {
+            let zone = find_zone(provider, &args.next("ZONE")?).await?;
+            args.finish()?;
+            output::print_zone(output, &zone);
+        }
+        "create" => {
+            let domain = parse_name(&args.next("DOMAIN")?)?;
+            args.finish()?;
+            output::print_zone(output, &provider.create_zone(&domain).await?);
+        }
+        "delete" => {
+            let zone = find_zone(provider, &args.next("ZONE")?).await?;
+            args.finish()?;
+            provider.delete_zone(zone.id()).await?;
+        }
+        _ => return Err(UsageError(format!("unknown subcommand zones {}", subcommand)).into()),
+    }
+    Ok(())
+}
+
+async fn records(
+    provider: &DynProvider,
+    mut args: Args,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let subcommand = args.next("SUBCOMMAND")?;
+    let zone = find_zone(provider, &args.next("ZONE")?).await?;
+    let ttl = match args.option("ttl") {
+        Some(ttl) => ttl
+            .parse()
+            .map_err(|_| UsageError(format!("invalid TTL {}", ttl)))?,
+        None => DEFAULT_TTL,
+    };
+
+    match subcommand.as_str() {
+        "list" => {
+            args.finish()?;
+            let host = match args.option("host") {
+                Some(host) => Some(parse_host(&zone, host)?),
+                None => None,
+            };
+            let typ = args.option("type").map(str::to_ascii_uppercase);
+
+            let records = zone
+                .list_records()
+                .await?
+                .into_iter()
+                .filter(|record| host.as_ref().is_none_or(|host| record.host == *host))
+                .filter(|record| typ.as_ref().is_none_or(|typ| record.data.get_type() == typ))
+                .collect::<Vec<_>>();
+            output::print_records(output, &records);
+        }
+        "get" => {
+            let record_id = args.next("RECORD_ID")?;
+            args.finish()?;
+            output::print_record(output, &zone.get_record(&record_id).await?);
+        }
+        "create" => {
+            let host = parse_host(&zone, &args.next("HOST")?)?;
+            let typ = args.next("TYPE")?.to_ascii_uppercase();
+            let value = args.next("VALUE")?;
+            args.finish()?;
+
+            let data = RecordData::from_raw(&typ, &value);
+            output::print_record(output, &zone.create_record(&host, &data, ttl).await?);
+        }
+        "delete" => {
+            let record_id = args.next("RECORD_ID")?;
+            args.finish()?;
+            zone.delete_record(&record_id).await?;
+        }
+        "upsert" => {
+            let host = parse_host(&zone, &args.next("HOST")?)?;
+            let typ = args.next("TYPE")?.to_ascii_uppercase();
+            let values = args.rest();
+            if values.is_empty() {
+                return Err(UsageError("missing argument <VALUE>".to_owned()).into());
+            }
+
+            let existing = zone
+                .list_records()
+                .await?
+                .into_iter()
+                .filter(|record| record.host == host && record.data.get_type() == typ)
+                .collect::<Vec<_>>();
+            let desired = values
+                .iter()
+                .map(|value| Record {
+                    id: String::new(),
+                    host: host.clone(),
+                    data: RecordData::from_raw(&typ, value),
+                    ttl,
+                })
+                .collect::<Vec<_>>();
+            let changes = transfer::apply(&zone, &existing, &desired, true).await?;
+            print_changes(output, &changes);
+        }
+        _ => return Err(UsageError(format!("unknown subcommand records {}", subcommand)).into()),
+    }
+    Ok(())
+}
+
+async fn export(provider: &DynProvider, mut args: Args) -> Result<(), Box<dyn Error>> {
+    let zone = find_zone(provider, &args.next("ZONE")?).await?;
+    args.finish()?;
+    let format = Format::parse(args.option("format"))?;
+
+    let records = zone.list_records().await?;
+    let content = transfer::format_records(format, &records, zone.domain());
+    match args.option("file") {
+        Some(path) => fs::write(path, content)?,
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+async fn import(
+    provider: &DynProvider,
+    mut args: Args,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let zone = find_zone(provider, &args.next("ZONE")?).await?;
+    args.finish()?;
+    let format = Format::parse(args.option("format"))?;
+
+    let content = match args.option("file") {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    let records = transfer::parse_records(format, &content, zone.domain())?;
+    let desired = transfer::relative_hosts(&zone, records)?
+        .into_iter()
+        .filter(|record| !transfer::is_managed(record))
+        .collect::<Vec<_>>();
+    let existing = zone
+        .list_records()
+        .await?
+        .into_iter()
+        .filter(|record| !transfer::is_managed(record))
+        .collect::<Vec<_>>();
+
+    let changes = transfer::apply(&zone, &existing, &desired, args.flag("prune")).await?;
+    print_changes(output, &changes);
+    Ok(())
+}
+
+/// Reads the provider configuration from the file given by `--config`, overridden by the other provider options.
+fn provider_config(args: &Args) -> Result<ProviderConfig, Box<dyn Error>> {
+    let mut config = match args.option("config") {
+        Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        None => ProviderConfig::default(),
+    };
+
+    if let Some(provider) = args.option("provider") {
+        config.provider = provider.to_owned();
+    }
+    if config.provider.is_empty() {
+        return Err(UsageError("missing option --provider".to_owned()).into());
+    }
+
+    let token = (
+        args.option("token"),
+        args.option("token-env"),
+        args.option("token-file"),
+    );
+    if token != (None, None, None) {
+        config.token = token.0.map(str::to_owned);
+        config.token_env = token.1.map(str::to_owned);
+        config.token_file = token.2.map(Into::into);
+    }
+    if let Some(base_url) = args.option("base-url") {
+        config.base_url = Some(base_url.to_owned());
+    }
+    for option in args.options("option") {
+        let (key, value) = option.split_once('=').ok_or_else(|| {
+            UsageError(format!(
+                "the option {} is not of the form key=value",
+                option
+            ))
+        })?;
+        config.options.insert(key.to_owned(), value.to_owned());
+    }
+
+    Ok(config)
+}
+
+/// Finds a zone by its ID or domain, falling back to [`Provider::get_zone`] for zones which are not listed.
+async fn find_zone(provider: &DynProvider, zone: &str) -> Result<DynZone, Box<dyn Error>> {
+    let domain = DomainName::from_raw(zone).to_absolute();
+    if let Ok(zones) = provider.list_zones().await {
+        if let Some(found) = zones
+            .into_iter()
+            .find(|found| found.id() == zone || found.domain().to_absolute() == domain)
+        {
+            return Ok(found);
+        }
+    }
+    Ok(provider.get_zone(zone).await?)
+}
+
+fn parse_name(name: &str) -> Result<DomainName, UsageError> {
+    DomainName::new(name)
+        .map_err(|err| UsageError(format!("invalid domain name {}: {}", name, err)))
+}
+
+fn parse_host(zone: &DynZone, host: &str) -> Result<DomainName, UsageError> {
+    zone.relative_host(&parse_name(host)?)
+        .ok_or_else(|| UsageError(format!("the host {} does not belong to the zone", host)))
+}
+
+fn print_changes(output: Output, changes: &transfer::Changes) {
+    match output {
+        Output::Table => println!(
+            "{} created, {} deleted, {} unchanged",
+            changes.created, changes.deleted, changes.unchanged
+        ),
+        Output::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(changes).expect("changes are serializable")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use super::*;
+
+    fn config(args: &[&str]) -> Result<ProviderConfig, String> {
+        let args = Args::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+        provider_config(&args).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn provider_configs() {
+        assert_eq!(
+            config(&[
+                "--provider=hetzner",
+                "--token-env",
+                "HETZNER_TOKEN",
+                "--base-url",
+                "http://127.0.0.1:8080",
+                "--option",
+                "key=a=b",
+                "--option=empty=",
+            ]),
+            Ok(ProviderConfig {
+                provider: "hetzner".to_owned(),
+                token_env: Some("HETZNER_TOKEN".to_owned()),
+                base_url: Some("http://127.0.0.1:8080".to_owned()),
+                options: [("key", "a=b"), ("empty", "")]
+                    .into_iter()
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+                ..ProviderConfig::default()
+            })
+        );
+
+        assert_eq!(config(&[]), Err("missing option --provider".to_owned()));
+        assert_eq!(
+            config(&["--provider", "hetzner", "--option", "key"]),
+            Err("the option key is not of the form key=value".to_owned())
+        );
+    }
+
+    #[test]
+    fn command_line_overrides_config_files() {
+        let path = env::temp_dir().join(format!("libdns-cli-config-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"provider": "desec", "token": "file-token", "options": {"a": "1"}}"#,
+        )
+        .unwrap();
+        let path_arg = path.to_str().unwrap();
+
+        let from_file = config(&["--config", path_arg]);
+        let overridden = config(&[
+            "--config",
+            path_arg,
+            "--provider",
+            "hetzner",
+            "--token-file",
+            "/run/secrets/token",
+            "--option",
+            "b=2",
+        ]);
+        fs::remove_file(&path).unwrap();
+
+        let from_file = from_file.unwrap();
+        assert_eq!(from_file.provider, "desec");
+        assert_eq!(from_file.token.as_deref(), Some("file-token"));
+
+        // Any token option replaces all token sources of the file, options are merged.
+        let overridden = overridden.unwrap();
+        assert_eq!(overridden.provider, "hetzner");
+        assert_eq!(overridden.token, None);
+        assert_eq!(
+            overridden.token_file,
+            Some(PathBuf::from("/run/secrets/token"))
+        );
+        assert_eq!(overridden.options.len(), 2);
+    }
+}
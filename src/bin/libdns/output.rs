@@ -0,0 +1,274 @@
+use libdns::{dynamic::DynZone, DomainName, Record, RecordData, Zone};
+use serde::{Deserialize, Serialize};
+
+/// Represents the output mode selected using `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Table,
+    Json,
+}
+
+impl Output {
+    pub fn parse(output: Option<&str>) -> Result<Self, String> {
+        match output {
+            None | Some("table") => Ok(Output::Table),
+            Some("json") => Ok(Output::Json),
+            Some(output) => Err(format!("unknown output mode {}", output)),
+        }
+    }
+}
+
+/// Represents a zone in JSON output.
+#[derive(Debug, Serialize)]
+pub struct ZoneEntry {
+    pub id: String,
+    pub domain: String,
+}
+
+impl From<&DynZone> for ZoneEntry {
+    fn from(zone: &DynZone) -> Self {
+        Self {
+            id: zone.id().to_owned(),
+            domain: zone.domain().to_string(),
+        }
+    }
+}
+
+/// Represents a record in JSON output and in JSON files read by `import`, flattening the data to its type and value.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub host: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub value: String,
+    pub ttl: u64,
+}
+
+impl From<&Record> for RecordEntry {
+    fn from(record: &Record) -> Self {
+        Self {
+            id: Some(record.id.clone()),
+            host: record.host.to_string(),
+            typ: record.data.get_type().to_owned(),
+            value: record.data.get_value(),
+            ttl: record.ttl,
+        }
+    }
+}
+
+impl RecordEntry {
+    /// Converts the entry to a record, validating the host.
+    pub fn into_record(self) -> Result<Record, String> {
+        let host = DomainName::new(&self.host)
+            .map_err(|err| format!("invalid host {}: {}", self.host, err))?;
+        Ok(Record {
+            id: self.id.unwrap_or_default(),
+            host,
+            data: RecordData::from_raw(&self.typ.to_ascii_uppercase(), &self.value),
+            ttl: self.ttl,
+        })
+    }
+}
+
+pub fn print_zones(output: Output, zones: &[DynZone]) {
+    match output {
+        Output::Table => print!(
+            "{}",
+            table(
+                &["ID", "DOMAIN"],
+                zones
+                    .iter()
+                    .map(|zone| vec![zone.id().to_owned(), zone.domain().to_string()])
+                    .collect(),
+            )
+        ),
+        Output::Json => print!(
+            "{}",
+            json(&zones.iter().map(ZoneEntry::from).collect::<Vec<_>>())
+        ),
+    }
+}
+
+pub fn print_zone(output: Output, zone: &DynZone) {
+    match output {
+        Output::Table => print_zones(output, std::slice::from_ref(zone)),
+        Output::Json => print!("{}", json(&ZoneEntry::from(zone))),
+    }
+}
+
+pub fn print_records(output: Output, records: &[Record]) {
+    print!("{}", format_records(output, records));
+}
+
+pub fn print_record(output: Output, record: &Record) {
+    match output {
+        Output::Table => print_records(output, std::slice::from_ref(record)),
+        Output::Json => print!("{}", json(&RecordEntry::from(record))),
+    }
+}
+
+/// Formats records as printed by [`print_records`].
+fn format_records(output: Output, records: &[Record]) -> String {
+    match output {
+        Output::Table => table(
+            &["ID", "HOST", "TYPE", "TTL", "VALUE"],
+            records
+                .iter()
+                .map(|record| {
+                    vec![
+                        record.id.clone(),
+                        record.host.to_string(),
+                        record.data.get_type().to_owned(),
+                        record.ttl.to_string(),
+                        record.data.get_value(),
+                    ]
+                })
+                .collect(),
+        ),
+        Output::Json => json(&records.iter().map(RecordEntry::from).collect::<Vec<_>>()),
+    }
+}
+
+/// Formats rows as columns aligned to the widest cell, leaving the last column unpadded.
+fn table(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths = header.iter().map(|cell| cell.len()).collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.iter().map(|cell| cell.to_string()).collect();
+    let mut table = String::new();
+    for row in std::iter::once(header).chain(rows) {
+        for (i, cell) in row.iter().enumerate() {
+            if i + 1 < row.len() {
+                table.push_str(&format!("{:<width$}  ", cell, width = widths[i]));
+            } else {
+                table.push_str(cell);
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> String {
+    // Serializing the entries cannot fail, as they only contain strings and numbers.
+    serde_json::to_string_pretty(value).expect("entries are serializable") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                id: "1".to_owned(),
+                host: DomainName::new("@").unwrap(),
+                data: RecordData::MX {
+                    priority: 10,
+                    mail_server: DomainName::new("mail.example.com.").unwrap(),
+                },
+                ttl: 3600,
+            },
+            Record {
+                id: "1234".to_owned(),
+                host: DomainName::new("_acme-challenge.www").unwrap(),
+                data: RecordData::txt("token"),
+                ttl: 60,
+            },
+        ]
+    }
+
+    #[test]
+    fn output_modes() {
+        assert_eq!(Output::parse(None), Ok(Output::Table));
+        assert_eq!(Output::parse(Some("table")), Ok(Output::Table));
+        assert_eq!(Output::parse(Some("json")), Ok(Output::Json));
+        assert_eq!(
+            Output::parse(Some("yaml")),
+            Err("unknown output mode yaml".to_owned())
+        );
+    }
+
+    #[test]
+    fn record_tables() {
+        assert_eq!(
+            format_records(Output::Table, &records()),
+            "\
+ID    HOST                 TYPE  TTL   VALUE
+1     @                    MX    3600  10 mail.example.com.
+1234  _acme-challenge.www  TXT   60    \"token\"
+"
+        );
+        assert_eq!(
+            format_records(Output::Table, &[]),
+            "ID  HOST  TYPE  TTL  VALUE\n"
+        );
+        assert_eq!(
+            table(
+                &["ID", "DOMAIN"],
+                vec![vec!["ä".to_owned(), "ö".to_owned()]]
+            ),
+            "ID  DOMAIN\nä   ö\n"
+        );
+    }
+
+    #[test]
+    fn record_json() {
+        assert_eq!(
+            format_records(Output::Json, &records()[1..]),
+            r#"[
+  {
+    "id": "1234",
+    "host": "_acme-challenge.www",
+    "type": "TXT",
+    "value": "\"token\"",
+    "ttl": 60
+  }
+]
+"#
+        );
+        assert_eq!(format_records(Output::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn record_entries() {
+        let entry = serde_json::from_str::<RecordEntry>(
+            r#"{"host": "www", "type": "mx", "value": "10 mail.example.com.", "ttl": 300}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entry.into_record(),
+            Ok(Record {
+                id: String::new(),
+                host: DomainName::new("www").unwrap(),
+                data: RecordData::MX {
+                    priority: 10,
+                    mail_server: DomainName::new("mail.example.com.").unwrap(),
+                },
+                ttl: 300,
+            })
+        );
+
+        for record in records() {
+            assert_eq!(RecordEntry::from(&record).into_record(), Ok(record));
+        }
+
+        let invalid = RecordEntry {
+            id: None,
+            host: "in valid".to_owned(),
+            typ: "A".to_owned(),
+            value: "192.0.2.1".to_owned(),
+            ttl: 300,
+        };
+        assert!(invalid
+            .into_record()
+            .unwrap_err()
+            .starts_with("invalid host in valid: "));
+    }
+}
@@ -0,0 +1,201 @@
+use std::error::Error;
+
+use libdns::{
+    dynamic::DynZone, zonefile, CreateRecord, DeleteRecord, DomainName, Record, RecordData, Zone,
+};
+use serde::Serialize;
+
+use crate::{args::UsageError, output::RecordEntry};
+
+/// Represents the file format used by `export` and `import`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The master file format (see RFC 1035, section 5).
+    Zone,
+    /// A JSON array of records in the same shape as the JSON output.
+    Json,
+}
+
+impl Format {
+    pub fn parse(format: Option<&str>) -> Result<Self, UsageError> {
+        match format {
+            None | Some("zone") => Ok(Format::Zone),
+            Some("json") => Ok(Format::Json),
+            Some(format) => Err(UsageError(format!("unknown format {}", format))),
+        }
+    }
+}
+
+/// Represents the changes made by [`apply`].
+#[derive(Debug, Default, Serialize)]
+pub struct Changes {
+    pub created: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+}
+
+pub fn format_records(format: Format, records: &[Record], domain: &DomainName) -> String {
+    match format {
+        Format::Zone => zonefile::format_records(records, domain),
+        Format::Json => {
+            let entries = records.iter().map(RecordEntry::from).collect::<Vec<_>>();
+            // Serializing the entries cannot fail, as they only contain strings and numbers.
+            serde_json::to_string_pretty(&entries).expect("entries are serializable") + "\n"
+        }
+    }
+}
+
+pub fn parse_records(
+    format: Format,
+    content: &str,
+    domain: &DomainName,
+) -> Result<Vec<Record>, Box<dyn Error>> {
+    match format {
+        Format::Zone => Ok(zonefile::parse_records(content, domain)?),
+        Format::Json => Ok(serde_json::from_str::<Vec<RecordEntry>>(content)?
+            .into_iter()
+            .map(RecordEntry::into_record)
+            .collect::<Result<_, _>>()?),
+    }
+}
+
+/// Returns whether a record is usually managed by the provider itself, i.e. is the SOA record or an NS record at the apex.
+pub fn is_managed(record: &Record) -> bool {
+    match record.data {
+        RecordData::NS(_) => record.host.is_apex(),
+        _ => record.data.get_type() == "SOA",
+    }
+}
+
+/// Makes the zone contain the desired records.
+///
+/// Records which already exist with the same TTL are left alone, records with a different TTL are deleted and created again.
+/// If `prune` is set, existing records which are not desired are deleted as well.
+pub async fn apply(
+    zone: &DynZone,
+    existing: &[Record],
+    desired: &[Record],
+    prune: bool,
+) -> Result<Changes, Box<dyn Error>> {
+    let mut desired_records = Vec::<&Record>::new();
+    for record in desired {
+        if !desired_records
+            .iter()
+            .any(|other| other.host == record.host && other.data == record.data)
+        {
+            desired_records.push(record);
+        }
+    }
+
+    let mut changes = Changes::default();
+    for record in desired_records.iter().copied() {
+        let same = existing
+            .iter()
+            .filter(|other| other.host == record.host && other.data == record.data)
+            .collect::<Vec<_>>();
+        if same.iter().any(|other| other.ttl == record.ttl) {
+            changes.unchanged += 1;
+            continue;
+        }
+
+        for other in same {
+            zone.delete_record(&other.id).await?;
+            changes.deleted += 1;
+        }
+        zone.create_record(&record.host, &record.data, record.ttl)
+            .await?;
+        changes.created += 1;
+    }
+
+    if prune {
+        for record in existing {
+            if !desired_records
+                .iter()
+                .any(|other| other.host == record.host && other.data == record.data)
+            {
+                zone.delete_record(&record.id).await?;
+                changes.deleted += 1;
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Makes the hosts of records relative to the zone, failing for hosts outside of it.
+pub fn relative_hosts(zone: &DynZone, records: Vec<Record>) -> Result<Vec<Record>, Box<dyn Error>> {
+    records
+        .into_iter()
+        .map(|record| {
+            let host = zone
+                .relative_host(&record.host)
+                .ok_or_else(|| format!("the host {} does not belong to the zone", record.host))?;
+            Ok(Record { host, ..record })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(Format::parse(None).unwrap(), Format::Zone);
+        assert_eq!(Format::parse(Some("zone")).unwrap(), Format::Zone);
+        assert_eq!(Format::parse(Some("json")).unwrap(), Format::Json);
+        assert_eq!(
+            Format::parse(Some("csv")).unwrap_err().to_string(),
+            "unknown format csv"
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let domain = DomainName::new("example.com.").unwrap();
+        let records = vec![
+            Record {
+                id: "1".to_owned(),
+                host: DomainName::new("www").unwrap(),
+                data: RecordData::A("192.0.2.1".parse().unwrap()),
+                ttl: 300,
+            },
+            Record {
+                id: "2".to_owned(),
+                host: DomainName::new("@").unwrap(),
+                data: RecordData::TXT(vec!["v=spf1".to_owned(), "-all".to_owned()]),
+                ttl: 3600,
+            },
+        ];
+
+        let content = format_records(Format::Json, &records, &domain);
+        assert!(content.ends_with("]\n"));
+        assert_eq!(
+            parse_records(Format::Json, &content, &domain).unwrap(),
+            records
+        );
+        assert!(parse_records(Format::Json, r#"[{"host": "www"}]"#, &domain).is_err());
+    }
+
+    #[test]
+    fn managed_records() {
+        let record = |host: &str, data: RecordData| Record {
+            id: String::new(),
+            host: DomainName::new(host).unwrap(),
+            data,
+            ttl: 3600,
+        };
+        let ns = RecordData::NS(DomainName::new("ns1.example.com.").unwrap());
+
+        assert!(is_managed(&record("@", ns.clone())));
+        assert!(!is_managed(&record("sub", ns)));
+        assert!(is_managed(&record(
+            "@",
+            RecordData::from_raw(
+                "SOA",
+                "ns1.example.com. hostmaster.example.com. 1 7200 3600 1209600 3600"
+            )
+        )));
+        assert!(!is_managed(&record("@", RecordData::txt("hello"))));
+    }
+}
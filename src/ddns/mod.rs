@@ -0,0 +1,427 @@
+//! Dynamic DNS, keeping the `A` and `AAAA` records of a host in sync with its current addresses.
+//!
+//! A [`DdnsUpdater`] determines the addresses using an [`AddressSource`]:
+//!
+//! - [`LocalAddressSource`] uses the addresses of the local interfaces used for outgoing traffic
+//! - [`HttpAddressSource`] requests the public addresses from "what's my IP" endpoints
+//! - [`from_fn`] calls a user-provided function
+//!
+//! The zone is only accessed if the addresses changed since the last update.
+//! The addresses last published can be persisted using [`DdnsUpdater::with_state_file`], so restarts do not cause updates either.
+//! Note that changes made to the records by other means are therefore not detected until the addresses change again.
+//!
+//! This module is only available with the `ddns` feature enabled.
+
+use std::{
+    io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use thiserror::Error;
+use tokio::time::sleep;
+
+use crate::{
+    CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName, Record,
+    RecordData, RetrieveRecordError,
+};
+
+mod source;
+mod state;
+
+pub use source::{
+    from_fn, AddressSource, Addresses, FnAddressSource, HttpAddressSource, HttpSourceError,
+    LocalAddressSource,
+};
+
+const DEFAULT_TTL: u64 = 300;
+
+/// Represents an error that occured when updating the records of a host.
+#[derive(Debug, Error)]
+pub enum DdnsError<S, Z>
+where
+    S: AddressSource,
+    Z: CreateRecord + DeleteRecord,
+{
+    /// Indicates that the address source failed.
+    #[error("failed to determine the current addresses: {0:?}")]
+    Source(S::Error),
+
+    /// Indicates that the address source did not return any address.
+    #[error("no address is available")]
+    NoAddress,
+
+    /// Indicates that the host does not belong to the zone.
+    #[error("the host does not belong to the zone")]
+    HostOutsideZone,
+
+    /// Indicates that the current records of the host could not be retrieved.
+    #[error("failed to retrieve the records: {0:?}")]
+    RetrieveRecord(RetrieveRecordError<Z::CustomRetrieveError>),
+
+    /// Indicates that a record could not be created.
+    #[error("failed to create a record: {0:?}")]
+    CreateRecord(CreateRecordError<Z::CustomCreateError>),
+
+    /// Indicates that an outdated record could not be deleted.
+    #[error("failed to delete an outdated record: {0:?}")]
+    DeleteRecord(DeleteRecordError<Z::CustomDeleteError>),
+
+    /// Indicates that the state file could not be read or written.
+    #[error("failed to access the state file")]
+    State(#[source] io::Error),
+}
+
+/// Represents the result of [`DdnsUpdater::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateOutcome {
+    /// Indicates that the addresses did not change, so the zone was not accessed.
+    Unchanged(Addresses),
+
+    /// Indicates that the records of the host were synchronized with the new addresses.
+    Updated(Addresses),
+}
+
+/// Keeps the `A` and `AAAA` records of a host in sync with its addresses.
+#[derive(Debug)]
+pub struct DdnsUpdater<Z, S> {
+    zone: Z,
+    host: DomainName,
+    source: S,
+    ttl: u64,
+    state_file: Option<PathBuf>,
+    /// The addresses last published, or [`None`] if unknown.
+    published: Option<Addresses>,
+}
+
+impl<Z, S> DdnsUpdater<Z, S>
+where
+    Z: CreateRecord + DeleteRecord,
+    S: AddressSource,
+{
+    /// Creates an updater for a host, which may either be relative to the zone's domain or fully qualified, using a TTL of 300 seconds.
+    pub fn new(zone: Z, host: DomainName, source: S) -> Self {
+        Self {
+            zone,
+            host,
+            source,
+            ttl: DEFAULT_TTL,
+            state_file: None,
+            published: None,
+        }
+    }
+
+    /// Sets the TTL of created records.
+    pub fn with_ttl(mut self, ttl: u64) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Persists the addresses last published in the given file, which is read on the first update.
+    ///
+    /// The file may be shared with updaters of other hosts, whose entries are preserved.
+    pub fn with_state_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.state_file = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Returns the zone of the host.
+    pub fn zone(&self) -> &Z {
+        &self.zone
+    }
+
+    /// Determines the current addresses and updates the records of the host if they changed.
+    ///
+    /// For every available address family, a record with the current address is created if it does not exist yet and all
+    /// other records of the family are deleted afterwards.
+    /// Records of unavailable address families are left untouched.
+    pub async fn update(&mut self) -> Result<UpdateOutcome, DdnsError<S, Z>> {
+        let addresses = self.source.addresses().await.map_err(DdnsError::Source)?;
+        if addresses.ipv4.is_none() && addresses.ipv6.is_none() {
+            return Err(DdnsError::NoAddress);
+        }
+
+        let host = self
+            .zone
+            .relative_host(&self.host)
+            .ok_or(DdnsError::HostOutsideZone)?;
+        let fqdn = self.zone.fqdn(&host);
+
+        if self.published.is_none() {
+            if let Some(path) = &self.state_file {
+                self.published = state::load(path, &fqdn).map_err(DdnsError::State)?;
+            }
+        }
+        if self.published == Some(addresses) {
+            return Ok(UpdateOutcome::Unchanged(addresses));
+        }
+
+        let records = self
+            .zone
+            .list_records()
+            .await
+            .map_err(DdnsError::RetrieveRecord)?
+            .into_iter()
+            .filter(|record| record.host == host)
+            .collect::<Vec<_>>();
+        if let Some(ipv4) = addresses.ipv4 {
+            self.sync(&host, &records, ipv4.into()).await?;
+        }
+        if let Some(ipv6) = addresses.ipv6 {
+            self.sync(&host, &records, ipv6.into()).await?;
+        }
+
+        if let Some(path) = &self.state_file {
+            state::save(path, &fqdn, &addresses).map_err(DdnsError::State)?;
+        }
+        self.published = Some(addresses);
+        Ok(UpdateOutcome::Updated(addresses))
+    }
+
+    /// Calls [`DdnsUpdater::update`] repeatedly, waiting for the given interval in between, until an error occurs.
+    pub async fn run(&mut self, interval: Duration) -> Result<(), DdnsError<S, Z>> {
+        loop {
+            self.update().await?;
+            sleep(interval).await;
+        }
+    }
+
+    /// Synchronizes the records of a single address family, creating the new record before deleting outdated ones.
+    async fn sync(
+        &self,
+        host: &DomainName,
+        records: &[Record],
+        address: IpAddr,
+    ) -> Result<(), DdnsError<S, Z>> {
+        let data = match address {
+            IpAddr::V4(address) => RecordData::A(address),
+            IpAddr::V6(address) => RecordData::AAAA(address),
+        };
+        let family = records
+            .iter()
+            .filter(|record| record.data.get_type() == data.get_type())
+            .collect::<Vec<_>>();
+
+        if !family.iter().any(|record| record.data == data) {
+            self.zone
+                .create_record(host, &data, self.ttl)
+                .await
+                .map_err(DdnsError::CreateRecord)?;
+        }
+        for record in family.into_iter().filter(|record| record.data != data) {
+            self.zone
+                .delete_record(&record.id)
+                .await
+                .map_err(DdnsError::DeleteRecord)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        env,
+        fmt::Debug,
+        fs,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::{memory::MemoryProvider, Provider};
+
+    fn addresses(ipv4: Option<&str>, ipv6: Option<&str>) -> Addresses {
+        Addresses {
+            ipv4: ipv4.map(|address| address.parse().unwrap()),
+            ipv6: ipv6.map(|address| address.parse().unwrap()),
+        }
+    }
+
+    /// Returns a source returning the addresses currently stored in the returned mutex.
+    fn source(
+        current: Addresses,
+    ) -> (
+        Arc<Mutex<Addresses>>,
+        impl AddressSource<Error = Infallible> + Debug,
+    ) {
+        let current = Arc::new(Mutex::new(current));
+        let shared = current.clone();
+        let source = from_fn(move || {
+            let addresses = *shared.lock().unwrap();
+            async move { Ok(addresses) }
+        });
+        (current, source)
+    }
+
+    /// Returns the values of the records of the given host and type.
+    fn values(provider: &MemoryProvider, host: &str, typ: &str) -> Vec<String> {
+        provider
+            .records("example.com")
+            .into_iter()
+            .filter(|record| record.host.as_ascii() == host && record.data.get_type() == typ)
+            .map(|record| record.data.get_value())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn records_follow_the_addresses() {
+        let provider = MemoryProvider::new()
+            .with_zone("example.com")
+            .with_record("example.com", "home", RecordData::txt("kept"))
+            .with_record(
+                "example.com",
+                "www",
+                RecordData::A("192.0.2.9".parse().unwrap()),
+            );
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let (current, source) = source(addresses(Some("192.0.2.1"), None));
+        let mut updater =
+            DdnsUpdater::new(zone, DomainName::new("home").unwrap(), source).with_ttl(60);
+
+        // The missing A record is created, no AAAA record is created without an IPv6 address.
+        assert_eq!(
+            updater.update().await.unwrap(),
+            UpdateOutcome::Updated(addresses(Some("192.0.2.1"), None))
+        );
+        assert_eq!(values(&provider, "home", "A"), ["192.0.2.1"]);
+        assert!(values(&provider, "home", "AAAA").is_empty());
+        assert_eq!(provider.records("example.com")[2].ttl, 60);
+
+        // Unchanged addresses do not access the zone.
+        assert_eq!(
+            updater.update().await.unwrap(),
+            UpdateOutcome::Unchanged(addresses(Some("192.0.2.1"), None))
+        );
+
+        // A changed IPv4 address replaces the A record, a new IPv6 address creates an AAAA record.
+        *current.lock().unwrap() = addresses(Some("192.0.2.2"), Some("2001:db8::1"));
+        assert_eq!(
+            updater.update().await.unwrap(),
+            UpdateOutcome::Updated(addresses(Some("192.0.2.2"), Some("2001:db8::1")))
+        );
+        assert_eq!(values(&provider, "home", "A"), ["192.0.2.2"]);
+        assert_eq!(values(&provider, "home", "AAAA"), ["2001:db8::1"]);
+
+        // Records of unavailable address families are left untouched.
+        *current.lock().unwrap() = addresses(None, Some("2001:db8::2"));
+        updater.update().await.unwrap();
+        assert_eq!(values(&provider, "home", "A"), ["192.0.2.2"]);
+        assert_eq!(values(&provider, "home", "AAAA"), ["2001:db8::2"]);
+
+        assert_eq!(values(&provider, "home", "TXT"), ["\"kept\""]);
+        assert_eq!(values(&provider, "www", "A"), ["192.0.2.9"]);
+        assert_eq!(
+            provider.calls(),
+            [
+                "get_zone example.com",
+                "list_records example.com",
+                "create_record example.com home A",
+                "list_records example.com",
+                "create_record example.com home A",
+                "delete_record example.com 3",
+                "create_record example.com home AAAA",
+                "list_records example.com",
+                "create_record example.com home AAAA",
+                "delete_record example.com 5",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn existing_records_are_kept_and_duplicates_deleted() {
+        let provider = MemoryProvider::new()
+            .with_zone("example.com")
+            .with_record(
+                "example.com",
+                "home",
+                RecordData::A("192.0.2.1".parse().unwrap()),
+            )
+            .with_record(
+                "example.com",
+                "home",
+                RecordData::A("192.0.2.3".parse().unwrap()),
+            )
+            .with_record(
+                "example.com",
+                "home",
+                RecordData::AAAA("2001:db8::1".parse().unwrap()),
+            );
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let (_, source) = source(addresses(Some("192.0.2.1"), Some("2001:db8::1")));
+        let mut updater =
+            DdnsUpdater::new(zone, DomainName::new("home.example.com.").unwrap(), source);
+
+        updater.update().await.unwrap();
+        assert_eq!(values(&provider, "home", "A"), ["192.0.2.1"]);
+        assert_eq!(values(&provider, "home", "AAAA"), ["2001:db8::1"]);
+        assert_eq!(
+            provider.calls(),
+            [
+                "get_zone example.com",
+                "list_records example.com",
+                "delete_record example.com 2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn published_addresses_are_persisted() {
+        let path = env::temp_dir().join(format!("libdns-ddns-updater-{}", std::process::id()));
+        let provider = MemoryProvider::new().with_zone("example.com");
+        let current = addresses(Some("192.0.2.1"), Some("2001:db8::1"));
+
+        for expected in [
+            UpdateOutcome::Updated(current),
+            UpdateOutcome::Unchanged(current),
+        ] {
+            let zone = provider.get_zone("example.com").await.unwrap();
+            let mut updater =
+                DdnsUpdater::new(zone, DomainName::new("home").unwrap(), source(current).1)
+                    .with_state_file(&path);
+            assert_eq!(updater.update().await.unwrap(), expected);
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            provider.calls(),
+            [
+                "get_zone example.com",
+                "list_records example.com",
+                "create_record example.com home A",
+                "create_record example.com home AAAA",
+                "get_zone example.com",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_updates_are_rejected() {
+        let provider = MemoryProvider::new().with_zone("example.com");
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let mut updater = DdnsUpdater::new(
+            zone,
+            DomainName::new("home").unwrap(),
+            source(addresses(None, None)).1,
+        );
+        assert!(matches!(updater.update().await, Err(DdnsError::NoAddress)));
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let mut updater = DdnsUpdater::new(
+            zone,
+            DomainName::new("home.example.org.").unwrap(),
+            source(addresses(Some("192.0.2.1"), None)).1,
+        );
+        assert!(matches!(
+            updater.update().await,
+            Err(DdnsError::HostOutsideZone)
+        ));
+
+        assert_eq!(
+            provider.calls(),
+            ["get_zone example.com", "get_zone example.com"]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt::{self, Debug},
+    future::Future,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+};
+
+use reqwest::Client as HttpClient;
+use thiserror::Error;
+
+/// The endpoints of the [ipify](https://www.ipify.org/) API used by [`HttpAddressSource::ipify`].
+const IPIFY_IPV4_URL: &str = "https://api.ipify.org";
+const IPIFY_IPV6_URL: &str = "https://api6.ipify.org";
+
+/// Addresses used to look up the routes of outgoing traffic, taken from the documentation ranges (see RFC 5737 and RFC 3849).
+const ROUTE_PROBE_IPV4: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+const ROUTE_PROBE_IPV6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+/// Represents the current addresses of a host.
+///
+/// Address families which are [`None`] are not available and their records are left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Addresses {
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>,
+}
+
+/// Represents a source of the current addresses of a host.
+pub trait AddressSource {
+    /// The error type returned when the addresses cannot be determined.
+    type Error: Debug;
+
+    /// Determines the current addresses.
+    fn addresses(&self) -> impl Future<Output = Result<Addresses, Self::Error>>;
+}
+
+/// Uses the addresses of the local interfaces the system would send traffic to the internet through.
+///
+/// The addresses are determined by looking up the routes of outgoing traffic, without sending any packets.
+/// Hosts behind NAT should use an [`HttpAddressSource`] instead, as their local IPv4 address is private.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LocalAddressSource;
+
+impl LocalAddressSource {
+    fn route_source(unspecified: IpAddr, destination: IpAddr) -> Option<IpAddr> {
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0)).ok()?;
+        socket.connect(SocketAddr::new(destination, 53)).ok()?;
+        let address = socket.local_addr().ok()?.ip();
+        (!address.is_unspecified() && !address.is_loopback()).then_some(address)
+    }
+}
+
+impl AddressSource for LocalAddressSource {
+    type Error = Infallible;
+
+    async fn addresses(&self) -> Result<Addresses, Self::Error> {
+        let ipv4 = Self::route_source(Ipv4Addr::UNSPECIFIED.into(), ROUTE_PROBE_IPV4.into());
+        let ipv6 = Self::route_source(Ipv6Addr::UNSPECIFIED.into(), ROUTE_PROBE_IPV6.into());
+
+        Ok(Addresses {
+            ipv4: match ipv4 {
+                Some(IpAddr::V4(address)) => Some(address),
+                _ => None,
+            },
+            ipv6: match ipv6 {
+                Some(IpAddr::V6(address)) => Some(address),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// Represents an error that occured when requesting addresses from an HTTP endpoint.
+#[derive(Debug, Error)]
+pub enum HttpSourceError {
+    /// Indicates that the request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that the response is not an address of the requested family.
+    #[error("the endpoint responded with an invalid address: {0}")]
+    InvalidAddress(String),
+}
+
+/// Requests the public addresses from "what's my IP" endpoints responding with the address as plain text.
+///
+/// Every address family uses its own endpoint and the requests are bound to the respective family.
+/// If the endpoint of a family cannot be connected to, e.g. because there is no IPv6 connectivity, the family is considered unavailable.
+#[derive(Debug, Clone)]
+pub struct HttpAddressSource {
+    ipv4: Option<(HttpClient, String)>,
+    ipv6: Option<(HttpClient, String)>,
+}
+
+impl HttpAddressSource {
+    /// Creates a source using the given endpoints, skipping families without an endpoint.
+    pub fn new(ipv4_url: Option<&str>, ipv6_url: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let client =
+            |local_address: IpAddr| HttpClient::builder().local_address(local_address).build();
+
+        Ok(Self {
+            ipv4: match ipv4_url {
+                Some(url) => Some((client(Ipv4Addr::UNSPECIFIED.into())?, url.to_owned())),
+                None => None,
+            },
+            ipv6: match ipv6_url {
+                Some(url) => Some((client(Ipv6Addr::UNSPECIFIED.into())?, url.to_owned())),
+                None => None,
+            },
+        })
+    }
+
+    /// Creates a source using the [ipify](https://www.ipify.org/) API for both address families.
+    pub fn ipify() -> Result<Self, Box<dyn Error>> {
+        Self::new(Some(IPIFY_IPV4_URL), Some(IPIFY_IPV6_URL))
+    }
+
+    async fn request<T: std::str::FromStr>(
+        endpoint: &Option<(HttpClient, String)>,
+    ) -> Result<Option<T>, HttpSourceError> {
+        let Some((client, url)) = endpoint else {
+            return Ok(None);
+        };
+
+        let response = match client.get(url).send().await {
+            Ok(response) => response,
+            Err(err) if err.is_connect() => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let body = response.error_for_status()?.text().await?;
+        let body = body.trim();
+        body.parse()
+            .map(Some)
+            .map_err(|_| HttpSourceError::InvalidAddress(body.to_owned()))
+    }
+}
+
+impl AddressSource for HttpAddressSource {
+    type Error = HttpSourceError;
+
+    async fn addresses(&self) -> Result<Addresses, Self::Error> {
+        Ok(Addresses {
+            ipv4: Self::request(&self.ipv4).await?,
+            ipv6: Self::request(&self.ipv6).await?,
+        })
+    }
+}
+
+/// Determines the addresses using a callback, created using [`from_fn`].
+#[derive(Clone)]
+pub struct FnAddressSource<F>(F);
+
+/// Creates an [`AddressSource`] calling the given function, e.g. to read the addresses from a router.
+pub fn from_fn<F, Fut, E>(f: F) -> FnAddressSource<F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Addresses, E>>,
+    E: Debug,
+{
+    FnAddressSource(f)
+}
+
+impl<F, Fut, E> AddressSource for FnAddressSource<F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Addresses, E>>,
+    E: Debug,
+{
+    type Error = E;
+
+    fn addresses(&self) -> impl Future<Output = Result<Addresses, Self::Error>> {
+        (self.0)()
+    }
+}
+
+impl<F> Debug for FnAddressSource<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnAddressSource").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn http_sources_parse_plain_text_addresses() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/ipv4" => Response::new(200, "192.0.2.1\n"),
+            "/ipv6" => Response::new(200, " 2001:db8::1 "),
+            "/html" => Response::new(200, "<html>192.0.2.1</html>"),
+            _ => Response::new(500, ""),
+        })
+        .await;
+        let url = |path: &str| format!("{}{}", server.url(), path);
+
+        // The requests for IPv6 addresses can not reach the server listening on an IPv4 address,
+        // so the family is considered unavailable.
+        let source = HttpAddressSource::new(Some(&url("/ipv4")), Some(&url("/ipv6"))).unwrap();
+        assert_eq!(
+            source.addresses().await.unwrap(),
+            Addresses {
+                ipv4: Some(Ipv4Addr::new(192, 0, 2, 1)),
+                ipv6: None,
+            }
+        );
+
+        // Without an endpoint the family is skipped.
+        let source = HttpAddressSource::new(Some(&url("/ipv4")), None).unwrap();
+        assert_eq!(
+            source.addresses().await.unwrap().ipv4,
+            Some(Ipv4Addr::new(192, 0, 2, 1))
+        );
+        assert_eq!(
+            HttpAddressSource::new(None, None)
+                .unwrap()
+                .addresses()
+                .await
+                .unwrap(),
+            Addresses::default()
+        );
+
+        // An IPv6 address returned for IPv4 is rejected like any other body not being an IPv4 address.
+        for (path, body) in [
+            ("/ipv6", "2001:db8::1"),
+            ("/html", "<html>192.0.2.1</html>"),
+        ] {
+            let source = HttpAddressSource::new(Some(&url(path)), None).unwrap();
+            assert!(
+                matches!(
+                    source.addresses().await,
+                    Err(HttpSourceError::InvalidAddress(invalid)) if invalid == body
+                ),
+                "{}",
+                path
+            );
+        }
+
+        let source = HttpAddressSource::new(Some(&url("/error")), None).unwrap();
+        assert!(matches!(
+            source.addresses().await,
+            Err(HttpSourceError::Http(err)) if err.status() == Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        ));
+        assert_eq!(
+            server
+                .requests()
+                .iter()
+                .map(|request| request.path.as_str())
+                .collect::<Vec<_>>(),
+            ["/ipv4", "/ipv4", "/ipv6", "/html", "/error"]
+        );
+    }
+
+    #[tokio::test]
+    async fn fn_sources_return_the_callback_result() {
+        let source = from_fn(|| async { Err::<Addresses, _>("unavailable") });
+        assert_eq!(source.addresses().await, Err("unavailable"));
+        assert_eq!(format!("{:?}", source), "FnAddressSource { .. }");
+    }
+}
@@ -0,0 +1,100 @@
+use std::{fs, io, path::Path};
+
+use super::Addresses;
+use crate::{fs::write_atomic, DomainName};
+
+/// Loads the addresses last published for a host.
+///
+/// The state file contains one line of the form `<fqdn> <type> <address>` per address, e.g. `home.example.com. A 192.0.2.1`.
+/// A missing file or a file without lines for the host result in [`None`], as do malformed lines.
+pub fn load(path: &Path, host: &DomainName) -> io::Result<Option<Addresses>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let mut addresses = None::<Addresses>;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(typ), Some(address), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if DomainName::from_raw(name) != *host {
+            continue;
+        }
+
+        let entry = addresses.get_or_insert_with(Addresses::default);
+        match typ {
+            "A" => entry.ipv4 = address.parse().ok(),
+            "AAAA" => entry.ipv6 = address.parse().ok(),
+            _ => return Ok(None),
+        }
+    }
+    Ok(addresses)
+}
+
+/// Stores the addresses published for a host, replacing the file atomically.
+///
+/// The lines of other hosts are preserved, so multiple updaters can share a state file as long as they do not save concurrently.
+pub fn save(path: &Path, host: &DomainName, addresses: &Addresses) -> io::Result<()> {
+    let mut content = match fs::read_to_string(path) {
+        Ok(content) => content
+            .lines()
+            .filter(|line| {
+                line.split_whitespace()
+                    .next()
+                    .is_none_or(|name| DomainName::from_raw(name) != *host)
+            })
+            .map(|line| format!("{}\n", line))
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+
+    if let Some(ipv4) = addresses.ipv4 {
+        content.push_str(&format!("{} A {}\n", host, ipv4));
+    }
+    if let Some(ipv6) = addresses.ipv6 {
+        content.push_str(&format!("{} AAAA {}\n", host, ipv6));
+    }
+
+    write_atomic(path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn save_preserves_other_hosts() {
+        let path = env::temp_dir().join(format!("libdns-ddns-state-{}", std::process::id()));
+        let home = DomainName::new("home.example.com.").unwrap();
+        let office = DomainName::new("office.example.com.").unwrap();
+
+        let first = Addresses {
+            ipv4: Some("192.0.2.1".parse().unwrap()),
+            ipv6: None,
+        };
+        let second = Addresses {
+            ipv4: Some("192.0.2.2".parse().unwrap()),
+            ipv6: Some("2001:db8::2".parse().unwrap()),
+        };
+        save(&path, &home, &first).unwrap();
+        save(&path, &office, &second).unwrap();
+        assert_eq!(load(&path, &home).unwrap(), Some(first));
+        assert_eq!(load(&path, &office).unwrap(), Some(second));
+
+        save(&path, &home, &second).unwrap();
+        assert_eq!(load(&path, &home).unwrap(), Some(second));
+        assert_eq!(load(&path, &office).unwrap(), Some(second));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 4);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(load(&path, &home).unwrap(), None);
+    }
+}
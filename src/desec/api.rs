@@ -0,0 +1,278 @@
+use std::{error::Error as StdErr, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION, LINK},
+    Client as HttpClient, RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::http::{retry_after, Page, StatusError};
+#[cfg(feature = "retry")]
+use crate::layer::Transient;
+
+pub const DESEC_API_URL: &str = "https://desec.io/api/v1";
+
+/// Represents an error returned by the deSEC API client.
+#[derive(Debug, Error)]
+pub enum DesecError {
+    /// Indicates that the HTTP request failed or deSEC responded with an error status.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that the request was throttled by deSEC.
+    ///
+    /// `retry_after` contains the time to wait before retrying the request, if deSEC provided it.
+    #[error("the request was throttled by deSEC")]
+    Throttled { retry_after: Option<Duration> },
+}
+
+impl DesecError {
+    /// Returns the HTTP status code deSEC responded with.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            DesecError::Http(err) => err.status(),
+            DesecError::Throttled { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+        }
+    }
+}
+
+impl StatusError for DesecError {
+    fn status(&self) -> Option<StatusCode> {
+        DesecError::status(self)
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for DesecError {
+    fn is_transient(&self) -> bool {
+        match self {
+            DesecError::Http(err) => err.is_transient(),
+            DesecError::Throttled { .. } => true,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            DesecError::Http(_) => None,
+            DesecError::Throttled { retry_after } => *retry_after,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(api_token: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value = HeaderValue::from_str(&format!("Token {}", api_token))?;
+        auth_value.set_sensitive(true);
+        headers.append(AUTHORIZATION, auth_value);
+
+        let http_client = HttpClient::builder().default_headers(headers).build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    /// Sends a request, turning throttling responses into [`DesecError::Throttled`] and other error statuses into [`DesecError::Http`].
+    async fn send(request: RequestBuilder) -> Result<Response, DesecError> {
+        let response = request.send().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = retry_after(response.headers());
+            #[cfg(feature = "metrics")]
+            crate::metrics::recorder().record_rate_limit(super::PROVIDER_NAME);
+            return Err(DesecError::Throttled { retry_after });
+        }
+
+        Ok(response.error_for_status()?)
+    }
+
+    /// Retrieves a page of domains using cursor pagination.
+    ///
+    /// `page_url` is the URL of the next page as returned by the previous call, [`None`] requests the first page.
+    pub async fn retrieve_domains(
+        &self,
+        page_url: Option<&str>,
+    ) -> Result<Page<Domain, String>, DesecError> {
+        let url = match page_url {
+            Some(url) => url.to_owned(),
+            None => format!("{}/domains/?cursor=", self.base_url),
+        };
+        self.retrieve_page(url).await
+    }
+
+    pub async fn retrieve_domain(&self, domain: &str) -> Result<Domain, DesecError> {
+        Ok(Self::send(
+            self.http_client
+                .get(format!("{}/domains/{}/", self.base_url, domain)),
+        )
+        .await?
+        .json()
+        .await?)
+    }
+
+    pub async fn create_domain(&self, domain: &str) -> Result<Domain, DesecError> {
+        Ok(Self::send(
+            self.http_client
+                .post(format!("{}/domains/", self.base_url))
+                .json(&CreateDomainRequest { name: domain }),
+        )
+        .await?
+        .json()
+        .await?)
+    }
+
+    pub async fn delete_domain(&self, domain: &str) -> Result<(), DesecError> {
+        Self::send(
+            self.http_client
+                .delete(format!("{}/domains/{}/", self.base_url, domain)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves a page of RRsets using cursor pagination.
+    ///
+    /// `page_url` is the URL of the next page as returned by the previous call, [`None`] requests the first page.
+    pub async fn retrieve_rrsets(
+        &self,
+        domain: &str,
+        page_url: Option<&str>,
+    ) -> Result<Page<RRset, String>, DesecError> {
+        let url = match page_url {
+            Some(url) => url.to_owned(),
+            None => format!("{}/domains/{}/rrsets/?cursor=", self.base_url, domain),
+        };
+        self.retrieve_page(url).await
+    }
+
+    /// Retrieves a page of a listing, taking the URL of the next page from the `Link` header.
+    async fn retrieve_page<T: DeserializeOwned>(
+        &self,
+        url: String,
+    ) -> Result<Page<T, String>, DesecError> {
+        let response = Self::send(self.http_client.get(url)).await?;
+        let next = response
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(next_link);
+
+        Ok(Page {
+            items: response.json().await?,
+            next,
+        })
+    }
+
+    /// Retrieves the RRset with the given subname and type if it exists.
+    pub async fn retrieve_rrset(
+        &self,
+        domain: &str,
+        subname: &str,
+        typ: &str,
+    ) -> Result<Option<RRset>, DesecError> {
+        let rrsets: Vec<RRset> = Self::send(
+            self.http_client
+                .get(format!("{}/domains/{}/rrsets/", self.base_url, domain))
+                .query(&[("subname", subname), ("type", typ)]),
+        )
+        .await?
+        .json()
+        .await?;
+
+        Ok(rrsets.into_iter().next())
+    }
+
+    /// Creates, replaces or deletes (if `records` is empty) multiple RRsets at once.
+    pub async fn patch_rrsets(&self, domain: &str, rrsets: &[RRset]) -> Result<(), DesecError> {
+        Self::send(
+            self.http_client
+                .patch(format!("{}/domains/{}/rrsets/", self.base_url, domain))
+                .json(rrsets),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Extracts the URL of the next page from a `Link` header value.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_owned()
+            })
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+struct CreateDomainRequest<'a> {
+    name: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub name: String,
+    pub minimum_ttl: u64,
+    #[serde(default)]
+    pub keys: Vec<Key>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Key {
+    pub dnskey: String,
+    #[serde(default)]
+    pub ds: Vec<String>,
+    pub flags: u16,
+    pub keytype: String,
+    #[serde(default)]
+    pub managed: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct RRset {
+    pub subname: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub ttl: u64,
+    pub records: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_links() {
+        assert_eq!(
+            next_link(
+                r#"<https://desec.io/api/v1/domains/?cursor=>; rel="first", <https://desec.io/api/v1/domains/?cursor=abc>; rel="next""#
+            ),
+            Some("https://desec.io/api/v1/domains/?cursor=abc".to_owned())
+        );
+        assert_eq!(
+            next_link(
+                r#"<https://desec.io/api/v1/domains/?cursor=>; rel="first"; title="first page",<https://desec.io/api/v1/domains/?cursor=def>;rel="next""#
+            ),
+            Some("https://desec.io/api/v1/domains/?cursor=def".to_owned())
+        );
+        assert_eq!(
+            next_link(r#"<https://desec.io/api/v1/domains/?cursor=abc>; rel="prev""#),
+            None
+        );
+        assert_eq!(next_link(""), None);
+    }
+}
@@ -0,0 +1,523 @@
+//! Provider implementation for [deSEC](https://desec.io/).
+//!
+//! deSEC manages records as RRsets, so every value is represented as a [`Record`] with a [synthetic ID](crate::rrset).
+//! Creating or deleting a record replaces the whole RRset using a single bulk request.
+//!
+//! deSEC rate limits its API rigorously. Throttled requests fail with [`DesecError::Throttled`],
+//! carrying the time to wait before retrying if deSEC provided it.
+//! Zones are signed automatically, the keys required to set up the chain of trust are available via [`DesecZone::dnssec_keys`].
+
+use std::{error::Error as StdErr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus},
+    rrset::{self, parse_record_id, record_id},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+pub use api::DesecError;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 23] = &[
+    "A",
+    "AAAA",
+    "CAA",
+    "CERT",
+    "CNAME",
+    "DS",
+    "HINFO",
+    "HTTPS",
+    "LOC",
+    "MX",
+    "NAPTR",
+    "NS",
+    "OPENPGPKEY",
+    "PTR",
+    "RP",
+    "SMIMEA",
+    "SPF",
+    "SRV",
+    "SSHFP",
+    "SVCB",
+    "TLSA",
+    "TXT",
+    "URI",
+];
+
+/// deSEC expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+const PROVIDER_NAME: &str = "desec";
+
+#[derive(Debug, Clone)]
+pub struct DesecProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl DesecProvider {
+    pub fn new(api_token: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(api_token, api::DESEC_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://desec.io/api/v1`, e.g. a mock server.
+    pub fn with_base_url(api_token: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_token, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for DesecProvider {
+    type Zone = DesecZone;
+    type CustomRetrieveError = DesecError;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domain = self
+            .api_client
+            .retrieve_domain(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(DesecZone::new(self.api_client.clone(), domain))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domains = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_domains(cursor.as_deref())
+                .await
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| DesecZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+impl CreateZone for DesecProvider {
+    type CustomCreateError = DesecError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let domain = self
+            .api_client
+            .create_domain(domain.as_ascii())
+            .await
+            .map_err(CreateZoneError::from_status)?;
+
+        Ok(DesecZone::new(self.api_client.clone(), domain))
+    }
+}
+
+impl DeleteZone for DesecProvider {
+    type CustomDeleteError = DesecError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_domain(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+/// Represents a DNSSEC key deSEC signs a zone with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DnssecKey {
+    /// The `DNSKEY` record of the key.
+    pub dnskey: RecordData,
+
+    /// The `DS` records which have to be published in the parent zone to establish the chain of trust.
+    pub ds: Vec<RecordData>,
+
+    /// The flags of the `DNSKEY` record, e.g. `257` for a key signing key with the secure entry point flag set.
+    pub flags: u16,
+
+    /// The type of the key, e.g. `csk`.
+    pub key_type: String,
+
+    /// Whether the key is managed by deSEC.
+    pub managed: bool,
+}
+
+impl From<api::Key> for DnssecKey {
+    fn from(key: api::Key) -> Self {
+        Self {
+            dnskey: RecordData::from_raw("DNSKEY", &key.dnskey),
+            ds: key
+                .ds
+                .iter()
+                .map(|ds| RecordData::from_raw("DS", ds))
+                .collect(),
+            flags: key.flags,
+            key_type: key.keytype,
+            managed: key.managed,
+        }
+    }
+}
+
+/// Represents a domain managed by deSEC.
+///
+/// The zone ID is the domain itself, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct DesecZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    domain: DomainName,
+}
+
+impl DesecZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let domain = DomainName::from_raw(&repr.name).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+
+    /// Returns the smallest TTL deSEC accepts for records of this zone.
+    pub fn minimum_ttl(&self) -> u64 {
+        self.repr.minimum_ttl
+    }
+
+    /// Retrieves the DNSSEC keys of the zone, including the `DS` records to publish in the parent zone.
+    pub async fn dnssec_keys(&self) -> Result<Vec<DnssecKey>, DesecError> {
+        let domain = self.api_client.retrieve_domain(&self.repr.name).await?;
+        Ok(domain.keys.into_iter().map(DnssecKey::from).collect())
+    }
+
+    async fn find_rrset(
+        &self,
+        name: &DomainName,
+        typ: &str,
+    ) -> Result<Option<api::RRset>, DesecError> {
+        let Some(host) = name.relative_to(&self.domain) else {
+            return Ok(None);
+        };
+
+        self.api_client
+            .retrieve_rrset(&self.repr.name, host.as_ascii(), typ)
+            .await
+    }
+
+    fn to_records(&self, set: api::RRset) -> Vec<Record> {
+        let host = DomainName::from_raw(&set.subname);
+        let name = self.fqdn(&host);
+
+        rrset::to_records(
+            &name,
+            &host,
+            set.records
+                .iter()
+                .map(|value| RecordData::from_raw(&set.typ, value)),
+            set.ttl,
+        )
+    }
+}
+
+impl Zone for DesecZone {
+    type CustomRetrieveError = DesecError;
+
+    fn id(&self) -> &str {
+        &self.repr.name
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let sets = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_rrsets(&self.repr.name, cursor.as_deref())
+                .await
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(sets
+            .into_iter()
+            .flat_map(|set| self.to_records(set))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(RetrieveRecordError::NotFound)?;
+
+        let set = self
+            .find_rrset(&name, data.get_type())
+            .await
+            .map_err(RetrieveRecordError::from_status)?
+            .ok_or(RetrieveRecordError::NotFound)?;
+
+        rrset::find_record(self.to_records(set), record_id).ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for DesecZone {
+    type CustomCreateError = DesecError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let name = self.fqdn(&host);
+
+        let existing = self
+            .find_rrset(&name, typ)
+            .await
+            .map_err(CreateRecordError::from_status)?
+            .map(|set| set.records)
+            .unwrap_or_default();
+        let records = rrset::add_value(existing, data, data.get_value_as(TXT_FORMAT), |value| {
+            RecordData::from_raw(typ, value)
+        });
+
+        let set = api::RRset {
+            subname: host.as_ascii().to_owned(),
+            typ: typ.to_owned(),
+            ttl,
+            records,
+        };
+        self.api_client
+            .patch_rrsets(&self.repr.name, &[set])
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(Record {
+            id: record_id(&name, data),
+            host,
+            data: data.clone(),
+            ttl,
+        })
+    }
+}
+
+impl DeleteRecord for DesecZone {
+    type CustomDeleteError = DesecError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(DeleteRecordError::NotFound)?;
+        let typ = data.get_type();
+
+        let existing = self
+            .find_rrset(&name, typ)
+            .await
+            .map_err(DeleteRecordError::from_status)?
+            .ok_or(DeleteRecordError::NotFound)?;
+
+        let records = rrset::remove_value(existing.records, &data, |value| {
+            RecordData::from_raw(typ, value)
+        })
+        .ok_or(DeleteRecordError::NotFound)?;
+
+        // An RRset without records is deleted by deSEC.
+        let set = api::RRset {
+            records,
+            ..existing
+        };
+        self.api_client
+            .patch_rrsets(&self.repr.name, &[set])
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, OnceLock},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn pages_are_followed_and_throttling_is_reported() {
+        let url = Arc::new(OnceLock::<String>::new());
+        let server_url = url.clone();
+        let server = MockServer::start(move |request| {
+            if request.header("authorization") != Some("Token api-token") {
+                return Response::json(401, r#"{"detail":"Invalid token."}"#);
+            }
+
+            let url = server_url.get().unwrap();
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/domains/?cursor=") => Response::json(
+                    200,
+                    r#"[{"name":"example.com","minimum_ttl":3600}]"#,
+                )
+                .with_header(
+                    "Link",
+                    &format!(
+                        r#"<{url}/domains/?cursor=>; rel="first", <{url}/domains/?cursor=page-2>; rel="next""#
+                    ),
+                ),
+                ("GET", "/domains/?cursor=page-2") => Response::json(
+                    200,
+                    r#"[{"name":"example.org","minimum_ttl":3600}]"#,
+                )
+                .with_header(
+                    "Link",
+                    &format!(r#"<{url}/domains/?cursor=>; rel="first""#),
+                ),
+                ("GET", "/domains/example.com/rrsets/?cursor=") => Response::json(
+                    200,
+                    r#"[{"subname":"","type":"MX","ttl":3600,"records":["10 mail.example.com."]},{"subname":"www","type":"TXT","ttl":3600,"records":["\"hello\" \"world\""]}]"#,
+                ),
+                ("GET", "/domains/example.com/rrsets/?subname=www&type=TXT") => Response::json(
+                    200,
+                    r#"[{"subname":"www","type":"TXT","ttl":3600,"records":["\"hello\" \"world\""]}]"#,
+                ),
+                ("PATCH", "/domains/example.com/rrsets/") => Response::json(200, "[]"),
+                ("GET", "/domains/example.net/") => {
+                    Response::json(429, r#"{"detail":"Request was throttled."}"#)
+                        .with_header("Retry-After", "60")
+                }
+                _ => Response::json(404, r#"{"detail":"Not found."}"#),
+            }
+        })
+        .await;
+        url.set(server.url().to_owned()).unwrap();
+
+        let provider = DesecProvider::with_base_url("api-token", server.url()).unwrap();
+        assert!(matches!(
+            provider.get_zone("example.net").await,
+            Err(RetrieveZoneError::Custom(DesecError::Throttled {
+                retry_after: Some(retry_after)
+            })) if retry_after == Duration::from_secs(60)
+        ));
+
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(
+            zones.iter().map(|zone| zone.id()).collect::<Vec<_>>(),
+            ["example.com", "example.org"]
+        );
+        let zone = &zones[0];
+        assert_eq!(zone.minimum_ttl(), 3600);
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.host.to_string(), &record.data))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "@".to_owned(),
+                    &RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mail.example.com."),
+                    }
+                ),
+                (
+                    "www".to_owned(),
+                    &RecordData::TXT(vec!["hello".to_owned(), "world".to_owned()])
+                ),
+            ]
+        );
+
+        let data = RecordData::TXT(vec!["v=1".to_owned()]);
+        zone.create_record(&DomainName::from_raw("www"), &data, 3600)
+            .await
+            .unwrap();
+        zone.delete_record(&records[1].id).await.unwrap();
+
+        let requests = server.requests();
+        let patches = requests
+            .iter()
+            .filter(|request| request.method == "PATCH")
+            .map(|request| request.body.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            patches,
+            [
+                r#"[{"subname":"www","type":"TXT","ttl":3600,"records":["\"hello\" \"world\"","\"v=1\""]}]"#,
+                // An RRset without records is deleted.
+                r#"[{"subname":"www","type":"TXT","ttl":3600,"records":[]}]"#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "retry")]
+    #[tokio::test]
+    async fn retry_layer_waits_as_long_as_requested() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        use crate::layer::Retry;
+
+        let throttled = AtomicBool::new(false);
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/domains/example.com/" if !throttled.swap(true, Ordering::SeqCst) => {
+                Response::json(429, r#"{"detail":"Request was throttled."}"#)
+                    .with_header("Retry-After", "0")
+            }
+            "/domains/example.com/" => {
+                Response::json(200, r#"{"name":"example.com","minimum_ttl":3600}"#)
+            }
+            _ => Response::json(429, r#"{"detail":"Request was throttled."}"#)
+                .with_header("Retry-After", "60"),
+        })
+        .await;
+
+        let provider = DesecProvider::with_base_url("api-token", server.url())
+            .unwrap()
+            .layer(Retry::new().with_max_delay(Duration::from_secs(1)));
+        let zone = provider.get_zone("example.com").await.unwrap();
+        assert_eq!(zone.id(), "example.com");
+
+        // deSEC asks to wait longer than the maximum delay, so the request is not retried.
+        assert!(matches!(
+            provider.get_zone("example.org").await,
+            Err(RetrieveZoneError::Custom(DesecError::Throttled { .. }))
+        ));
+        assert_eq!(
+            server
+                .requests()
+                .iter()
+                .map(|request| request.path.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "/domains/example.com/",
+                "/domains/example.com/",
+                "/domains/example.org/"
+            ]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::{collections::HashMap, error::Error};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client as HttpClient,
+};
+use serde::{Deserialize, Serialize};
+
+pub const DIGITALOCEAN_API_URL: &str = "https://api.digitalocean.com/v2";
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(api_token: &str, base_url: &str) -> Result<Self, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value = HeaderValue::from_str(&format!("Bearer {}", api_token))?;
+        auth_value.set_sensitive(true);
+        headers.append(AUTHORIZATION, auth_value);
+
+        let http_client = HttpClient::builder().default_headers(headers).build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    pub async fn retrieve_domains(
+        &self,
+        page_url: Option<&str>,
+        per_page: u32,
+    ) -> Result<DomainsResponse, reqwest::Error> {
+        let url = match page_url {
+            Some(url) => url.to_owned(),
+            None => format!("{}/domains?per_page={}", self.base_url, per_page),
+        };
+
+        self.http_client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_domain(&self, domain: &str) -> Result<DomainResponse, reqwest::Error> {
+        self.http_client
+            .get(format!("{}/domains/{}", self.base_url, domain))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_domain(&self, domain: &str) -> Result<DomainResponse, reqwest::Error> {
+        let mut request_body = HashMap::new();
+        request_body.insert("name", domain);
+
+        self.http_client
+            .post(format!("{}/domains", self.base_url))
+            .json(&request_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_domain(&self, domain: &str) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!("{}/domains/{}", self.base_url, domain))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+
+    pub async fn retrieve_records(
+        &self,
+        domain: &str,
+        page_url: Option<&str>,
+        per_page: u32,
+    ) -> Result<RecordsResponse, reqwest::Error> {
+        let url = match page_url {
+            Some(url) => url.to_owned(),
+            None => format!(
+                "{}/domains/{}/records?per_page={}",
+                self.base_url, domain, per_page
+            ),
+        };
+
+        self.http_client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_record(
+        &self,
+        domain: &str,
+        record_id: u64,
+    ) -> Result<RecordResponse, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_record(
+        &self,
+        domain: &str,
+        record: &RecordRequest<'_>,
+    ) -> Result<RecordResponse, reqwest::Error> {
+        self.http_client
+            .post(format!("{}/domains/{}/records", self.base_url, domain))
+            .json(record)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_record(&self, domain: &str, record_id: u64) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub name: String,
+    pub ttl: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct DomainResponse {
+    pub domain: Domain,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct DomainsResponse {
+    pub domains: Vec<Domain>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Record {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub name: String,
+    pub data: String,
+    pub priority: Option<u16>,
+    pub port: Option<u16>,
+    pub ttl: u64,
+    pub weight: Option<u16>,
+    pub flags: Option<u8>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct RecordRequest<'a> {
+    #[serde(rename = "type")]
+    pub typ: &'a str,
+    pub name: String,
+    pub data: String,
+    pub priority: Option<u16>,
+    pub port: Option<u16>,
+    pub ttl: u64,
+    pub weight: Option<u16>,
+    pub flags: Option<u8>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RecordResponse {
+    pub domain_record: Record,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RecordsResponse {
+    pub domain_records: Vec<Record>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default, Deserialize)]
+pub struct Links {
+    #[serde(default)]
+    pub pages: Pages,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default, Deserialize)]
+pub struct Pages {
+    pub next: Option<String>,
+}
@@ -0,0 +1,416 @@
+use std::{error::Error as StdErr, str::FromStr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 8] = &["A", "AAAA", "CAA", "CNAME", "MX", "NS", "SRV", "TXT"];
+
+/// DigitalOcean expects TXT values as a single unquoted string and splits it by itself.
+const TXT_FORMAT: TxtFormat = TxtFormat::Concatenated;
+
+const PAGE_SIZE: u32 = 200;
+
+const PROVIDER_NAME: &str = "digitalocean";
+
+#[derive(Debug, Clone)]
+pub struct DigitalOceanProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl DigitalOceanProvider {
+    pub fn new(api_token: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(api_token, api::DIGITALOCEAN_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://api.digitalocean.com/v2`, e.g. a mock server.
+    pub fn with_base_url(api_token: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_token, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for DigitalOceanProvider {
+    type Zone = DigitalOceanZone;
+    type CustomRetrieveError = reqwest::Error;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let response = self
+            .api_client
+            .retrieve_domain(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(DigitalOceanZone::new(
+            self.api_client.clone(),
+            response.domain,
+        ))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domains = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_domains(cursor.as_deref(), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.domains,
+                    next: response.links.pages.next,
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| DigitalOceanZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+impl CreateZone for DigitalOceanProvider {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let response = self
+            .api_client
+            .create_domain(domain.as_ascii())
+            .await
+            .map_err(CreateZoneError::from_status)?;
+
+        Ok(DigitalOceanZone::new(
+            self.api_client.clone(),
+            response.domain,
+        ))
+    }
+}
+
+impl DeleteZone for DigitalOceanProvider {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_domain(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+/// Represents a DigitalOcean domain.
+///
+/// The zone ID is the domain name itself, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct DigitalOceanZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    domain: DomainName,
+}
+
+impl DigitalOceanZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let domain = DomainName::from_raw(&repr.name).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+}
+
+impl Zone for DigitalOceanZone {
+    type CustomRetrieveError = reqwest::Error;
+
+    fn id(&self) -> &str {
+        &self.repr.name
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let records = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_records(&self.repr.name, cursor.as_deref(), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.domain_records,
+                    next: response.links.pages.next,
+                })
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| record.into_generic(&self.domain))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| RetrieveRecordError::NotFound)?;
+
+        let response = self
+            .api_client
+            .retrieve_record(&self.repr.name, record_id)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        Ok(response.domain_record.into_generic(&self.domain))
+    }
+}
+
+impl CreateRecord for DigitalOceanZone {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        let mut request = api::RecordRequest {
+            typ,
+            name: host.to_string(),
+            data: data.get_value_as(TXT_FORMAT),
+            priority: None,
+            port: None,
+            ttl,
+            weight: None,
+            flags: None,
+            tag: None,
+        };
+
+        match data {
+            RecordData::CNAME(target) | RecordData::NS(target) => {
+                request.data = self.fqdn(target).to_string();
+            }
+            RecordData::MX {
+                priority,
+                mail_server,
+            } => {
+                request.data = self.fqdn(mail_server).to_string();
+                request.priority = Some(*priority);
+            }
+            RecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                request.data = self.fqdn(target).to_string();
+                request.priority = Some(*priority);
+                request.weight = Some(*weight);
+                request.port = Some(*port);
+            }
+            RecordData::Other { typ, value } if typ == "CAA" => {
+                let mut iter = value.splitn(3, char::is_whitespace);
+                let opt_flags = iter.next().and_then(|raw| raw.parse::<u8>().ok());
+                let opt_tag = iter.next();
+                let opt_value = iter.next().map(|raw| raw.trim().trim_matches('"'));
+
+                let (Some(flags), Some(tag), Some(value)) = (opt_flags, opt_tag, opt_value) else {
+                    return Err(CreateRecordError::InvalidRecord);
+                };
+                request.data = value.to_owned();
+                request.flags = Some(flags);
+                request.tag = Some(tag.to_owned());
+            }
+            _ => {}
+        }
+
+        let response = self
+            .api_client
+            .create_record(&self.repr.name, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(response.domain_record.into_generic(&self.domain))
+    }
+}
+
+impl DeleteRecord for DigitalOceanZone {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| DeleteRecordError::NotFound)?;
+
+        self.api_client
+            .delete_record(&self.repr.name, record_id)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::Record {
+    pub fn into_generic(self, zone_domain: &DomainName) -> Record {
+        // DigitalOcean returns target names without a trailing dot and uses `@` for the zone's apex.
+        let target = || match self.data.as_str() {
+            "@" => Some(zone_domain.clone()),
+            data => DomainName::from_str(data)
+                .ok()
+                .map(|name| name.to_absolute()),
+        };
+
+        let data = match self.typ.as_str() {
+            "CNAME" => target().map(RecordData::CNAME),
+            "NS" => target().map(RecordData::NS),
+            "MX" => target().map(|mail_server| RecordData::MX {
+                priority: self.priority.unwrap_or_default(),
+                mail_server,
+            }),
+            "SRV" => target().map(|target| RecordData::SRV {
+                priority: self.priority.unwrap_or_default(),
+                weight: self.weight.unwrap_or_default(),
+                port: self.port.unwrap_or_default(),
+                target,
+            }),
+            "TXT" => Some(RecordData::txt(self.data.clone())),
+            "CAA" => Some(RecordData::from_raw(
+                "CAA",
+                &format!(
+                    "{} {} \"{}\"",
+                    self.flags.unwrap_or_default(),
+                    self.tag.as_deref().unwrap_or_default(),
+                    self.data
+                ),
+            )),
+            _ => None,
+        };
+
+        let host = DomainName::from_raw(&self.name);
+        Record {
+            id: self.id.to_string(),
+            host: host.relative_to(zone_domain).unwrap_or(host),
+            data: data.unwrap_or_else(|| RecordData::from_raw(&self.typ, &self.data)),
+            ttl: self.ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, OnceLock};
+
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn records_are_paged_and_created() {
+        let url = Arc::new(OnceLock::<String>::new());
+        let server_url = url.clone();
+        let server = MockServer::start(move |request| {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/domains/example.com") => Response::json(
+                    200,
+                    r#"{"domain":{"name":"example.com","ttl":1800}}"#,
+                ),
+                ("GET", "/domains/example.com/records?per_page=200") => Response::json(
+                    200,
+                    &format!(
+                        r#"{{"domain_records":[{{"id":1,"type":"CNAME","name":"www","data":"@","ttl":1800}}],"links":{{"pages":{{"next":"{}/domains/example.com/records?page=2&per_page=200"}}}}}}"#,
+                        server_url.get().unwrap()
+                    ),
+                ),
+                ("GET", "/domains/example.com/records?page=2&per_page=200") => Response::json(
+                    200,
+                    r#"{"domain_records":[{"id":2,"type":"MX","name":"@","data":"mail.example.com","priority":10,"ttl":1800}],"links":{}}"#,
+                ),
+                ("POST", "/domains/example.com/records") => Response::json(
+                    201,
+                    r#"{"domain_record":{"id":3,"type":"SRV","name":"_sip._tcp","data":"sip.example.com","priority":10,"weight":20,"port":5060,"ttl":600}}"#,
+                ),
+                _ => Response::json(404, r#"{"id":"not_found","message":"not found"}"#),
+            }
+        })
+        .await;
+        url.set(server.url().to_owned()).unwrap();
+
+        let provider = DigitalOceanProvider::with_base_url("api-token", server.url()).unwrap();
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| &record.data)
+                .collect::<Vec<_>>(),
+            [
+                &RecordData::CNAME(DomainName::from_raw("example.com.")),
+                &RecordData::MX {
+                    priority: 10,
+                    mail_server: DomainName::from_raw("mail.example.com."),
+                },
+            ]
+        );
+        assert_eq!(records[0].host, DomainName::from_raw("www"));
+        assert!(records[1].host.is_apex());
+
+        let data = RecordData::SRV {
+            priority: 10,
+            weight: 20,
+            port: 5060,
+            target: DomainName::from_raw("sip"),
+        };
+        let record = zone
+            .create_record(&DomainName::from_raw("_sip._tcp"), &data, 600)
+            .await
+            .unwrap();
+        assert_eq!(record.id, "3");
+
+        assert!(matches!(
+            zone.delete_record("4").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let requests = server.requests();
+        let create = requests
+            .iter()
+            .find(|request| request.method == "POST")
+            .unwrap();
+        assert!(create.body.contains(r#""type":"SRV""#));
+        assert!(create.body.contains(r#""data":"sip.example.com.""#));
+        assert!(create.body.contains(r#""port":5060"#));
+        assert!(requests
+            .iter()
+            .all(|request| request.header("Authorization") == Some("Bearer api-token")));
+    }
+}
@@ -0,0 +1,496 @@
+//! Previewing the changes made to a provider without applying them.
+//!
+//! [`DryRun`] wraps any provider, passing retrievals through to it while recording creations and deletions
+//! of zones and records in a [`ChangeLog`] instead of performing them:
+//!
+//! ```ignore
+//! let provider = DryRun::new(HetznerProvider::new(api_key)?);
+//! reconcile(&provider).await?;
+//!
+//! for change in provider.log().changes() {
+//!     println!("{}", change);
+//! }
+//! ```
+//!
+//! A [`ChangeLog`] is also a [`Layer`], wrapping a provider in a [`DryRun`] recording into it:
+//!
+//! ```ignore
+//! let log = ChangeLog::new();
+//! let provider = HetznerProvider::new(api_key)?.layer(log.clone());
+//! ```
+//!
+//! Created zones and records are returned with IDs of the form `dry-run-<n>`.
+//! Zones created during the dry run contain no records and can not be retrieved using [`Provider::get_zone`].
+
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    layer::Layer, CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord,
+    DeleteRecordError, DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData,
+    RetrieveRecordError, RetrieveZoneError, Zone,
+};
+
+/// Represents a change recorded by [`DryRun`] instead of being applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// Indicates that a zone would have been created, `zone_id` being the fake ID of the returned zone.
+    CreateZone { zone_id: String, domain: DomainName },
+
+    /// Indicates that a zone would have been deleted.
+    DeleteZone { zone_id: String },
+
+    /// Indicates that a record would have been created, `record` being the returned fake record.
+    CreateRecord {
+        zone_id: String,
+        zone: DomainName,
+        record: Record,
+    },
+
+    /// Indicates that a record would have been deleted.
+    DeleteRecord {
+        zone_id: String,
+        zone: DomainName,
+        record_id: String,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::CreateZone { domain, .. } => write!(f, "create zone {}", domain),
+            Change::DeleteZone { zone_id } => write!(f, "delete zone {}", zone_id),
+            Change::CreateRecord { zone, record, .. } => write!(
+                f,
+                "create record {} {} {} {}",
+                record.host.to_fqdn(zone),
+                record.ttl,
+                record.data.get_type(),
+                record.data.get_value()
+            ),
+            Change::DeleteRecord {
+                zone, record_id, ..
+            } => write!(f, "delete record {} from zone {}", record_id, zone),
+        }
+    }
+}
+
+/// The changes recorded by a [`DryRun`] provider and its zones.
+///
+/// Clones share the same changes.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeLog {
+    state: Arc<Mutex<ChangeLogState>>,
+}
+
+#[derive(Debug, Default)]
+struct ChangeLogState {
+    changes: Vec<Change>,
+    next_id: u64,
+}
+
+impl ChangeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all recorded changes in the order they were made.
+    pub fn changes(&self) -> Vec<Change> {
+        self.lock().changes.clone()
+    }
+
+    /// Returns and removes all recorded changes.
+    pub fn take(&self) -> Vec<Change> {
+        std::mem::take(&mut self.lock().changes)
+    }
+
+    /// Returns whether no changes were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().changes.is_empty()
+    }
+
+    fn push(&self, change: Change) {
+        self.lock().changes.push(change);
+    }
+
+    fn fake_id(&self) -> String {
+        let mut state = self.lock();
+        state.next_id += 1;
+        format!("dry-run-{}", state.next_id)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChangeLogState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<P: Provider> Layer<P> for ChangeLog {
+    type Provider = DryRun<P>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        DryRun::with_log(provider, self.clone())
+    }
+}
+
+/// Wraps a provider, recording the changes made to it in a [`ChangeLog`] instead of applying them.
+///
+/// Zones and records are created and deleted only if the wrapped provider and its zones support doing so.
+#[derive(Debug, Clone)]
+pub struct DryRun<P> {
+    inner: P,
+    log: ChangeLog,
+}
+
+impl<P> DryRun<P> {
+    pub fn new(provider: P) -> Self {
+        Self::with_log(provider, ChangeLog::new())
+    }
+
+    /// Wraps a provider, recording the changes in an existing change log.
+    pub fn with_log(provider: P, log: ChangeLog) -> Self {
+        Self {
+            inner: provider,
+            log,
+        }
+    }
+
+    /// Returns the change log.
+    pub fn log(&self) -> &ChangeLog {
+        &self.log
+    }
+
+    /// Returns the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Unwraps the provider, discarding the change log.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    fn wrap<Z>(&self, zone: Z) -> DryRunZone<Z> {
+        DryRunZone {
+            repr: ZoneRepr::Existing(zone),
+            log: self.log.clone(),
+        }
+    }
+}
+
+impl<P: Provider> Provider for DryRun<P> {
+    type Zone = DryRunZone<P::Zone>;
+    type CustomRetrieveError = P::CustomRetrieveError;
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = self.inner.list_zones().await?;
+        Ok(zones.into_iter().map(|zone| self.wrap(zone)).collect())
+    }
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self.inner.get_zone(zone_id).await?;
+        Ok(self.wrap(zone))
+    }
+}
+
+impl<P: CreateZone> CreateZone for DryRun<P> {
+    type CustomCreateError = P::CustomCreateError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        if domain.is_apex() {
+            return Err(CreateZoneError::InvalidDomainName);
+        }
+
+        let id = self.log.fake_id();
+        let domain = domain.to_absolute();
+        self.log.push(Change::CreateZone {
+            zone_id: id.clone(),
+            domain: domain.clone(),
+        });
+        Ok(DryRunZone {
+            repr: ZoneRepr::Planned { id, domain },
+            log: self.log.clone(),
+        })
+    }
+}
+
+impl<P: DeleteZone> DeleteZone for DryRun<P> {
+    type CustomDeleteError = P::CustomDeleteError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.log.push(Change::DeleteZone {
+            zone_id: zone_id.to_owned(),
+        });
+        Ok(())
+    }
+}
+
+/// Represents a zone of a [`DryRun`] provider.
+#[derive(Debug, Clone)]
+pub struct DryRunZone<Z> {
+    repr: ZoneRepr<Z>,
+    log: ChangeLog,
+}
+
+#[derive(Debug, Clone)]
+enum ZoneRepr<Z> {
+    Existing(Z),
+    Planned { id: String, domain: DomainName },
+}
+
+impl<Z> DryRunZone<Z> {
+    /// Returns the wrapped zone, [`None`] if the zone was created during the dry run.
+    pub fn inner(&self) -> Option<&Z> {
+        match &self.repr {
+            ZoneRepr::Existing(zone) => Some(zone),
+            ZoneRepr::Planned { .. } => None,
+        }
+    }
+
+    /// Returns whether the zone was created during the dry run.
+    pub fn is_planned(&self) -> bool {
+        matches!(self.repr, ZoneRepr::Planned { .. })
+    }
+}
+
+impl<Z: Zone> Zone for DryRunZone<Z> {
+    type CustomRetrieveError = Z::CustomRetrieveError;
+
+    fn id(&self) -> &str {
+        match &self.repr {
+            ZoneRepr::Existing(zone) => zone.id(),
+            ZoneRepr::Planned { id, .. } => id,
+        }
+    }
+
+    fn domain(&self) -> &DomainName {
+        match &self.repr {
+            ZoneRepr::Existing(zone) => zone.domain(),
+            ZoneRepr::Planned { domain, .. } => domain,
+        }
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        match &self.repr {
+            ZoneRepr::Existing(zone) => zone.list_records().await,
+            ZoneRepr::Planned { .. } => Ok(Vec::new()),
+        }
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        match &self.repr {
+            ZoneRepr::Existing(zone) => zone.get_record(record_id).await,
+            ZoneRepr::Planned { .. } => Err(RetrieveRecordError::NotFound),
+        }
+    }
+}
+
+impl<Z: CreateRecord> CreateRecord for DryRunZone<Z> {
+    type CustomCreateError = Z::CustomCreateError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let record = Record {
+            id: self.log.fake_id(),
+            host,
+            data: data.clone(),
+            ttl,
+        };
+
+        self.log.push(Change::CreateRecord {
+            zone_id: self.id().to_owned(),
+            zone: self.domain().clone(),
+            record: record.clone(),
+        });
+        Ok(record)
+    }
+}
+
+impl<Z: DeleteRecord> DeleteRecord for DryRunZone<Z> {
+    type CustomDeleteError = Z::CustomDeleteError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        self.log.push(Change::DeleteRecord {
+            zone_id: self.id().to_owned(),
+            zone: self.domain().clone(),
+            record_id: record_id.to_owned(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryProvider;
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new(name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn changes_are_recorded_instead_of_applied() {
+        let inner = MemoryProvider::new().with_zone("example.com").with_record(
+            "example.com",
+            "www",
+            RecordData::A("192.0.2.1".parse().unwrap()),
+        );
+        let log = ChangeLog::new();
+        let provider = inner.clone().layer(log.clone());
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        assert!(!zone.is_planned());
+        assert_eq!(zone.list_records().await.unwrap().len(), 1);
+
+        let txt = RecordData::TXT(vec!["token".to_owned()]);
+        let created = zone
+            .create_record(&name("_acme-challenge.example.com."), &txt, 60)
+            .await
+            .unwrap();
+        assert_eq!(
+            created,
+            Record {
+                id: "dry-run-1".to_owned(),
+                host: name("_acme-challenge"),
+                data: txt.clone(),
+                ttl: 60,
+            }
+        );
+        let second = zone.create_record(&name("www"), &txt, 300).await.unwrap();
+        assert_eq!(second.id, "dry-run-2");
+        assert!(matches!(
+            zone.create_record(&name("www.example.org."), &txt, 300)
+                .await,
+            Err(CreateRecordError::InvalidRecord)
+        ));
+        zone.delete_record("1").await.unwrap();
+        provider.delete_zone("example.com").await.unwrap();
+
+        // Only retrievals reach the inner provider, which is left unchanged.
+        assert_eq!(
+            inner.calls(),
+            ["get_zone example.com", "list_records example.com"]
+        );
+        assert_eq!(inner.records("example.com").len(), 1);
+        assert_eq!(
+            zone.get_record(&created.id).await,
+            Err(RetrieveRecordError::NotFound)
+        );
+
+        assert_eq!(
+            log.changes(),
+            [
+                Change::CreateRecord {
+                    zone_id: "example.com".to_owned(),
+                    zone: name("example.com."),
+                    record: created,
+                },
+                Change::CreateRecord {
+                    zone_id: "example.com".to_owned(),
+                    zone: name("example.com."),
+                    record: second,
+                },
+                Change::DeleteRecord {
+                    zone_id: "example.com".to_owned(),
+                    zone: name("example.com."),
+                    record_id: "1".to_owned(),
+                },
+                Change::DeleteZone {
+                    zone_id: "example.com".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            log.changes()
+                .iter()
+                .map(Change::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "create record _acme-challenge.example.com. 60 TXT \"token\"",
+                "create record www.example.com. 300 TXT \"token\"",
+                "delete record 1 from zone example.com.",
+                "delete zone example.com",
+            ]
+        );
+        assert_eq!(log.take().len(), 4);
+        assert!(log.is_empty());
+    }
+
+    #[tokio::test]
+    async fn planned_zones_can_be_read_and_changed() {
+        let inner = MemoryProvider::new();
+        let provider = DryRun::new(inner.clone());
+
+        let zone = provider.create_zone(&name("example.org")).await.unwrap();
+        assert!(zone.is_planned());
+        assert!(zone.inner().is_none());
+        assert_eq!(zone.id(), "dry-run-1");
+        assert_eq!(zone.domain(), &name("example.org."));
+        assert_eq!(zone.list_records().await.unwrap(), []);
+        assert_eq!(
+            zone.get_record("1").await,
+            Err(RetrieveRecordError::NotFound)
+        );
+
+        let record = zone
+            .create_record(
+                &name("www"),
+                &RecordData::A("192.0.2.1".parse().unwrap()),
+                300,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.id, "dry-run-2");
+        assert!(matches!(
+            provider.get_zone("example.org").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+        assert!(matches!(
+            provider.create_zone(&name("@")).await,
+            Err(CreateZoneError::InvalidDomainName)
+        ));
+
+        assert_eq!(inner.calls(), ["get_zone example.org"]);
+        assert_eq!(
+            provider.log().changes(),
+            [
+                Change::CreateZone {
+                    zone_id: "dry-run-1".to_owned(),
+                    domain: name("example.org."),
+                },
+                Change::CreateRecord {
+                    zone_id: "dry-run-1".to_owned(),
+                    zone: name("example.org."),
+                    record,
+                },
+            ]
+        );
+    }
+}
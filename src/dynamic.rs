@@ -0,0 +1,669 @@
+//! Type-erased providers and zones for selecting a provider at runtime, e.g. based on configuration.
+//!
+//! The traits of this crate use `async fn` and associated types, so they cannot be used as trait objects.
+//! [`DynProvider`] and [`DynZone`] wrap any provider and its zones instead, implementing all traits themselves.
+//! Custom errors are boxed into [`DynError::Custom`] and operations not supported by the wrapped provider fail with [`DynError::Unsupported`].
+//! Like the wrapped providers, type-erased providers, zones and their futures are [`Send`] and [`Sync`].
+
+use std::{error::Error as StdErr, fmt::Debug, future::Future, pin::Pin, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Represents the custom error of a type-erased provider or zone.
+#[derive(Debug, Error)]
+pub enum DynError {
+    /// Indicates that the wrapped provider does not support the operation, e.g. creating zones.
+    #[error("the provider does not support this operation")]
+    Unsupported,
+
+    /// Provides the custom error of the wrapped provider, forwarding its message and [`source`](StdErr::source).
+    #[error(transparent)]
+    Custom(Box<dyn StdErr + Send + Sync>),
+}
+
+/// Converts the custom error of an error to [`DynError`].
+trait EraseCustom {
+    type Erased;
+
+    fn erase(self) -> Self::Erased;
+}
+
+fn erase<T: StdErr + Send + Sync + 'static>(err: T) -> DynError {
+    DynError::Custom(Box::new(err))
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for RetrieveZoneError<T> {
+    type Erased = RetrieveZoneError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            RetrieveZoneError::Unauthorized => RetrieveZoneError::Unauthorized,
+            RetrieveZoneError::NotFound => RetrieveZoneError::NotFound,
+            RetrieveZoneError::Custom(err) => RetrieveZoneError::Custom(erase(err)),
+        }
+    }
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for CreateZoneError<T> {
+    type Erased = CreateZoneError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            CreateZoneError::Unauthorized => CreateZoneError::Unauthorized,
+            CreateZoneError::InvalidDomainName => CreateZoneError::InvalidDomainName,
+            CreateZoneError::Custom(err) => CreateZoneError::Custom(erase(err)),
+        }
+    }
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for DeleteZoneError<T> {
+    type Erased = DeleteZoneError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            DeleteZoneError::Unauthorized => DeleteZoneError::Unauthorized,
+            DeleteZoneError::NotFound => DeleteZoneError::NotFound,
+            DeleteZoneError::Custom(err) => DeleteZoneError::Custom(erase(err)),
+        }
+    }
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for RetrieveRecordError<T> {
+    type Erased = RetrieveRecordError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            RetrieveRecordError::Unauthorized => RetrieveRecordError::Unauthorized,
+            RetrieveRecordError::NotFound => RetrieveRecordError::NotFound,
+            RetrieveRecordError::Custom(err) => RetrieveRecordError::Custom(erase(err)),
+        }
+    }
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for CreateRecordError<T> {
+    type Erased = CreateRecordError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            CreateRecordError::Unauthorized => CreateRecordError::Unauthorized,
+            CreateRecordError::UnsupportedType => CreateRecordError::UnsupportedType,
+            CreateRecordError::InvalidRecord => CreateRecordError::InvalidRecord,
+            CreateRecordError::Custom(err) => CreateRecordError::Custom(erase(err)),
+        }
+    }
+}
+
+impl<T: StdErr + Send + Sync + 'static> EraseCustom for DeleteRecordError<T> {
+    type Erased = DeleteRecordError<DynError>;
+
+    fn erase(self) -> Self::Erased {
+        match self {
+            DeleteRecordError::Unauthorized => DeleteRecordError::Unauthorized,
+            DeleteRecordError::NotFound => DeleteRecordError::NotFound,
+            DeleteRecordError::Custom(err) => DeleteRecordError::Custom(erase(err)),
+        }
+    }
+}
+
+/// The object-safe counterpart of the provider traits.
+trait ErasedProvider: Send + Sync {
+    fn list_zones(&self) -> BoxFuture<'_, Result<Vec<DynZone>, RetrieveZoneError<DynError>>>;
+
+    fn get_zone<'a>(
+        &'a self,
+        zone_id: &'a str,
+    ) -> BoxFuture<'a, Result<DynZone, RetrieveZoneError<DynError>>>;
+
+    fn create_zone<'a>(
+        &'a self,
+        domain: &'a DomainName,
+    ) -> BoxFuture<'a, Result<DynZone, CreateZoneError<DynError>>>;
+
+    fn delete_zone<'a>(
+        &'a self,
+        zone_id: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteZoneError<DynError>>>;
+
+    fn supports_zone_management(&self) -> bool;
+}
+
+/// Wraps a provider, converting its zones using `wrap_zone`.
+struct ProviderWrapper<P: Provider> {
+    provider: P,
+    wrap_zone: fn(P::Zone) -> DynZone,
+}
+
+impl<P> ProviderWrapper<P>
+where
+    P: Provider,
+    P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+{
+    async fn list_zones(&self) -> Result<Vec<DynZone>, RetrieveZoneError<DynError>> {
+        let zones = self
+            .provider
+            .list_zones()
+            .await
+            .map_err(|err| err.erase())?;
+        Ok(zones.into_iter().map(self.wrap_zone).collect())
+    }
+
+    async fn get_zone(&self, zone_id: &str) -> Result<DynZone, RetrieveZoneError<DynError>> {
+        let zone = self
+            .provider
+            .get_zone(zone_id)
+            .await
+            .map_err(|err| err.erase())?;
+        Ok((self.wrap_zone)(zone))
+    }
+}
+
+/// A provider without zone management.
+struct BasicProvider<P: Provider>(ProviderWrapper<P>);
+
+impl<P> ErasedProvider for BasicProvider<P>
+where
+    P: Provider,
+    P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+{
+    fn list_zones(&self) -> BoxFuture<'_, Result<Vec<DynZone>, RetrieveZoneError<DynError>>> {
+        Box::pin(self.0.list_zones())
+    }
+
+    fn get_zone<'a>(
+        &'a self,
+        zone_id: &'a str,
+    ) -> BoxFuture<'a, Result<DynZone, RetrieveZoneError<DynError>>> {
+        Box::pin(self.0.get_zone(zone_id))
+    }
+
+    fn create_zone<'a>(
+        &'a self,
+        _: &'a DomainName,
+    ) -> BoxFuture<'a, Result<DynZone, CreateZoneError<DynError>>> {
+        Box::pin(async { Err(CreateZoneError::Custom(DynError::Unsupported)) })
+    }
+
+    fn delete_zone<'a>(
+        &'a self,
+        _: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteZoneError<DynError>>> {
+        Box::pin(async { Err(DeleteZoneError::Custom(DynError::Unsupported)) })
+    }
+
+    fn supports_zone_management(&self) -> bool {
+        false
+    }
+}
+
+/// A provider supporting zone creation and deletion.
+struct ManagedProvider<P: Provider>(ProviderWrapper<P>);
+
+impl<P> ErasedProvider for ManagedProvider<P>
+where
+    P: CreateZone + DeleteZone,
+    P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+    P::CustomCreateError: StdErr + Send + Sync + 'static,
+    P::CustomDeleteError: StdErr + Send + Sync + 'static,
+{
+    fn list_zones(&self) -> BoxFuture<'_, Result<Vec<DynZone>, RetrieveZoneError<DynError>>> {
+        Box::pin(self.0.list_zones())
+    }
+
+    fn get_zone<'a>(
+        &'a self,
+        zone_id: &'a str,
+    ) -> BoxFuture<'a, Result<DynZone, RetrieveZoneError<DynError>>> {
+        Box::pin(self.0.get_zone(zone_id))
+    }
+
+    fn create_zone<'a>(
+        &'a self,
+        domain: &'a DomainName,
+    ) -> BoxFuture<'a, Result<DynZone, CreateZoneError<DynError>>> {
+        Box::pin(async move {
+            let zone = self
+                .0
+                .provider
+                .create_zone(domain)
+                .await
+                .map_err(|err| err.erase())?;
+            Ok((self.0.wrap_zone)(zone))
+        })
+    }
+
+    fn delete_zone<'a>(
+        &'a self,
+        zone_id: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteZoneError<DynError>>> {
+        Box::pin(async move {
+            self.0
+                .provider
+                .delete_zone(zone_id)
+                .await
+                .map_err(|err| err.erase())
+        })
+    }
+
+    fn supports_zone_management(&self) -> bool {
+        true
+    }
+}
+
+/// Represents any provider selected at runtime.
+///
+/// Cloning is cheap, as the wrapped provider is shared.
+#[derive(Clone)]
+pub struct DynProvider {
+    inner: Arc<dyn ErasedProvider>,
+}
+
+impl DynProvider {
+    /// Wraps a provider supporting zone and record management.
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: CreateZone + DeleteZone + 'static,
+        P::Zone: CreateRecord + DeleteRecord + 'static,
+        P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        P::CustomCreateError: StdErr + Send + Sync + 'static,
+        P::CustomDeleteError: StdErr + Send + Sync + 'static,
+        <P::Zone as Zone>::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        <P::Zone as CreateRecord>::CustomCreateError: StdErr + Send + Sync + 'static,
+        <P::Zone as DeleteRecord>::CustomDeleteError: StdErr + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(ManagedProvider(ProviderWrapper {
+                provider,
+                wrap_zone: DynZone::new,
+            })),
+        }
+    }
+
+    /// Wraps a provider whose zones support record management, but which does not support creating and deleting zones.
+    pub fn without_zone_management<P>(provider: P) -> Self
+    where
+        P: Provider + 'static,
+        P::Zone: CreateRecord + DeleteRecord + 'static,
+        P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        <P::Zone as Zone>::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        <P::Zone as CreateRecord>::CustomCreateError: StdErr + Send + Sync + 'static,
+        <P::Zone as DeleteRecord>::CustomDeleteError: StdErr + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(BasicProvider(ProviderWrapper {
+                provider,
+                wrap_zone: DynZone::new,
+            })),
+        }
+    }
+
+    /// Wraps a provider which only supports retrieving zones and records.
+    pub fn read_only<P>(provider: P) -> Self
+    where
+        P: Provider + 'static,
+        P::Zone: 'static,
+        P::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        <P::Zone as Zone>::CustomRetrieveError: StdErr + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(BasicProvider(ProviderWrapper {
+                provider,
+                wrap_zone: DynZone::read_only,
+            })),
+        }
+    }
+
+    /// Returns whether the wrapped provider supports creating and deleting zones.
+    pub fn supports_zone_management(&self) -> bool {
+        self.inner.supports_zone_management()
+    }
+}
+
+impl Debug for DynProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynProvider")
+            .field("zone_management", &self.supports_zone_management())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Provider for DynProvider {
+    type Zone = DynZone;
+    type CustomRetrieveError = DynError;
+
+    async fn list_zones(&self) -> Result<Vec<Self::Zone>, RetrieveZoneError<DynError>> {
+        self.inner.list_zones().await
+    }
+
+    async fn get_zone(&self, zone_id: &str) -> Result<Self::Zone, RetrieveZoneError<DynError>> {
+        self.inner.get_zone(zone_id).await
+    }
+}
+
+impl CreateZone for DynProvider {
+    type CustomCreateError = DynError;
+
+    /// Creates a zone, failing with [`DynError::Unsupported`] if the wrapped provider does not support it.
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<DynError>> {
+        self.inner.create_zone(domain).await
+    }
+}
+
+impl DeleteZone for DynProvider {
+    type CustomDeleteError = DynError;
+
+    /// Deletes a zone, failing with [`DynError::Unsupported`] if the wrapped provider does not support it.
+    async fn delete_zone(&self, zone_id: &str) -> Result<(), DeleteZoneError<DynError>> {
+        self.inner.delete_zone(zone_id).await
+    }
+}
+
+/// The object-safe counterpart of the zone traits.
+trait ErasedZone: Send + Sync {
+    fn id(&self) -> &str;
+
+    fn domain(&self) -> &DomainName;
+
+    fn list_records(&self) -> BoxFuture<'_, Result<Vec<Record>, RetrieveRecordError<DynError>>>;
+
+    fn get_record<'a>(
+        &'a self,
+        record_id: &'a str,
+    ) -> BoxFuture<'a, Result<Record, RetrieveRecordError<DynError>>>;
+
+    fn create_record<'a>(
+        &'a self,
+        host: &'a DomainName,
+        data: &'a RecordData,
+        ttl: u64,
+    ) -> BoxFuture<'a, Result<Record, CreateRecordError<DynError>>>;
+
+    fn delete_record<'a>(
+        &'a self,
+        record_id: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteRecordError<DynError>>>;
+
+    fn supports_record_management(&self) -> bool;
+}
+
+/// A zone only supporting record retrieval.
+struct BasicZone<Z>(Z);
+
+impl<Z> ErasedZone for BasicZone<Z>
+where
+    Z: Zone,
+    Z::CustomRetrieveError: StdErr + Send + Sync + 'static,
+{
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.0.domain()
+    }
+
+    fn list_records(&self) -> BoxFuture<'_, Result<Vec<Record>, RetrieveRecordError<DynError>>> {
+        Box::pin(async { self.0.list_records().await.map_err(|err| err.erase()) })
+    }
+
+    fn get_record<'a>(
+        &'a self,
+        record_id: &'a str,
+    ) -> BoxFuture<'a, Result<Record, RetrieveRecordError<DynError>>> {
+        Box::pin(async {
+            self.0
+                .get_record(record_id)
+                .await
+                .map_err(|err| err.erase())
+        })
+    }
+
+    fn create_record<'a>(
+        &'a self,
+        _: &'a DomainName,
+        _: &'a RecordData,
+        _: u64,
+    ) -> BoxFuture<'a, Result<Record, CreateRecordError<DynError>>> {
+        Box::pin(async { Err(CreateRecordError::Custom(DynError::Unsupported)) })
+    }
+
+    fn delete_record<'a>(
+        &'a self,
+        _: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteRecordError<DynError>>> {
+        Box::pin(async { Err(DeleteRecordError::Custom(DynError::Unsupported)) })
+    }
+
+    fn supports_record_management(&self) -> bool {
+        false
+    }
+}
+
+/// A zone supporting record creation and deletion.
+struct ManagedZone<Z>(BasicZone<Z>);
+
+impl<Z> ErasedZone for ManagedZone<Z>
+where
+    Z: CreateRecord + DeleteRecord,
+    Z::CustomRetrieveError: StdErr + Send + Sync + 'static,
+    Z::CustomCreateError: StdErr + Send + Sync + 'static,
+    Z::CustomDeleteError: StdErr + Send + Sync + 'static,
+{
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.0.domain()
+    }
+
+    fn list_records(&self) -> BoxFuture<'_, Result<Vec<Record>, RetrieveRecordError<DynError>>> {
+        self.0.list_records()
+    }
+
+    fn get_record<'a>(
+        &'a self,
+        record_id: &'a str,
+    ) -> BoxFuture<'a, Result<Record, RetrieveRecordError<DynError>>> {
+        self.0.get_record(record_id)
+    }
+
+    fn create_record<'a>(
+        &'a self,
+        host: &'a DomainName,
+        data: &'a RecordData,
+        ttl: u64,
+    ) -> BoxFuture<'a, Result<Record, CreateRecordError<DynError>>> {
+        Box::pin(async move {
+            (self.0)
+                .0
+                .create_record(host, data, ttl)
+                .await
+                .map_err(|err| err.erase())
+        })
+    }
+
+    fn delete_record<'a>(
+        &'a self,
+        record_id: &'a str,
+    ) -> BoxFuture<'a, Result<(), DeleteRecordError<DynError>>> {
+        Box::pin(async move {
+            (self.0)
+                .0
+                .delete_record(record_id)
+                .await
+                .map_err(|err| err.erase())
+        })
+    }
+
+    fn supports_record_management(&self) -> bool {
+        true
+    }
+}
+
+/// Represents a zone of a [`DynProvider`].
+pub struct DynZone {
+    inner: Box<dyn ErasedZone>,
+}
+
+impl DynZone {
+    /// Wraps a zone supporting record management.
+    pub fn new<Z>(zone: Z) -> Self
+    where
+        Z: CreateRecord + DeleteRecord + 'static,
+        Z::CustomRetrieveError: StdErr + Send + Sync + 'static,
+        Z::CustomCreateError: StdErr + Send + Sync + 'static,
+        Z::CustomDeleteError: StdErr + Send + Sync + 'static,
+    {
+        Self {
+            inner: Box::new(ManagedZone(BasicZone(zone))),
+        }
+    }
+
+    /// Wraps a zone which only supports retrieving records.
+    pub fn read_only<Z>(zone: Z) -> Self
+    where
+        Z: Zone + 'static,
+        Z::CustomRetrieveError: StdErr + Send + Sync + 'static,
+    {
+        Self {
+            inner: Box::new(BasicZone(zone)),
+        }
+    }
+
+    /// Returns whether the wrapped zone supports creating and deleting records.
+    pub fn supports_record_management(&self) -> bool {
+        self.inner.supports_record_management()
+    }
+}
+
+impl Debug for DynZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynZone")
+            .field("id", &self.id())
+            .field("domain", self.domain())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Zone for DynZone {
+    type CustomRetrieveError = DynError;
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.inner.domain()
+    }
+
+    async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<DynError>> {
+        self.inner.list_records().await
+    }
+
+    async fn get_record(&self, record_id: &str) -> Result<Record, RetrieveRecordError<DynError>> {
+        self.inner.get_record(record_id).await
+    }
+}
+
+impl CreateRecord for DynZone {
+    type CustomCreateError = DynError;
+
+    /// Creates a record, failing with [`DynError::Unsupported`] if the wrapped zone does not support it.
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<DynError>> {
+        self.inner.create_record(host, data, ttl).await
+    }
+}
+
+impl DeleteRecord for DynZone {
+    type CustomDeleteError = DynError;
+
+    /// Deletes a record, failing with [`DynError::Unsupported`] if the wrapped zone does not support it.
+    async fn delete_record(&self, record_id: &str) -> Result<(), DeleteRecordError<DynError>> {
+        self.inner.delete_record(record_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug, Error)]
+    #[error("request failed")]
+    struct TestError(#[source] io::Error);
+
+    struct TestProvider;
+
+    impl Provider for TestProvider {
+        type Zone = TestZone;
+        type CustomRetrieveError = TestError;
+
+        async fn list_zones(&self) -> Result<Vec<TestZone>, RetrieveZoneError<TestError>> {
+            Err(RetrieveZoneError::Custom(TestError(io::Error::other(
+                "timeout",
+            ))))
+        }
+
+        async fn get_zone(&self, _: &str) -> Result<TestZone, RetrieveZoneError<TestError>> {
+            Err(RetrieveZoneError::NotFound)
+        }
+    }
+
+    struct TestZone;
+
+    impl Zone for TestZone {
+        type CustomRetrieveError = TestError;
+
+        fn id(&self) -> &str {
+            "test"
+        }
+
+        fn domain(&self) -> &DomainName {
+            unreachable!()
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<TestError>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_record(&self, _: &str) -> Result<Record, RetrieveRecordError<TestError>> {
+            Err(RetrieveRecordError::NotFound)
+        }
+    }
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    #[tokio::test]
+    async fn custom_errors_keep_their_source() {
+        let provider = assert_send(DynProvider::read_only(TestProvider));
+        let Err(RetrieveZoneError::Custom(err)) = assert_send(provider.list_zones()).await else {
+            panic!("expected a custom error");
+        };
+
+        assert_eq!(err.to_string(), "request failed");
+        assert_eq!(err.source().unwrap().to_string(), "timeout");
+        assert!(matches!(
+            provider.create_zone(&DomainName::apex()).await,
+            Err(CreateZoneError::Custom(DynError::Unsupported))
+        ));
+    }
+}
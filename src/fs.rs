@@ -0,0 +1,33 @@
+//! Utilities shared by modules persisting state in local files.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+/// Replaces the contents of the file at `path` atomically.
+///
+/// The contents are written to a temporary file `.<file name>.<pid>.tmp` in the same directory which is then renamed
+/// over the file, so readers never see a partially written file. The temporary file is removed if writing fails.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let write = || -> io::Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
@@ -0,0 +1,153 @@
+use std::error::Error;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client as HttpClient, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+pub const GANDI_API_URL: &str = "https://api.gandi.net/v5/livedns";
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(access_token: &str, base_url: &str) -> Result<Self, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value = HeaderValue::from_str(&format!("Bearer {}", access_token))?;
+        auth_value.set_sensitive(true);
+        headers.append(AUTHORIZATION, auth_value);
+
+        let http_client = HttpClient::builder().default_headers(headers).build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    pub async fn retrieve_domains(
+        &self,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<Domain>, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains?page={}&per_page={}",
+                self.base_url, page, per_page
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_domain(&self, fqdn: &str) -> Result<Domain, reqwest::Error> {
+        self.http_client
+            .get(format!("{}/domains/{}", self.base_url, fqdn))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_rrsets(
+        &self,
+        fqdn: &str,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<RRset>, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains/{}/records?page={}&per_page={}",
+                self.base_url, fqdn, page, per_page
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Retrieves the RRset with the given name and type, returning [`None`] if it does not exist.
+    pub async fn retrieve_rrset(
+        &self,
+        fqdn: &str,
+        name: &str,
+        typ: &str,
+    ) -> Result<Option<RRset>, reqwest::Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/domains/{}/records/{}/{}",
+                self.base_url, fqdn, name, typ
+            ))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        response.error_for_status()?.json().await.map(Some)
+    }
+
+    /// Creates or replaces the RRset with the given name and type.
+    pub async fn replace_rrset(
+        &self,
+        fqdn: &str,
+        name: &str,
+        typ: &str,
+        request: &RRsetRequest,
+    ) -> Result<(), reqwest::Error> {
+        self.http_client
+            .put(format!(
+                "{}/domains/{}/records/{}/{}",
+                self.base_url, fqdn, name, typ
+            ))
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+
+    pub async fn delete_rrset(
+        &self,
+        fqdn: &str,
+        name: &str,
+        typ: &str,
+    ) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!(
+                "{}/domains/{}/records/{}/{}",
+                self.base_url, fqdn, name, typ
+            ))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub fqdn: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RRset {
+    pub rrset_name: String,
+    pub rrset_type: String,
+    pub rrset_ttl: Option<u64>,
+    pub rrset_values: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct RRsetRequest {
+    pub rrset_values: Vec<String>,
+    pub rrset_ttl: u64,
+}
@@ -0,0 +1,391 @@
+//! Provider implementation for [Gandi LiveDNS](https://www.gandi.net/domain/dns).
+//!
+//! Requests are authenticated using a personal access token with the permission to manage the domains' technical configuration.
+//! LiveDNS manages records as RRsets, so every value is represented as a [`Record`] with a [synthetic ID](crate::rrset).
+//! Zones belong to registered domains and can thus neither be created nor deleted using this provider.
+
+use std::{error::Error as StdErr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    rrset::{self, parse_record_id, record_id},
+    CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName, Provider, Record,
+    RecordData, RetrieveRecordError, RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 21] = &[
+    "A",
+    "AAAA",
+    "ALIAS",
+    "CAA",
+    "CDS",
+    "CNAME",
+    "DNAME",
+    "DS",
+    "HTTPS",
+    "KEY",
+    "LOC",
+    "MX",
+    "NAPTR",
+    "NS",
+    "OPENPGPKEY",
+    "PTR",
+    "RP",
+    "SPF",
+    "SRV",
+    "SSHFP",
+    "TXT",
+];
+
+/// LiveDNS expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+/// The TTL LiveDNS applies to RRsets created without one.
+const DEFAULT_TTL: u64 = 10800;
+
+const PAGE_SIZE: u32 = 100;
+
+const PROVIDER_NAME: &str = "gandi";
+
+#[derive(Debug, Clone)]
+pub struct GandiProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl GandiProvider {
+    pub fn new(access_token: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(access_token, api::GANDI_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://api.gandi.net/v5/livedns`, e.g. a mock server.
+    pub fn with_base_url(access_token: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(access_token, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for GandiProvider {
+    type Zone = GandiZone;
+    type CustomRetrieveError = reqwest::Error;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domain = self
+            .api_client
+            .retrieve_domain(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(GandiZone::new(self.api_client.clone(), domain))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        // LiveDNS does not report the number of pages, so a page shorter than the page size is the last one.
+        let domains = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            let page = page.unwrap_or(1);
+            self.api_client
+                .retrieve_domains(page, PAGE_SIZE)
+                .await
+                .map(|domains| Page {
+                    next: (domains.len() == PAGE_SIZE as usize).then_some(page + 1),
+                    items: domains,
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| GandiZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+/// Represents a domain managed by Gandi LiveDNS.
+///
+/// The zone ID is the domain itself, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct GandiZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    domain: DomainName,
+}
+
+impl GandiZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let domain = DomainName::from_raw(&repr.fqdn).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+
+    async fn find_rrset(
+        &self,
+        name: &DomainName,
+        typ: &str,
+    ) -> Result<Option<api::RRset>, reqwest::Error> {
+        let Some(host) = name.relative_to(&self.domain) else {
+            return Ok(None);
+        };
+
+        self.api_client
+            .retrieve_rrset(&self.repr.fqdn, &host.to_string(), typ)
+            .await
+    }
+
+    fn to_records(&self, set: api::RRset) -> Vec<Record> {
+        let host = DomainName::from_raw(&set.rrset_name);
+        let name = self.fqdn(&host);
+
+        rrset::to_records(
+            &name,
+            &host,
+            set.rrset_values
+                .iter()
+                .map(|value| RecordData::from_raw(&set.rrset_type, value)),
+            set.rrset_ttl.unwrap_or(DEFAULT_TTL),
+        )
+    }
+}
+
+impl Zone for GandiZone {
+    type CustomRetrieveError = reqwest::Error;
+
+    fn id(&self) -> &str {
+        &self.repr.fqdn
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let rrsets = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            let page = page.unwrap_or(1);
+            self.api_client
+                .retrieve_rrsets(&self.repr.fqdn, page, PAGE_SIZE)
+                .await
+                .map(|rrsets| Page {
+                    next: (rrsets.len() == PAGE_SIZE as usize).then_some(page + 1),
+                    items: rrsets,
+                })
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(rrsets
+            .into_iter()
+            .flat_map(|rrset| self.to_records(rrset))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(RetrieveRecordError::NotFound)?;
+
+        let set = self
+            .find_rrset(&name, data.get_type())
+            .await
+            .map_err(RetrieveRecordError::from_status)?
+            .ok_or(RetrieveRecordError::NotFound)?;
+
+        rrset::find_record(self.to_records(set), record_id).ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for GandiZone {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let name = self.fqdn(&host);
+
+        let existing = self
+            .find_rrset(&name, typ)
+            .await
+            .map_err(CreateRecordError::from_status)?
+            .map(|set| set.rrset_values)
+            .unwrap_or_default();
+
+        let request = api::RRsetRequest {
+            rrset_values: rrset::add_value(
+                existing,
+                data,
+                data.get_value_as(TXT_FORMAT),
+                |value| RecordData::from_raw(typ, value),
+            ),
+            rrset_ttl: ttl,
+        };
+        self.api_client
+            .replace_rrset(&self.repr.fqdn, &host.to_string(), typ, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(Record {
+            id: record_id(&name, data),
+            host,
+            data: data.clone(),
+            ttl,
+        })
+    }
+}
+
+impl DeleteRecord for GandiZone {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(DeleteRecordError::NotFound)?;
+        let typ = data.get_type();
+
+        let existing = self
+            .find_rrset(&name, typ)
+            .await
+            .map_err(DeleteRecordError::from_status)?
+            .ok_or(DeleteRecordError::NotFound)?;
+        let host = DomainName::from_raw(&existing.rrset_name).to_string();
+
+        let remaining = rrset::remove_value(existing.rrset_values, &data, |value| {
+            RecordData::from_raw(typ, value)
+        })
+        .ok_or(DeleteRecordError::NotFound)?;
+
+        if remaining.is_empty() {
+            return self
+                .api_client
+                .delete_rrset(&self.repr.fqdn, &host, typ)
+                .await
+                .map_err(DeleteRecordError::from_status);
+        }
+
+        let request = api::RRsetRequest {
+            rrset_values: remaining,
+            rrset_ttl: existing.rrset_ttl.unwrap_or(DEFAULT_TTL),
+        };
+        self.api_client
+            .replace_rrset(&self.repr.fqdn, &host, typ, &request)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn rrsets_are_replaced_and_deleted() {
+        let server = MockServer::start(|request| {
+            if request.header("authorization") != Some("Bearer access-token") {
+                return Response::json(401, r#"{"code":401,"message":"Unauthorized"}"#);
+            }
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/domains?page=1&per_page=100") => {
+                    Response::json(200, r#"[{"fqdn":"example.com"}]"#)
+                }
+                ("GET", "/domains/example.com/records?page=1&per_page=100") => Response::json(
+                    200,
+                    r#"[{"rrset_name":"@","rrset_type":"MX","rrset_ttl":3600,"rrset_values":["10 mail.example.com."]},{"rrset_name":"www","rrset_type":"TXT","rrset_values":["\"hello\""]}]"#,
+                ),
+                ("GET", "/domains/example.com/records/www/TXT") => Response::json(
+                    200,
+                    r#"{"rrset_name":"www","rrset_type":"TXT","rrset_values":["\"hello\""]}"#,
+                ),
+                ("PUT", "/domains/example.com/records/www/TXT")
+                | ("DELETE", "/domains/example.com/records/www/TXT") => {
+                    Response::json(201, r#"{"message":"DNS Record Created"}"#)
+                }
+                _ => Response::json(404, r#"{"code":404,"message":"Not Found"}"#),
+            }
+        })
+        .await;
+
+        let unauthorized = GandiProvider::with_base_url("wrong-token", server.url()).unwrap();
+        assert!(matches!(
+            unauthorized.list_zones().await,
+            Err(RetrieveZoneError::Unauthorized)
+        ));
+
+        let provider = GandiProvider::with_base_url("access-token", server.url()).unwrap();
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(zones.len(), 1);
+        let zone = &zones[0];
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.host.to_string(), &record.data, record.ttl))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "@".to_owned(),
+                    &RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mail.example.com."),
+                    },
+                    3600
+                ),
+                (
+                    "www".to_owned(),
+                    &RecordData::TXT(vec!["hello".to_owned()]),
+                    DEFAULT_TTL
+                ),
+            ]
+        );
+
+        let data = RecordData::TXT(vec!["world".to_owned()]);
+        zone.create_record(&DomainName::from_raw("www"), &data, 300)
+            .await
+            .unwrap();
+        // Deleting the only value of an RRset deletes the RRset.
+        zone.delete_record(&records[1].id).await.unwrap();
+
+        let requests = server.requests();
+        let changes = requests
+            .iter()
+            .filter(|request| request.method != "GET")
+            .map(|request| (request.method.as_str(), request.body.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "PUT",
+                    r#"{"rrset_values":["\"hello\"","\"world\""],"rrset_ttl":300}"#
+                ),
+                ("DELETE", ""),
+            ]
+        );
+    }
+}
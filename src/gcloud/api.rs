@@ -0,0 +1,243 @@
+use std::error::Error as StdErr;
+
+use reqwest::{Client as HttpClient, Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::auth::TokenSource;
+use crate::http::StatusError;
+#[cfg(feature = "retry")]
+use crate::layer::Transient;
+
+pub const CLOUD_DNS_API_URL: &str = "https://dns.googleapis.com/dns/v1";
+pub const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// Represents an error returned by the Cloud DNS API client.
+#[derive(Debug, Error)]
+pub enum CloudDnsError {
+    /// Indicates that the HTTP request failed or Cloud DNS responded with an error status.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that no access token could be obtained for the service account.
+    #[error("could not obtain an access token: {0}")]
+    Authentication(reqwest::Error),
+
+    /// Indicates that the JWT assertion could not be signed using the service account key.
+    #[error("could not sign the token assertion: {0}")]
+    Signing(rsa::signature::Error),
+}
+
+impl CloudDnsError {
+    /// Returns the HTTP status code Cloud DNS responded with.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            CloudDnsError::Http(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the request was rejected because of missing or insufficient credentials.
+    pub fn is_unauthorized(&self) -> bool {
+        match self {
+            CloudDnsError::Authentication(err) => {
+                err.status().is_some_and(|status| status.is_client_error())
+            }
+            _ => matches!(
+                self.status(),
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            ),
+        }
+    }
+}
+
+impl StatusError for CloudDnsError {
+    /// Returns the status code Cloud DNS responded with, treating a rejected token request as `401 Unauthorized`.
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            CloudDnsError::Authentication(_) if self.is_unauthorized() => {
+                Some(StatusCode::UNAUTHORIZED)
+            }
+            _ => CloudDnsError::status(self),
+        }
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for CloudDnsError {
+    fn is_transient(&self) -> bool {
+        match self {
+            CloudDnsError::Http(err) | CloudDnsError::Authentication(err) => err.is_transient(),
+            CloudDnsError::Signing(_) => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    http_client: HttpClient,
+    project_url: String,
+    token_source: TokenSource,
+}
+
+impl Client {
+    pub fn new(
+        api_url: &str,
+        project_id: &str,
+        token_source: TokenSource,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let http_client = HttpClient::builder().build()?;
+        Ok(Self {
+            http_client,
+            project_url: format!("{}/projects/{}", api_url.trim_end_matches('/'), project_id),
+            token_source,
+        })
+    }
+
+    async fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, CloudDnsError> {
+        let token = self.token_source.token().await?;
+        Ok(self
+            .http_client
+            .request(method, format!("{}{}", self.project_url, path))
+            .bearer_auth(token))
+    }
+
+    pub async fn list_managed_zones(
+        &self,
+        page_token: Option<&str>,
+        max_results: u32,
+    ) -> Result<ListManagedZonesResponse, CloudDnsError> {
+        let mut query = vec![("maxResults", max_results.to_string())];
+        if let Some(page_token) = page_token {
+            query.push(("pageToken", page_token.to_owned()));
+        }
+
+        Ok(self
+            .request(Method::GET, "/managedZones")
+            .await?
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn get_managed_zone(&self, zone: &str) -> Result<ManagedZone, CloudDnsError> {
+        Ok(self
+            .request(Method::GET, &format!("/managedZones/{}", zone))
+            .await?
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn create_managed_zone(
+        &self,
+        zone: &ManagedZone,
+    ) -> Result<ManagedZone, CloudDnsError> {
+        Ok(self
+            .request(Method::POST, "/managedZones")
+            .await?
+            .json(zone)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn delete_managed_zone(&self, zone: &str) -> Result<(), CloudDnsError> {
+        self.request(Method::DELETE, &format!("/managedZones/{}", zone))
+            .await?
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Lists the record sets of a zone, optionally filtered by their name and type.
+    pub async fn list_resource_record_sets(
+        &self,
+        zone: &str,
+        filter: Option<(&str, &str)>,
+        page_token: Option<&str>,
+        max_results: u32,
+    ) -> Result<ListResourceRecordSetsResponse, CloudDnsError> {
+        let mut query = vec![("maxResults", max_results.to_string())];
+        if let Some((name, typ)) = filter {
+            query.push(("name", name.to_owned()));
+            query.push(("type", typ.to_owned()));
+        }
+        if let Some(page_token) = page_token {
+            query.push(("pageToken", page_token.to_owned()));
+        }
+
+        Ok(self
+            .request(Method::GET, &format!("/managedZones/{}/rrsets", zone))
+            .await?
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    pub async fn create_change(&self, zone: &str, change: &Change) -> Result<(), CloudDnsError> {
+        self.request(Method::POST, &format!("/managedZones/{}/changes", zone))
+            .await?
+            .json(change)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedZone {
+    pub name: String,
+    pub dns_name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListManagedZonesResponse {
+    #[serde(default)]
+    pub managed_zones: Vec<ManagedZone>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceRecordSet {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+    #[serde(default)]
+    pub rrdatas: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_policy: Option<serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceRecordSetsResponse {
+    #[serde(default)]
+    pub rrsets: Vec<ResourceRecordSet>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize)]
+pub struct Change {
+    pub additions: Vec<ResourceRecordSet>,
+    pub deletions: Vec<ResourceRecordSet>,
+}
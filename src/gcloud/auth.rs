@@ -0,0 +1,124 @@
+//! OAuth2 authentication of service accounts using the JWT bearer flow.
+//!
+//! See <https://developers.google.com/identity/protocols/oauth2/service-account#httprest>.
+
+use std::{
+    error::Error as StdErr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::Client as HttpClient;
+use rsa::{
+    pkcs1v15::SigningKey,
+    pkcs8::DecodePrivateKey,
+    signature::{SignatureEncoding, Signer},
+    RsaPrivateKey,
+};
+use serde::Serialize;
+use sha2::Sha256;
+
+use super::{CloudDnsError, ServiceAccountKey};
+use crate::oauth::{TokenCache, TokenResponse};
+
+const SCOPE: &str = "https://www.googleapis.com/auth/ndev.clouddns.readwrite";
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// The lifetime requested for assertions, Google accepts at most one hour.
+const ASSERTION_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Exchanges self-signed JWT assertions of a service account for OAuth2 access tokens and caches them until shortly before they expire.
+#[derive(Debug)]
+pub struct TokenSource {
+    http_client: HttpClient,
+    client_email: String,
+    signing_key: SigningKey<Sha256>,
+    token_url: String,
+    cache: TokenCache,
+}
+
+impl TokenSource {
+    pub fn new(key: &ServiceAccountKey, token_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let private_key = RsaPrivateKey::from_pkcs8_pem(&key.private_key)?;
+
+        Ok(Self {
+            http_client: HttpClient::builder().build()?,
+            client_email: key.client_email.clone(),
+            signing_key: SigningKey::<Sha256>::new(private_key),
+            token_url: token_url.to_owned(),
+            cache: TokenCache::default(),
+        })
+    }
+
+    /// Returns a valid access token, requesting a new one if the cached one is missing or about to expire.
+    pub async fn token(&self) -> Result<String, CloudDnsError> {
+        self.cache.get(|| self.request_token()).await
+    }
+
+    async fn request_token(&self) -> Result<TokenResponse, CloudDnsError> {
+        self.http_client
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", GRANT_TYPE),
+                ("assertion", &self.assertion()?),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(CloudDnsError::Authentication)?
+            .json()
+            .await
+            .map_err(CloudDnsError::Authentication)
+    }
+
+    /// Creates a JWT assertion signed with RS256.
+    fn assertion(&self) -> Result<String, CloudDnsError> {
+        let issued_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let header = JwtHeader {
+            alg: "RS256",
+            typ: "JWT",
+        };
+        let claims = JwtClaims {
+            iss: &self.client_email,
+            scope: SCOPE,
+            aud: &self.token_url,
+            iat: issued_at,
+            exp: issued_at + ASSERTION_LIFETIME.as_secs(),
+        };
+
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header).unwrap()),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap())
+        );
+        let signature = self
+            .signing_key
+            .try_sign(message.as_bytes())
+            .map_err(CloudDnsError::Signing)?;
+
+        Ok(format!(
+            "{}.{}",
+            message,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        ))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JwtHeader {
+    alg: &'static str,
+    typ: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct JwtClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
@@ -2,26 +2,65 @@ use std::{borrow::Cow, collections::HashMap, error::Error};
 
 use reqwest::{
     header::{HeaderMap, HeaderValue},
-    Client as HttpClient,
+    Client as HttpClient, RequestBuilder, Response,
 };
 use serde::Deserialize;
 
-const HETZNER_API_URL: &str = "https://dns.hetzner.com/api/v1";
+pub const HETZNER_API_URL: &str = "https://dns.hetzner.com/api/v1";
 
 #[derive(Debug, Clone)]
 pub struct Client {
     http_client: HttpClient,
+    base_url: String,
 }
 
 impl Client {
-    pub fn new(api_key: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn new(api_key: &str, base_url: &str) -> Result<Self, Box<dyn Error>> {
         let mut headers = HeaderMap::new();
         let mut auth_value = HeaderValue::from_str(api_key)?;
         auth_value.set_sensitive(true);
         headers.append("Auth-API-Token", auth_value);
 
         let http_client = HttpClient::builder().default_headers(headers).build()?;
-        Ok(Self { http_client })
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    /// Sends a request, failing for error status codes.
+    ///
+    /// With the `tracing` feature enabled, the request is wrapped in a `hetzner_request` span carrying the method,
+    /// the path (without the query), the requested page and the status code. Failed requests are not retried.
+    /// The API token is only ever sent in a sensitive header and therefore never recorded.
+    /// With the `metrics` feature enabled, responses with `429 Too Many Requests` are recorded as rate-limit hits.
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))] page: Option<u32>,
+    ) -> Result<Response, reqwest::Error> {
+        let request = request.build()?;
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "hetzner_request",
+            method = %request.method(),
+            path = request.url().path(),
+            page,
+            status = tracing::field::Empty,
+        );
+        let response = self.http_client.execute(request);
+        #[cfg(feature = "tracing")]
+        let response = tracing::Instrument::instrument(response, span.clone());
+        let response = response.await?;
+
+        #[cfg(feature = "tracing")]
+        span.record("status", response.status().as_u16());
+        #[cfg(feature = "metrics")]
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            crate::metrics::recorder().record_rate_limit(super::PROVIDER_NAME);
+        }
+        response.error_for_status()
     }
 
     pub async fn retrieve_zones(
@@ -29,45 +68,39 @@ impl Client {
         page: u32,
         per_page: u32,
     ) -> Result<ZonesResponse, reqwest::Error> {
-        self.http_client
-            .get(format!(
-                "{}/zones?page={}&per_page={}",
-                HETZNER_API_URL, page, per_page
-            ))
-            .send()
+        let request = self.http_client.get(format!(
+            "{}/zones?page={}&per_page={}",
+            self.base_url, page, per_page
+        ));
+        self.send(request, Some(page))
             .await?
             .json::<ZonesResponse>()
             .await
     }
 
     pub async fn retrieve_zone(&self, zone_id: &str) -> Result<ZoneResponse, reqwest::Error> {
-        self.http_client
-            .get(format!("{}/zones/{}", HETZNER_API_URL, zone_id))
-            .send()
-            .await?
-            .json()
-            .await
+        let request = self
+            .http_client
+            .get(format!("{}/zones/{}", self.base_url, zone_id));
+        self.send(request, None).await?.json().await
     }
 
     pub async fn create_zone(&self, domain: &str) -> Result<ZoneResponse, reqwest::Error> {
         let mut request_body = HashMap::new();
         request_body.insert("name", domain);
 
-        self.http_client
-            .post(format!("{}/zones", HETZNER_API_URL))
-            .json(&request_body)
-            .send()
-            .await?
-            .json()
-            .await
+        let request = self
+            .http_client
+            .post(format!("{}/zones", self.base_url))
+            .json(&request_body);
+        self.send(request, None).await?.json().await
     }
 
     pub async fn delete_zone(&self, zone_id: &str) -> Result<(), reqwest::Error> {
-        self.http_client
-            .delete(format!("{}/zones/{}", HETZNER_API_URL, zone_id))
-            .send()
-            .await
-            .map(|_| ())
+        let request = self
+            .http_client
+            .delete(format!("{}/zones/{}", self.base_url, zone_id));
+        self.send(request, None).await.map(|_| ())
     }
 
     pub async fn retrieve_records(
@@ -76,24 +109,18 @@ impl Client {
         page: u32,
         per_page: u32,
     ) -> Result<RecordsResponse, reqwest::Error> {
-        self.http_client
-            .get(format!(
-                "{}/records?zone_id={}&page={}&per_page={}",
-                HETZNER_API_URL, zone_id, page, per_page
-            ))
-            .send()
-            .await?
-            .json()
-            .await
+        let request = self.http_client.get(format!(
+            "{}/records?zone_id={}&page={}&per_page={}",
+            self.base_url, zone_id, page, per_page
+        ));
+        self.send(request, Some(page)).await?.json().await
     }
 
     pub async fn retrieve_record(&self, record_id: &str) -> Result<RecordResponse, reqwest::Error> {
-        self.http_client
-            .get(format!("{}/records/{}", HETZNER_API_URL, record_id))
-            .send()
-            .await?
-            .json()
-            .await
+        let request = self
+            .http_client
+            .get(format!("{}/records/{}", self.base_url, record_id));
+        self.send(request, None).await?.json().await
     }
 
     pub async fn create_record(
@@ -115,21 +142,18 @@ impl Client {
             request_body.insert("ttl", Cow::Owned(ttl_str.to_string()));
         }
 
-        self.http_client
-            .post(format!("{}/records", HETZNER_API_URL))
-            .json(&request_body)
-            .send()
-            .await?
-            .json()
-            .await
+        let request = self
+            .http_client
+            .post(format!("{}/records", self.base_url))
+            .json(&request_body);
+        self.send(request, None).await?.json().await
     }
 
     pub async fn delete_record(&self, record_id: &str) -> Result<(), reqwest::Error> {
-        self.http_client
-            .delete(format!("{}/records/{}", HETZNER_API_URL, record_id))
-            .send()
-            .await
-            .map(|_| ())
+        let request = self
+            .http_client
+            .delete(format!("{}/records/{}", self.base_url, record_id));
+        self.send(request, None).await.map(|_| ())
     }
 }
 
@@ -1,34 +1,39 @@
 use std::{error::Error as StdErr, sync::Arc};
 
 use crate::{
+    http::{collect_pages, FromStatus, Page},
     CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
-    DeleteZone, DeleteZoneError, Provider, Record, RecordData, RetrieveRecordError,
-    RetrieveZoneError, Zone,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
 };
 
 mod api;
 
-const SUPPORTED_RECORD_TYPES: &[&str; 14] = &[
-    "A", "AAAA", "NS", "MX", "CNAME", "RP", "TXT", "SOA", "HINFO", "SRV", "DANE", "TLSA", "DS",
-    "CAA",
+const SUPPORTED_RECORD_TYPES: &[&str; 15] = &[
+    "A", "AAAA", "PTR", "NS", "MX", "CNAME", "RP", "TXT", "SOA", "HINFO", "SRV", "DANE", "TLSA",
+    "DS", "CAA",
 ];
 
-#[derive(Debug)]
+/// Hetzner expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+const PAGE_SIZE: u32 = 100;
+
+const PROVIDER_NAME: &str = "hetzner";
+
+#[derive(Debug, Clone)]
 pub struct HetznerProvider {
     api_client: Arc<api::Client>,
 }
 
-impl Clone for HetznerProvider {
-    fn clone(&self) -> Self {
-        return HetznerProvider {
-            api_client: Arc::from(self.api_client.as_ref().clone()),
-        };
-    }
-}
-
 impl HetznerProvider {
     pub fn new(api_key: &str) -> Result<Self, Box<dyn StdErr>> {
-        let api_client = api::Client::new(api_key)?;
+        Self::with_base_url(api_key, api::HETZNER_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://dns.hetzner.com/api/v1`, e.g. a mock server.
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_key, base_url)?;
         Ok(Self {
             api_client: Arc::new(api_client),
         })
@@ -47,81 +52,35 @@ impl Provider for HetznerProvider {
             .api_client
             .retrieve_zone(zone_id)
             .await
-            .map_err(|err| {
-                if err.is_status() {
-                    return match err.status().unwrap() {
-                        reqwest::StatusCode::NOT_FOUND => RetrieveZoneError::NotFound,
-                        reqwest::StatusCode::UNAUTHORIZED => RetrieveZoneError::Unauthorized,
-                        _ => RetrieveZoneError::Custom(err),
-                    };
-                }
-                RetrieveZoneError::Custom(err)
-            })?;
-
-        Ok(HetznerZone {
-            api_client: self.api_client.clone(),
-            repr: response.zone,
-        })
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(HetznerZone::new(self.api_client.clone(), response.zone))
     }
 
     async fn list_zones(
         &self,
     ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
-        let mut zones = Vec::new();
-        let mut total: Option<usize> = None;
-        let mut page = 1;
-
-        loop {
-            let result =
-                self.api_client
-                    .retrieve_zones(page, 100)
-                    .await
-                    .map_err(|err| {
-                        if err.is_status() {
-                            return match err.status().unwrap() {
-                                reqwest::StatusCode::NOT_FOUND => RetrieveZoneError::NotFound,
-                                reqwest::StatusCode::UNAUTHORIZED
-                                | reqwest::StatusCode::FORBIDDEN => RetrieveZoneError::Unauthorized,
-                                _ => RetrieveZoneError::Custom(err),
-                            };
-                        }
-                        RetrieveZoneError::Custom(err)
-                    });
-
-            match result {
-                Ok(response) => {
-                    if total.is_none() {
-                        total = Some(response.meta.pagination.total_entries as usize);
-                    }
-
-                    zones.append(
-                        response
-                            .zones
-                            .into_iter()
-                            .map(|zone| HetznerZone {
-                                api_client: self.api_client.clone(),
-                                repr: zone,
-                            })
-                            .collect::<Vec<HetznerZone>>()
-                            .as_mut(),
-                    );
-                }
-                Err(err) => {
-                    if let RetrieveZoneError::NotFound = err {
-                        break;
-                    }
-                    return Err(err);
-                }
+        let zones = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            let page = page.unwrap_or(1);
+            match self.api_client.retrieve_zones(page, PAGE_SIZE).await {
+                Ok(response) => Ok(Page {
+                    items: response.zones,
+                    next: (page < response.meta.pagination.last_page).then_some(page + 1),
+                }),
+                // Hetzner responds with 404 Not Found if there are no zones at all.
+                Err(err) if err.status() == Some(reqwest::StatusCode::NOT_FOUND) => Ok(Page {
+                    items: Vec::new(),
+                    next: None,
+                }),
+                Err(err) => Err(RetrieveZoneError::from_status(err)),
             }
+        })
+        .await?;
 
-            if total.is_some_and(|t| zones.len() == t) {
-                break;
-            }
-
-            page += 1;
-        }
-
-        Ok(zones)
+        Ok(zones
+            .into_iter()
+            .map(|zone| HetznerZone::new(self.api_client.clone(), zone))
+            .collect())
     }
 }
 
@@ -130,23 +89,15 @@ impl CreateZone for HetznerProvider {
 
     async fn create_zone(
         &self,
-        domain: &str,
+        domain: &DomainName,
     ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
-        let response = self.api_client.create_zone(domain).await.map_err(|err| {
-            if err.is_status() {
-                return match err.status().unwrap() {
-                    reqwest::StatusCode::UNAUTHORIZED => CreateZoneError::Unauthorized,
-                    reqwest::StatusCode::UNPROCESSABLE_ENTITY => CreateZoneError::InvalidDomainName,
-                    _ => CreateZoneError::Custom(err),
-                };
-            }
-            CreateZoneError::Custom(err)
-        })?;
+        let response = self
+            .api_client
+            .create_zone(domain.as_ascii())
+            .await
+            .map_err(CreateZoneError::from_status)?;
 
-        Ok(HetznerZone {
-            api_client: self.api_client.clone(),
-            repr: response.zone,
-        })
+        Ok(HetznerZone::new(self.api_client.clone(), response.zone))
     }
 }
 
@@ -157,16 +108,10 @@ impl DeleteZone for HetznerProvider {
         &self,
         zone_id: &str,
     ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
-        self.api_client.delete_zone(zone_id).await.map_err(|err| {
-            if err.is_status() {
-                return match err.status().unwrap() {
-                    reqwest::StatusCode::NOT_FOUND => DeleteZoneError::NotFound,
-                    reqwest::StatusCode::UNAUTHORIZED => DeleteZoneError::Unauthorized,
-                    _ => DeleteZoneError::Custom(err),
-                };
-            }
-            DeleteZoneError::Custom(err)
-        })
+        self.api_client
+            .delete_zone(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
     }
 }
 
@@ -174,6 +119,18 @@ impl DeleteZone for HetznerProvider {
 pub struct HetznerZone {
     api_client: Arc<api::Client>,
     repr: api::Zone,
+    domain: DomainName,
+}
+
+impl HetznerZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Zone) -> Self {
+        let domain = DomainName::from_raw(&repr.name).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
 }
 
 impl Zone for HetznerZone {
@@ -183,66 +140,38 @@ impl Zone for HetznerZone {
         &self.repr.id
     }
 
-    fn domain(&self) -> &str {
-        &self.repr.name
+    fn domain(&self) -> &DomainName {
+        &self.domain
     }
 
     async fn list_records(
         &self,
     ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
-        let mut records = Vec::new();
-        let mut total: Option<usize> = None;
-        let mut page = 1;
-
-        loop {
-            let result = self
+        let records = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            let page = page.unwrap_or(1);
+            match self
                 .api_client
-                .retrieve_records(&self.repr.id, page, 100)
+                .retrieve_records(&self.repr.id, page, PAGE_SIZE)
                 .await
-                .map_err(|err| {
-                    if err.is_status() {
-                        return match err.status().unwrap() {
-                            reqwest::StatusCode::NOT_FOUND => RetrieveRecordError::NotFound,
-                            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
-                                RetrieveRecordError::Unauthorized
-                            }
-                            _ => RetrieveRecordError::Custom(err),
-                        };
-                    }
-                    RetrieveRecordError::Custom(err)
-                });
-
-            match result {
-                Ok(response) => {
-                    if total.is_none() {
-                        total = Some(response.meta.pagination.total_entries as usize);
-                    }
-
-                    records.append(
-                        response
-                            .records
-                            .into_iter()
-                            .map(|record| record.into_generic(self.repr.ttl))
-                            .collect::<Vec<Record>>()
-                            .as_mut(),
-                    );
-                }
-                Err(err) => {
-                    if let RetrieveRecordError::NotFound = err {
-                        break;
-                    }
-                    return Err(err);
-                }
+            {
+                Ok(response) => Ok(Page {
+                    items: response.records,
+                    next: (page < response.meta.pagination.last_page).then_some(page + 1),
+                }),
+                // Hetzner responds with 404 Not Found if there are no records at all.
+                Err(err) if err.status() == Some(reqwest::StatusCode::NOT_FOUND) => Ok(Page {
+                    items: Vec::new(),
+                    next: None,
+                }),
+                Err(err) => Err(RetrieveRecordError::from_status(err)),
             }
+        })
+        .await?;
 
-            if total.is_some_and(|t| records.len() == t) {
-                break;
-            }
-
-            page += 1;
-        }
-
-        Ok(records)
+        Ok(records
+            .into_iter()
+            .map(|record| record.into_generic(&self.domain, self.repr.ttl))
+            .collect())
     }
 
     async fn get_record(
@@ -253,22 +182,13 @@ impl Zone for HetznerZone {
             .api_client
             .retrieve_record(record_id)
             .await
-            .map_err(|err| {
-                if err.is_status() {
-                    return match err.status().unwrap() {
-                        reqwest::StatusCode::NOT_FOUND => RetrieveRecordError::NotFound,
-                        reqwest::StatusCode::UNAUTHORIZED => RetrieveRecordError::Unauthorized,
-                        _ => RetrieveRecordError::Custom(err),
-                    };
-                }
-                RetrieveRecordError::Custom(err)
-            })?;
+            .map_err(RetrieveRecordError::from_status)?;
 
         if response.record.zone_id != self.repr.id {
             return Err(RetrieveRecordError::NotFound);
         }
 
-        Ok(response.record.into_generic(self.repr.ttl))
+        Ok(response.record.into_generic(&self.domain, self.repr.ttl))
     }
 }
 
@@ -277,15 +197,19 @@ impl CreateRecord for HetznerZone {
 
     async fn create_record(
         &self,
-        host: &str,
+        host: &DomainName,
         data: &RecordData,
         ttl: u64,
     ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
         let typ = data.get_type();
-        if !SUPPORTED_RECORD_TYPES.iter().any(|r| *r == typ) {
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
             return Err(CreateRecordError::UnsupportedType);
         }
 
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
         let mut opt_ttl = None;
         if ttl != self.repr.ttl {
             opt_ttl = Some(ttl);
@@ -295,26 +219,15 @@ impl CreateRecord for HetznerZone {
             .api_client
             .create_record(
                 &self.repr.id,
-                host,
+                host.to_string().as_str(),
                 data.get_type(),
-                data.get_value().as_str(),
+                data.get_value_as(TXT_FORMAT).as_str(),
                 opt_ttl,
             )
             .await
-            .map_err(|err| {
-                if err.is_status() {
-                    return match err.status().unwrap() {
-                        reqwest::StatusCode::UNAUTHORIZED => CreateRecordError::Unauthorized,
-                        reqwest::StatusCode::UNPROCESSABLE_ENTITY => {
-                            CreateRecordError::InvalidRecord
-                        }
-                        _ => CreateRecordError::Custom(err),
-                    };
-                }
-                CreateRecordError::Custom(err)
-            })?;
-
-        Ok(response.record.into_generic(self.repr.ttl))
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(response.record.into_generic(&self.domain, self.repr.ttl))
     }
 }
 
@@ -334,26 +247,95 @@ impl DeleteRecord for HetznerZone {
         self.api_client
             .delete_record(record_id)
             .await
-            .map_err(|err| {
-                if err.is_status() {
-                    return match err.status().unwrap() {
-                        reqwest::StatusCode::NOT_FOUND => DeleteRecordError::NotFound,
-                        reqwest::StatusCode::UNAUTHORIZED => DeleteRecordError::Unauthorized,
-                        _ => DeleteRecordError::Custom(err),
-                    };
-                }
-                DeleteRecordError::Custom(err)
-            })
+            .map_err(DeleteRecordError::from_status)
     }
 }
 
 impl api::Record {
-    pub fn into_generic(self, default_ttl: u64) -> Record {
+    pub fn into_generic(self, zone_domain: &DomainName, default_ttl: u64) -> Record {
+        let host = DomainName::from_raw(&self.name);
         Record {
             id: self.id,
-            host: self.name,
+            host: host.relative_to(zone_domain).unwrap_or(host),
             data: RecordData::from_raw(self.typ.as_str(), self.value.as_str()),
             ttl: self.ttl.unwrap_or(default_ttl),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    fn zones_page(page: u32, name: &str) -> String {
+        format!(
+            r#"{{"meta":{{"pagination":{{"last_page":2,"page":{},"per_page":100,"total_entries":2}}}},"zones":[{{"id":"{}","name":"{}","status":"verified","ttl":3600}}]}}"#,
+            page, name, name
+        )
+    }
+
+    #[tokio::test]
+    async fn list_zones_pages_and_reports_rate_limited_requests() {
+        let throttled = AtomicBool::new(false);
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/zones?page=1&per_page=100" if !throttled.swap(true, Ordering::SeqCst) => {
+                Response::json(429, "{}").with_header("Retry-After", "0")
+            }
+            "/zones?page=1&per_page=100" => Response::json(200, &zones_page(1, "example.com")),
+            "/zones?page=2&per_page=100" => Response::json(200, &zones_page(2, "example.org")),
+            _ => Response::json(404, "{}"),
+        })
+        .await;
+
+        // Installs the recorder before the first request, pages and rate limits being recorded by the client.
+        #[cfg(feature = "metrics")]
+        let recorder = crate::metrics::test_recorder();
+
+        // Rate-limited requests are not retried by the provider itself.
+        let provider = HetznerProvider::with_base_url("api-key", server.url()).unwrap();
+        #[cfg(feature = "metrics")]
+        let provider = provider.layer(crate::layer::Metrics::new(PROVIDER_NAME));
+        let Err(RetrieveZoneError::Custom(err)) = provider.list_zones().await else {
+            panic!("expected the rate-limited request to fail");
+        };
+        assert_eq!(err.status(), Some(reqwest::StatusCode::TOO_MANY_REQUESTS));
+
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(
+            zones.iter().map(|zone| zone.id()).collect::<Vec<_>>(),
+            ["example.com", "example.org"]
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|request| request.method == "GET"
+            && request.header("Auth-API-Token") == Some("api-key")));
+        assert!(matches!(
+            provider.get_zone("missing").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+
+        #[cfg(feature = "metrics")]
+        {
+            let samples = recorder
+                .samples(PROVIDER_NAME)
+                .into_iter()
+                .filter(|sample| !sample.starts_with("libdns_call_duration_seconds"))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                samples,
+                [
+                    r#"libdns_calls_total{provider="hetzner",operation="get_zone"} 1"#,
+                    r#"libdns_calls_total{provider="hetzner",operation="list_zones"} 2"#,
+                    r#"libdns_errors_total{provider="hetzner",operation="get_zone",error="not_found"} 1"#,
+                    r#"libdns_errors_total{provider="hetzner",operation="list_zones",error="custom"} 1"#,
+                    r#"libdns_pages_total{provider="hetzner"} 2"#,
+                    r#"libdns_rate_limits_total{provider="hetzner"} 1"#,
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Utilities shared by provider implementations talking to REST APIs which report errors using HTTP status codes.
+
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "digitalocean",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "hetzner",
+    feature = "linode",
+    feature = "porkbun",
+    feature = "route53",
+    feature = "vultr"
+))]
+use std::future::Future;
+#[cfg(feature = "desec")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "desec")]
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use crate::{
+    CreateRecordError, CreateZoneError, DeleteRecordError, DeleteZoneError, RetrieveRecordError,
+    RetrieveZoneError,
+};
+
+/// Converts an error of a failed request into one of the crate's error types based on the HTTP status code,
+/// intended to be passed to [`Result::map_err`].
+///
+/// `404 Not Found` is mapped to `NotFound`, `401 Unauthorized` and `403 Forbidden` are mapped to `Unauthorized`
+/// and `400 Bad Request` and `422 Unprocessable Entity` are mapped to `InvalidRecord` or `InvalidDomainName` respectively.
+/// All other errors are wrapped as `Custom`.
+pub(crate) trait FromStatus {
+    /// The error of the failed request.
+    type Error;
+
+    fn from_status(err: Self::Error) -> Self;
+}
+
+/// Implemented by the errors of API clients carrying the HTTP status code of a failed request.
+pub(crate) trait StatusError {
+    /// Returns the status code of the response, [`None`] if the request failed without one.
+    fn status(&self) -> Option<StatusCode>;
+}
+
+impl StatusError for reqwest::Error {
+    fn status(&self) -> Option<StatusCode> {
+        reqwest::Error::status(self)
+    }
+}
+
+/// Returns whether a request answered with the given status may succeed when retried,
+/// i.e. it was rate-limited or failed because of a server error.
+#[cfg(feature = "retry")]
+pub(crate) fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(feature = "retry")]
+impl crate::layer::Transient for reqwest::Error {
+    fn is_transient(&self) -> bool {
+        self.is_timeout() || self.is_connect() || self.status().is_some_and(is_transient_status)
+    }
+}
+
+impl<E: StatusError> FromStatus for RetrieveZoneError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => RetrieveZoneError::NotFound,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                RetrieveZoneError::Unauthorized
+            }
+            _ => RetrieveZoneError::Custom(err),
+        }
+    }
+}
+
+impl<E: StatusError> FromStatus for CreateZoneError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => CreateZoneError::Unauthorized,
+            Some(StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY) => {
+                CreateZoneError::InvalidDomainName
+            }
+            _ => CreateZoneError::Custom(err),
+        }
+    }
+}
+
+impl<E: StatusError> FromStatus for DeleteZoneError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => DeleteZoneError::NotFound,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => DeleteZoneError::Unauthorized,
+            _ => DeleteZoneError::Custom(err),
+        }
+    }
+}
+
+impl<E: StatusError> FromStatus for RetrieveRecordError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => RetrieveRecordError::NotFound,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                RetrieveRecordError::Unauthorized
+            }
+            _ => RetrieveRecordError::Custom(err),
+        }
+    }
+}
+
+impl<E: StatusError> FromStatus for CreateRecordError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                CreateRecordError::Unauthorized
+            }
+            Some(StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY) => {
+                CreateRecordError::InvalidRecord
+            }
+            _ => CreateRecordError::Custom(err),
+        }
+    }
+}
+
+impl<E: StatusError> FromStatus for DeleteRecordError<E> {
+    type Error = E;
+
+    fn from_status(err: E) -> Self {
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => DeleteRecordError::NotFound,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                DeleteRecordError::Unauthorized
+            }
+            _ => DeleteRecordError::Custom(err),
+        }
+    }
+}
+
+/// Returns the time to wait before retrying a rate-limited request as given by the `Retry-After` header of the response.
+///
+/// The header either contains the number of seconds to wait or an HTTP date to wait for, dates in the past resulting in no delay.
+#[cfg(feature = "desec")]
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = parse_http_date(value)?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Parses an HTTP date in the preferred IMF-fixdate format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// The obsolete RFC 850 and asctime formats are not supported.
+#[cfg(feature = "desec")]
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (_weekday, rest) = value.split_once(", ")?;
+    let [day, month, year, time, "GMT"] = rest.split(' ').collect::<Vec<&str>>()[..] else {
+        return None;
+    };
+    let [hour, minute, second] = time.split(':').collect::<Vec<&str>>()[..] else {
+        return None;
+    };
+
+    let day = day
+        .parse::<u64>()
+        .ok()
+        .filter(|day| (1..=31).contains(day))?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year = year.parse::<u64>().ok().filter(|year| *year >= 1970)?;
+    let hour = hour.parse::<u64>().ok().filter(|hour| *hour < 24)?;
+    let minute = minute.parse::<u64>().ok().filter(|minute| *minute < 60)?;
+    let second = second.parse::<u64>().ok().filter(|second| *second <= 60)?;
+
+    // Counts the days since 1970-01-01 using the days-from-civil algorithm, treating January and February as months of the previous year.
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hour * 3600 + minute * 60 + second))
+}
+
+/// Represents a single page of a paginated listing.
+///
+/// `next` is the cursor (e.g. a page number or an opaque token) used to request the following page, [`None`] if this is the last page.
+/// Not needed by OVHcloud, which does not paginate its listings.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "digitalocean",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "hetzner",
+    feature = "linode",
+    feature = "porkbun",
+    feature = "route53",
+    feature = "vultr"
+))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Page<T, C> {
+    pub items: Vec<T>,
+    pub next: Option<C>,
+}
+
+/// Collects the items of all pages of a paginated listing.
+///
+/// `fetch_page` is called with [`None`] for the first page and with the cursor returned by the previous page afterwards,
+/// until a page without a cursor for the next page is returned.
+/// Every fetched page is recorded if the `metrics` feature is enabled, labeled with `provider`, the provider's lowercase name.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "digitalocean",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "hetzner",
+    feature = "linode",
+    feature = "porkbun",
+    feature = "route53",
+    feature = "vultr"
+))]
+pub(crate) async fn collect_pages<T, C, E, F, Fut>(
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))] provider: &str,
+    mut fetch_page: F,
+) -> Result<Vec<T>, E>
+where
+    F: FnMut(Option<C>) -> Fut,
+    Fut: Future<Output = Result<Page<T, C>, E>>,
+{
+    let mut items = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let page = fetch_page(cursor).await?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::recorder().record_page(provider);
+        items.extend(page.items);
+
+        cursor = page.next;
+        if cursor.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "desec"))]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn http_dates() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(784_111_777))
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_164_800))
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+    }
+
+    #[test]
+    fn retry_after_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{future::Future, time::Instant};
+
+use crate::{metrics, Provider};
+
+use super::{Intercepted, Interceptor, Layer, Operation, OperationError};
+
+/// A [`Layer`] recording every call to a provider and its zones using the installed [`Recorder`](metrics::Recorder).
+///
+/// The provider is labeled with the given name, which should match the name used by the provider itself
+/// for recording pages and rate limits (e.g. `hetzner`), see the [`metrics`] module.
+#[derive(Debug, Clone, Copy)]
+pub struct Metrics {
+    provider_name: &'static str,
+}
+
+impl Metrics {
+    pub fn new(provider_name: &'static str) -> Self {
+        Self { provider_name }
+    }
+}
+
+impl<P: Provider> Layer<P> for Metrics {
+    type Provider = Intercepted<P, MetricsInterceptor>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        Intercepted::new(
+            provider,
+            MetricsInterceptor {
+                provider_name: self.provider_name,
+            },
+        )
+    }
+}
+
+/// The [`Interceptor`] applied by the [`Metrics`] layer.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsInterceptor {
+    provider_name: &'static str,
+}
+
+impl MetricsInterceptor {
+    /// Returns the name the provider is labeled with.
+    pub fn provider_name(&self) -> &'static str {
+        self.provider_name
+    }
+}
+
+impl Interceptor for MetricsInterceptor {
+    async fn intercept<T, E, F>(
+        &self,
+        operation: Operation<'_>,
+        mut call: impl FnMut() -> F + Send,
+    ) -> Result<T, E>
+    where
+        T: Send,
+        E: OperationError + Send,
+        F: Future<Output = Result<T, E>> + Send,
+    {
+        let start = Instant::now();
+        let result = call().await;
+        metrics::recorder().record_call(
+            self.provider_name,
+            operation.name(),
+            start.elapsed(),
+            result.as_ref().err().map(OperationError::variant),
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        memory::{Failure, MemoryError, MemoryProvider},
+        metrics::test_recorder,
+        CreateRecord, DomainName, RecordData,
+    };
+
+    #[tokio::test]
+    async fn calls_are_recorded_per_provider_and_operation() {
+        let recorder = test_recorder();
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let provider = inner.clone().layer(Metrics::new("meter-test"));
+        let other = MemoryProvider::new().layer(Metrics::new("meter-test-other"));
+
+        provider.list_zones().await.unwrap();
+        inner.fail_next(Failure::Unauthorized);
+        provider.list_zones().await.unwrap_err();
+        provider.get_zone("example.org").await.unwrap_err();
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        let data = RecordData::TXT(vec!["hello".to_owned()]);
+        zone.create_record(&DomainName::from_raw("www"), &data, 300)
+            .await
+            .unwrap();
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        zone.create_record(&DomainName::from_raw("www"), &data, 300)
+            .await
+            .unwrap_err();
+        other.list_zones().await.unwrap();
+
+        let samples = recorder.samples("meter-test");
+        let (calls, errors, durations) = (
+            samples
+                .iter()
+                .filter(|sample| sample.starts_with("libdns_calls_total"))
+                .collect::<Vec<_>>(),
+            samples
+                .iter()
+                .filter(|sample| sample.starts_with("libdns_errors_total"))
+                .collect::<Vec<_>>(),
+            // Calls to the in-memory provider take less than 10 seconds, but any of the shorter buckets may be hit.
+            samples
+                .iter()
+                .filter(|sample| {
+                    sample.contains(r#"le="10""#)
+                        || sample.contains(r#"le="+Inf""#)
+                        || sample.starts_with("libdns_call_duration_seconds_count")
+                })
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            calls,
+            [
+                r#"libdns_calls_total{provider="meter-test",operation="create_record"} 2"#,
+                r#"libdns_calls_total{provider="meter-test",operation="get_zone"} 2"#,
+                r#"libdns_calls_total{provider="meter-test",operation="list_zones"} 2"#,
+            ]
+        );
+        assert_eq!(
+            errors,
+            [
+                r#"libdns_errors_total{provider="meter-test",operation="create_record",error="custom"} 1"#,
+                r#"libdns_errors_total{provider="meter-test",operation="get_zone",error="not_found"} 1"#,
+                r#"libdns_errors_total{provider="meter-test",operation="list_zones",error="unauthorized"} 1"#,
+            ]
+        );
+        assert_eq!(
+            durations,
+            [
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="create_record",le="10"} 2"#,
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="create_record",le="+Inf"} 2"#,
+                r#"libdns_call_duration_seconds_count{provider="meter-test",operation="create_record"} 2"#,
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="get_zone",le="10"} 2"#,
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="get_zone",le="+Inf"} 2"#,
+                r#"libdns_call_duration_seconds_count{provider="meter-test",operation="get_zone"} 2"#,
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="list_zones",le="10"} 2"#,
+                r#"libdns_call_duration_seconds_bucket{provider="meter-test",operation="list_zones",le="+Inf"} 2"#,
+                r#"libdns_call_duration_seconds_count{provider="meter-test",operation="list_zones"} 2"#,
+            ]
+        );
+        assert!(recorder.samples("meter-test-other").contains(
+            &r#"libdns_calls_total{provider="meter-test-other",operation="list_zones"} 1"#
+                .to_owned()
+        ));
+    }
+
+    #[cfg(feature = "retry")]
+    #[tokio::test]
+    async fn retries_are_recorded() {
+        use std::time::Duration;
+
+        use crate::layer::Retry;
+
+        let recorder = test_recorder();
+        let inner = MemoryProvider::new();
+        let provider = inner
+            .clone()
+            .layer(
+                Retry::new()
+                    .with_name("meter-retry")
+                    .with_initial_delay(Duration::from_millis(1)),
+            )
+            .layer(Metrics::new("meter-retry"));
+
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        provider.list_zones().await.unwrap();
+
+        let samples = recorder.samples("meter-retry");
+        assert!(samples.contains(
+            &r#"libdns_calls_total{provider="meter-retry",operation="list_zones"} 1"#.to_owned()
+        ));
+        assert!(samples.contains(
+            &r#"libdns_retries_total{provider="meter-retry",operation="list_zones"} 2"#.to_owned()
+        ));
+    }
+}
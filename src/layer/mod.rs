@@ -0,0 +1,630 @@
+//! Stacking cross-cutting behavior like logging or metrics on top of any provider.
+//!
+//! A [`Layer`] wraps a provider in another provider, which in turn wraps the provider's zones.
+//! Layers are applied using [`Provider::layer`] and can be stacked, the last layer applied being the outermost one:
+//!
+//! ```ignore
+//! let provider = HetznerProvider::new(api_key)?
+//!     .layer(Retry::default())
+//!     .layer(Tracing::new());
+//! ```
+//!
+//! Here every traced call covers all of its retries, while applying `Tracing` first would emit a span per attempt.
+//!
+//! Most behavior only needs to observe or wrap the individual calls. Such behavior implements [`Interceptor`] and is applied
+//! using the [`Intercept`] layer, which forwards every capability the wrapped provider and its zones implement.
+//!
+//! With the `retry` feature enabled, the `Retry` layer retries calls failing with a transient error, e.g. because of rate limiting.
+//! With the `tracing` feature enabled, the `Tracing` layer emits a span for every call.
+//! With the `metrics` feature enabled, the `Metrics` layer records every call using the recorder of the `metrics` module.
+
+use std::{fmt::Debug, future::Future};
+
+use crate::{
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+#[cfg(feature = "metrics")]
+mod meter;
+#[cfg(feature = "retry")]
+mod retry;
+#[cfg(feature = "tracing")]
+mod trace;
+
+#[cfg(feature = "metrics")]
+pub use meter::{Metrics, MetricsInterceptor};
+#[cfg(feature = "retry")]
+pub use retry::{Retried, Retry, Transient};
+#[cfg(feature = "tracing")]
+pub use trace::{Tracing, TracingInterceptor};
+
+/// Wraps a provider in another provider.
+pub trait Layer<P: Provider> {
+    /// The provider wrapping `P`.
+    type Provider: Provider;
+
+    /// Wraps the given provider.
+    fn layer(&self, provider: P) -> Self::Provider;
+}
+
+/// A [`Layer`] calling a function, created using [`layer_fn`].
+#[derive(Debug, Clone, Copy)]
+pub struct LayerFn<F>(F);
+
+/// Creates a [`Layer`] from a function wrapping a provider.
+pub fn layer_fn<F>(f: F) -> LayerFn<F> {
+    LayerFn(f)
+}
+
+impl<P, Q, F> Layer<P> for LayerFn<F>
+where
+    P: Provider,
+    Q: Provider,
+    F: Fn(P) -> Q,
+{
+    type Provider = Q;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        (self.0)(provider)
+    }
+}
+
+/// Describes a call to a provider or zone, passed to [`Interceptor::intercept`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation<'a> {
+    /// [`Provider::list_zones`]
+    ListZones,
+
+    /// [`Provider::get_zone`]
+    GetZone { zone_id: &'a str },
+
+    /// [`CreateZone::create_zone`]
+    CreateZone { domain: &'a DomainName },
+
+    /// [`DeleteZone::delete_zone`]
+    DeleteZone { zone_id: &'a str },
+
+    /// [`Zone::list_records`]
+    ListRecords {
+        zone_id: &'a str,
+        zone: &'a DomainName,
+    },
+
+    /// [`Zone::get_record`]
+    GetRecord {
+        zone_id: &'a str,
+        zone: &'a DomainName,
+        record_id: &'a str,
+    },
+
+    /// [`CreateRecord::create_record`]
+    CreateRecord {
+        zone_id: &'a str,
+        zone: &'a DomainName,
+        host: &'a DomainName,
+        data: &'a RecordData,
+        ttl: u64,
+    },
+
+    /// [`DeleteRecord::delete_record`]
+    DeleteRecord {
+        zone_id: &'a str,
+        zone: &'a DomainName,
+        record_id: &'a str,
+    },
+}
+
+impl<'a> Operation<'a> {
+    /// Returns the name of the called method, e.g. `create_record`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::ListZones => "list_zones",
+            Operation::GetZone { .. } => "get_zone",
+            Operation::CreateZone { .. } => "create_zone",
+            Operation::DeleteZone { .. } => "delete_zone",
+            Operation::ListRecords { .. } => "list_records",
+            Operation::GetRecord { .. } => "get_record",
+            Operation::CreateRecord { .. } => "create_record",
+            Operation::DeleteRecord { .. } => "delete_record",
+        }
+    }
+
+    /// Returns the ID of the zone the operation concerns, [`None`] for [`Operation::ListZones`] and [`Operation::CreateZone`].
+    pub fn zone_id(&self) -> Option<&'a str> {
+        match *self {
+            Operation::ListZones | Operation::CreateZone { .. } => None,
+            Operation::GetZone { zone_id }
+            | Operation::DeleteZone { zone_id }
+            | Operation::ListRecords { zone_id, .. }
+            | Operation::GetRecord { zone_id, .. }
+            | Operation::CreateRecord { zone_id, .. }
+            | Operation::DeleteRecord { zone_id, .. } => Some(zone_id),
+        }
+    }
+
+    /// Returns the domain of the zone the operation concerns, if already known before the call.
+    pub fn zone(&self) -> Option<&'a DomainName> {
+        match *self {
+            Operation::ListZones | Operation::GetZone { .. } | Operation::DeleteZone { .. } => None,
+            Operation::CreateZone { domain } => Some(domain),
+            Operation::ListRecords { zone, .. }
+            | Operation::GetRecord { zone, .. }
+            | Operation::CreateRecord { zone, .. }
+            | Operation::DeleteRecord { zone, .. } => Some(zone),
+        }
+    }
+
+    /// Returns whether the operation changes zones or records.
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Operation::CreateZone { .. }
+                | Operation::DeleteZone { .. }
+                | Operation::CreateRecord { .. }
+                | Operation::DeleteRecord { .. }
+        )
+    }
+}
+
+/// Implemented by the error types returned by the calls passed to [`Interceptor::intercept`].
+pub trait OperationError: Debug {
+    /// Returns the name of the error variant, e.g. `not_found`.
+    fn variant(&self) -> &'static str;
+}
+
+impl<C: Debug> OperationError for RetrieveZoneError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            RetrieveZoneError::Unauthorized => "unauthorized",
+            RetrieveZoneError::NotFound => "not_found",
+            RetrieveZoneError::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<C: Debug> OperationError for CreateZoneError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            CreateZoneError::Unauthorized => "unauthorized",
+            CreateZoneError::InvalidDomainName => "invalid_domain_name",
+            CreateZoneError::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<C: Debug> OperationError for DeleteZoneError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            DeleteZoneError::Unauthorized => "unauthorized",
+            DeleteZoneError::NotFound => "not_found",
+            DeleteZoneError::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<C: Debug> OperationError for RetrieveRecordError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            RetrieveRecordError::Unauthorized => "unauthorized",
+            RetrieveRecordError::NotFound => "not_found",
+            RetrieveRecordError::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<C: Debug> OperationError for CreateRecordError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            CreateRecordError::Unauthorized => "unauthorized",
+            CreateRecordError::UnsupportedType => "unsupported_type",
+            CreateRecordError::InvalidRecord => "invalid_record",
+            CreateRecordError::Custom(_) => "custom",
+        }
+    }
+}
+
+impl<C: Debug> OperationError for DeleteRecordError<C> {
+    fn variant(&self) -> &'static str {
+        match self {
+            DeleteRecordError::Unauthorized => "unauthorized",
+            DeleteRecordError::NotFound => "not_found",
+            DeleteRecordError::Custom(_) => "custom",
+        }
+    }
+}
+
+/// Wraps every call to a provider and its zones, e.g. to log or measure them.
+pub trait Interceptor: Send + Sync {
+    /// Performs the described operation by calling `call`.
+    ///
+    /// `call` starts the operation on the wrapped provider or zone every time it is called, e.g. to retry failed operations.
+    fn intercept<T, E, F>(
+        &self,
+        operation: Operation<'_>,
+        call: impl FnMut() -> F + Send,
+    ) -> impl Future<Output = Result<T, E>> + Send
+    where
+        T: Send,
+        E: OperationError + Send,
+        F: Future<Output = Result<T, E>> + Send;
+}
+
+/// A [`Layer`] wrapping every call using an [`Interceptor`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Intercept<I>(I);
+
+impl<I> Intercept<I> {
+    pub fn new(interceptor: I) -> Self {
+        Self(interceptor)
+    }
+}
+
+impl<P, I> Layer<P> for Intercept<I>
+where
+    P: Provider,
+    I: Interceptor + Clone,
+{
+    type Provider = Intercepted<P, I>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        Intercepted::new(provider, self.0.clone())
+    }
+}
+
+/// Represents a provider wrapped by the [`Intercept`] layer.
+#[derive(Debug, Clone)]
+pub struct Intercepted<T, I> {
+    inner: T,
+    interceptor: I,
+}
+
+impl<T, I> Intercepted<T, I> {
+    /// Wraps a provider or zone.
+    pub fn new(inner: T, interceptor: I) -> Self {
+        Self { inner, interceptor }
+    }
+
+    /// Returns the wrapped provider or zone.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the interceptor.
+    pub fn interceptor(&self) -> &I {
+        &self.interceptor
+    }
+
+    /// Unwraps the provider or zone.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<P, I> Provider for Intercepted<P, I>
+where
+    P: Provider,
+    I: Interceptor + Clone,
+{
+    type Zone = Intercepted<P::Zone, I>;
+    type CustomRetrieveError = P::CustomRetrieveError;
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = self
+            .interceptor
+            .intercept(Operation::ListZones, || self.inner.list_zones())
+            .await?;
+        Ok(zones
+            .into_iter()
+            .map(|zone| Intercepted::new(zone, self.interceptor.clone()))
+            .collect())
+    }
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self
+            .interceptor
+            .intercept(Operation::GetZone { zone_id }, || {
+                self.inner.get_zone(zone_id)
+            })
+            .await?;
+        Ok(Intercepted::new(zone, self.interceptor.clone()))
+    }
+}
+
+impl<P, I> CreateZone for Intercepted<P, I>
+where
+    P: CreateZone,
+    I: Interceptor + Clone,
+{
+    type CustomCreateError = P::CustomCreateError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let zone = self
+            .interceptor
+            .intercept(Operation::CreateZone { domain }, || {
+                self.inner.create_zone(domain)
+            })
+            .await?;
+        Ok(Intercepted::new(zone, self.interceptor.clone()))
+    }
+}
+
+impl<P, I> DeleteZone for Intercepted<P, I>
+where
+    P: DeleteZone,
+    I: Interceptor + Clone,
+{
+    type CustomDeleteError = P::CustomDeleteError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.interceptor
+            .intercept(Operation::DeleteZone { zone_id }, || {
+                self.inner.delete_zone(zone_id)
+            })
+            .await
+    }
+}
+
+impl<Z, I> Zone for Intercepted<Z, I>
+where
+    Z: Zone,
+    I: Interceptor,
+{
+    type CustomRetrieveError = Z::CustomRetrieveError;
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.inner.domain()
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (zone_id, zone) = (self.inner.id(), self.inner.domain());
+        self.interceptor
+            .intercept(Operation::ListRecords { zone_id, zone }, || {
+                self.inner.list_records()
+            })
+            .await
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (zone_id, zone) = (self.inner.id(), self.inner.domain());
+        self.interceptor
+            .intercept(
+                Operation::GetRecord {
+                    zone_id,
+                    zone,
+                    record_id,
+                },
+                || self.inner.get_record(record_id),
+            )
+            .await
+    }
+}
+
+impl<Z, I> CreateRecord for Intercepted<Z, I>
+where
+    Z: CreateRecord,
+    I: Interceptor,
+{
+    type CustomCreateError = Z::CustomCreateError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let (zone_id, zone) = (self.inner.id(), self.inner.domain());
+        let operation = Operation::CreateRecord {
+            zone_id,
+            zone,
+            host,
+            data,
+            ttl,
+        };
+        self.interceptor
+            .intercept(operation, || self.inner.create_record(host, data, ttl))
+            .await
+    }
+}
+
+impl<Z, I> DeleteRecord for Intercepted<Z, I>
+where
+    Z: DeleteRecord,
+    I: Interceptor,
+{
+    type CustomDeleteError = Z::CustomDeleteError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (zone_id, zone) = (self.inner.id(), self.inner.domain());
+        self.interceptor
+            .intercept(
+                Operation::DeleteRecord {
+                    zone_id,
+                    zone,
+                    record_id,
+                },
+                || self.inner.delete_record(record_id),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        slice,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::memory::MemoryProvider;
+
+    /// Logs every intercepted call before and after calling the wrapped provider.
+    #[derive(Debug, Clone)]
+    struct Log {
+        label: &'static str,
+        entries: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Interceptor for Log {
+        async fn intercept<T, E, F>(
+            &self,
+            operation: Operation<'_>,
+            mut call: impl FnMut() -> F + Send,
+        ) -> Result<T, E>
+        where
+            T: Send,
+            E: OperationError + Send,
+            F: Future<Output = Result<T, E>> + Send,
+        {
+            let entry = match operation.zone_id() {
+                Some(zone_id) => format!("{} {} {}", self.label, operation.name(), zone_id),
+                None => format!("{} {}", self.label, operation.name()),
+            };
+            self.entries.lock().unwrap().push(entry.clone());
+            let result = call().await;
+            let outcome = result.as_ref().err().map_or("ok", OperationError::variant);
+            self.entries
+                .lock()
+                .unwrap()
+                .push(format!("{} -> {}", entry, outcome));
+            result
+        }
+    }
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new(name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn every_capability_is_forwarded() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let entries = Arc::new(Mutex::new(Vec::new()));
+        let provider = inner.clone().layer(Intercept::new(Log {
+            label: "log",
+            entries: entries.clone(),
+        }));
+
+        assert_eq!(provider.list_zones().await.unwrap().len(), 1);
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let record = zone
+            .create_record(
+                &name("www"),
+                &RecordData::TXT(vec!["hello".to_owned()]),
+                300,
+            )
+            .await
+            .unwrap();
+        assert_eq!(zone.get_record(&record.id).await.unwrap(), record);
+        assert_eq!(zone.list_records().await.unwrap(), slice::from_ref(&record));
+        zone.delete_record(&record.id).await.unwrap();
+        assert!(matches!(
+            zone.delete_record(&record.id).await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let created = provider.create_zone(&name("example.org")).await.unwrap();
+        assert_eq!(created.domain(), &name("example.org").to_absolute());
+        provider.delete_zone(created.id()).await.unwrap();
+
+        assert_eq!(
+            inner.calls(),
+            [
+                "list_zones",
+                "get_zone example.com",
+                "create_record example.com www TXT",
+                "get_record example.com 1",
+                "list_records example.com",
+                "delete_record example.com 1",
+                "delete_record example.com 1",
+                "create_zone example.org",
+                "delete_zone example.org",
+            ]
+        );
+        assert_eq!(
+            *entries.lock().unwrap(),
+            [
+                "log list_zones",
+                "log list_zones -> ok",
+                "log get_zone example.com",
+                "log get_zone example.com -> ok",
+                "log create_record example.com",
+                "log create_record example.com -> ok",
+                "log get_record example.com",
+                "log get_record example.com -> ok",
+                "log list_records example.com",
+                "log list_records example.com -> ok",
+                "log delete_record example.com",
+                "log delete_record example.com -> ok",
+                "log delete_record example.com",
+                "log delete_record example.com -> not_found",
+                "log create_zone",
+                "log create_zone -> ok",
+                "log delete_zone example.org",
+                "log delete_zone example.org -> ok",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn last_layer_is_outermost() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let entries = Arc::new(Mutex::new(Vec::new()));
+        let provider = inner
+            .clone()
+            .layer(Intercept::new(Log {
+                label: "first",
+                entries: entries.clone(),
+            }))
+            .layer(Intercept::new(Log {
+                label: "second",
+                entries: entries.clone(),
+            }));
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        zone.list_records().await.unwrap();
+
+        assert_eq!(
+            inner.calls(),
+            ["get_zone example.com", "list_records example.com"]
+        );
+        assert_eq!(
+            *entries.lock().unwrap(),
+            [
+                "second get_zone example.com",
+                "first get_zone example.com",
+                "first get_zone example.com -> ok",
+                "second get_zone example.com -> ok",
+                "second list_records example.com",
+                "first list_records example.com",
+                "first list_records example.com -> ok",
+                "second list_records example.com -> ok",
+            ]
+        );
+        assert_eq!(zone.inner().inner().id(), "example.com");
+    }
+}
@@ -0,0 +1,532 @@
+use std::{any, future::Future, time::Duration};
+
+use crate::{
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+use super::Layer;
+
+/// Implemented by custom errors of providers to tell failures which may succeed when retried apart from permanent ones.
+///
+/// The errors of the providers of this crate are transient if the request timed out, could not connect
+/// or was answered with `429 Too Many Requests` or a server error.
+pub trait Transient {
+    /// Returns whether the failed call may succeed when retried.
+    fn is_transient(&self) -> bool;
+
+    /// Returns the time to wait before retrying as requested by the provider, e.g. using the `Retry-After` header.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// A [`Layer`] retrying calls to a provider and its zones failing with a [`Transient`] custom error.
+///
+/// Failed calls are retried up to [`Retry::with_max_retries`] times, waiting an exponentially growing delay
+/// starting at [`Retry::with_initial_delay`] in between, or the delay requested by the provider if it reports one.
+/// A call is not retried if the provider requests waiting longer than [`Retry::with_max_delay`].
+///
+/// Changes are retried as well. A change whose request timed out after the provider applied it may thus be applied twice,
+/// which most providers reject as a duplicate.
+/// With the `metrics` feature enabled, every retry is recorded using the recorder of the `metrics` module.
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    name: Option<&'static str>,
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for Retry {
+    /// Retries up to 3 times, waiting 500 ms, 1 s and 2 s or up to 30 s if requested by the provider.
+    fn default() -> Self {
+        Self {
+            name: None,
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Retry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the provider retries are recorded for, e.g. `hetzner`, instead of naming it by its type.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets how often a failed call is retried at most.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry, which is doubled for every further retry.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the longest delay to wait before a retry.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Returns the delay before the given retry (starting at 0), [`None`] if the call should not be retried anymore.
+    fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+
+        match retry_after {
+            Some(delay) => (delay <= self.max_delay).then_some(delay),
+            None => Some(
+                self.initial_delay
+                    .saturating_mul(2u32.saturating_pow(retry))
+                    .min(self.max_delay),
+            ),
+        }
+    }
+}
+
+impl<P: Provider> Layer<P> for Retry
+where
+    Retried<P>: Provider,
+{
+    type Provider = Retried<P>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        let provider_name = self.name.unwrap_or_else(any::type_name::<P>);
+        Retried::new(provider, *self, provider_name)
+    }
+}
+
+/// Gives access to the custom error of the error types of the provider and zone traits.
+trait CustomError {
+    type Custom;
+
+    fn custom(&self) -> Option<&Self::Custom>;
+}
+
+macro_rules! impl_custom_error {
+    ($($error:ident),*) => {
+        $(
+            impl<C> CustomError for $error<C> {
+                type Custom = C;
+
+                fn custom(&self) -> Option<&C> {
+                    match self {
+                        $error::Custom(err) => Some(err),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_custom_error!(
+    RetrieveZoneError,
+    CreateZoneError,
+    DeleteZoneError,
+    RetrieveRecordError,
+    CreateRecordError,
+    DeleteRecordError
+);
+
+/// Wraps a provider, retrying calls failing with a [`Transient`] custom error as configured by a [`Retry`] layer.
+#[derive(Debug, Clone)]
+pub struct Retried<T> {
+    inner: T,
+    retry: Retry,
+    provider_name: &'static str,
+}
+
+impl<T> Retried<T> {
+    /// Wraps a provider or zone, recording retries under the given provider name.
+    pub fn new(inner: T, retry: Retry, provider_name: &'static str) -> Self {
+        Self {
+            inner,
+            retry,
+            provider_name,
+        }
+    }
+
+    /// Returns the wrapped provider or zone.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the name retries are recorded under.
+    pub fn provider_name(&self) -> &'static str {
+        self.provider_name
+    }
+
+    /// Unwraps the provider or zone.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn wrap<Z>(&self, zone: Z) -> Retried<Z> {
+        Retried::new(zone, self.retry, self.provider_name)
+    }
+
+    /// Calls `call` until it succeeds, fails permanently or the retries are exhausted.
+    async fn run<R, E, F>(
+        &self,
+        #[cfg_attr(
+            not(any(feature = "metrics", feature = "tracing")),
+            allow(unused_variables)
+        )]
+        operation: &'static str,
+        mut call: impl FnMut() -> F,
+    ) -> Result<R, E>
+    where
+        E: CustomError,
+        E::Custom: Transient,
+        F: Future<Output = Result<R, E>>,
+    {
+        let mut retry = 0;
+        loop {
+            let err = match call().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            let delay = err
+                .custom()
+                .filter(|custom| custom.is_transient())
+                .and_then(|custom| self.retry.delay(retry, custom.retry_after()));
+            let Some(delay) = delay else {
+                return Err(err);
+            };
+            drop(err);
+
+            #[cfg(feature = "metrics")]
+            crate::metrics::recorder().record_retry(self.provider_name, operation);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                operation,
+                retry = retry + 1,
+                ?delay,
+                "retrying transient failure"
+            );
+
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+}
+
+impl<P> Provider for Retried<P>
+where
+    P: Provider,
+    P::CustomRetrieveError: Transient,
+    <P::Zone as Zone>::CustomRetrieveError: Transient,
+{
+    type Zone = Retried<P::Zone>;
+    type CustomRetrieveError = P::CustomRetrieveError;
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = self.run("list_zones", || self.inner.list_zones()).await?;
+        Ok(zones.into_iter().map(|zone| self.wrap(zone)).collect())
+    }
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self
+            .run("get_zone", || self.inner.get_zone(zone_id))
+            .await?;
+        Ok(self.wrap(zone))
+    }
+}
+
+impl<P> CreateZone for Retried<P>
+where
+    P: CreateZone,
+    P::CustomCreateError: Transient,
+    Retried<P>: Provider<Zone = Retried<P::Zone>>,
+{
+    type CustomCreateError = P::CustomCreateError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let zone = self
+            .run("create_zone", || self.inner.create_zone(domain))
+            .await?;
+        Ok(self.wrap(zone))
+    }
+}
+
+impl<P> DeleteZone for Retried<P>
+where
+    P: DeleteZone,
+    P::CustomDeleteError: Transient,
+    Retried<P>: Provider,
+{
+    type CustomDeleteError = P::CustomDeleteError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.run("delete_zone", || self.inner.delete_zone(zone_id))
+            .await
+    }
+}
+
+impl<Z> Zone for Retried<Z>
+where
+    Z: Zone,
+    Z::CustomRetrieveError: Transient,
+{
+    type CustomRetrieveError = Z::CustomRetrieveError;
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.inner.domain()
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        self.run("list_records", || self.inner.list_records()).await
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        self.run("get_record", || self.inner.get_record(record_id))
+            .await
+    }
+}
+
+impl<Z> CreateRecord for Retried<Z>
+where
+    Z: CreateRecord,
+    Z::CustomRetrieveError: Transient,
+    Z::CustomCreateError: Transient,
+{
+    type CustomCreateError = Z::CustomCreateError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        self.run("create_record", || {
+            self.inner.create_record(host, data, ttl)
+        })
+        .await
+    }
+}
+
+impl<Z> DeleteRecord for Retried<Z>
+where
+    Z: DeleteRecord,
+    Z::CustomRetrieveError: Transient,
+    Z::CustomDeleteError: Transient,
+{
+    type CustomDeleteError = Z::CustomDeleteError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        self.run("delete_record", || self.inner.delete_record(record_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        layer::{Intercept, Interceptor, Operation, OperationError},
+        memory::{Failure, MemoryError, MemoryProvider},
+    };
+
+    impl Transient for MemoryError {
+        fn is_transient(&self) -> bool {
+            self.0 != "permanent"
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            match self.0 {
+                "throttled" => Some(Duration::from_millis(1)),
+                "throttled for long" => Some(Duration::from_secs(3600)),
+                _ => None,
+            }
+        }
+    }
+
+    fn fast() -> Retry {
+        Retry::new().with_initial_delay(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn delays_grow_exponentially_up_to_the_maximum() {
+        let retry = Retry::default();
+        assert_eq!(retry.delay(0, None), Some(Duration::from_millis(500)));
+        assert_eq!(retry.delay(1, None), Some(Duration::from_secs(1)));
+        assert_eq!(retry.delay(2, None), Some(Duration::from_secs(2)));
+        assert_eq!(retry.delay(3, None), None);
+
+        let retry = retry
+            .with_max_retries(10)
+            .with_max_delay(Duration::from_secs(3));
+        assert_eq!(retry.delay(2, None), Some(Duration::from_secs(2)));
+        assert_eq!(retry.delay(9, None), Some(Duration::from_secs(3)));
+        assert_eq!(
+            retry.delay(0, Some(Duration::from_secs(3))),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(retry.delay(0, Some(Duration::from_secs(4))), None);
+        assert_eq!(retry.delay(10, Some(Duration::ZERO)), None);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let provider = inner.clone().layer(fast());
+
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        inner.fail_next(Failure::Custom(MemoryError("throttled")));
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        // Zones are wrapped as well.
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        assert_eq!(zone.list_records().await.unwrap(), []);
+
+        assert_eq!(
+            inner.calls(),
+            [
+                "get_zone example.com",
+                "get_zone example.com",
+                "get_zone example.com",
+                "list_records example.com",
+                "list_records example.com",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_are_not_retried() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let provider = inner.clone().layer(fast());
+
+        inner.fail_next(Failure::Custom(MemoryError("permanent")));
+        assert_eq!(
+            provider.list_zones().await.unwrap_err(),
+            RetrieveZoneError::Custom(MemoryError("permanent"))
+        );
+
+        inner.fail_next(Failure::Unauthorized);
+        assert!(matches!(
+            provider
+                .create_zone(&DomainName::from_raw("example.org"))
+                .await,
+            Err(CreateZoneError::Unauthorized)
+        ));
+        assert!(matches!(
+            provider.delete_zone("example.net").await,
+            Err(DeleteZoneError::NotFound)
+        ));
+
+        // The provider asks to wait longer than the maximum delay.
+        inner.fail_next(Failure::Custom(MemoryError("throttled for long")));
+        assert_eq!(
+            provider.list_zones().await.unwrap_err(),
+            RetrieveZoneError::Custom(MemoryError("throttled for long"))
+        );
+
+        assert_eq!(
+            inner.calls(),
+            [
+                "list_zones",
+                "create_zone example.org",
+                "delete_zone example.net",
+                "list_zones",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_are_limited() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let provider = inner.clone().layer(fast().with_max_retries(2));
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        for _ in 0..3 {
+            inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        }
+        let data = RecordData::TXT(vec!["hello".to_owned()]);
+        assert_eq!(
+            zone.create_record(&DomainName::from_raw("www"), &data, 300)
+                .await
+                .unwrap_err(),
+            CreateRecordError::Custom(MemoryError("timeout"))
+        );
+        assert_eq!(inner.calls().len(), 4);
+        assert_eq!(inner.records("example.com"), []);
+    }
+
+    /// Counts the intercepted calls.
+    #[derive(Debug, Clone, Default)]
+    struct Count(Arc<Mutex<usize>>);
+
+    impl Interceptor for Count {
+        async fn intercept<T, E, F>(
+            &self,
+            _: Operation<'_>,
+            mut call: impl FnMut() -> F + Send,
+        ) -> Result<T, E>
+        where
+            T: Send,
+            E: OperationError + Send,
+            F: Future<Output = Result<T, E>> + Send,
+        {
+            *self.0.lock().unwrap() += 1;
+            call().await
+        }
+    }
+
+    #[tokio::test]
+    async fn outer_layers_observe_all_retries_as_one_call() {
+        let inner = MemoryProvider::new();
+        let (outer, per_attempt) = (Count::default(), Count::default());
+        let provider = inner
+            .clone()
+            .layer(Intercept::new(per_attempt.clone()))
+            .layer(fast())
+            .layer(Intercept::new(outer.clone()));
+
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        inner.fail_next(Failure::Custom(MemoryError("timeout")));
+        provider.list_zones().await.unwrap();
+
+        assert_eq!(*outer.0.lock().unwrap(), 1);
+        assert_eq!(*per_attempt.0.lock().unwrap(), 3);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{any, fmt::Debug, future::Future, time::Instant};
+
+use tracing::{field, Instrument};
+
+use crate::Provider;
+
+use super::{Intercepted, Interceptor, Layer, Operation};
+
+/// A [`Layer`] emitting a [`tracing`] span for every call to a provider and its zones.
+///
+/// Every span is named `libdns` and carries the following fields, the ones not applying to an operation being left empty:
+///
+/// | Field         | Description                                                          |
+/// |---------------|----------------------------------------------------------------------|
+/// | `provider`    | The name given using [`Tracing::with_name`] or the provider's type   |
+/// | `operation`   | The called method as returned by [`Operation::name`]                 |
+/// | `zone_id`     | The ID of the zone                                                   |
+/// | `zone`        | The domain of the zone                                               |
+/// | `host`        | The host of the created record                                       |
+/// | `record_type` | The type of the created record                                       |
+/// | `record_id`   | The ID of the retrieved or deleted record                            |
+/// | `outcome`     | `ok` or `error`                                                      |
+/// | `error`       | The debug representation of the error, if the call failed            |
+/// | `duration_ms` | The time the call took in milliseconds                               |
+///
+/// Record values are never recorded, as e.g. TSIG or DKIM keys may be sensitive.
+/// Spans of the underlying HTTP requests emitted by providers are children of these spans.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tracing {
+    name: Option<&'static str>,
+}
+
+impl Tracing {
+    /// Creates a layer naming the provider by its type.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the provider in the `provider` field, e.g. `hetzner`.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+}
+
+impl<P: Provider> Layer<P> for Tracing {
+    type Provider = Intercepted<P, TracingInterceptor>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        let provider_name = self.name.unwrap_or_else(any::type_name::<P>);
+        Intercepted::new(provider, TracingInterceptor { provider_name })
+    }
+}
+
+/// The [`Interceptor`] applied by the [`Tracing`] layer.
+#[derive(Debug, Clone, Copy)]
+pub struct TracingInterceptor {
+    provider_name: &'static str,
+}
+
+impl TracingInterceptor {
+    /// Returns the name recorded in the `provider` field.
+    pub fn provider_name(&self) -> &'static str {
+        self.provider_name
+    }
+}
+
+impl Interceptor for TracingInterceptor {
+    async fn intercept<T, E, F>(
+        &self,
+        operation: Operation<'_>,
+        mut call: impl FnMut() -> F + Send,
+    ) -> Result<T, E>
+    where
+        T: Send,
+        E: Debug + Send,
+        F: Future<Output = Result<T, E>> + Send,
+    {
+        let (host, record_type, record_id) = match operation {
+            Operation::CreateRecord { host, data, .. } => (Some(host), Some(data.get_type()), None),
+            Operation::GetRecord { record_id, .. } | Operation::DeleteRecord { record_id, .. } => {
+                (None, None, Some(record_id))
+            }
+            _ => (None, None, None),
+        };
+        let span = tracing::info_span!(
+            "libdns",
+            provider = self.provider_name,
+            operation = operation.name(),
+            zone_id = operation.zone_id(),
+            zone = operation.zone().map(field::display),
+            host = host.map(field::display),
+            record_type,
+            record_id,
+            outcome = field::Empty,
+            error = field::Empty,
+            duration_ms = field::Empty,
+        );
+
+        let start = Instant::now();
+        let result = call().instrument(span.clone()).await;
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(_) => {
+                span.record("outcome", "ok");
+                tracing::debug!(parent: &span, "{} succeeded", operation.name());
+            }
+            Err(err) => {
+                span.record("outcome", "error");
+                span.record("error", field::debug(err));
+                tracing::warn!(parent: &span, "{} failed", operation.name());
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    use super::*;
+    use crate::{
+        memory::{Failure, MemoryProvider},
+        CreateRecord, DomainName, RecordData, RetrieveZoneError,
+    };
+
+    /// The name and fields of a span, fields being recorded using their debug representation.
+    type Span = (&'static str, BTreeMap<&'static str, String>);
+
+    /// A subscriber collecting the spans created while it is the default.
+    #[derive(Default, Clone)]
+    struct Collector {
+        spans: Arc<Mutex<Vec<Span>>>,
+    }
+
+    /// Records the visited fields into a map.
+    struct Fields<'a>(&'a mut BTreeMap<&'static str, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_owned());
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            let mut fields = BTreeMap::new();
+            attrs.record(&mut Fields(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((attrs.metadata().name(), fields));
+            span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1].1));
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    impl Collector {
+        /// Returns the collected spans, omitting the `duration_ms` field after checking its presence.
+        fn spans(&self) -> Vec<Span> {
+            let mut spans = self.spans.lock().unwrap().clone();
+            for (_, fields) in &mut spans {
+                let duration = fields.remove("duration_ms").expect("missing duration");
+                assert!(duration.parse::<u64>().is_ok(), "{}", duration);
+            }
+            spans
+        }
+    }
+
+    fn fields<const N: usize>(fields: [(&'static str, &str); N]) -> BTreeMap<&'static str, String> {
+        fields
+            .into_iter()
+            .map(|(name, value)| (name, value.to_owned()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn spans_carry_the_call_and_its_outcome() {
+        let collector = Collector::default();
+        let _guard = tracing::subscriber::set_default(collector.clone());
+
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let provider = inner.clone().layer(Tracing::new().with_name("memory"));
+        let zone = provider.get_zone("example.com").await.unwrap();
+        zone.create_record(
+            &DomainName::new("www").unwrap(),
+            &RecordData::TXT(vec!["secret".to_owned()]),
+            300,
+        )
+        .await
+        .unwrap();
+        inner.fail_next(Failure::Unauthorized);
+        assert!(matches!(
+            provider.list_zones().await,
+            Err(RetrieveZoneError::Unauthorized)
+        ));
+
+        assert_eq!(
+            collector.spans(),
+            [
+                (
+                    "libdns",
+                    fields([
+                        ("provider", "memory"),
+                        ("operation", "get_zone"),
+                        ("zone_id", "example.com"),
+                        ("outcome", "ok"),
+                    ])
+                ),
+                (
+                    "libdns",
+                    fields([
+                        ("provider", "memory"),
+                        ("operation", "create_record"),
+                        ("zone_id", "example.com"),
+                        ("zone", "example.com."),
+                        ("host", "www"),
+                        ("record_type", "TXT"),
+                        ("outcome", "ok"),
+                    ])
+                ),
+                (
+                    "libdns",
+                    fields([
+                        ("provider", "memory"),
+                        ("operation", "list_zones"),
+                        ("outcome", "error"),
+                        ("error", "Unauthorized"),
+                    ])
+                ),
+            ]
+        );
+
+        // Without a name, the provider is named by its type.
+        let provider = MemoryProvider::new().layer(Tracing::new());
+        assert_eq!(
+            provider.interceptor().provider_name(),
+            any::type_name::<MemoryProvider>()
+        );
+    }
+}
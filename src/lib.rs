@@ -17,6 +17,17 @@
 //!
 //! - [`CreateRecord`]
 //! - [`DeleteRecord`]
+//!
+//! # Hosts
+//!
+//! Regardless of the provider, [`Record::host`] is always relative to the [`Zone::domain`] of its zone and uses [`DomainName::apex`] (`@`) for the zone's apex,
+//! e.g. `www` for `www.example.com.` in the zone `example.com.`.
+//! Zone domains are always absolute.
+//! [`Zone::relative_host`] and [`Zone::fqdn`] convert between both forms.
+//!
+//! # Layers
+//!
+//! Cross-cutting behavior like logging can be added to any provider and its zones using [`Provider::layer`], see the [`layer`] module.
 
 #![deny(rustdoc::broken_intra_doc_links)]
 #![forbid(unsafe_code)]
@@ -33,8 +44,105 @@ use serde::{Deserialize, Serialize};
 
 use thiserror::Error;
 
+pub use name::{DomainName, DomainNameError};
+
+#[cfg(feature = "acme")]
+pub mod acme;
+#[cfg(feature = "axfr")]
+pub mod axfr;
+#[cfg(feature = "azure")]
+pub mod azure;
+#[cfg(feature = "ddns")]
+pub mod ddns;
+#[cfg(feature = "desec")]
+pub mod desec;
+#[cfg(feature = "digitalocean")]
+pub mod digitalocean;
+pub mod dry_run;
+pub mod dynamic;
+#[cfg(any(feature = "ddns", feature = "zonefile"))]
+mod fs;
+#[cfg(feature = "gandi")]
+pub mod gandi;
+#[cfg(feature = "gcloud")]
+pub mod gcloud;
 #[cfg(feature = "hetzner")]
 pub mod hetzner;
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "digitalocean",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "hetzner",
+    feature = "linode",
+    feature = "ovh",
+    feature = "porkbun",
+    feature = "route53",
+    feature = "vultr"
+))]
+mod http;
+pub mod layer;
+#[cfg(feature = "linode")]
+pub mod linode;
+#[cfg(test)]
+mod memory;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(all(
+    test,
+    any(
+        feature = "azure",
+        feature = "ddns",
+        feature = "desec",
+        feature = "digitalocean",
+        feature = "gandi",
+        feature = "gcloud",
+        feature = "hetzner",
+        feature = "linode",
+        feature = "ovh",
+        feature = "porkbun",
+        feature = "route53",
+        feature = "vultr"
+    )
+))]
+mod mock;
+mod name;
+#[cfg(any(feature = "azure", feature = "gcloud"))]
+mod oauth;
+#[cfg(feature = "ovh")]
+pub mod ovh;
+pub mod policy;
+#[cfg(feature = "porkbun")]
+pub mod porkbun;
+mod presentation;
+#[cfg(feature = "propagation")]
+pub mod propagation;
+#[cfg(feature = "propagation")]
+mod query;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod reverse;
+#[cfg(feature = "route53")]
+pub mod route53;
+#[cfg(any(
+    feature = "axfr",
+    feature = "azure",
+    feature = "desec",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "route53",
+    feature = "zonefile"
+))]
+pub mod rrset;
+#[cfg(feature = "sshfp")]
+pub mod sshfp;
+#[cfg(feature = "vultr")]
+pub mod vultr;
+#[cfg(any(feature = "axfr", feature = "propagation"))]
+mod wire;
+#[cfg(feature = "zonefile")]
+pub mod zonefile;
 
 /// Represents a DNS zone provider.
 ///
@@ -43,26 +151,36 @@ pub mod hetzner;
 ///
 /// - [`CreateZone`]
 /// - [`DeleteZone`]
-pub trait Provider {
+///
+/// Providers, their zones and the futures they return are [`Send`], so they can be used from multi-threaded runtimes.
+pub trait Provider: Send + Sync {
     /// The provider-specific zone type.
     type Zone: Zone;
 
     /// The provider-specific custom zone retrieval error type used for [`RetrieveZoneError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomRetrieveError: Debug;
+    type CustomRetrieveError: Debug + Send;
 
     /// Retrieves all available zones.  
     /// When no record exists, an [`Ok`] value with an empty [`Vec`] will be returned, not [`RetrieveZoneError::NotFound`].
     fn list_zones(
         &self,
-    ) -> impl Future<Output = Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>>>;
+    ) -> impl Future<Output = Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>>> + Send;
 
     /// Retrieves a zone by its provider-specific ID.  
     /// Refer to the provider's documentation to figure out which value is used as the ID.
     fn get_zone(
         &self,
         zone_id: &str,
-    ) -> impl Future<Output = Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>>>;
+    ) -> impl Future<Output = Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>>> + Send;
+
+    /// Wraps the provider using a [`Layer`](layer::Layer), e.g. to add cross-cutting behavior like logging.
+    fn layer<L: layer::Layer<Self>>(self, layer: L) -> L::Provider
+    where
+        Self: Sized,
+    {
+        layer.layer(self)
+    }
 }
 
 /// Represents an error that occured when retrieving DNS zones using [`Provider::list_zones`] or [`Provider::get_zone`].
@@ -89,13 +207,13 @@ pub enum RetrieveZoneError<T> {
 pub trait CreateZone: Provider {
     /// The provider-specific custom zone creation error type used for [`CreateZoneError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomCreateError: Debug;
+    type CustomCreateError: Debug + Send;
 
     /// Creates a new DNS zone with the given domain.
     fn create_zone(
         &self,
-        domain: &str,
-    ) -> impl Future<Output = Result<Self::Zone, CreateZoneError<Self::CustomCreateError>>>;
+        domain: &DomainName,
+    ) -> impl Future<Output = Result<Self::Zone, CreateZoneError<Self::CustomCreateError>>> + Send;
 }
 
 /// Represents an error that occured when creating DNS zones using [`CreateZone::create_zone`].
@@ -122,14 +240,14 @@ pub enum CreateZoneError<T> {
 pub trait DeleteZone: Provider {
     /// The provider-specific custom zone deletion error type used for [`DeleteZoneError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomDeleteError: Debug;
+    type CustomDeleteError: Debug + Send;
 
     /// Deletes a zone by its provider-specific ID.  
     /// Refer to the provider's documentation to figure out which value is used as the ID.
     fn delete_zone(
         &self,
         zone_id: &str,
-    ) -> impl Future<Output = Result<(), DeleteZoneError<Self::CustomDeleteError>>>;
+    ) -> impl Future<Output = Result<(), DeleteZoneError<Self::CustomDeleteError>>> + Send;
 }
 
 /// Represents an error that occured when deleting DNS zones using [`DeleteZone::delete_zone`].
@@ -158,70 +276,290 @@ pub enum DeleteZoneError<T> {
 pub enum RecordData {
     A(Ipv4Addr),
     AAAA(Ipv6Addr),
-    CNAME(String),
+    CNAME(DomainName),
     MX {
         priority: u16,
-        mail_server: String,
+        mail_server: DomainName,
     },
-    NS(String),
+    NS(DomainName),
+    PTR(DomainName),
     SRV {
         priority: u16,
         weight: u16,
         port: u16,
+        target: DomainName,
+    },
+    /// The character-strings of the record, each of them being split into chunks of at most 255 bytes when formatted.
+    TXT(Vec<String>),
+    SSHFP {
+        algorithm: u8,
+        fingerprint_type: u8,
+        fingerprint: Vec<u8>,
+    },
+    NAPTR {
+        order: u16,
+        preference: u16,
+        flags: String,
+        services: String,
+        regexp: String,
+        replacement: DomainName,
+    },
+    URI {
+        priority: u16,
+        weight: u16,
         target: String,
     },
-    TXT(String),
+    LOC {
+        /// The latitude in thousandths of an arc second, positive values being north of the equator.
+        latitude: i64,
+        /// The longitude in thousandths of an arc second, positive values being east of the prime meridian.
+        longitude: i64,
+        /// The altitude in centimeters.
+        altitude: i64,
+        /// The diameter of the enclosing sphere in centimeters.
+        size: u64,
+        /// The horizontal precision in centimeters.
+        horizontal_precision: u64,
+        /// The vertical precision in centimeters.
+        vertical_precision: u64,
+    },
+    HINFO {
+        cpu: String,
+        os: String,
+    },
+    RP {
+        mailbox: DomainName,
+        txt: DomainName,
+    },
+    CERT {
+        cert_type: u16,
+        key_tag: u16,
+        algorithm: u8,
+        certificate: String,
+    },
+    OPENPGPKEY(String),
     Other {
         typ: String,
         value: String,
     },
 }
 
+/// Describes how the character-strings of [`RecordData::TXT`] values are formatted by [`RecordData::get_value_as`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TxtFormat {
+    /// Quotes and escapes every character-string and separates them using spaces, e.g. `"v=DKIM1; k=rsa; " "p=MIIB..."`.
+    /// Character-strings exceeding 255 bytes are split into multiple ones.
+    #[default]
+    Quoted,
+
+    /// Concatenates all character-strings without quoting them.
+    /// This is used by providers splitting the value by themselves.
+    Concatenated,
+}
+
+const CERT_TYPES: &[(&str, u16)] = &[
+    ("PKIX", 1),
+    ("SPKI", 2),
+    ("PGP", 3),
+    ("IPKIX", 4),
+    ("ISPKI", 5),
+    ("IPGP", 6),
+    ("ACPKIX", 7),
+    ("IACPKIX", 8),
+    ("URI", 253),
+    ("OID", 254),
+];
+
+const DNSSEC_ALGORITHMS: &[(&str, u8)] = &[
+    ("RSAMD5", 1),
+    ("DH", 2),
+    ("DSA", 3),
+    ("RSASHA1", 5),
+    ("DSA-NSEC3-SHA1", 6),
+    ("RSASHA1-NSEC3-SHA1", 7),
+    ("RSASHA256", 8),
+    ("RSASHA512", 10),
+    ("ECC-GOST", 12),
+    ("ECDSAP256SHA256", 13),
+    ("ECDSAP384SHA384", 14),
+    ("ED25519", 15),
+    ("ED448", 16),
+];
+
 impl RecordData {
     /// Tries to parse raw DNS record data to their corresponsing [`RecordData`] value.
     ///
+    /// Values using the [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597) generic representation (`\# <length> <hex data>`)
+    /// are passed to [`RecordData::from_generic`].
     /// This function falls back to [`RecordData::Other`] if the value could not be parsed or the type is not supported.
     pub fn from_raw(typ: &str, value: &str) -> RecordData {
+        if let Some(rdata) = presentation::parse_generic(value) {
+            return RecordData::from_generic(typ, &rdata);
+        }
+
         let data = match typ {
             "A" => Ipv4Addr::from_str(value).ok().map(RecordData::A),
             "AAAA" => Ipv6Addr::from_str(value).ok().map(RecordData::AAAA),
-            "CNAME" => Some(RecordData::CNAME(value.to_owned())),
+            "CNAME" => DomainName::from_str(value.trim())
+                .ok()
+                .map(RecordData::CNAME),
             "MX" => {
                 let mut iter = value.split_whitespace();
 
                 let opt_priority = iter.next().and_then(|raw| raw.parse::<u16>().ok());
-                let opt_server = iter.next();
+                let opt_server = iter.next().and_then(|raw| DomainName::from_str(raw).ok());
 
-                match (opt_priority, opt_server) {
-                    (Some(priority), Some(server)) => Some(RecordData::MX {
+                match (opt_priority, opt_server, iter.next()) {
+                    (Some(priority), Some(mail_server), None) => Some(RecordData::MX {
                         priority,
-                        mail_server: server.to_owned(),
+                        mail_server,
                     }),
                     _ => None,
                 }
             }
-            "NS" => Some(RecordData::NS(value.to_owned())),
+            "NS" => DomainName::from_str(value.trim()).ok().map(RecordData::NS),
+            "PTR" => DomainName::from_str(value.trim()).ok().map(RecordData::PTR),
             "SRV" => {
                 let mut iter = value.split_whitespace();
 
                 let opt_priority = iter.next().and_then(|raw| raw.parse::<u16>().ok());
                 let opt_weight = iter.next().and_then(|raw| raw.parse::<u16>().ok());
                 let opt_port = iter.next().and_then(|raw| raw.parse::<u16>().ok());
-                let opt_target = iter.next();
+                let opt_target = iter.next().and_then(|raw| DomainName::from_str(raw).ok());
 
-                match (opt_priority, opt_weight, opt_port, opt_target) {
-                    (Some(priority), Some(weight), Some(port), Some(target)) => {
+                match (opt_priority, opt_weight, opt_port, opt_target, iter.next()) {
+                    (Some(priority), Some(weight), Some(port), Some(target), None) => {
                         Some(RecordData::SRV {
                             priority,
                             weight,
                             port,
-                            target: target.to_owned(),
+                            target,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            "TXT" => {
+                if value.trim_start().starts_with('"') {
+                    presentation::tokenize(value).map(RecordData::TXT)
+                } else {
+                    Some(RecordData::TXT(vec![value.to_owned()]))
+                }
+            }
+            "SSHFP" => {
+                let mut iter = value.split_whitespace();
+
+                let opt_algorithm = iter.next().and_then(|raw| raw.parse::<u8>().ok());
+                let opt_fingerprint_type = iter.next().and_then(|raw| raw.parse::<u8>().ok());
+                let opt_fingerprint = presentation::decode_hex(&iter.collect::<String>());
+
+                match (opt_algorithm, opt_fingerprint_type, opt_fingerprint) {
+                    (Some(algorithm), Some(fingerprint_type), Some(fingerprint))
+                        if !fingerprint.is_empty() =>
+                    {
+                        Some(RecordData::SSHFP {
+                            algorithm,
+                            fingerprint_type,
+                            fingerprint,
                         })
                     }
                     _ => None,
                 }
             }
-            "TXT" => Some(RecordData::TXT(value.to_owned())),
+            "NAPTR" => match presentation::tokenize(value).as_deref() {
+                Some([order, preference, flags, services, regexp, replacement]) => {
+                    match (
+                        order.parse::<u16>(),
+                        preference.parse::<u16>(),
+                        DomainName::from_str(replacement),
+                    ) {
+                        (Ok(order), Ok(preference), Ok(replacement)) => Some(RecordData::NAPTR {
+                            order,
+                            preference,
+                            flags: flags.clone(),
+                            services: services.clone(),
+                            regexp: regexp.clone(),
+                            replacement,
+                        }),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            "URI" => match presentation::tokenize(value).as_deref() {
+                Some([priority, weight, target]) => {
+                    match (priority.parse::<u16>(), weight.parse::<u16>()) {
+                        (Ok(priority), Ok(weight)) => Some(RecordData::URI {
+                            priority,
+                            weight,
+                            target: target.clone(),
+                        }),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            "LOC" => parse_loc(value),
+            "HINFO" => match presentation::tokenize(value).as_deref() {
+                Some([cpu, os]) => Some(RecordData::HINFO {
+                    cpu: cpu.clone(),
+                    os: os.clone(),
+                }),
+                _ => None,
+            },
+            "RP" => {
+                let mut iter = value
+                    .split_whitespace()
+                    .map(|raw| DomainName::from_str(raw).ok());
+
+                match (iter.next(), iter.next(), iter.next()) {
+                    (Some(Some(mailbox)), Some(Some(txt)), None) => {
+                        Some(RecordData::RP { mailbox, txt })
+                    }
+                    _ => None,
+                }
+            }
+            "CERT" => {
+                let mut iter = value.split_whitespace();
+
+                let opt_cert_type = iter.next().and_then(|raw| {
+                    raw.parse::<u16>().ok().or_else(|| {
+                        CERT_TYPES
+                            .iter()
+                            .find(|(name, _)| raw.eq_ignore_ascii_case(name))
+                            .map(|(_, typ)| *typ)
+                    })
+                });
+                let opt_key_tag = iter.next().and_then(|raw| raw.parse::<u16>().ok());
+                let opt_algorithm = iter.next().and_then(|raw| {
+                    raw.parse::<u8>().ok().or_else(|| {
+                        DNSSEC_ALGORITHMS
+                            .iter()
+                            .find(|(name, _)| raw.eq_ignore_ascii_case(name))
+                            .map(|(_, algorithm)| *algorithm)
+                    })
+                });
+                let certificate = iter.collect::<String>();
+
+                match (opt_cert_type, opt_key_tag, opt_algorithm) {
+                    (Some(cert_type), Some(key_tag), Some(algorithm))
+                        if !certificate.is_empty() =>
+                    {
+                        Some(RecordData::CERT {
+                            cert_type,
+                            key_tag,
+                            algorithm,
+                            certificate,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            "OPENPGPKEY" => {
+                let key = value.split_whitespace().collect::<String>();
+                (!key.is_empty()).then_some(RecordData::OPENPGPKEY(key))
+            }
             _ => None,
         };
 
@@ -231,47 +569,307 @@ impl RecordData {
         })
     }
 
+    /// Converts binary record data (RDATA) as used by the [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597) generic representation
+    /// to their corresponding [`RecordData`] value.
+    ///
+    /// Only types whose wire format does not contain domain names are decoded (`A`, `AAAA` and `SSHFP`).
+    /// All other types are represented as [`RecordData::Other`] using the generic representation (`\# <length> <hex data>`).
+    pub fn from_generic(typ: &str, rdata: &[u8]) -> RecordData {
+        let data = match (typ, rdata.len()) {
+            ("A", 4) => <[u8; 4]>::try_from(rdata)
+                .ok()
+                .map(|octets| RecordData::A(octets.into())),
+            ("AAAA", 16) => <[u8; 16]>::try_from(rdata)
+                .ok()
+                .map(|octets| RecordData::AAAA(octets.into())),
+            ("SSHFP", 3..) => Some(RecordData::SSHFP {
+                algorithm: rdata[0],
+                fingerprint_type: rdata[1],
+                fingerprint: rdata[2..].to_vec(),
+            }),
+            _ => None,
+        };
+
+        data.unwrap_or(RecordData::Other {
+            typ: typ.to_owned(),
+            value: presentation::format_generic(rdata),
+        })
+    }
+
     pub fn get_type(&self) -> &str {
         match self {
             RecordData::A(_) => "A",
-            RecordData::AAAA(_) => "A",
+            RecordData::AAAA(_) => "AAAA",
             RecordData::CNAME(_) => "CNAME",
             RecordData::MX { .. } => "MX",
             RecordData::NS(_) => "NS",
+            RecordData::PTR(_) => "PTR",
             RecordData::SRV { .. } => "SRV",
             RecordData::TXT(_) => "TXT",
+            RecordData::SSHFP { .. } => "SSHFP",
+            RecordData::NAPTR { .. } => "NAPTR",
+            RecordData::URI { .. } => "URI",
+            RecordData::LOC { .. } => "LOC",
+            RecordData::HINFO { .. } => "HINFO",
+            RecordData::RP { .. } => "RP",
+            RecordData::CERT { .. } => "CERT",
+            RecordData::OPENPGPKEY(_) => "OPENPGPKEY",
             RecordData::Other { typ, .. } => typ.as_str(),
         }
     }
 
+    /// Creates a [`RecordData::TXT`] value consisting of a single character-string.
+    pub fn txt(value: impl Into<String>) -> RecordData {
+        RecordData::TXT(vec![value.into()])
+    }
+
+    /// Returns the value in presentation format, e.g. as used in zone files.
+    ///
+    /// Equal to calling [`RecordData::get_value_as`] with [`TxtFormat::Quoted`].
     pub fn get_value(&self) -> String {
+        self.get_value_as(TxtFormat::Quoted)
+    }
+
+    /// Returns the value, formatting [`RecordData::TXT`] values using the given [`TxtFormat`].
+    ///
+    /// Providers use this to pass TXT records to their backend in the format it requires.
+    pub fn get_value_as(&self, txt_format: TxtFormat) -> String {
         match self {
             RecordData::A(addr) => addr.to_string(),
             RecordData::AAAA(addr) => addr.to_string(),
-            RecordData::CNAME(alias) => alias.clone(),
+            RecordData::CNAME(alias) => alias.to_string(),
             RecordData::MX {
                 priority,
                 mail_server,
             } => format!("{} {}", priority, mail_server),
-            RecordData::NS(ns) => ns.clone(),
+            RecordData::NS(ns) => ns.to_string(),
+            RecordData::PTR(target) => target.to_string(),
             RecordData::SRV {
                 priority,
                 weight,
                 port,
                 target,
             } => format!("{} {} {} {}", priority, weight, port, target),
-            RecordData::TXT(val) => val.clone(),
+            RecordData::TXT(strings) => match txt_format {
+                TxtFormat::Quoted => {
+                    let chunks = strings
+                        .iter()
+                        .flat_map(|string| presentation::split_chunks(string, 255))
+                        .map(presentation::quote)
+                        .collect::<Vec<String>>();
+                    if chunks.is_empty() {
+                        return presentation::quote("");
+                    }
+                    chunks.join(" ")
+                }
+                TxtFormat::Concatenated => strings.concat(),
+            },
+            RecordData::SSHFP {
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+            } => format!(
+                "{} {} {}",
+                algorithm,
+                fingerprint_type,
+                presentation::encode_hex(fingerprint)
+            ),
+            RecordData::NAPTR {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => format!(
+                "{} {} {} {} {} {}",
+                order,
+                preference,
+                presentation::quote(flags),
+                presentation::quote(services),
+                presentation::quote(regexp),
+                replacement
+            ),
+            RecordData::URI {
+                priority,
+                weight,
+                target,
+            } => format!("{} {} {}", priority, weight, presentation::quote(target)),
+            RecordData::LOC {
+                latitude,
+                longitude,
+                altitude,
+                size,
+                horizontal_precision,
+                vertical_precision,
+            } => format!(
+                "{} {} {} {} {} {}",
+                format_loc_coordinate(*latitude, 'N', 'S'),
+                format_loc_coordinate(*longitude, 'E', 'W'),
+                format_loc_meters(*altitude),
+                format_loc_meters(*size as i64),
+                format_loc_meters(*horizontal_precision as i64),
+                format_loc_meters(*vertical_precision as i64)
+            ),
+            RecordData::HINFO { cpu, os } => {
+                format!("{} {}", presentation::quote(cpu), presentation::quote(os))
+            }
+            RecordData::RP { mailbox, txt } => format!("{} {}", mailbox, txt),
+            RecordData::CERT {
+                cert_type,
+                key_tag,
+                algorithm,
+                certificate,
+            } => format!("{} {} {} {}", cert_type, key_tag, algorithm, certificate),
+            RecordData::OPENPGPKEY(key) => key.clone(),
             RecordData::Other { value, .. } => value.clone(),
         }
     }
+
+    /// Returns the binary record data (RDATA) as used by the [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597) generic representation.
+    ///
+    /// [`None`] is returned for types not supported by [`RecordData::from_generic`] and [`RecordData::Other`] values
+    /// not using the generic representation.
+    pub fn get_generic_data(&self) -> Option<Vec<u8>> {
+        match self {
+            RecordData::A(addr) => Some(addr.octets().to_vec()),
+            RecordData::AAAA(addr) => Some(addr.octets().to_vec()),
+            RecordData::SSHFP {
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+            } => Some([&[*algorithm, *fingerprint_type], fingerprint.as_slice()].concat()),
+            RecordData::Other { value, .. } => presentation::parse_generic(value),
+            _ => None,
+        }
+    }
+}
+
+fn parse_loc(value: &str) -> Option<RecordData> {
+    let mut iter = value.split_whitespace().peekable();
+
+    let latitude = parse_loc_coordinate(&mut iter, 90, 'N', 'S')?;
+    let longitude = parse_loc_coordinate(&mut iter, 180, 'E', 'W')?;
+    let altitude = parse_loc_meters(iter.next()?)?;
+
+    let mut optional = [100, 1_000_000, 1_000];
+    for field in optional.iter_mut() {
+        match iter.next() {
+            Some(raw) => *field = u64::try_from(parse_loc_meters(raw)?).ok()?,
+            None => break,
+        }
+    }
+    if iter.next().is_some() {
+        return None;
+    }
+
+    let [size, horizontal_precision, vertical_precision] = optional;
+    Some(RecordData::LOC {
+        latitude,
+        longitude,
+        altitude,
+        size,
+        horizontal_precision,
+        vertical_precision,
+    })
+}
+
+fn parse_loc_coordinate<'a>(
+    iter: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+    max_degrees: i64,
+    positive: char,
+    negative: char,
+) -> Option<i64> {
+    let is_hemisphere = |raw: &str| {
+        raw.eq_ignore_ascii_case(&positive.to_string())
+            || raw.eq_ignore_ascii_case(&negative.to_string())
+    };
+
+    let degrees = iter.next()?.parse::<i64>().ok()?;
+    let mut minutes = 0;
+    let mut seconds = 0;
+    if !is_hemisphere(iter.peek()?) {
+        minutes = iter.next()?.parse::<i64>().ok()?;
+        if !is_hemisphere(iter.peek()?) {
+            seconds = parse_fixed(iter.next()?, 3)?;
+        }
+    }
+
+    let hemisphere = iter.next()?;
+    if !(0..=max_degrees).contains(&degrees)
+        || !(0..60).contains(&minutes)
+        || !(0..60_000).contains(&seconds)
+        || !is_hemisphere(hemisphere)
+    {
+        return None;
+    }
+
+    let value = (degrees * 3600 + minutes * 60) * 1000 + seconds;
+    if value > max_degrees * 3_600_000 {
+        return None;
+    }
+
+    if hemisphere.eq_ignore_ascii_case(&negative.to_string()) {
+        return Some(-value);
+    }
+    Some(value)
+}
+
+fn parse_loc_meters(raw: &str) -> Option<i64> {
+    parse_fixed(raw.strip_suffix(['m', 'M']).unwrap_or(raw), 2)
+}
+
+/// Parses a decimal number with at most `decimals` fractional digits into an integer scaled by `10^decimals`.
+fn parse_fixed(raw: &str, decimals: usize) -> Option<i64> {
+    let (negative, raw) = match raw.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    };
+    let (integer, fraction) = raw.split_once('.').unwrap_or((raw, ""));
+
+    if integer.is_empty()
+        || fraction.len() > decimals
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let value = format!("{}{:0<width$}", integer, fraction, width = decimals)
+        .parse::<i64>()
+        .ok()?;
+    Some(if negative { -value } else { value })
+}
+
+fn format_loc_coordinate(value: i64, positive: char, negative: char) -> String {
+    let hemisphere = if value < 0 { negative } else { positive };
+    let value = value.unsigned_abs();
+    format!(
+        "{} {} {}.{:03} {}",
+        value / 3_600_000,
+        value / 60_000 % 60,
+        value / 1000 % 60,
+        value % 1000,
+        hemisphere
+    )
+}
+
+fn format_loc_meters(value: i64) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.unsigned_abs();
+    format!("{}{}.{:02}m", sign, value / 100, value % 100)
 }
 
 /// Represents a DNS record.
+///
+/// The host is relative to the domain of the zone containing the record (see [`Zone::relative_host`]).
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Record {
     pub id: String,
-    pub host: String,
+    pub host: DomainName,
     pub data: RecordData,
     pub ttl: u64,
 }
@@ -282,30 +880,43 @@ pub struct Record {
 /// By default, only record retrieval is supported, but the following capabilities may be implemented to allow further record management:
 ///
 /// - [`CreateRecord`]
-/// - [`CreateRecord`]
-pub trait Zone {
+/// - [`DeleteRecord`]
+pub trait Zone: Send + Sync {
     /// The provider-specific custom record retrieval error type used for [`RetrieveRecordError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomRetrieveError: Debug;
+    type CustomRetrieveError: Debug + Send;
 
     /// Returns the provider-specific ID of the zone.
     fn id(&self) -> &str;
 
-    /// Returns the domain the zone manages.
-    fn domain(&self) -> &str;
+    /// Returns the absolute domain the zone manages.
+    fn domain(&self) -> &DomainName;
+
+    /// Converts a record host to the canonical form relative to the zone's domain.
+    ///
+    /// Relative hosts are returned as they are, absolute ones are made relative to [`Zone::domain`].
+    /// [`None`] is returned if an absolute host does not belong to the zone.
+    fn relative_host(&self, host: &DomainName) -> Option<DomainName> {
+        host.relative_to(self.domain())
+    }
+
+    /// Converts a record host to its fully qualified form by appending the zone's domain.
+    fn fqdn(&self, host: &DomainName) -> DomainName {
+        host.to_fqdn(self.domain())
+    }
 
     /// Retrieves all available records.  
     /// When no record exists, an [`Ok`] value with an empty [`Vec`] will be returned, not [`RetrieveRecordError::NotFound`].
     fn list_records(
         &self,
-    ) -> impl Future<Output = Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>>>;
+    ) -> impl Future<Output = Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>>> + Send;
 
     /// Retrieves a record by its provider-specific ID.  
     /// Refer to the provider's documentation to figure out which value is used as the ID.
     fn get_record(
         &self,
         record_id: &str,
-    ) -> impl Future<Output = Result<Record, RetrieveRecordError<Self::CustomRetrieveError>>>;
+    ) -> impl Future<Output = Result<Record, RetrieveRecordError<Self::CustomRetrieveError>>> + Send;
 }
 
 /// Represents an error that occured when retrieving DNS records using [`Zone::list_records`] or [`Zone::get_record`].
@@ -332,15 +943,17 @@ pub enum RetrieveRecordError<T> {
 pub trait CreateRecord: Zone {
     /// The provider-specific custom record creation error type used for [`CreateRecordError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomCreateError: Debug;
+    type CustomCreateError: Debug + Send;
 
     /// Creates a new record.
+    ///
+    /// The host may either be relative to the zone's domain or fully qualified (see [`Zone::relative_host`]).
     fn create_record(
         &self,
-        host: &str,
+        host: &DomainName,
         data: &RecordData,
         ttl: u64,
-    ) -> impl Future<Output = Result<Record, CreateRecordError<Self::CustomCreateError>>>;
+    ) -> impl Future<Output = Result<Record, CreateRecordError<Self::CustomCreateError>>> + Send;
 }
 
 /// Represents an error that occured when creating DNS records using [`CreateRecord::create_record`].
@@ -371,13 +984,13 @@ pub enum CreateRecordError<T> {
 pub trait DeleteRecord: Zone {
     /// The provider-specific custom record creation error type used for [`DeleteRecordError::Custom`].  
     /// If no custom errors should be provided, use `()`.
-    type CustomDeleteError: Debug;
+    type CustomDeleteError: Debug + Send;
 
     /// Deletes a record by its ID.
     fn delete_record(
         &self,
         record_id: &str,
-    ) -> impl Future<Output = Result<(), DeleteRecordError<Self::CustomDeleteError>>>;
+    ) -> impl Future<Output = Result<(), DeleteRecordError<Self::CustomDeleteError>>> + Send;
 }
 
 /// Represents an error that occured when deleting DNS records using [`DeleteRecord::delete_record`].
@@ -399,3 +1012,360 @@ pub enum DeleteRecordError<T> {
     #[error(transparent)]
     Custom(#[from] T),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_types() {
+        for (typ, value) in [
+            ("A", "192.0.2.1"),
+            ("AAAA", "2001:db8::1"),
+            ("CNAME", "example.com."),
+            ("MX", "10 mail.example.com."),
+            ("SRV", "10 20 443 example.com."),
+            ("TXT", "\"hello\""),
+        ] {
+            assert_eq!(RecordData::from_raw(typ, value).get_type(), typ);
+        }
+    }
+
+    #[test]
+    fn txt_quoting_and_chunking() {
+        let long = "a".repeat(300);
+        let data = RecordData::TXT(vec!["v=spf1 \"quoted\"".to_owned(), long.clone()]);
+        assert_eq!(
+            data.get_value_as(TxtFormat::Quoted),
+            format!(
+                r#""v=spf1 \"quoted\"" "{}" "{}""#,
+                &long[..255],
+                &long[255..]
+            )
+        );
+        assert_eq!(
+            data.get_value_as(TxtFormat::Concatenated),
+            format!(r#"v=spf1 "quoted"{}"#, long)
+        );
+
+        assert_eq!(RecordData::TXT(Vec::new()).get_value(), r#""""#);
+        assert_eq!(RecordData::txt("").get_value(), r#""""#);
+        assert_eq!(
+            RecordData::TXT(vec!["a".to_owned(), String::new()]).get_value(),
+            r#""a" """#
+        );
+
+        let multibyte = RecordData::txt("ä".repeat(128));
+        let value = multibyte.get_value();
+        assert_eq!(
+            RecordData::from_raw("TXT", &value),
+            RecordData::TXT(vec!["ä".repeat(127), "ä".to_owned()])
+        );
+    }
+
+    #[test]
+    fn txt_parsing() {
+        assert_eq!(
+            RecordData::from_raw("TXT", r#""first" "second \"part\"""#),
+            RecordData::TXT(vec!["first".to_owned(), r#"second "part""#.to_owned()])
+        );
+        assert_eq!(
+            RecordData::from_raw("TXT", "unquoted value"),
+            RecordData::txt("unquoted value")
+        );
+        assert_eq!(RecordData::from_raw("TXT", r#""""#), RecordData::txt(""));
+    }
+
+    #[test]
+    fn loc_round_trip() {
+        let data = RecordData::from_raw("LOC", "42 21 54 N 71 06 18 W -24m 30m");
+        assert_eq!(
+            data,
+            RecordData::LOC {
+                latitude: 152_514_000,
+                longitude: -255_978_000,
+                altitude: -2400,
+                size: 3000,
+                horizontal_precision: 1_000_000,
+                vertical_precision: 1000,
+            }
+        );
+        assert_eq!(
+            data.get_value(),
+            "42 21 54.000 N 71 6 18.000 W -24.00m 30.00m 10000.00m 10.00m"
+        );
+
+        for value in [
+            "42 21 54.000 N 71 6 18.000 W -24.00m 30.00m 10000.00m 10.00m",
+            "52 22 23.123 N 4 53 32.500 E 2.05m 1.00m 0.50m 0.01m",
+            "0 0 0.000 N 180 0 0.000 E 0.00m 1.00m 10000.00m 10.00m",
+            "90 0 0.000 S 0 0 0.000 E 42849672.95m 1.00m 10000.00m 10.00m",
+        ] {
+            let data = RecordData::from_raw("LOC", value);
+            assert!(matches!(data, RecordData::LOC { .. }), "{}", value);
+            assert_eq!(data.get_value(), value);
+        }
+
+        assert_eq!(
+            RecordData::from_raw("LOC", "52 N 4 E 10m"),
+            RecordData::LOC {
+                latitude: 187_200_000,
+                longitude: 14_400_000,
+                altitude: 1000,
+                size: 100,
+                horizontal_precision: 1_000_000,
+                vertical_precision: 1000,
+            }
+        );
+
+        for value in [
+            "91 0 0 N 0 0 0 E 0m",
+            "90 0 0.001 N 0 0 0 E 0m",
+            "42 60 0 N 0 0 0 E 0m",
+            "42 0 0 X 0 0 0 E 0m",
+            "42 0 0 N 0 0 0 E",
+            "42 0 0 N 0 0 0 E 0.001m",
+            "42 0 0 N 0 0 0 E 0m 1m 1m 1m 1m",
+        ] {
+            assert!(
+                matches!(RecordData::from_raw("LOC", value), RecordData::Other { .. }),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn trailing_tokens_are_rejected() {
+        for (typ, value) in [
+            ("MX", "10 mail.example.com. extra"),
+            ("SRV", "10 20 443 example.com. extra"),
+        ] {
+            assert_eq!(
+                RecordData::from_raw(typ, value),
+                RecordData::Other {
+                    typ: typ.to_owned(),
+                    value: value.to_owned()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for (typ, value, data) in [
+            (
+                "SSHFP",
+                "4 2 d955f4f25f784b29d3503af4162abb92618a8f9d30ae12e2403effee560acb89",
+                RecordData::SSHFP {
+                    algorithm: 4,
+                    fingerprint_type: 2,
+                    fingerprint: presentation::decode_hex(
+                        "d955f4f25f784b29d3503af4162abb92618a8f9d30ae12e2403effee560acb89",
+                    )
+                    .unwrap(),
+                },
+            ),
+            (
+                "NAPTR",
+                r#"100 10 "S" "SIP+D2U" "!^.*$!sip:info@example.com!" _sip._udp.example.com."#,
+                RecordData::NAPTR {
+                    order: 100,
+                    preference: 10,
+                    flags: "S".to_owned(),
+                    services: "SIP+D2U".to_owned(),
+                    regexp: "!^.*$!sip:info@example.com!".to_owned(),
+                    replacement: DomainName::new("_sip._udp.example.com.").unwrap(),
+                },
+            ),
+            (
+                "NAPTR",
+                r#"10 0 "" "" "" ."#,
+                RecordData::NAPTR {
+                    order: 10,
+                    preference: 0,
+                    flags: String::new(),
+                    services: String::new(),
+                    regexp: String::new(),
+                    replacement: DomainName::new(".").unwrap(),
+                },
+            ),
+            (
+                "URI",
+                r#"10 1 "ftp://ftp1.example.com/public""#,
+                RecordData::URI {
+                    priority: 10,
+                    weight: 1,
+                    target: "ftp://ftp1.example.com/public".to_owned(),
+                },
+            ),
+            (
+                "HINFO",
+                r#""INTEL-386" "Linux \"custom\"""#,
+                RecordData::HINFO {
+                    cpu: "INTEL-386".to_owned(),
+                    os: r#"Linux "custom""#.to_owned(),
+                },
+            ),
+            (
+                "RP",
+                "admin.example.com. info.example.com.",
+                RecordData::RP {
+                    mailbox: DomainName::new("admin.example.com.").unwrap(),
+                    txt: DomainName::new("info.example.com.").unwrap(),
+                },
+            ),
+            (
+                "CERT",
+                "3 0 0 mQENBFVmRwcBCAC",
+                RecordData::CERT {
+                    cert_type: 3,
+                    key_tag: 0,
+                    algorithm: 0,
+                    certificate: "mQENBFVmRwcBCAC".to_owned(),
+                },
+            ),
+            (
+                "OPENPGPKEY",
+                "mQINBFit2jsBEADrbl5vjVxYeAE0g0IDYCBpHirv1Sjlqx",
+                RecordData::OPENPGPKEY("mQINBFit2jsBEADrbl5vjVxYeAE0g0IDYCBpHirv1Sjlqx".to_owned()),
+            ),
+        ] {
+            let parsed = RecordData::from_raw(typ, value);
+            assert_eq!(parsed, data, "{} {}", typ, value);
+            assert_eq!(parsed.get_type(), typ);
+            assert_eq!(parsed.get_value(), value);
+        }
+
+        // Alternative spellings are normalized when formatting.
+        for (typ, value, formatted) in [
+            (
+                "SSHFP",
+                "1 1 4FFB422B59E5B3E7 681B0963D1AA87A2BAE463EA",
+                "1 1 4ffb422b59e5b3e7681b0963d1aa87a2bae463ea",
+            ),
+            (
+                "NAPTR",
+                r#"100 10 S SIP+D2U "" example.com."#,
+                r#"100 10 "S" "SIP+D2U" "" example.com."#,
+            ),
+            ("HINFO", "ARM64 Linux", r#""ARM64" "Linux""#),
+            (
+                "CERT",
+                "PGP 0 RSASHA256 mQENBFVm RwcBCAC",
+                "3 0 8 mQENBFVmRwcBCAC",
+            ),
+            (
+                "CERT",
+                "ipkix 12345 ed25519 aHR0cHM6",
+                "4 12345 15 aHR0cHM6",
+            ),
+            ("OPENPGPKEY", "mQINBFit 2jsBEADr", "mQINBFit2jsBEADr"),
+        ] {
+            assert_eq!(
+                RecordData::from_raw(typ, value).get_value(),
+                formatted,
+                "{} {}",
+                typ,
+                value
+            );
+        }
+
+        for (typ, value) in [
+            ("SSHFP", "4 2"),
+            ("SSHFP", "4 2 not-hex"),
+            (
+                "NAPTR",
+                r#"100 10 "S" "SIP+D2U" "!^.*$!sip:info@example.com!""#,
+            ),
+            ("URI", r#"10 "ftp://ftp1.example.com/public""#),
+            ("HINFO", r#""INTEL-386""#),
+            ("RP", "admin.example.com."),
+            ("CERT", "FOO 0 0 mQENBFVmRwcBCAC"),
+            ("CERT", "3 0 0"),
+            ("OPENPGPKEY", " "),
+        ] {
+            assert_eq!(
+                RecordData::from_raw(typ, value),
+                RecordData::Other {
+                    typ: typ.to_owned(),
+                    value: value.to_owned()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn generic_representation() {
+        assert_eq!(
+            RecordData::from_raw("A", r"\# 4 c0000201"),
+            RecordData::A("192.0.2.1".parse().unwrap())
+        );
+        assert_eq!(
+            RecordData::from_raw("AAAA", r"\# 16 20010db8 00000000 00000000 00000001"),
+            RecordData::AAAA("2001:db8::1".parse().unwrap())
+        );
+        let sshfp = RecordData::from_raw("SSHFP", r"\# 6 0401 DEADBEEF");
+        assert_eq!(sshfp.get_value(), "4 1 deadbeef");
+        assert_eq!(
+            sshfp.get_generic_data(),
+            Some(vec![4, 1, 0xde, 0xad, 0xbe, 0xef])
+        );
+
+        // Types whose wire format contains domain names are kept in the generic representation.
+        let mx = RecordData::from_raw("MX", r"\# 3 000a00");
+        assert_eq!(
+            mx,
+            RecordData::Other {
+                typ: "MX".to_owned(),
+                value: r"\# 3 000a00".to_owned()
+            }
+        );
+        assert_eq!(mx.get_generic_data(), Some(vec![0, 10, 0]));
+        assert_eq!(
+            RecordData::from_generic("TYPE65534", &[]).get_value(),
+            r"\# 0"
+        );
+        assert_eq!(
+            RecordData::from_raw("TYPE65534", r"\# 0").get_generic_data(),
+            Some(Vec::new())
+        );
+
+        // Data of unexpected lengths are not decoded.
+        assert_eq!(
+            RecordData::from_generic("A", &[192, 0, 2]),
+            RecordData::Other {
+                typ: "A".to_owned(),
+                value: r"\# 3 c00002".to_owned()
+            }
+        );
+        assert_eq!(
+            RecordData::from_generic("SSHFP", &[4, 1]).get_value(),
+            r"\# 2 0401"
+        );
+
+        // Mismatched lengths and malformed hex data are not treated as the generic representation.
+        for value in [
+            r"\# 5 c0000201",
+            r"\# 3 c0000201",
+            r"\# 4 c00002zz",
+            r"\#4 c0000201",
+        ] {
+            let data = RecordData::from_raw("A", value);
+            assert_eq!(
+                data,
+                RecordData::Other {
+                    typ: "A".to_owned(),
+                    value: value.to_owned()
+                }
+            );
+            assert_eq!(data.get_generic_data(), None);
+        }
+
+        assert_eq!(
+            RecordData::A("192.0.2.1".parse().unwrap()).get_generic_data(),
+            Some(vec![192, 0, 2, 1])
+        );
+        assert_eq!(RecordData::txt("hello").get_generic_data(), None);
+    }
+}
@@ -0,0 +1,208 @@
+use std::error::Error;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client as HttpClient,
+};
+use serde::{Deserialize, Serialize};
+
+pub const LINODE_API_URL: &str = "https://api.linode.com/v4";
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(api_token: &str, base_url: &str) -> Result<Self, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value = HeaderValue::from_str(&format!("Bearer {}", api_token))?;
+        auth_value.set_sensitive(true);
+        headers.append(AUTHORIZATION, auth_value);
+
+        let http_client = HttpClient::builder().default_headers(headers).build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    pub async fn retrieve_domains(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PageResponse<Domain>, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains?page={}&page_size={}",
+                self.base_url, page, page_size
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_domain(&self, domain_id: u64) -> Result<Domain, reqwest::Error> {
+        self.http_client
+            .get(format!("{}/domains/{}", self.base_url, domain_id))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_domain(
+        &self,
+        request: &DomainRequest<'_>,
+    ) -> Result<Domain, reqwest::Error> {
+        self.http_client
+            .post(format!("{}/domains", self.base_url))
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_domain(&self, domain_id: u64) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!("{}/domains/{}", self.base_url, domain_id))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+
+    pub async fn retrieve_records(
+        &self,
+        domain_id: u64,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PageResponse<Record>, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains/{}/records?page={}&page_size={}",
+                self.base_url, domain_id, page, page_size
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_record(
+        &self,
+        domain_id: u64,
+        record_id: u64,
+    ) -> Result<Record, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain_id, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_record(
+        &self,
+        domain_id: u64,
+        request: &RecordRequest<'_>,
+    ) -> Result<Record, reqwest::Error> {
+        self.http_client
+            .post(format!("{}/domains/{}/records", self.base_url, domain_id))
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_record(
+        &self,
+        domain_id: u64,
+        record_id: u64,
+    ) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain_id, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct PageResponse<T> {
+    pub data: Vec<T>,
+    pub page: u32,
+    pub pages: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub id: u64,
+    pub domain: String,
+    #[serde(default)]
+    pub ttl_sec: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct DomainRequest<'a> {
+    pub domain: &'a str,
+    #[serde(rename = "type")]
+    pub typ: &'a str,
+    pub soa_email: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Record {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub name: String,
+    pub target: String,
+    #[serde(default)]
+    pub priority: u16,
+    #[serde(default)]
+    pub weight: u16,
+    #[serde(default)]
+    pub port: u16,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub ttl_sec: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct RecordRequest<'a> {
+    #[serde(rename = "type")]
+    pub typ: &'a str,
+    pub name: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub ttl_sec: u64,
+}
@@ -0,0 +1,470 @@
+//! Provider implementation for [Linode](https://www.linode.com/products/dns-manager/).
+//!
+//! Requests are authenticated using a personal access token with read/write access to domains.
+//! Zones and records are identified by the integer IDs Linode assigns to them.
+
+use std::{error::Error as StdErr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 9] =
+    &["A", "AAAA", "CAA", "CNAME", "MX", "NS", "PTR", "SRV", "TXT"];
+
+/// Linode expects TXT values as a single unquoted string and splits it by itself.
+const TXT_FORMAT: TxtFormat = TxtFormat::Concatenated;
+
+/// The TTL Linode applies to domains and records with a `ttl_sec` of `0`.
+const DEFAULT_TTL: u64 = 86400;
+
+const PAGE_SIZE: u32 = 500;
+
+const PROVIDER_NAME: &str = "linode";
+
+#[derive(Debug, Clone)]
+pub struct LinodeProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl LinodeProvider {
+    pub fn new(api_token: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(api_token, api::LINODE_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://api.linode.com/v4`, e.g. a mock server.
+    pub fn with_base_url(api_token: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_token, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for LinodeProvider {
+    type Zone = LinodeZone;
+    type CustomRetrieveError = reqwest::Error;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone_id = zone_id
+            .parse::<u64>()
+            .map_err(|_| RetrieveZoneError::NotFound)?;
+
+        let domain = self
+            .api_client
+            .retrieve_domain(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(LinodeZone::new(self.api_client.clone(), domain))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domains = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            self.api_client
+                .retrieve_domains(page.unwrap_or(1), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.data,
+                    next: (response.page < response.pages).then_some(response.page + 1),
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| LinodeZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+impl CreateZone for LinodeProvider {
+    type CustomCreateError = reqwest::Error;
+
+    /// Creates a primary zone, using `hostmaster@<domain>` as the SOA email address.
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let request = api::DomainRequest {
+            domain: domain.as_ascii(),
+            typ: "master",
+            soa_email: format!("hostmaster@{}", domain.as_ascii()),
+        };
+
+        let domain = self
+            .api_client
+            .create_domain(&request)
+            .await
+            .map_err(CreateZoneError::from_status)?;
+
+        Ok(LinodeZone::new(self.api_client.clone(), domain))
+    }
+}
+
+impl DeleteZone for LinodeProvider {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        let zone_id = zone_id
+            .parse::<u64>()
+            .map_err(|_| DeleteZoneError::NotFound)?;
+
+        self.api_client
+            .delete_domain(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LinodeZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    id: String,
+    domain: DomainName,
+}
+
+impl LinodeZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let id = repr.id.to_string();
+        let domain = DomainName::from_raw(&repr.domain).to_absolute();
+        Self {
+            api_client,
+            repr,
+            id,
+            domain,
+        }
+    }
+
+    /// Returns the TTL records without an explicit TTL inherit from the domain.
+    fn default_ttl(&self) -> u64 {
+        match self.repr.ttl_sec {
+            0 => DEFAULT_TTL,
+            ttl => ttl,
+        }
+    }
+}
+
+impl Zone for LinodeZone {
+    type CustomRetrieveError = reqwest::Error;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let records = collect_pages(PROVIDER_NAME, |page: Option<u32>| async move {
+            self.api_client
+                .retrieve_records(self.repr.id, page.unwrap_or(1), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.data,
+                    next: (response.page < response.pages).then_some(response.page + 1),
+                })
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| record.into_generic(&self.domain, self.default_ttl()))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| RetrieveRecordError::NotFound)?;
+
+        let record = self
+            .api_client
+            .retrieve_record(self.repr.id, record_id)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        Ok(record.into_generic(&self.domain, self.default_ttl()))
+    }
+}
+
+impl CreateRecord for LinodeZone {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        let mut request = api::RecordRequest {
+            typ,
+            name: host.as_ascii().to_owned(),
+            target: data.get_value_as(TXT_FORMAT),
+            priority: None,
+            weight: None,
+            port: None,
+            service: None,
+            protocol: None,
+            tag: None,
+            ttl_sec: ttl,
+        };
+
+        match data {
+            RecordData::CNAME(target) | RecordData::NS(target) | RecordData::PTR(target) => {
+                request.target = self.fqdn(target).as_ascii().to_owned();
+            }
+            RecordData::MX {
+                priority,
+                mail_server,
+            } => {
+                request.target = self.fqdn(mail_server).as_ascii().to_owned();
+                request.priority = Some(*priority);
+            }
+            RecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                // Linode expects the service and protocol labels of SRV records as separate fields.
+                let mut labels = host.labels();
+                let (Some(service), Some(protocol)) = (
+                    labels.next().and_then(|label| label.strip_prefix('_')),
+                    labels.next().and_then(|label| label.strip_prefix('_')),
+                ) else {
+                    return Err(CreateRecordError::InvalidRecord);
+                };
+
+                request.service = Some(service.to_owned());
+                request.protocol = Some(protocol.to_owned());
+                request.name = labels.collect::<Vec<&str>>().join(".");
+                request.target = self.fqdn(target).as_ascii().to_owned();
+                request.priority = Some(*priority);
+                request.weight = Some(*weight);
+                request.port = Some(*port);
+            }
+            RecordData::Other { typ, value } if typ == "CAA" => {
+                let mut iter = value.splitn(3, char::is_whitespace);
+                let opt_tag = iter.nth(1);
+                let opt_value = iter.next().map(|raw| raw.trim().trim_matches('"'));
+
+                let (Some(tag), Some(value)) = (opt_tag, opt_value) else {
+                    return Err(CreateRecordError::InvalidRecord);
+                };
+                request.target = value.to_owned();
+                request.tag = Some(tag.to_owned());
+            }
+            _ => {}
+        }
+
+        let record = self
+            .api_client
+            .create_record(self.repr.id, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(record.into_generic(&self.domain, self.default_ttl()))
+    }
+}
+
+impl DeleteRecord for LinodeZone {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| DeleteRecordError::NotFound)?;
+
+        self.api_client
+            .delete_record(self.repr.id, record_id)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::Record {
+    pub fn into_generic(self, zone_domain: &DomainName, default_ttl: u64) -> Record {
+        // Linode returns target names without a trailing dot.
+        let target = || DomainName::from_raw(&self.target).to_absolute();
+
+        let data = match self.typ.as_str() {
+            "CNAME" => RecordData::CNAME(target()),
+            "NS" => RecordData::NS(target()),
+            "PTR" => RecordData::PTR(target()),
+            "MX" => RecordData::MX {
+                priority: self.priority,
+                mail_server: target(),
+            },
+            "SRV" => RecordData::SRV {
+                priority: self.priority,
+                weight: self.weight,
+                port: self.port,
+                target: target(),
+            },
+            "TXT" => RecordData::txt(self.target.clone()),
+            "CAA" => RecordData::from_raw(
+                "CAA",
+                &format!(
+                    "0 {} \"{}\"",
+                    self.tag.as_deref().unwrap_or_default(),
+                    self.target
+                ),
+            ),
+            _ => RecordData::from_raw(&self.typ, &self.target),
+        };
+
+        let host = DomainName::from_raw(&self.name);
+        Record {
+            id: self.id.to_string(),
+            host: host.relative_to(zone_domain).unwrap_or(host),
+            data,
+            ttl: match self.ttl_sec {
+                0 => default_ttl,
+                ttl => ttl,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn pages_are_numbered_and_srv_labels_split() {
+        let server = MockServer::start(|request| {
+            if request.header("authorization") != Some("Bearer api-token") {
+                return Response::json(401, r#"{"errors":[{"reason":"Invalid Token"}]}"#);
+            }
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/domains?page=1&page_size=500") => Response::json(
+                    200,
+                    r#"{"data":[{"id":1,"domain":"example.com","ttl_sec":0}],"page":1,"pages":2,"results":2}"#,
+                ),
+                ("GET", "/domains?page=2&page_size=500") => Response::json(
+                    200,
+                    r#"{"data":[{"id":2,"domain":"example.org","ttl_sec":300}],"page":2,"pages":2,"results":2}"#,
+                ),
+                ("GET", "/domains/1/records?page=1&page_size=500") => Response::json(
+                    200,
+                    r#"{"data":[{"id":10,"type":"MX","name":"","target":"mail.example.com","priority":10,"weight":0,"port":0,"tag":null,"ttl_sec":0},{"id":11,"type":"CAA","name":"","target":"letsencrypt.org","priority":0,"weight":0,"port":0,"tag":"issue","ttl_sec":3600}],"page":1,"pages":1,"results":2}"#,
+                ),
+                ("POST", "/domains/1/records") => Response::json(
+                    200,
+                    r#"{"id":12,"type":"SRV","name":"_sip._tcp","target":"sip.example.com","priority":10,"weight":20,"port":5060,"tag":null,"ttl_sec":600}"#,
+                ),
+                _ => Response::json(404, r#"{"errors":[{"reason":"Not found"}]}"#),
+            }
+        })
+        .await;
+
+        let provider = LinodeProvider::with_base_url("api-token", server.url()).unwrap();
+        assert!(matches!(
+            provider.get_zone("example.com").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+
+        let zones = provider.list_zones().await.unwrap();
+        assert_eq!(
+            zones.iter().map(|zone| zone.id()).collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+        let zone = &zones[0];
+
+        // Records without a TTL inherit the default TTL of the domain.
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (&record.data, record.ttl))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    &RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mail.example.com."),
+                    },
+                    DEFAULT_TTL
+                ),
+                (
+                    &RecordData::from_raw("CAA", r#"0 issue "letsencrypt.org""#),
+                    3600
+                ),
+            ]
+        );
+        assert!(records.iter().all(|record| record.host.is_apex()));
+
+        let data = RecordData::SRV {
+            priority: 10,
+            weight: 20,
+            port: 5060,
+            target: DomainName::from_raw("sip"),
+        };
+        let record = zone
+            .create_record(&DomainName::from_raw("_sip._tcp"), &data, 600)
+            .await
+            .unwrap();
+        assert_eq!(record.id, "12");
+        assert_eq!(
+            record.data,
+            RecordData::SRV {
+                priority: 10,
+                weight: 20,
+                port: 5060,
+                target: DomainName::from_raw("sip.example.com."),
+            }
+        );
+
+        assert!(matches!(
+            zone.delete_record("13").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let requests = server.requests();
+        let create = requests
+            .iter()
+            .find(|request| request.method == "POST")
+            .unwrap();
+        assert_eq!(
+            create.body,
+            r#"{"type":"SRV","name":"","target":"sip.example.com","priority":10,"weight":20,"port":5060,"service":"sip","protocol":"tcp","ttl_sec":600}"#
+        );
+    }
+}
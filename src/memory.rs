@@ -0,0 +1,321 @@
+//! An in-memory provider logging every call it receives, used to test layers and wrappers of providers.
+
+// Every module's tests use a different part of the provider, leaving the rest unused in some feature combinations.
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+use crate::{
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+/// The custom error returned by calls failed using [`MemoryProvider::fail_next`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Error)]
+#[error("the provider failed ({0})")]
+pub struct MemoryError(pub &'static str);
+
+/// Represents a failure of the next call, queued using [`MemoryProvider::fail_next`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Failure {
+    Unauthorized,
+    Custom(MemoryError),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    zones: Vec<MemoryZoneState>,
+    next_record_id: u64,
+    calls: Vec<String>,
+    failures: VecDeque<Failure>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryZoneState {
+    domain: DomainName,
+    records: Vec<Record>,
+}
+
+impl State {
+    /// Logs a call and returns the failure queued for it, if any.
+    fn call(&mut self, call: String) -> Option<Failure> {
+        self.calls.push(call);
+        self.failures.pop_front()
+    }
+
+    fn zone(&mut self, zone_id: &str) -> Option<&mut MemoryZoneState> {
+        self.zones
+            .iter_mut()
+            .find(|zone| zone.domain.as_ascii() == zone_id)
+    }
+}
+
+/// A provider keeping its zones and records in memory, shared by all clones.
+///
+/// Zone IDs are the domains of the zones without a trailing dot, record IDs are consecutive numbers.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryProvider {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a zone without logging a call.
+    pub fn with_zone(self, domain: &str) -> Self {
+        self.state.lock().unwrap().zones.push(MemoryZoneState {
+            domain: DomainName::new(domain).unwrap().to_absolute(),
+            records: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a record to a zone added before without logging a call.
+    pub fn with_record(self, zone_id: &str, host: &str, data: RecordData) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.next_record_id += 1;
+        let id = state.next_record_id.to_string();
+        state.zone(zone_id).unwrap().records.push(Record {
+            id,
+            host: DomainName::from_raw(host),
+            data,
+            ttl: 300,
+        });
+        drop(state);
+        self
+    }
+
+    /// Returns the calls received so far, e.g. `create_record example.com www TXT`.
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Returns the records of a zone without logging a call.
+    pub fn records(&self, zone_id: &str) -> Vec<Record> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .zone(zone_id)
+            .map(|zone| zone.records.clone())
+            .unwrap_or_default()
+    }
+
+    /// Queues a failure for the next call, failures being used in the order they were queued.
+    pub fn fail_next(&self, failure: Failure) {
+        self.state.lock().unwrap().failures.push_back(failure);
+    }
+
+    fn zone(&self, domain: DomainName) -> MemoryZone {
+        MemoryZone {
+            state: self.state.clone(),
+            id: domain.as_ascii().to_owned(),
+            domain,
+        }
+    }
+}
+
+impl Provider for MemoryProvider {
+    type Zone = MemoryZone;
+    type CustomRetrieveError = MemoryError;
+
+    async fn list_zones(&self) -> Result<Vec<MemoryZone>, RetrieveZoneError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call("list_zones".to_owned()) {
+            Some(Failure::Unauthorized) => return Err(RetrieveZoneError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(RetrieveZoneError::Custom(err)),
+            None => {}
+        }
+
+        let domains = state
+            .zones
+            .iter()
+            .map(|zone| zone.domain.clone())
+            .collect::<Vec<_>>();
+        drop(state);
+        Ok(domains
+            .into_iter()
+            .map(|domain| self.zone(domain))
+            .collect())
+    }
+
+    async fn get_zone(&self, zone_id: &str) -> Result<MemoryZone, RetrieveZoneError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("get_zone {}", zone_id)) {
+            Some(Failure::Unauthorized) => return Err(RetrieveZoneError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(RetrieveZoneError::Custom(err)),
+            None => {}
+        }
+
+        let domain = state
+            .zone(zone_id)
+            .map(|zone| zone.domain.clone())
+            .ok_or(RetrieveZoneError::NotFound)?;
+        drop(state);
+        Ok(self.zone(domain))
+    }
+}
+
+impl CreateZone for MemoryProvider {
+    type CustomCreateError = MemoryError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<MemoryZone, CreateZoneError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("create_zone {}", domain.as_ascii())) {
+            Some(Failure::Unauthorized) => return Err(CreateZoneError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(CreateZoneError::Custom(err)),
+            None => {}
+        }
+
+        let domain = domain.to_absolute();
+        state.zones.push(MemoryZoneState {
+            domain: domain.clone(),
+            records: Vec::new(),
+        });
+        drop(state);
+        Ok(self.zone(domain))
+    }
+}
+
+impl DeleteZone for MemoryProvider {
+    type CustomDeleteError = MemoryError;
+
+    async fn delete_zone(&self, zone_id: &str) -> Result<(), DeleteZoneError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("delete_zone {}", zone_id)) {
+            Some(Failure::Unauthorized) => return Err(DeleteZoneError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(DeleteZoneError::Custom(err)),
+            None => {}
+        }
+
+        let count = state.zones.len();
+        state.zones.retain(|zone| zone.domain.as_ascii() != zone_id);
+        if state.zones.len() == count {
+            return Err(DeleteZoneError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+/// A zone of a [`MemoryProvider`].
+#[derive(Debug, Clone)]
+pub struct MemoryZone {
+    state: Arc<Mutex<State>>,
+    id: String,
+    domain: DomainName,
+}
+
+impl Zone for MemoryZone {
+    type CustomRetrieveError = MemoryError;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("list_records {}", self.id)) {
+            Some(Failure::Unauthorized) => return Err(RetrieveRecordError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(RetrieveRecordError::Custom(err)),
+            None => {}
+        }
+
+        state
+            .zone(&self.id)
+            .map(|zone| zone.records.clone())
+            .ok_or(RetrieveRecordError::NotFound)
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("get_record {} {}", self.id, record_id)) {
+            Some(Failure::Unauthorized) => return Err(RetrieveRecordError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(RetrieveRecordError::Custom(err)),
+            None => {}
+        }
+
+        state
+            .zone(&self.id)
+            .and_then(|zone| zone.records.iter().find(|record| record.id == record_id))
+            .cloned()
+            .ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for MemoryZone {
+    type CustomCreateError = MemoryError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        let call = format!(
+            "create_record {} {} {}",
+            self.id,
+            host.as_ascii(),
+            data.get_type()
+        );
+        match state.call(call) {
+            Some(Failure::Unauthorized) => return Err(CreateRecordError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(CreateRecordError::Custom(err)),
+            None => {}
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        state.next_record_id += 1;
+        let record = Record {
+            id: state.next_record_id.to_string(),
+            host,
+            data: data.clone(),
+            ttl,
+        };
+        state
+            .zone(&self.id)
+            .ok_or(CreateRecordError::InvalidRecord)?
+            .records
+            .push(record.clone());
+        Ok(record)
+    }
+}
+
+impl DeleteRecord for MemoryZone {
+    type CustomDeleteError = MemoryError;
+
+    async fn delete_record(&self, record_id: &str) -> Result<(), DeleteRecordError<MemoryError>> {
+        let mut state = self.state.lock().unwrap();
+        match state.call(format!("delete_record {} {}", self.id, record_id)) {
+            Some(Failure::Unauthorized) => return Err(DeleteRecordError::Unauthorized),
+            Some(Failure::Custom(err)) => return Err(DeleteRecordError::Custom(err)),
+            None => {}
+        }
+
+        let zone = state.zone(&self.id).ok_or(DeleteRecordError::NotFound)?;
+        let count = zone.records.len();
+        zone.records.retain(|record| record.id != record_id);
+        if zone.records.len() == count {
+            return Err(DeleteRecordError::NotFound);
+        }
+        Ok(())
+    }
+}
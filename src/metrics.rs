@@ -0,0 +1,442 @@
+//! Collecting metrics about DNS operations through a pluggable [`Recorder`].
+//!
+//! A recorder is installed once per process using [`set_recorder`], similar to loggers.
+//! Calls, errors and latencies are recorded by wrapping a provider in the [`Metrics`](crate::layer::Metrics) layer:
+//!
+//! ```ignore
+//! let recorder = PrometheusRecorder::new();
+//! metrics::set_recorder(recorder.clone())?;
+//!
+//! let provider = HetznerProvider::new(api_key)?.layer(Metrics::new("hetzner"));
+//! // ...
+//! println!("{}", recorder.render());
+//! ```
+//!
+//! Pages fetched by the HTTP API providers and requests rate-limited by Hetzner and deSEC are recorded whether the provider
+//! is wrapped or not, labeled with the provider's lowercase name, e.g. `hetzner`.
+//! Listings returned in a single unpaginated response, i.e. OVHcloud's zones and records and Porkbun's records, are not counted as pages.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+static RECORDER: OnceLock<Box<dyn Recorder>> = OnceLock::new();
+
+/// Receives the metrics of DNS operations.
+///
+/// All methods do nothing by default, so recorders only need to implement the ones they are interested in.
+pub trait Recorder: Send + Sync {
+    /// Records a finished call to a provider or zone.
+    ///
+    /// `operation` is the name of the called method, e.g. `create_record`, and `error` the variant of the returned error
+    /// as returned by [`OperationError::variant`](crate::layer::OperationError::variant), [`None`] if the call succeeded.
+    fn record_call(
+        &self,
+        provider: &str,
+        operation: &'static str,
+        duration: Duration,
+        error: Option<&'static str>,
+    ) {
+        let _ = (provider, operation, duration, error);
+    }
+
+    /// Records a page fetched from a paginated listing.
+    fn record_page(&self, provider: &str) {
+        let _ = provider;
+    }
+
+    /// Records a call being retried.
+    fn record_retry(&self, provider: &str, operation: &'static str) {
+        let _ = (provider, operation);
+    }
+
+    /// Records a request rejected because of rate limiting.
+    fn record_rate_limit(&self, provider: &str) {
+        let _ = provider;
+    }
+}
+
+/// Represents an error that occured when installing a recorder using [`set_recorder`].
+#[derive(Debug, Error)]
+#[error("a metrics recorder is already installed")]
+pub struct SetRecorderError;
+
+/// Installs the recorder receiving all metrics of this process.
+///
+/// Fails if a recorder is already installed.
+pub fn set_recorder(recorder: impl Recorder + 'static) -> Result<(), SetRecorderError> {
+    RECORDER
+        .set(Box::new(recorder))
+        .map_err(|_| SetRecorderError)
+}
+
+/// Returns the recorder shared by the tests of this crate, installing it on first use.
+///
+/// As a recorder can only be installed once per process, every test has to label its calls with provider names of its own.
+#[cfg(test)]
+pub(crate) fn test_recorder() -> &'static PrometheusRecorder {
+    static TEST_RECORDER: OnceLock<PrometheusRecorder> = OnceLock::new();
+    TEST_RECORDER.get_or_init(|| {
+        let recorder = PrometheusRecorder::new();
+        set_recorder(recorder.clone()).unwrap();
+        recorder
+    })
+}
+
+/// Returns the installed recorder or a recorder discarding everything if none is installed.
+pub fn recorder() -> &'static dyn Recorder {
+    struct NoopRecorder;
+    impl Recorder for NoopRecorder {}
+
+    match RECORDER.get() {
+        Some(recorder) => recorder.as_ref(),
+        None => &NoopRecorder,
+    }
+}
+
+/// The upper bounds of the latency histogram buckets in seconds, matching the defaults of the Prometheus client libraries.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A [`Recorder`] keeping all metrics in memory and rendering them in the Prometheus text exposition format.
+///
+/// Clones share the same metrics, so a clone can be installed while the original is used to serve them.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheusRecorder {
+    metrics: Arc<Mutex<PrometheusMetrics>>,
+}
+
+#[derive(Debug, Default)]
+struct PrometheusMetrics {
+    calls: BTreeMap<(String, &'static str), CallMetrics>,
+    errors: BTreeMap<(String, &'static str, &'static str), u64>,
+    pages: BTreeMap<String, u64>,
+    retries: BTreeMap<(String, &'static str), u64>,
+    rate_limits: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Default)]
+struct CallMetrics {
+    count: u64,
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+}
+
+impl PrometheusRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format, e.g. to be served on a `/metrics` endpoint.
+    ///
+    /// The following metrics are rendered, each labeled with the `provider` and, if applicable, the `operation` and `error` variant:
+    /// `libdns_calls_total`, `libdns_errors_total`, `libdns_call_duration_seconds` (a histogram),
+    /// `libdns_pages_total`, `libdns_retries_total` and `libdns_rate_limits_total`.
+    pub fn render(&self) -> String {
+        let metrics = self.metrics.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "libdns_calls_total",
+            "counter",
+            "Calls to providers and zones.",
+        );
+        for ((provider, operation), call) in &metrics.calls {
+            let labels = labels(&[("provider", provider), ("operation", operation)]);
+            let _ = writeln!(out, "libdns_calls_total{{{}}} {}", labels, call.count);
+        }
+
+        header(
+            &mut out,
+            "libdns_errors_total",
+            "counter",
+            "Failed calls to providers and zones by error variant.",
+        );
+        for ((provider, operation, error), count) in &metrics.errors {
+            let labels = labels(&[
+                ("provider", provider),
+                ("operation", operation),
+                ("error", error),
+            ]);
+            let _ = writeln!(out, "libdns_errors_total{{{}}} {}", labels, count);
+        }
+
+        header(
+            &mut out,
+            "libdns_call_duration_seconds",
+            "histogram",
+            "Duration of calls to providers and zones.",
+        );
+        for ((provider, operation), call) in &metrics.calls {
+            let labels = labels(&[("provider", provider), ("operation", operation)]);
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(call.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "libdns_call_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "libdns_call_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, call.count
+            );
+            let _ = writeln!(
+                out,
+                "libdns_call_duration_seconds_sum{{{}}} {}",
+                labels, call.sum
+            );
+            let _ = writeln!(
+                out,
+                "libdns_call_duration_seconds_count{{{}}} {}",
+                labels, call.count
+            );
+        }
+
+        header(
+            &mut out,
+            "libdns_pages_total",
+            "counter",
+            "Pages fetched from paginated listings.",
+        );
+        for (provider, count) in &metrics.pages {
+            let _ = writeln!(
+                out,
+                "libdns_pages_total{{{}}} {}",
+                labels(&[("provider", provider)]),
+                count
+            );
+        }
+
+        header(
+            &mut out,
+            "libdns_retries_total",
+            "counter",
+            "Retried calls to providers and zones.",
+        );
+        for ((provider, operation), count) in &metrics.retries {
+            let labels = labels(&[("provider", provider), ("operation", operation)]);
+            let _ = writeln!(out, "libdns_retries_total{{{}}} {}", labels, count);
+        }
+
+        header(
+            &mut out,
+            "libdns_rate_limits_total",
+            "counter",
+            "Requests rejected because of rate limiting.",
+        );
+        for (provider, count) in &metrics.rate_limits {
+            let _ = writeln!(
+                out,
+                "libdns_rate_limits_total{{{}}} {}",
+                labels(&[("provider", provider)]),
+                count
+            );
+        }
+
+        out
+    }
+
+    /// Returns the rendered samples labeled with the given provider, except for the time-dependent `_sum` samples.
+    #[cfg(test)]
+    pub(crate) fn samples(&self, provider: &str) -> Vec<String> {
+        let label = labels(&[("provider", provider)]);
+        self.render()
+            .lines()
+            .filter(|line| {
+                line.contains(&format!("{{{},", label)) || line.contains(&format!("{{{}}}", label))
+            })
+            .filter(|line| !line.starts_with("libdns_call_duration_seconds_sum"))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PrometheusMetrics)) {
+        f(&mut self.metrics.lock().unwrap_or_else(|err| err.into_inner()));
+    }
+}
+
+impl Recorder for PrometheusRecorder {
+    fn record_call(
+        &self,
+        provider: &str,
+        operation: &'static str,
+        duration: Duration,
+        error: Option<&'static str>,
+    ) {
+        let seconds = duration.as_secs_f64();
+        self.update(|metrics| {
+            let call = metrics
+                .calls
+                .entry((provider.to_owned(), operation))
+                .or_default();
+            call.count += 1;
+            call.sum += seconds;
+            if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+                call.buckets[bucket] += 1;
+            }
+
+            if let Some(error) = error {
+                *metrics
+                    .errors
+                    .entry((provider.to_owned(), operation, error))
+                    .or_default() += 1;
+            }
+        });
+    }
+
+    fn record_page(&self, provider: &str) {
+        self.update(|metrics| *metrics.pages.entry(provider.to_owned()).or_default() += 1);
+    }
+
+    fn record_retry(&self, provider: &str, operation: &'static str) {
+        self.update(|metrics| {
+            *metrics
+                .retries
+                .entry((provider.to_owned(), operation))
+                .or_default() += 1
+        });
+    }
+
+    fn record_rate_limit(&self, provider: &str) {
+        self.update(|metrics| *metrics.rate_limits.entry(provider.to_owned()).or_default() += 1);
+    }
+}
+
+fn header(out: &mut String, name: &str, typ: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, typ);
+}
+
+/// Formats label pairs, escaping backslashes, quotes and line feeds in the values.
+fn labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prometheus_text_format() {
+        let recorder = PrometheusRecorder::new();
+        // The durations are chosen to be exactly representable, keeping the sums exact.
+        recorder.record_call(
+            "hetzner",
+            "list_zones",
+            Duration::from_nanos(3_906_250),
+            None,
+        );
+        recorder.record_call(
+            "hetzner",
+            "list_zones",
+            Duration::from_millis(250),
+            Some("custom"),
+        );
+        recorder.record_call(
+            "hetzner",
+            "list_zones",
+            Duration::from_secs(16),
+            Some("custom"),
+        );
+        recorder.record_call(
+            "a \"quoted\\\n\" name",
+            "get_zone",
+            Duration::from_millis(500),
+            Some("not_found"),
+        );
+        recorder.record_page("hetzner");
+        recorder.record_page("hetzner");
+        recorder.record_retry("hetzner", "list_zones");
+        recorder.record_rate_limit("hetzner");
+
+        let escaped = r#"provider="a \"quoted\\\n\" name",operation="get_zone""#;
+        let hetzner = r#"provider="hetzner",operation="list_zones""#;
+        let mut expected = String::new();
+        expected.push_str("# HELP libdns_calls_total Calls to providers and zones.\n");
+        expected.push_str("# TYPE libdns_calls_total counter\n");
+        expected.push_str(&format!("libdns_calls_total{{{escaped}}} 1\n"));
+        expected.push_str(&format!("libdns_calls_total{{{hetzner}}} 3\n"));
+        expected.push_str(
+            "# HELP libdns_errors_total Failed calls to providers and zones by error variant.\n",
+        );
+        expected.push_str("# TYPE libdns_errors_total counter\n");
+        expected.push_str(&format!(
+            "libdns_errors_total{{{escaped},error=\"not_found\"}} 1\n"
+        ));
+        expected.push_str(&format!(
+            "libdns_errors_total{{{hetzner},error=\"custom\"}} 2\n"
+        ));
+        expected.push_str(
+            "# HELP libdns_call_duration_seconds Duration of calls to providers and zones.\n",
+        );
+        expected.push_str("# TYPE libdns_call_duration_seconds histogram\n");
+        for (labels, counts, sum, count) in [
+            (escaped, [0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1], "0.5", 1),
+            (hetzner, [1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2], "16.25390625", 3),
+        ] {
+            let bounds = [
+                "0.005", "0.01", "0.025", "0.05", "0.1", "0.25", "0.5", "1", "2.5", "5", "10",
+            ];
+            for (bound, cumulative) in bounds.iter().zip(counts) {
+                expected.push_str(&format!(
+                    "libdns_call_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {cumulative}\n"
+                ));
+            }
+            expected.push_str(&format!(
+                "libdns_call_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {count}\n"
+            ));
+            expected.push_str(&format!(
+                "libdns_call_duration_seconds_sum{{{labels}}} {sum}\n"
+            ));
+            expected.push_str(&format!(
+                "libdns_call_duration_seconds_count{{{labels}}} {count}\n"
+            ));
+        }
+        expected.push_str("# HELP libdns_pages_total Pages fetched from paginated listings.\n");
+        expected.push_str("# TYPE libdns_pages_total counter\n");
+        expected.push_str("libdns_pages_total{provider=\"hetzner\"} 2\n");
+        expected.push_str("# HELP libdns_retries_total Retried calls to providers and zones.\n");
+        expected.push_str("# TYPE libdns_retries_total counter\n");
+        expected.push_str(&format!("libdns_retries_total{{{hetzner}}} 1\n"));
+        expected.push_str(
+            "# HELP libdns_rate_limits_total Requests rejected because of rate limiting.\n",
+        );
+        expected.push_str("# TYPE libdns_rate_limits_total counter\n");
+        expected.push_str("libdns_rate_limits_total{provider=\"hetzner\"} 1\n");
+
+        assert_eq!(recorder.render(), expected);
+    }
+
+    #[test]
+    fn recorders_are_installed_once() {
+        let installed = test_recorder();
+        assert!(set_recorder(PrometheusRecorder::new()).is_err());
+
+        installed.record_page("metrics-test");
+        recorder().record_page("metrics-test");
+        assert_eq!(
+            installed.samples("metrics-test"),
+            [r#"libdns_pages_total{provider="metrics-test"} 2"#]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! A minimal HTTP/1.1 server answering requests using a handler function, used to test providers against canned responses.
+
+// Every provider's tests use a different part of the server, leaving the rest unused if only one provider is enabled.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// The path including the query string, e.g. `/zones?page=1`.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Returns the value of a header, ignoring the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_owned(),
+        }
+    }
+
+    /// Creates a response with a JSON body.
+    pub fn json(status: u16, body: &str) -> Self {
+        Self::new(status, body).with_header("Content-Type", "application/json")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+/// Serves requests on a random local port until the test's runtime shuts down.
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    pub async fn start(handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = Arc::new(handler);
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let log = log.clone();
+                tokio::spawn(async move {
+                    if let Some(request) = read_request(stream, |request| handler(request)).await {
+                        log.lock().unwrap().push(request);
+                    }
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Returns the base URL of the server, e.g. `http://127.0.0.1:1234`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the requests received so far.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Reads a single request, answers it and closes the connection.
+async fn read_request(
+    mut stream: TcpStream,
+    handler: impl Fn(&Request) -> Response,
+) -> Option<Request> {
+    let mut reader = BufReader::new(&mut stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).await.ok()?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = (parts.next()?.to_owned(), parts.next()?.to_owned());
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_owned(), value.trim().to_owned()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: String::new(),
+    };
+    let length = request
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.ok()?;
+    request.body = String::from_utf8(body).ok()?;
+
+    let response = handler(&request);
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await.ok()?;
+    stream.write_all(response.body.as_bytes()).await.ok()?;
+    stream.shutdown().await.ok()?;
+    Some(request)
+}
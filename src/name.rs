@@ -0,0 +1,484 @@
+use std::{
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use thiserror::Error;
+
+const MAX_LABEL_LENGTH: usize = 63;
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Represents a validated domain name like `www.example.com.` or `www`.
+///
+/// Names ending with a dot are absolute (fully qualified), all others are relative.
+/// The empty relative name is written as `@` and refers to the apex of a zone when used as a record host.
+///
+/// Names are stored in their ASCII form. Internationalized names are converted to punycode
+/// (following [UTS #46](https://www.unicode.org/reports/tr46/)) when the `idna` feature is enabled and can be converted back
+/// using [`DomainName::to_unicode`].
+/// Names are compared case-insensitively.
+#[derive(Debug, Clone)]
+pub struct DomainName {
+    name: String,
+    absolute: bool,
+}
+
+/// Represents an error that occured when parsing a [`DomainName`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Error)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DomainNameError {
+    /// Indicates that the name contains an empty label, e.g. `www..example.com`.
+    #[error("the domain name contains an empty label")]
+    EmptyLabel,
+
+    /// Indicates that a label exceeds 63 bytes.
+    #[error("the label {0} exceeds 63 bytes")]
+    LabelTooLong(String),
+
+    /// Indicates that the name exceeds 255 bytes in wire format.
+    #[error("the domain name exceeds 255 bytes")]
+    NameTooLong,
+
+    /// Indicates that a label contains a character which is not allowed in domain names.
+    #[error("the label {0} contains invalid characters")]
+    InvalidLabel(String),
+
+    /// Indicates that a wildcard label (`*`) is used anywhere but as the first label.
+    #[error("the wildcard label may only be used as the first label")]
+    InvalidWildcard,
+
+    /// Indicates that an internationalized name could not be converted to its ASCII form.
+    #[error("the internationalized domain name is invalid")]
+    InvalidIdna,
+}
+
+impl DomainName {
+    /// Parses and validates a domain name, converting internationalized names to punycode.
+    pub fn new(name: &str) -> Result<Self, DomainNameError> {
+        let (name, absolute) = split_root(name);
+        let name = to_ascii(name)?;
+
+        let mut length = 1;
+        if !name.is_empty() {
+            for (i, label) in name.split('.').enumerate() {
+                validate_label(label, i == 0)?;
+                length += label.len() + 1;
+            }
+        }
+        if length > MAX_NAME_LENGTH {
+            return Err(DomainNameError::NameTooLong);
+        }
+
+        Ok(Self { name, absolute })
+    }
+
+    /// Converts a domain name received from a provider without validating it.
+    ///
+    /// Internationalized names are converted to punycode if possible.
+    /// This function is used by provider implementations to stay compatible with whatever names their backend returns.
+    pub fn from_raw(name: &str) -> Self {
+        let (raw, absolute) = split_root(name);
+        let name = to_ascii(raw).unwrap_or_else(|_| raw.to_owned());
+        Self { name, absolute }
+    }
+
+    /// Returns the root name `.`.
+    pub fn root() -> Self {
+        Self {
+            name: String::new(),
+            absolute: true,
+        }
+    }
+
+    /// Returns the empty relative name `@`, referring to the apex of a zone when used as a record host.
+    pub fn apex() -> Self {
+        Self {
+            name: String::new(),
+            absolute: false,
+        }
+    }
+
+    /// Returns whether the name is absolute, i.e. fully qualified.
+    pub fn is_absolute(&self) -> bool {
+        self.absolute
+    }
+
+    /// Returns whether the name is the root name `.`.
+    pub fn is_root(&self) -> bool {
+        self.absolute && self.name.is_empty()
+    }
+
+    /// Returns whether the name is the empty relative name `@`.
+    pub fn is_apex(&self) -> bool {
+        !self.absolute && self.name.is_empty()
+    }
+
+    /// Returns whether the first label of the name is the wildcard label `*`.
+    pub fn is_wildcard(&self) -> bool {
+        self.labels().next() == Some("*")
+    }
+
+    /// Returns the ASCII labels of the name, starting with the leftmost one.
+    pub fn labels(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.name.split('.').filter(|label| !label.is_empty())
+    }
+
+    /// Returns the number of labels of the name, not counting the root label.
+    pub fn label_count(&self) -> usize {
+        self.labels().count()
+    }
+
+    /// Returns the absolute version of the name.
+    pub fn to_absolute(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            absolute: true,
+        }
+    }
+
+    /// Returns the name without the trailing dot, making it relative.
+    pub fn to_relative(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            absolute: false,
+        }
+    }
+
+    /// Returns whether the name equals or lies below `other`, comparing labels case-insensitively.
+    ///
+    /// Both names have to be either absolute or relative.
+    pub fn is_subdomain_of(&self, other: &DomainName) -> bool {
+        if self.absolute != other.absolute {
+            return false;
+        }
+
+        let mut labels = self.labels().rev();
+        other.labels().rev().all(|label| {
+            labels
+                .next()
+                .is_some_and(|own| own.eq_ignore_ascii_case(label))
+        })
+    }
+
+    /// Returns the name relative to `origin`, e.g. `www` for `www.example.com.` and the origin `example.com.`.
+    ///
+    /// Relative names are returned as they are and the origin itself results in [`DomainName::apex`].
+    /// [`None`] is returned if the name is absolute but does not lie below the origin.
+    pub fn relative_to(&self, origin: &DomainName) -> Option<DomainName> {
+        if !self.absolute {
+            return Some(self.clone());
+        }
+        if !self.is_subdomain_of(&origin.to_absolute()) {
+            return None;
+        }
+
+        let count = self.label_count() - origin.label_count();
+        let name = self.labels().take(count).collect::<Vec<&str>>().join(".");
+        Some(Self {
+            name,
+            absolute: false,
+        })
+    }
+
+    /// Returns the fully qualified version of the name, appending `origin` to relative names.
+    ///
+    /// [`DomainName::apex`] results in the origin itself and absolute names are returned as they are.
+    pub fn to_fqdn(&self, origin: &DomainName) -> DomainName {
+        if self.absolute {
+            return self.clone();
+        }
+
+        let name = match (self.name.is_empty(), origin.name.is_empty()) {
+            (true, _) => origin.name.clone(),
+            (false, true) => self.name.clone(),
+            (false, false) => format!("{}.{}", self.name, origin.name),
+        };
+        Self {
+            name,
+            absolute: true,
+        }
+    }
+
+    /// Returns the name in its ASCII (punycode) form, without a trailing dot and without replacing the empty name by `@`.
+    pub fn as_ascii(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name with its punycode labels converted to Unicode.
+    ///
+    /// Without the `idna` feature enabled, the ASCII form is returned.
+    pub fn to_unicode(&self) -> String {
+        #[cfg(feature = "idna")]
+        if self
+            .labels()
+            .any(|label| label.len() > 4 && label[..4].eq_ignore_ascii_case("xn--"))
+        {
+            let (unicode, result) = idna::domain_to_unicode(&self.name);
+            if result.is_ok() {
+                return self.format(&unicode);
+            }
+        }
+        self.to_string()
+    }
+
+    fn format(&self, name: &str) -> String {
+        match (name.is_empty(), self.absolute) {
+            (true, true) => ".".to_owned(),
+            (true, false) => "@".to_owned(),
+            (false, true) => format!("{}.", name),
+            (false, false) => name.to_owned(),
+        }
+    }
+}
+
+fn split_root(name: &str) -> (&str, bool) {
+    match name {
+        "@" => ("", false),
+        "." => ("", true),
+        _ => match name.strip_suffix('.') {
+            Some(name) => (name, true),
+            None => (name, false),
+        },
+    }
+}
+
+#[cfg(feature = "idna")]
+fn to_ascii(name: &str) -> Result<String, DomainNameError> {
+    if name.is_ascii() {
+        return Ok(name.to_owned());
+    }
+    idna::domain_to_ascii(name).map_err(|_| DomainNameError::InvalidIdna)
+}
+
+#[cfg(not(feature = "idna"))]
+fn to_ascii(name: &str) -> Result<String, DomainNameError> {
+    if name.is_ascii() {
+        return Ok(name.to_owned());
+    }
+    Err(DomainNameError::InvalidIdna)
+}
+
+fn validate_label(label: &str, first: bool) -> Result<(), DomainNameError> {
+    if label.is_empty() {
+        return Err(DomainNameError::EmptyLabel);
+    }
+    if label.len() > MAX_LABEL_LENGTH {
+        return Err(DomainNameError::LabelTooLong(label.to_owned()));
+    }
+    if label == "*" {
+        return if first {
+            Ok(())
+        } else {
+            Err(DomainNameError::InvalidWildcard)
+        };
+    }
+
+    // Slashes are only allowed in the `<address>/<prefix length>` labels of RFC 2317 reverse zones (e.g. `64/26`).
+    if let Some((address, prefix)) = label.split_once('/') {
+        let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+        return match is_number(address) && is_number(prefix) {
+            true => Ok(()),
+            false => Err(DomainNameError::InvalidLabel(label.to_owned())),
+        };
+    }
+
+    // Underscores are used by service labels (e.g. `_acme-challenge`).
+    let valid_chars = label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid_chars || label.starts_with('-') || label.ends_with('-') {
+        return Err(DomainNameError::InvalidLabel(label.to_owned()));
+    }
+    Ok(())
+}
+
+impl FromStr for DomainName {
+    type Err = DomainNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Display for DomainName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(&self.name))
+    }
+}
+
+impl PartialEq for DomainName {
+    fn eq(&self, other: &Self) -> bool {
+        self.absolute == other.absolute && self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl Eq for DomainName {}
+
+impl Hash for DomainName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.to_ascii_lowercase().hash(state);
+        self.absolute.hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for DomainName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DomainName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::new(&raw).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slashes_are_only_allowed_in_classless_reverse_labels() {
+        assert!(DomainName::new("64/26.2.0.192.in-addr.arpa").is_ok());
+        for name in [
+            "/tmp/evil",
+            "a/b",
+            "64/.example.com",
+            "/26.example.com",
+            "1/2/3.example",
+        ] {
+            assert!(
+                matches!(
+                    DomainName::new(name),
+                    Err(DomainNameError::InvalidLabel(_) | DomainNameError::EmptyLabel)
+                ),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn validation() {
+        for name in [
+            "example.com",
+            "www.example.com.",
+            "*.example.com",
+            "_acme-challenge.example.com",
+            "@",
+            ".",
+            "xn--bcher-kva.example",
+        ] {
+            assert!(DomainName::new(name).is_ok(), "{}", name);
+        }
+
+        assert_eq!(
+            DomainName::new("www..example.com"),
+            Err(DomainNameError::EmptyLabel)
+        );
+        assert_eq!(
+            DomainName::new(&format!("{}.com", "a".repeat(64))),
+            Err(DomainNameError::LabelTooLong("a".repeat(64)))
+        );
+        assert!(DomainName::new(&format!("{}.com", "a".repeat(63))).is_ok());
+        assert_eq!(
+            DomainName::new(&vec!["a".repeat(63); 4].join(".")),
+            Err(DomainNameError::NameTooLong)
+        );
+        assert_eq!(
+            DomainName::new("www.*.example.com"),
+            Err(DomainNameError::InvalidWildcard)
+        );
+        for name in ["-www.example.com", "www-.example.com", "w w.example.com"] {
+            assert!(
+                matches!(DomainName::new(name), Err(DomainNameError::InvalidLabel(_))),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn comparison_and_formatting() {
+        let name = DomainName::new("WWW.Example.com.").unwrap();
+        assert_eq!(name, DomainName::new("www.example.com.").unwrap());
+        assert_ne!(name, DomainName::new("www.example.com").unwrap());
+        assert_eq!(name.to_string(), "WWW.Example.com.");
+        assert_eq!(DomainName::apex().to_string(), "@");
+        assert_eq!(DomainName::root().to_string(), ".");
+        assert_eq!(name.labels().collect::<Vec<_>>(), ["WWW", "Example", "com"]);
+        assert_eq!(DomainName::root().label_count(), 0);
+    }
+
+    #[test]
+    fn relative_and_fully_qualified_names() {
+        let origin = DomainName::new("example.com.").unwrap();
+        let name = |name: &str| DomainName::new(name).unwrap();
+
+        assert_eq!(
+            name("www.example.com.").relative_to(&origin),
+            Some(name("www"))
+        );
+        assert_eq!(
+            name("a.b.EXAMPLE.com.").relative_to(&origin),
+            Some(name("a.b"))
+        );
+        assert_eq!(
+            name("example.com.").relative_to(&origin),
+            Some(DomainName::apex())
+        );
+        assert_eq!(name("www").relative_to(&origin), Some(name("www")));
+        assert_eq!(name("www.example.org.").relative_to(&origin), None);
+        assert_eq!(name("notexample.com.").relative_to(&origin), None);
+        assert_eq!(
+            name("www.example.com.").relative_to(&name("example.com")),
+            Some(name("www"))
+        );
+
+        assert_eq!(name("www").to_fqdn(&origin), name("www.example.com."));
+        assert_eq!(DomainName::apex().to_fqdn(&origin), origin);
+        assert_eq!(
+            name("www.example.org.").to_fqdn(&origin),
+            name("www.example.org.")
+        );
+        assert_eq!(name("com").to_fqdn(&DomainName::root()), name("com."));
+
+        for host in ["www", "a.b", "@"] {
+            assert_eq!(
+                name(host).to_fqdn(&origin).relative_to(&origin),
+                Some(name(host))
+            );
+        }
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn internationalized_names() {
+        let name = DomainName::new("Bücher.example.").unwrap();
+        assert_eq!(name.as_ascii(), "xn--bcher-kva.example");
+        assert_eq!(name.to_unicode(), "bücher.example.");
+        assert_eq!(name, DomainName::new("xn--bcher-kva.example.").unwrap());
+        assert_eq!(DomainName::from_raw("bücher").as_ascii(), "xn--bcher-kva");
+        assert_eq!(
+            DomainName::new("example.com").unwrap().to_unicode(),
+            "example.com"
+        );
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn internationalized_names_require_idna() {
+        assert_eq!(
+            DomainName::new("bücher.example"),
+            Err(DomainNameError::InvalidIdna)
+        );
+        assert_eq!(DomainName::from_raw("bücher").as_ascii(), "bücher");
+    }
+}
@@ -0,0 +1,105 @@
+//! Caching of OAuth2 access tokens, shared by providers authenticating using short-lived bearer tokens.
+
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+/// Tokens are refreshed this long before they expire to account for clock skew and request latency.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Represents a successful response of an OAuth2 token endpoint.
+#[derive(Deserialize)]
+pub(crate) struct TokenResponse {
+    pub access_token: String,
+    /// The lifetime of the token in seconds.
+    pub expires_in: u64,
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// Caches an access token until shortly before it expires.
+///
+/// Refreshes are single-flight: callers arriving while a token is requested wait for that request instead of starting their own.
+/// The token is omitted from the [`Debug`](fmt::Debug) output.
+#[derive(Default)]
+pub(crate) struct TokenCache {
+    cached: Mutex<Option<CachedToken>>,
+}
+
+impl TokenCache {
+    /// Returns the cached token, calling `refresh` to request a new one if it is missing or about to expire.
+    pub async fn get<E, F>(&self, refresh: impl FnOnce() -> F) -> Result<String, E>
+    where
+        F: Future<Output = Result<TokenResponse, E>>,
+    {
+        // The lock is held while refreshing, so concurrent callers wait for the new token.
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at > Instant::now() + REFRESH_MARGIN {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        let requested_at = Instant::now();
+        let response = refresh().await?;
+        let token = cached.insert(CachedToken {
+            access_token: response.access_token,
+            expires_at: requested_at + Duration::from_secs(response.expires_in),
+        });
+        Ok(token.access_token.clone())
+    }
+}
+
+impl fmt::Debug for TokenCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenCache").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn refreshes_once_until_expiry() {
+        let cache = TokenCache::default();
+        let requests = AtomicUsize::new(0);
+        let refresh = |expires_in| {
+            let requests = &requests;
+            move || async move {
+                let n = requests.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::task::yield_now().await;
+                Ok::<_, ()>(TokenResponse {
+                    access_token: format!("token-{}", n),
+                    expires_in,
+                })
+            }
+        };
+
+        let (first, second) = tokio::join!(cache.get(refresh(3600)), cache.get(refresh(3600)));
+        assert_eq!(first, Ok("token-1".to_owned()));
+        assert_eq!(second, Ok("token-1".to_owned()));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Tokens expiring within the refresh margin are replaced.
+        let cache = TokenCache::default();
+        assert_eq!(cache.get(refresh(30)).await, Ok("token-2".to_owned()));
+        assert_eq!(cache.get(refresh(30)).await, Ok("token-3".to_owned()));
+        assert_eq!(
+            cache
+                .get(|| async { Err::<TokenResponse, _>("failed") })
+                .await,
+            Err("failed")
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    error::Error as StdErr,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{Client as HttpClient, Method, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+
+use super::Credentials;
+#[cfg(feature = "retry")]
+use crate::{http::is_transient_status, layer::Transient};
+use crate::{http::StatusError, presentation::encode_hex};
+
+/// Represents an error returned by the OVHcloud API client.
+#[derive(Debug, Error)]
+pub enum OvhError {
+    /// Indicates that the HTTP request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that OVHcloud responded with an error.
+    #[error("OVHcloud responded with {status}: {message}")]
+    Api { status: StatusCode, message: String },
+}
+
+impl OvhError {
+    /// Returns the HTTP status code of an API error.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            OvhError::Api { status, .. } => Some(*status),
+            OvhError::Http(err) => err.status(),
+        }
+    }
+}
+
+impl StatusError for OvhError {
+    fn status(&self) -> Option<StatusCode> {
+        OvhError::status(self)
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for OvhError {
+    fn is_transient(&self) -> bool {
+        match self {
+            OvhError::Http(err) => err.is_transient(),
+            OvhError::Api { status, .. } => is_transient_status(*status),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+    credentials: Credentials,
+    /// The difference between the server's and the local clock in seconds, determined on the first request.
+    time_delta: Mutex<Option<i64>>,
+}
+
+impl Client {
+    pub fn new(base_url: &str, credentials: Credentials) -> Result<Self, Box<dyn StdErr>> {
+        let http_client = HttpClient::builder().build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            credentials,
+            time_delta: Mutex::new(None),
+        })
+    }
+
+    /// Returns the current timestamp of the OVHcloud API, synchronizing the local clock with it if not done yet.
+    ///
+    /// Requests carrying a timestamp deviating too much from the server's clock are rejected.
+    async fn timestamp(&self) -> Result<i64, OvhError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+
+        if let Some(delta) = *self.time_delta.lock().unwrap() {
+            return Ok(now + delta);
+        }
+
+        let server_time = self
+            .http_client
+            .get(format!("{}/auth/time", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<i64>()
+            .await?;
+
+        *self.time_delta.lock().unwrap() = Some(server_time - now);
+        Ok(server_time)
+    }
+
+    /// Sends a signed request and deserializes the response body.
+    async fn request<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T, OvhError> {
+        let response = self.send(method, path, body).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Sends a signed request, failing if OVHcloud responds with an error.
+    ///
+    /// The signature is the SHA1 hash of the application secret, consumer key, method, URL, body and timestamp joined by `+`.
+    async fn send<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Response, OvhError> {
+        let url = format!("{}{}", self.base_url, path);
+        let body = body
+            .map(|body| serde_json::to_string(body).unwrap())
+            .unwrap_or_default();
+        let timestamp = self.timestamp().await?.to_string();
+
+        let signature = Sha1::digest(
+            [
+                self.credentials.application_secret.as_str(),
+                &self.credentials.consumer_key,
+                method.as_str(),
+                &url,
+                &body,
+                &timestamp,
+            ]
+            .join("+"),
+        );
+
+        let mut request = self
+            .http_client
+            .request(method, url)
+            .header("X-Ovh-Application", &self.credentials.application_key)
+            .header("X-Ovh-Consumer", &self.credentials.consumer_key)
+            .header("X-Ovh-Timestamp", timestamp)
+            .header("X-Ovh-Signature", format!("$1${}", encode_hex(&signature)));
+        if !body.is_empty() {
+            request = request
+                .header("Content-Type", "application/json")
+                .body(body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await?;
+            let message = serde_json::from_str::<ErrorResponse>(&text)
+                .map(|response| response.message)
+                .unwrap_or(text);
+            return Err(OvhError::Api { status, message });
+        }
+
+        Ok(response)
+    }
+
+    pub async fn retrieve_zones(&self) -> Result<Vec<String>, OvhError> {
+        self.request::<(), _>(Method::GET, "/domain/zone", None)
+            .await
+    }
+
+    pub async fn retrieve_zone(&self, zone: &str) -> Result<Zone, OvhError> {
+        self.request::<(), _>(Method::GET, &format!("/domain/zone/{}", zone), None)
+            .await
+    }
+
+    pub async fn retrieve_record_ids(&self, zone: &str) -> Result<Vec<u64>, OvhError> {
+        self.request::<(), _>(Method::GET, &format!("/domain/zone/{}/record", zone), None)
+            .await
+    }
+
+    pub async fn retrieve_record(&self, zone: &str, record_id: u64) -> Result<Record, OvhError> {
+        self.request::<(), _>(
+            Method::GET,
+            &format!("/domain/zone/{}/record/{}", zone, record_id),
+            None,
+        )
+        .await
+    }
+
+    pub async fn create_record(
+        &self,
+        zone: &str,
+        request: &RecordRequest<'_>,
+    ) -> Result<Record, OvhError> {
+        self.request(
+            Method::POST,
+            &format!("/domain/zone/{}/record", zone),
+            Some(request),
+        )
+        .await
+    }
+
+    /// Deletes a record, ignoring the response body which is empty or `null`.
+    pub async fn delete_record(&self, zone: &str, record_id: u64) -> Result<(), OvhError> {
+        self.send::<()>(
+            Method::DELETE,
+            &format!("/domain/zone/{}/record/{}", zone, record_id),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Applies the changes made to the records of a zone, which are not published before.
+    ///
+    /// The response body is ignored as it is empty or `null`.
+    pub async fn refresh_zone(&self, zone: &str) -> Result<(), OvhError> {
+        self.send::<()>(
+            Method::POST,
+            &format!("/domain/zone/{}/refresh", zone),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Zone {
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+    pub id: u64,
+    pub sub_domain: String,
+    pub field_type: String,
+    pub target: String,
+    pub ttl: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordRequest<'a> {
+    pub field_type: &'a str,
+    pub sub_domain: &'a str,
+    pub target: String,
+    pub ttl: u64,
+}
@@ -0,0 +1,324 @@
+//! Provider implementation for [OVHcloud](https://www.ovhcloud.com/en/domains/dns-subdomain/).
+//!
+//! Requests are signed using an application key and secret together with a consumer key granting access to `/domain/zone/*`,
+//! see <https://help.ovhcloud.com/csm/en-api-getting-started-ovhcloud-api> on how to create them.
+//! Changes to the records of a zone are only published after refreshing it, which is done automatically after every change.
+//! Zones belong to ordered DNS zones or registered domains and can thus neither be created nor deleted using this provider.
+
+use std::{error::Error as StdErr, fmt, sync::Arc};
+
+use crate::{
+    http::FromStatus, CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName,
+    Provider, Record, RecordData, RetrieveRecordError, RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+pub use api::OvhError;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 17] = &[
+    "A", "AAAA", "CAA", "CNAME", "DNAME", "HTTPS", "LOC", "MX", "NAPTR", "NS", "PTR", "SPF", "SRV",
+    "SSHFP", "SVCB", "TLSA", "TXT",
+];
+
+/// OVHcloud expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+/// Represents the regional OVHcloud API endpoint to talk to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Endpoint {
+    /// OVHcloud Europe, `https://eu.api.ovh.com/1.0`.
+    #[default]
+    Eu,
+
+    /// OVHcloud Canada, `https://ca.api.ovh.com/1.0`.
+    Ca,
+
+    /// OVHcloud US, `https://api.us.ovhcloud.com/1.0`.
+    Us,
+
+    /// A custom base URL, e.g. a mock server.
+    Custom(String),
+}
+
+impl Endpoint {
+    pub fn url(&self) -> &str {
+        match self {
+            Endpoint::Eu => "https://eu.api.ovh.com/1.0",
+            Endpoint::Ca => "https://ca.api.ovh.com/1.0",
+            Endpoint::Us => "https://api.us.ovhcloud.com/1.0",
+            Endpoint::Custom(url) => url,
+        }
+    }
+}
+
+/// Represents the keys used to sign requests.
+///
+/// The application secret and the consumer key are omitted from the [`Debug`](fmt::Debug) output.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Credentials {
+    pub application_key: String,
+    pub application_secret: String,
+    pub consumer_key: String,
+}
+
+impl Credentials {
+    pub fn new(application_key: &str, application_secret: &str, consumer_key: &str) -> Self {
+        Self {
+            application_key: application_key.to_owned(),
+            application_secret: application_secret.to_owned(),
+            consumer_key: consumer_key.to_owned(),
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("application_key", &self.application_key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OvhProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl OvhProvider {
+    pub fn new(endpoint: Endpoint, credentials: Credentials) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(endpoint.url(), credentials)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for OvhProvider {
+    type Zone = OvhZone;
+    type CustomRetrieveError = OvhError;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self
+            .api_client
+            .retrieve_zone(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(OvhZone::new(self.api_client.clone(), zone))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let names = self
+            .api_client
+            .retrieve_zones()
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(names
+            .into_iter()
+            .map(|name| OvhZone::new(self.api_client.clone(), api::Zone { name }))
+            .collect())
+    }
+}
+
+/// Represents a DNS zone hosted by OVHcloud.
+///
+/// The zone ID is the name of the zone, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct OvhZone {
+    api_client: Arc<api::Client>,
+    repr: api::Zone,
+    domain: DomainName,
+}
+
+impl OvhZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Zone) -> Self {
+        let domain = DomainName::from_raw(&repr.name).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+}
+
+impl Zone for OvhZone {
+    type CustomRetrieveError = OvhError;
+
+    fn id(&self) -> &str {
+        &self.repr.name
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    /// Lists the records of the zone.
+    ///
+    /// OVHcloud only lists the IDs of records, so every record has to be retrieved separately.
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let record_ids = self
+            .api_client
+            .retrieve_record_ids(&self.repr.name)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        let mut records = Vec::with_capacity(record_ids.len());
+        for record_id in record_ids {
+            let record = self
+                .api_client
+                .retrieve_record(&self.repr.name, record_id)
+                .await
+                .map_err(RetrieveRecordError::from_status)?;
+            records.push(record.into_generic());
+        }
+
+        Ok(records)
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| RetrieveRecordError::NotFound)?;
+
+        let record = self
+            .api_client
+            .retrieve_record(&self.repr.name, record_id)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        Ok(record.into_generic())
+    }
+}
+
+impl CreateRecord for OvhZone {
+    type CustomCreateError = OvhError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        let request = api::RecordRequest {
+            field_type: typ,
+            sub_domain: host.as_ascii(),
+            target: data.get_value_as(TXT_FORMAT),
+            ttl,
+        };
+        let record = self
+            .api_client
+            .create_record(&self.repr.name, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        self.api_client
+            .refresh_zone(&self.repr.name)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(record.into_generic())
+    }
+}
+
+impl DeleteRecord for OvhZone {
+    type CustomDeleteError = OvhError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let record_id = record_id
+            .parse::<u64>()
+            .map_err(|_| DeleteRecordError::NotFound)?;
+
+        self.api_client
+            .delete_record(&self.repr.name, record_id)
+            .await
+            .map_err(DeleteRecordError::from_status)?;
+
+        self.api_client
+            .refresh_zone(&self.repr.name)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::Record {
+    pub fn into_generic(self) -> Record {
+        Record {
+            id: self.id.to_string(),
+            host: DomainName::from_raw(&self.sub_domain),
+            data: RecordData::from_raw(&self.field_type, &self.target),
+            ttl: self.ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[test]
+    fn debug_omits_secrets() {
+        let credentials = Credentials::new("app-key", "app-secret", "consumer-key");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("app-key"));
+        assert!(!debug.contains("app-secret"));
+        assert!(!debug.contains("consumer-key"));
+    }
+
+    #[tokio::test]
+    async fn delete_record_accepts_empty_responses() {
+        let server =
+            MockServer::start(
+                |request| match (request.method.as_str(), request.path.as_str()) {
+                    ("GET", "/auth/time") => Response::json(200, "1700000000"),
+                    ("GET", "/domain/zone/example.com") => {
+                        Response::json(200, r#"{"name":"example.com"}"#)
+                    }
+                    ("DELETE", "/domain/zone/example.com/record/1") => Response::new(200, ""),
+                    ("POST", "/domain/zone/example.com/refresh") => Response::json(200, "null"),
+                    _ => Response::json(404, r#"{"message":"not found"}"#),
+                },
+            )
+            .await;
+
+        let credentials = Credentials::new("app-key", "app-secret", "consumer-key");
+        let provider =
+            OvhProvider::new(Endpoint::Custom(server.url().to_owned()), credentials).unwrap();
+        let zone = provider.get_zone("example.com").await.unwrap();
+        zone.delete_record("1").await.unwrap();
+        assert!(matches!(
+            zone.delete_record("2").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let requests = server.requests();
+        assert!(requests
+            .iter()
+            .all(|request| request.path == "/auth/time"
+                || request.header("X-Ovh-Signature").is_some()));
+    }
+}
@@ -0,0 +1,679 @@
+//! Limiting the changes which may be made to a provider.
+//!
+//! A [`Policy`] describes which zones and records may be changed. [`Guarded`] wraps any provider and rejects changes
+//! violating its policy with the `Unauthorized` variant of the respective error before calling the wrapped provider:
+//!
+//! ```ignore
+//! let policy = Policy::new()
+//!     .with_zones([DomainName::new("example.com")?])
+//!     .with_hosts([HostPattern::new("_acme-challenge.*")])
+//!     .with_types(["TXT"])
+//!     .without_zone_management();
+//! let provider = HetznerProvider::new(api_key)?.layer(policy);
+//! ```
+//!
+//! Retrievals are always passed through. The reason of a rejection can be determined using the `check_*` methods of [`Policy`].
+
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::{
+    layer::Layer, CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord,
+    DeleteRecordError, DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData,
+    RetrieveRecordError, RetrieveZoneError, Zone,
+};
+
+/// Represents the reason a change is rejected by a [`Policy`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Error)]
+pub enum Violation {
+    /// Indicates that the policy does not allow any changes.
+    #[error("the provider is read-only")]
+    ReadOnly,
+
+    /// Indicates that the policy does not allow creating or deleting zones.
+    #[error("creating and deleting zones is not allowed")]
+    ZoneManagement,
+
+    /// Indicates that the zone is not one of the zones the policy allows changing.
+    #[error("changing the zone {0} is not allowed")]
+    Zone(DomainName),
+
+    /// Indicates that the host does not match any of the host patterns the policy allows.
+    #[error("changing records of the host {0} is not allowed")]
+    Host(DomainName),
+
+    /// Indicates that the record type is not one of the types the policy allows.
+    #[error("changing {0} records is not allowed")]
+    Type(String),
+
+    /// Indicates that the record to delete could not be retrieved to check its host and type.
+    #[error("the record {0} could not be retrieved to check it")]
+    UnknownRecord(String),
+}
+
+/// Represents a pattern matched against record hosts relative to their zone, e.g. `_acme-challenge.*`.
+///
+/// Patterns are compared label by label and case-insensitively.
+/// A `*` label matches any single label, except for a trailing `*`, which matches any number of labels including none.
+/// `_acme-challenge.*` therefore matches both `_acme-challenge` and `_acme-challenge.www`, `*.dev` matches `api.dev` only.
+/// The pattern `@` matches the apex of the zone.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct HostPattern {
+    labels: Vec<String>,
+}
+
+impl HostPattern {
+    pub fn new(pattern: &str) -> Self {
+        let labels = match pattern {
+            "@" => Vec::new(),
+            _ => pattern
+                .split('.')
+                .filter(|label| !label.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect(),
+        };
+        Self { labels }
+    }
+
+    /// Returns whether the pattern matches a host relative to its zone.
+    pub fn matches(&self, host: &DomainName) -> bool {
+        let labels = host.labels().collect::<Vec<_>>();
+        let (pattern, any_suffix) = match self.labels.split_last() {
+            Some((last, init)) if last == "*" => (init, true),
+            _ => (self.labels.as_slice(), false),
+        };
+
+        if labels.len() < pattern.len() || (!any_suffix && labels.len() != pattern.len()) {
+            return false;
+        }
+        pattern
+            .iter()
+            .zip(labels)
+            .all(|(pattern, label)| pattern == "*" || pattern.eq_ignore_ascii_case(label))
+    }
+}
+
+impl Display for HostPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.labels.is_empty() {
+            true => f.write_str("@"),
+            false => f.write_str(&self.labels.join(".")),
+        }
+    }
+}
+
+/// Describes which changes may be made to a provider.
+///
+/// A new policy allows all changes, which can then be restricted using the `with*` methods.
+/// Restrictions of zones, hosts and record types only apply to changes of records, except that zones can only be created
+/// for the allowed zones. As zones are deleted by their provider-specific ID, deleting zones is rejected if zones are restricted.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    read_only: bool,
+    zone_management: bool,
+    zones: Option<Vec<DomainName>>,
+    hosts: Option<Vec<HostPattern>>,
+    types: Option<Vec<String>>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            zone_management: true,
+            zones: None,
+            hosts: None,
+            types: None,
+        }
+    }
+}
+
+impl Policy {
+    /// Creates a policy allowing all changes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy rejecting all changes.
+    pub fn read_only() -> Self {
+        Self {
+            read_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// Rejects creating and deleting zones.
+    pub fn without_zone_management(mut self) -> Self {
+        self.zone_management = false;
+        self
+    }
+
+    /// Only allows changing records of the given zones.
+    pub fn with_zones(mut self, zones: impl IntoIterator<Item = DomainName>) -> Self {
+        let zones = zones.into_iter().map(|zone| zone.to_absolute());
+        self.zones.get_or_insert_with(Vec::new).extend(zones);
+        self
+    }
+
+    /// Only allows changing records whose host matches any of the given patterns.
+    pub fn with_hosts(mut self, patterns: impl IntoIterator<Item = HostPattern>) -> Self {
+        self.hosts.get_or_insert_with(Vec::new).extend(patterns);
+        self
+    }
+
+    /// Only allows changing records of the given types, e.g. `TXT`.
+    pub fn with_types<S: Into<String>>(mut self, types: impl IntoIterator<Item = S>) -> Self {
+        let types = types.into_iter().map(|typ| typ.into().to_ascii_uppercase());
+        self.types.get_or_insert_with(Vec::new).extend(types);
+        self
+    }
+
+    /// Checks whether a zone with the given domain may be created.
+    pub fn check_create_zone(&self, domain: &DomainName) -> Result<(), Violation> {
+        self.check_zone_management()?;
+        self.check_zone(domain)
+    }
+
+    /// Checks whether a zone may be deleted.
+    pub fn check_delete_zone(&self) -> Result<(), Violation> {
+        self.check_zone_management()?;
+        match &self.zones {
+            Some(_) => Err(Violation::ZoneManagement),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks whether a record of the given host and type may be created or deleted in the zone with the given domain.
+    ///
+    /// `host` is expected to be relative to the zone.
+    pub fn check_record(
+        &self,
+        zone: &DomainName,
+        host: &DomainName,
+        typ: &str,
+    ) -> Result<(), Violation> {
+        self.check_zone(zone)?;
+        if let Some(patterns) = &self.hosts {
+            if !patterns.iter().any(|pattern| pattern.matches(host)) {
+                return Err(Violation::Host(host.clone()));
+            }
+        }
+        if let Some(types) = &self.types {
+            if !types
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(typ))
+            {
+                return Err(Violation::Type(typ.to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the host and type of a record need to be known to check whether it may be deleted.
+    fn restricts_records(&self) -> bool {
+        self.hosts.is_some() || self.types.is_some()
+    }
+
+    fn check_writable(&self) -> Result<(), Violation> {
+        match self.read_only {
+            true => Err(Violation::ReadOnly),
+            false => Ok(()),
+        }
+    }
+
+    fn check_zone_management(&self) -> Result<(), Violation> {
+        self.check_writable()?;
+        match self.zone_management {
+            true => Ok(()),
+            false => Err(Violation::ZoneManagement),
+        }
+    }
+
+    fn check_zone(&self, zone: &DomainName) -> Result<(), Violation> {
+        self.check_writable()?;
+        let zone = zone.to_absolute();
+        match &self.zones {
+            Some(zones) if !zones.contains(&zone) => Err(Violation::Zone(zone)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<P: Provider> Layer<P> for Policy {
+    type Provider = Guarded<P>;
+
+    fn layer(&self, provider: P) -> Self::Provider {
+        Guarded::new(provider, self.clone())
+    }
+}
+
+/// Wraps a provider, rejecting changes violating a [`Policy`].
+///
+/// Deleting a record while hosts or record types are restricted retrieves the record first to check its host and type.
+/// If the record can not be retrieved, the deletion is rejected as well.
+#[derive(Debug, Clone)]
+pub struct Guarded<T> {
+    inner: T,
+    policy: Arc<Policy>,
+}
+
+impl<T> Guarded<T> {
+    pub fn new(inner: T, policy: Policy) -> Self {
+        Self {
+            inner,
+            policy: Arc::new(policy),
+        }
+    }
+
+    /// Returns the wrapped provider or zone.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the policy.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Unwraps the provider or zone.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn wrap<Z>(&self, zone: Z) -> Guarded<Z> {
+        Guarded {
+            inner: zone,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// Logs a rejected change if the `tracing` feature is enabled.
+fn reject(#[cfg_attr(not(feature = "tracing"), allow(unused_variables))] violation: Violation) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%violation, "change rejected by policy");
+}
+
+impl<P: Provider> Provider for Guarded<P> {
+    type Zone = Guarded<P::Zone>;
+    type CustomRetrieveError = P::CustomRetrieveError;
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = self.inner.list_zones().await?;
+        Ok(zones.into_iter().map(|zone| self.wrap(zone)).collect())
+    }
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone = self.inner.get_zone(zone_id).await?;
+        Ok(self.wrap(zone))
+    }
+}
+
+impl<P: CreateZone> CreateZone for Guarded<P> {
+    type CustomCreateError = P::CustomCreateError;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        if let Err(violation) = self.policy.check_create_zone(domain) {
+            reject(violation);
+            return Err(CreateZoneError::Unauthorized);
+        }
+
+        let zone = self.inner.create_zone(domain).await?;
+        Ok(self.wrap(zone))
+    }
+}
+
+impl<P: DeleteZone> DeleteZone for Guarded<P> {
+    type CustomDeleteError = P::CustomDeleteError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        if let Err(violation) = self.policy.check_delete_zone() {
+            reject(violation);
+            return Err(DeleteZoneError::Unauthorized);
+        }
+
+        self.inner.delete_zone(zone_id).await
+    }
+}
+
+impl<Z: Zone> Zone for Guarded<Z> {
+    type CustomRetrieveError = Z::CustomRetrieveError;
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        self.inner.domain()
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        self.inner.list_records().await
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        self.inner.get_record(record_id).await
+    }
+}
+
+impl<Z: CreateRecord> CreateRecord for Guarded<Z> {
+    type CustomCreateError = Z::CustomCreateError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let check = match self.inner.relative_host(host) {
+            Some(relative) => {
+                self.policy
+                    .check_record(self.inner.domain(), &relative, data.get_type())
+            }
+            None => Err(Violation::Host(host.clone())),
+        };
+        if let Err(violation) = check {
+            reject(violation);
+            return Err(CreateRecordError::Unauthorized);
+        }
+
+        self.inner.create_record(host, data, ttl).await
+    }
+}
+
+impl<Z: DeleteRecord> DeleteRecord for Guarded<Z> {
+    type CustomDeleteError = Z::CustomDeleteError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let check = match self.policy.check_zone(self.inner.domain()) {
+            Ok(()) if self.policy.restricts_records() => {
+                match self.inner.get_record(record_id).await {
+                    Ok(record) => {
+                        let host = self
+                            .inner
+                            .relative_host(&record.host)
+                            .unwrap_or(record.host);
+                        self.policy
+                            .check_record(self.inner.domain(), &host, record.data.get_type())
+                    }
+                    Err(RetrieveRecordError::NotFound) => return Err(DeleteRecordError::NotFound),
+                    Err(_) => Err(Violation::UnknownRecord(record_id.to_owned())),
+                }
+            }
+            check => check,
+        };
+        if let Err(violation) = check {
+            reject(violation);
+            return Err(DeleteRecordError::Unauthorized);
+        }
+
+        self.inner.delete_record(record_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryProvider;
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new(name).unwrap()
+    }
+
+    #[test]
+    fn host_patterns() {
+        let cases = [
+            ("_acme-challenge.*", "_acme-challenge", true),
+            ("_acme-challenge.*", "_ACME-Challenge.www", true),
+            ("_acme-challenge.*", "_acme-challenge.api.dev", true),
+            ("_acme-challenge.*", "www", false),
+            ("_acme-challenge.*", "www._acme-challenge", false),
+            ("*.dev", "api.dev", true),
+            ("*.dev", "dev", false),
+            ("*.dev", "v2.api.dev", false),
+            ("*", "@", true),
+            ("*", "www.api", true),
+            ("www", "www", true),
+            ("www", "www.api", false),
+            ("@", "@", true),
+            ("@", "www", false),
+            ("www.", "www", true),
+        ];
+
+        for (pattern, host, expected) in cases {
+            assert_eq!(
+                HostPattern::new(pattern).matches(&name(host)),
+                expected,
+                "{} matching {}",
+                pattern,
+                host
+            );
+        }
+
+        assert_eq!(
+            HostPattern::new("_ACME-challenge.*").to_string(),
+            "_acme-challenge.*"
+        );
+        assert_eq!(HostPattern::new("@").to_string(), "@");
+    }
+
+    #[test]
+    fn checks() {
+        let zone = name("example.com");
+        let policy = Policy::new()
+            .with_zones([zone.clone()])
+            .with_hosts([HostPattern::new("_acme-challenge.*")])
+            .with_types(["txt"]);
+
+        assert_eq!(
+            policy.check_record(&zone, &name("_acme-challenge"), "TXT"),
+            Ok(())
+        );
+        assert_eq!(
+            policy.check_record(&name("example.com."), &name("_acme-challenge.www"), "txt"),
+            Ok(())
+        );
+        assert_eq!(
+            policy.check_record(&name("example.org"), &name("_acme-challenge"), "TXT"),
+            Err(Violation::Zone(name("example.org.")))
+        );
+        assert_eq!(
+            policy.check_record(&zone, &name("www"), "TXT"),
+            Err(Violation::Host(name("www")))
+        );
+        assert_eq!(
+            policy.check_record(&zone, &name("_acme-challenge"), "A"),
+            Err(Violation::Type("A".to_owned()))
+        );
+
+        assert_eq!(policy.check_create_zone(&zone), Ok(()));
+        assert_eq!(
+            policy.check_create_zone(&name("example.org")),
+            Err(Violation::Zone(name("example.org.")))
+        );
+        assert_eq!(policy.check_delete_zone(), Err(Violation::ZoneManagement));
+        assert_eq!(Policy::new().check_delete_zone(), Ok(()));
+        assert_eq!(
+            Policy::new()
+                .without_zone_management()
+                .check_create_zone(&zone),
+            Err(Violation::ZoneManagement)
+        );
+
+        let read_only = Policy::read_only();
+        assert_eq!(read_only.check_delete_zone(), Err(Violation::ReadOnly));
+        assert_eq!(
+            read_only.check_record(&zone, &name("www"), "A"),
+            Err(Violation::ReadOnly)
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_changes_never_reach_the_inner_provider() {
+        let inner = MemoryProvider::new()
+            .with_zone("example.com")
+            .with_zone("example.org")
+            .with_record(
+                "example.com",
+                "www",
+                RecordData::A("192.0.2.1".parse().unwrap()),
+            )
+            .with_record(
+                "example.org",
+                "_acme-challenge",
+                RecordData::TXT(vec!["token".to_owned()]),
+            );
+        let policy = Policy::new()
+            .with_zones([name("example.com")])
+            .with_hosts([HostPattern::new("_acme-challenge.*")])
+            .with_types(["TXT"])
+            .without_zone_management();
+        let provider = inner.clone().layer(policy);
+        let txt = RecordData::TXT(vec!["token".to_owned()]);
+
+        assert!(matches!(
+            provider.create_zone(&name("example.com")).await,
+            Err(CreateZoneError::Unauthorized)
+        ));
+        assert!(matches!(
+            provider.delete_zone("example.org").await,
+            Err(DeleteZoneError::Unauthorized)
+        ));
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        for (host, data) in [
+            ("www", &txt),
+            (
+                "_acme-challenge",
+                &RecordData::A("192.0.2.1".parse().unwrap()),
+            ),
+            ("_acme-challenge.example.org.", &txt),
+        ] {
+            assert!(
+                matches!(
+                    zone.create_record(&name(host), data, 300).await,
+                    Err(CreateRecordError::Unauthorized)
+                ),
+                "{} {}",
+                host,
+                data.get_type()
+            );
+        }
+        assert!(matches!(
+            zone.delete_record("1").await,
+            Err(DeleteRecordError::Unauthorized)
+        ));
+        assert!(matches!(
+            zone.delete_record("missing").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let other = provider.get_zone("example.org").await.unwrap();
+        assert!(matches!(
+            other
+                .create_record(&name("_acme-challenge"), &txt, 300)
+                .await,
+            Err(CreateRecordError::Unauthorized)
+        ));
+        assert!(matches!(
+            other.delete_record("2").await,
+            Err(DeleteRecordError::Unauthorized)
+        ));
+
+        // Only retrievals reach the inner provider, the records to be deleted being retrieved to check them.
+        assert_eq!(
+            inner.calls(),
+            [
+                "get_zone example.com",
+                "get_record example.com 1",
+                "get_record example.com missing",
+                "get_zone example.org",
+            ]
+        );
+        assert_eq!(inner.records("example.com").len(), 1);
+        assert_eq!(inner.records("example.org").len(), 1);
+        assert_eq!(inner.list_zones().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn permitted_changes_are_passed_through() {
+        let inner = MemoryProvider::new().with_zone("example.com");
+        let policy = Policy::new()
+            .with_zones([name("example.com")])
+            .with_hosts([HostPattern::new("_acme-challenge.*")])
+            .with_types(["TXT"]);
+        let provider = inner.clone().layer(policy);
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        let record = zone
+            .create_record(
+                &name("_acme-challenge.www.example.com."),
+                &RecordData::TXT(vec!["token".to_owned()]),
+                300,
+            )
+            .await
+            .unwrap();
+        zone.delete_record(&record.id).await.unwrap();
+
+        assert_eq!(
+            inner.calls(),
+            [
+                "get_zone example.com",
+                "create_record example.com _acme-challenge.www.example.com TXT",
+                "get_record example.com 1",
+                "delete_record example.com 1",
+            ]
+        );
+        assert!(inner.records("example.com").is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_only_providers_reject_changes_without_retrieving_records() {
+        let inner = MemoryProvider::new().with_zone("example.com").with_record(
+            "example.com",
+            "www",
+            RecordData::A("192.0.2.1".parse().unwrap()),
+        );
+        let provider = inner.clone().layer(Policy::read_only());
+
+        let zone = provider.get_zone("example.com").await.unwrap();
+        assert_eq!(zone.list_records().await.unwrap().len(), 1);
+        assert!(matches!(
+            zone.delete_record("1").await,
+            Err(DeleteRecordError::Unauthorized)
+        ));
+        assert!(matches!(
+            provider.delete_zone("example.com").await,
+            Err(DeleteZoneError::Unauthorized)
+        ));
+
+        assert_eq!(
+            inner.calls(),
+            ["get_zone example.com", "list_records example.com"]
+        );
+        assert_eq!(inner.records("example.com").len(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{error::Error as StdErr, fmt};
+
+use reqwest::{Client as HttpClient, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::http::StatusError;
+#[cfg(feature = "retry")]
+use crate::{http::is_transient_status, layer::Transient};
+
+pub const PORKBUN_API_URL: &str = "https://api.porkbun.com/api/json/v3";
+
+/// The amount of domains Porkbun returns per page when listing domains.
+pub const DOMAINS_PAGE_SIZE: usize = 1000;
+
+/// Represents an error returned by the Porkbun API client.
+#[derive(Debug, Error)]
+pub enum PorkbunError {
+    /// Indicates that the HTTP request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that Porkbun responded with an error.
+    #[error("Porkbun responded with {status}: {message}")]
+    Api { status: StatusCode, message: String },
+}
+
+impl PorkbunError {
+    /// Returns the HTTP status code of an API error.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            PorkbunError::Api { status, .. } => Some(*status),
+            PorkbunError::Http(err) => err.status(),
+        }
+    }
+
+    /// Returns whether the request was rejected because of invalid API keys or disabled API access for a domain.
+    ///
+    /// Porkbun responds with `400 Bad Request` in these cases, so the error message has to be inspected.
+    pub fn is_unauthorized(&self) -> bool {
+        match self {
+            PorkbunError::Api { status, message } => {
+                matches!(*status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+                    || message.contains("API key")
+                    || message.contains("API access")
+            }
+            PorkbunError::Http(_) => false,
+        }
+    }
+}
+
+impl StatusError for PorkbunError {
+    /// Returns the status code Porkbun responded with, treating rejected API keys as `401 Unauthorized`.
+    fn status(&self) -> Option<StatusCode> {
+        if self.is_unauthorized() {
+            Some(StatusCode::UNAUTHORIZED)
+        } else {
+            PorkbunError::status(self)
+        }
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for PorkbunError {
+    fn is_transient(&self) -> bool {
+        match self {
+            PorkbunError::Http(err) => err.is_transient(),
+            PorkbunError::Api { status, .. } => is_transient_status(*status),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+    api_key: String,
+    secret_api_key: String,
+}
+
+impl Client {
+    pub fn new(
+        api_key: &str,
+        secret_api_key: &str,
+        base_url: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let http_client = HttpClient::builder().build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            api_key: api_key.to_owned(),
+            secret_api_key: secret_api_key.to_owned(),
+        })
+    }
+
+    /// Sends a request to Porkbun, which expects the credentials as part of every JSON body.
+    async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: B,
+    ) -> Result<T, PorkbunError> {
+        let response = self
+            .http_client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&AuthenticatedRequest {
+                apikey: &self.api_key,
+                secretapikey: &self.secret_api_key,
+                body,
+            })
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await?;
+            let message = error_message(&text).unwrap_or(text);
+            return Err(PorkbunError::Api { status, message });
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_domains(&self, start: usize) -> Result<DomainsResponse, PorkbunError> {
+        self.post("/domain/listAll", ListDomainsRequest { start })
+            .await
+    }
+
+    pub async fn retrieve_records(&self, domain: &str) -> Result<RecordsResponse, PorkbunError> {
+        self.post(&format!("/dns/retrieve/{}", domain), Empty {})
+            .await
+    }
+
+    pub async fn retrieve_record(
+        &self,
+        domain: &str,
+        record_id: &str,
+    ) -> Result<RecordsResponse, PorkbunError> {
+        self.post(&format!("/dns/retrieve/{}/{}", domain, record_id), Empty {})
+            .await
+    }
+
+    pub async fn create_record(
+        &self,
+        domain: &str,
+        request: &RecordRequest<'_>,
+    ) -> Result<CreateRecordResponse, PorkbunError> {
+        self.post(&format!("/dns/create/{}", domain), request).await
+    }
+
+    pub async fn delete_record(&self, domain: &str, record_id: &str) -> Result<(), PorkbunError> {
+        self.post::<_, StatusResponse>(&format!("/dns/delete/{}/{}", domain, record_id), Empty {})
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Extracts the message of an error response.
+fn error_message(text: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct ErrorResponse {
+        message: String,
+    }
+
+    serde_json::from_str::<ErrorResponse>(text)
+        .ok()
+        .map(|response| response.message)
+}
+
+#[derive(Debug, Serialize)]
+struct AuthenticatedRequest<'a, B> {
+    apikey: &'a str,
+    secretapikey: &'a str,
+    #[serde(flatten)]
+    body: B,
+}
+
+#[derive(Debug, Serialize)]
+struct Empty {}
+
+#[derive(Debug, Serialize)]
+struct ListDomainsRequest {
+    start: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct StatusResponse {
+    pub status: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct DomainsResponse {
+    #[serde(default)]
+    pub domains: Vec<Domain>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub domain: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RecordsResponse {
+    #[serde(default)]
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Record {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub content: String,
+    pub ttl: String,
+    pub prio: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct RecordRequest<'a> {
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub typ: &'a str,
+    pub content: String,
+    pub ttl: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prio: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct CreateRecordResponse {
+    pub id: RecordId,
+}
+
+/// Porkbun returns record IDs as numbers when creating records but as strings when listing them.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(untagged)]
+pub enum RecordId {
+    Number(u64),
+    String(String),
+}
+
+impl fmt::Display for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordId::Number(id) => write!(f, "{}", id),
+            RecordId::String(id) => f.write_str(id),
+        }
+    }
+}
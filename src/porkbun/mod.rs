@@ -0,0 +1,428 @@
+//! Provider implementation for [Porkbun](https://porkbun.com/).
+//!
+//! Requests are authenticated using an API key and secret API key, which Porkbun expects in the JSON body of every request.
+//! API access has to be enabled for every domain which should be managed using this provider.
+//! Zones belong to registered domains and can thus neither be created nor deleted using this provider.
+
+use std::{error::Error as StdErr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName, Provider, Record,
+    RecordData, RetrieveRecordError, RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+pub use api::PorkbunError;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 12] = &[
+    "A", "AAAA", "ALIAS", "CAA", "CNAME", "HTTPS", "MX", "NS", "SRV", "SVCB", "TLSA", "TXT",
+];
+
+/// Porkbun expects TXT values as a single unquoted string.
+const TXT_FORMAT: TxtFormat = TxtFormat::Concatenated;
+
+const PROVIDER_NAME: &str = "porkbun";
+
+#[derive(Debug, Clone)]
+pub struct PorkbunProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl PorkbunProvider {
+    pub fn new(api_key: &str, secret_api_key: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(api_key, secret_api_key, api::PORKBUN_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://api.porkbun.com/api/json/v3`, e.g. a mock server.
+    pub fn with_base_url(
+        api_key: &str,
+        secret_api_key: &str,
+        base_url: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_key, secret_api_key, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for PorkbunProvider {
+    type Zone = PorkbunZone;
+    type CustomRetrieveError = PorkbunError;
+
+    /// Retrieves a domain of the account.
+    ///
+    /// Porkbun does not offer retrieving a single domain, so all domains are listed and searched instead.
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zone_domain = DomainName::from_raw(zone_id);
+
+        self.list_zones()
+            .await?
+            .into_iter()
+            .find(|zone| zone.domain == zone_domain.to_absolute())
+            .ok_or(RetrieveZoneError::NotFound)
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domains = collect_pages(PROVIDER_NAME, |start: Option<usize>| async move {
+            let start = start.unwrap_or(0);
+            self.api_client
+                .list_domains(start)
+                .await
+                .map(|response| {
+                    let count = response.domains.len();
+                    Page {
+                        items: response.domains,
+                        next: (count == api::DOMAINS_PAGE_SIZE).then_some(start + count),
+                    }
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| PorkbunZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+/// Represents a domain managed by Porkbun.
+///
+/// The zone ID is the domain itself, e.g. `example.com`.
+#[derive(Debug, Clone)]
+pub struct PorkbunZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    domain: DomainName,
+}
+
+impl PorkbunZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let domain = DomainName::from_raw(&repr.domain).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+}
+
+impl Zone for PorkbunZone {
+    type CustomRetrieveError = PorkbunError;
+
+    fn id(&self) -> &str {
+        &self.repr.domain
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let response = self
+            .api_client
+            .retrieve_records(&self.repr.domain)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        Ok(response
+            .records
+            .into_iter()
+            .map(|record| record.into_generic(&self.domain))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        if record_id.is_empty() || !record_id.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(RetrieveRecordError::NotFound);
+        }
+
+        let response = self
+            .api_client
+            .retrieve_record(&self.repr.domain, record_id)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        response
+            .records
+            .into_iter()
+            .next()
+            .map(|record| record.into_generic(&self.domain))
+            .ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for PorkbunZone {
+    type CustomCreateError = PorkbunError;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        let mut request = api::RecordRequest {
+            name: host.as_ascii(),
+            typ,
+            content: data.get_value_as(TXT_FORMAT),
+            ttl: ttl.to_string(),
+            prio: None,
+        };
+
+        match data {
+            RecordData::CNAME(target) | RecordData::NS(target) => {
+                request.content = self.fqdn(target).as_ascii().to_owned();
+            }
+            RecordData::MX {
+                priority,
+                mail_server,
+            } => {
+                request.content = self.fqdn(mail_server).as_ascii().to_owned();
+                request.prio = Some(priority.to_string());
+            }
+            RecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                request.content = format!("{} {} {}", weight, port, self.fqdn(target).as_ascii());
+                request.prio = Some(priority.to_string());
+            }
+            _ => {}
+        }
+
+        let response = self
+            .api_client
+            .create_record(&self.repr.domain, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(Record {
+            id: response.id.to_string(),
+            host,
+            data: data.clone(),
+            ttl,
+        })
+    }
+}
+
+impl DeleteRecord for PorkbunZone {
+    type CustomDeleteError = PorkbunError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        if record_id.is_empty() || !record_id.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(DeleteRecordError::NotFound);
+        }
+
+        self.api_client
+            .delete_record(&self.repr.domain, record_id)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::Record {
+    pub fn into_generic(self, zone_domain: &DomainName) -> Record {
+        // Porkbun returns names and targets without a trailing dot.
+        let target = |name: &str| DomainName::from_raw(name).to_absolute();
+        let priority = self
+            .prio
+            .as_deref()
+            .and_then(|prio| prio.parse::<u16>().ok())
+            .unwrap_or_default();
+
+        let data = match self.typ.as_str() {
+            "CNAME" => RecordData::CNAME(target(&self.content)),
+            "NS" => RecordData::NS(target(&self.content)),
+            "MX" => RecordData::MX {
+                priority,
+                mail_server: target(&self.content),
+            },
+            "SRV" => {
+                // Porkbun returns the priority separately and the weight, port and target as the content.
+                let mut fields = self.content.split_whitespace();
+                match (
+                    fields.next().and_then(|weight| weight.parse().ok()),
+                    fields.next().and_then(|port| port.parse().ok()),
+                    fields.next(),
+                    fields.next(),
+                ) {
+                    (Some(weight), Some(port), Some(srv_target), None) => RecordData::SRV {
+                        priority,
+                        weight,
+                        port,
+                        target: target(srv_target),
+                    },
+                    _ => RecordData::from_raw(&self.typ, &format!("{} {}", priority, self.content)),
+                }
+            }
+            "TXT" => RecordData::txt(self.content.clone()),
+            _ => RecordData::from_raw(&self.typ, &self.content),
+        };
+
+        let name = target(&self.name);
+        Record {
+            id: self.id,
+            host: name.relative_to(zone_domain).unwrap_or(name),
+            data,
+            ttl: self.ttl.parse().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[test]
+    fn srv_targets_are_absolute() {
+        let record = api::Record {
+            id: "1".to_owned(),
+            name: "_sip._tcp.example.com".to_owned(),
+            typ: "SRV".to_owned(),
+            content: "5 5060 sip.example.com".to_owned(),
+            ttl: "600".to_owned(),
+            prio: Some("10".to_owned()),
+        };
+
+        let record = record.into_generic(&DomainName::new("example.com.").unwrap());
+        assert_eq!(record.host, DomainName::new("_sip._tcp").unwrap());
+        assert_eq!(
+            record.data,
+            RecordData::SRV {
+                priority: 10,
+                weight: 5,
+                port: 5060,
+                target: DomainName::new("sip.example.com.").unwrap(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn credentials_are_sent_in_request_bodies() {
+        let server = MockServer::start(|request| {
+            if request.method != "POST" {
+                return Response::json(405, r#"{"status":"ERROR","message":"Method not allowed."}"#);
+            }
+            if !request
+                .body
+                .contains(r#""apikey":"pk1_key","secretapikey":"sk1_secret""#)
+            {
+                return Response::json(
+                    400,
+                    r#"{"status":"ERROR","message":"Invalid API key. (002)"}"#,
+                );
+            }
+
+            match request.path.as_str() {
+                "/domain/listAll" => Response::json(
+                    200,
+                    r#"{"status":"SUCCESS","domains":[{"domain":"example.com","status":"ACTIVE"}]}"#,
+                ),
+                "/dns/retrieve/example.com" => Response::json(
+                    200,
+                    r#"{"status":"SUCCESS","records":[{"id":"1","name":"example.com","type":"MX","content":"mail.example.com","ttl":"600","prio":"10","notes":""},{"id":"2","name":"www.example.com","type":"CNAME","content":"example.com","ttl":"600","prio":null,"notes":""}]}"#,
+                ),
+                "/dns/create/example.com" => {
+                    Response::json(200, r#"{"status":"SUCCESS","id":3}"#)
+                }
+                _ => Response::json(400, r#"{"status":"ERROR","message":"Invalid domain."}"#),
+            }
+        })
+        .await;
+
+        let unauthorized =
+            PorkbunProvider::with_base_url("pk1_key", "sk1_wrong", server.url()).unwrap();
+        assert!(matches!(
+            unauthorized.list_zones().await,
+            Err(RetrieveZoneError::Unauthorized)
+        ));
+
+        let provider =
+            PorkbunProvider::with_base_url("pk1_key", "sk1_secret", server.url()).unwrap();
+        assert!(matches!(
+            provider.get_zone("example.org").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.id.as_str(), record.host.to_string(), &record.data))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "1",
+                    "@".to_owned(),
+                    &RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mail.example.com."),
+                    }
+                ),
+                (
+                    "2",
+                    "www".to_owned(),
+                    &RecordData::CNAME(DomainName::from_raw("example.com."))
+                ),
+            ]
+        );
+
+        let data = RecordData::MX {
+            priority: 20,
+            mail_server: DomainName::from_raw("backup"),
+        };
+        let record = zone
+            .create_record(&DomainName::from_raw("mail"), &data, 600)
+            .await
+            .unwrap();
+        assert_eq!(record.id, "3");
+
+        // Porkbun only assigns numeric IDs, so other IDs are rejected without a request.
+        assert!(matches!(
+            zone.delete_record("www|A|c0000201").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let requests = server.requests();
+        let create = requests
+            .iter()
+            .find(|request| request.path == "/dns/create/example.com")
+            .unwrap();
+        assert_eq!(
+            create.body,
+            r#"{"apikey":"pk1_key","secretapikey":"sk1_secret","name":"mail","type":"MX","content":"backup.example.com","ttl":"600","prio":"20"}"#
+        );
+        assert!(!requests
+            .iter()
+            .any(|request| request.path.starts_with("/dns/delete/")));
+    }
+}
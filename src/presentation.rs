@@ -0,0 +1,215 @@
+//! Utilities for the textual presentation format of record data as used in zone files (see RFC 1035, section 5.1).
+
+/// Splits presentation-format record data into its fields.
+///
+/// Fields are separated by whitespace. Quoted fields may contain whitespace and both quoted and unquoted fields
+/// support the `\X` and `\DDD` escape sequences.
+/// Returns [`None`] if a quote is not terminated, an escape sequence is invalid or a field is not valid UTF-8.
+pub(crate) fn tokenize(value: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = value.trim().chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let quoted = c == '"';
+        if quoted {
+            chars.next();
+        }
+
+        let mut token = Vec::new();
+        let mut terminated = !quoted;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    terminated = true;
+                    break;
+                }
+                c if c.is_whitespace() && !quoted => break,
+                '\\' => {
+                    let escaped = chars.next()?;
+                    if let Some(first) = escaped.to_digit(10) {
+                        let second = chars.next()?.to_digit(10)?;
+                        let third = chars.next()?.to_digit(10)?;
+                        token.push(u8::try_from(first * 100 + second * 10 + third).ok()?);
+                    } else {
+                        let mut buf = [0; 4];
+                        token.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+                    }
+                }
+                c => {
+                    let mut buf = [0; 4];
+                    token.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+
+        if !terminated {
+            return None;
+        }
+        tokens.push(String::from_utf8(token).ok()?);
+    }
+
+    Some(tokens)
+}
+
+/// Quotes a value as a presentation-format character-string, escaping quotes, backslashes and control characters.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_ascii_control() => quoted.push_str(&format!("\\{:03}", c as u8)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Splits a value into chunks of at most `max_len` bytes without splitting UTF-8 characters.
+/// An empty value results in a single empty chunk.
+pub(crate) fn split_chunks(value: &str, max_len: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = value;
+    while rest.len() > max_len {
+        let mut end = max_len;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, remainder) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = remainder;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Encodes bytes as lowercase hexadecimal digits.
+pub(crate) fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes hexadecimal digits into bytes, ignoring whitespace in between.
+pub(crate) fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect(),
+    )
+}
+
+/// Parses the [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597) generic representation `\# <length> <hex data>`.
+pub(crate) fn parse_generic(value: &str) -> Option<Vec<u8>> {
+    let rest = value.trim().strip_prefix("\\#")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let (length, data) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let length = length.parse::<usize>().ok()?;
+
+    let data = decode_hex(data)?;
+    (data.len() == length).then_some(data)
+}
+
+/// Formats data using the [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597) generic representation `\# <length> <hex data>`.
+pub(crate) fn format_generic(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "\\# 0".to_owned();
+    }
+    format!("\\# {} {}", data.len(), encode_hex(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_fields_and_escapes() {
+        assert_eq!(
+            tokenize(r#"  10 "hello world"  plain "#),
+            Some(vec!["10".into(), "hello world".into(), "plain".into()])
+        );
+        assert_eq!(
+            tokenize(r#""a \"quoted\" \\ value" \065\066 a\ b"#),
+            Some(vec![
+                r#"a "quoted" \ value"#.into(),
+                "AB".into(),
+                "a b".into()
+            ])
+        );
+        assert_eq!(tokenize(r#""""#), Some(vec![String::new()]));
+        assert_eq!(tokenize(""), Some(Vec::new()));
+        assert_eq!(tokenize(r#""\195\164""#), Some(vec!["ä".into()]));
+
+        assert_eq!(tokenize(r#""unterminated"#), None);
+        assert_eq!(tokenize(r"\25"), None);
+        assert_eq!(tokenize(r"\256"), None);
+        assert_eq!(tokenize(r"trailing\"), None);
+        assert_eq!(tokenize(r"\255"), None);
+    }
+
+    #[test]
+    fn quote_escapes() {
+        assert_eq!(quote(""), r#""""#);
+        assert_eq!(quote(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+        assert_eq!(quote("tab\there"), r#""tab\009here""#);
+        assert_eq!(quote("ä"), "\"ä\"");
+
+        for value in ["", r#"say "hi" \o/"#, "tab\there", "ä ö"] {
+            assert_eq!(tokenize(&quote(value)), Some(vec![value.to_owned()]));
+        }
+    }
+
+    #[test]
+    fn chunks_respect_utf8_boundaries() {
+        assert_eq!(split_chunks("", 255), [""]);
+        assert_eq!(split_chunks("abc", 3), ["abc"]);
+        assert_eq!(split_chunks("abcd", 3), ["abc", "d"]);
+        // `ä` takes two bytes and must not be split.
+        assert_eq!(split_chunks("aää", 2), ["a", "ä", "ä"]);
+        assert_eq!(split_chunks("aää", 3), ["aä", "ä"]);
+
+        let long = "ä".repeat(200);
+        let chunks = split_chunks(&long, 255);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            [254, 146]
+        );
+        assert_eq!(chunks.concat(), long);
+    }
+
+    #[test]
+    fn hex_and_generic_representation() {
+        assert_eq!(encode_hex(&[0x00, 0xab, 0xff]), "00abff");
+        assert_eq!(decode_hex("00 AB ff"), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+
+        assert_eq!(parse_generic(r"\# 3 00abff"), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(parse_generic(r"\# 0"), Some(Vec::new()));
+        assert_eq!(parse_generic(r"\# 2 00abff"), None);
+        assert_eq!(parse_generic(r"\#3 00abff"), None);
+        assert_eq!(format_generic(&[0x00, 0xab, 0xff]), r"\# 3 00abff");
+        assert_eq!(format_generic(&[]), r"\# 0");
+    }
+}
@@ -0,0 +1,455 @@
+//! Checking whether a record is served by all authoritative name servers of its zone, e.g. after creating it using
+//! [`CreateRecord::create_record`](crate::CreateRecord::create_record).
+//!
+//! By default, the name servers are discovered using the NS records at the apex of the zone and resolved using the system's
+//! resolver. Every address of every name server is queried directly over UDP (falling back to TCP for truncated responses),
+//! bypassing any caches.
+//! [`PropagationChecker::with_nameservers`] replaces the discovered name servers by fixed addresses, e.g. for local name servers.
+//!
+//! The checker has to be used within a Tokio runtime with the I/O and time drivers enabled.
+//! This module is only available with the `propagation` feature enabled.
+
+use std::{io, net::SocketAddr, time::Duration};
+
+use thiserror::Error;
+use tokio::time::{sleep, Instant};
+
+use crate::{query, DomainName, RecordData, RetrieveRecordError, Zone};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The response code indicating that the name does not exist, which is expected as long as the record does not exist.
+const RCODE_NXDOMAIN: u8 = 3;
+
+/// Represents an error that occured when checking the propagation of a record.
+#[derive(Debug, Error)]
+pub enum PropagationError<T> {
+    /// Indicates that the records of the zone could not be retrieved to discover its name servers.
+    #[error("failed to retrieve the records of the zone: {0:?}")]
+    RetrieveRecord(RetrieveRecordError<T>),
+
+    /// Indicates that the zone has no NS records at its apex.
+    #[error("the zone has no name servers")]
+    NoNameservers,
+
+    /// Indicates that some name servers did not serve the record before the timeout elapsed.
+    #[error("the record did not propagate to all name servers in time")]
+    Timeout(PropagationReport),
+}
+
+/// Represents the state of a record on a single name server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerStatus {
+    /// Indicates that the server answers authoritatively with the record.
+    Propagated,
+
+    /// Indicates that the server answers authoritatively, but without the record.
+    Pending,
+
+    /// Indicates that the server is not authoritative for the record's name.
+    NotAuthoritative,
+
+    /// Indicates that the server responded with an error code, e.g. `2` (SERVFAIL).
+    Rcode(u8),
+
+    /// Indicates that the server could not be queried.
+    Unreachable(io::ErrorKind),
+
+    /// Indicates that no address of the name server could be resolved.
+    Unresolved,
+}
+
+/// Represents the state of a record on a single address of a name server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerReport {
+    /// The name of the name server, which is [`None`] for addresses given using [`PropagationChecker::with_nameservers`].
+    pub nameserver: Option<DomainName>,
+
+    /// The address queried, which is [`None`] if the name server could not be resolved.
+    pub address: Option<SocketAddr>,
+
+    pub status: ServerStatus,
+}
+
+/// Represents the result of checking all name servers of a zone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropagationReport {
+    pub servers: Vec<ServerReport>,
+}
+
+impl PropagationReport {
+    /// Returns whether every name server serves the record.
+    pub fn is_propagated(&self) -> bool {
+        self.servers
+            .iter()
+            .all(|server| server.status == ServerStatus::Propagated)
+    }
+
+    /// Returns the reports of the name servers not serving the record.
+    pub fn pending(&self) -> impl Iterator<Item = &ServerReport> {
+        self.servers
+            .iter()
+            .filter(|server| server.status != ServerStatus::Propagated)
+    }
+}
+
+/// Represents a name server to query.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Nameserver {
+    Name(DomainName),
+    Address(SocketAddr),
+}
+
+/// Checks the propagation of records to the authoritative name servers of their zone.
+#[derive(Debug, Clone)]
+pub struct PropagationChecker {
+    timeout: Duration,
+    polling_interval: Duration,
+    query_timeout: Duration,
+    nameservers: Option<Vec<SocketAddr>>,
+}
+
+impl Default for PropagationChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropagationChecker {
+    /// Creates a checker using a timeout of two minutes, a polling interval of five seconds and a query timeout of five seconds.
+    pub fn new() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            polling_interval: DEFAULT_POLLING_INTERVAL,
+            query_timeout: DEFAULT_QUERY_TIMEOUT,
+            nameservers: None,
+        }
+    }
+
+    /// Sets the maximum time [`PropagationChecker::wait`] waits for the record to be served by all name servers.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the time to wait between checking the name servers.
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = polling_interval;
+        self
+    }
+
+    /// Sets the time to wait for a single name server to respond.
+    pub fn with_query_timeout(mut self, query_timeout: Duration) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    /// Queries the given addresses instead of the name servers listed by the zone's NS records.
+    pub fn with_nameservers(mut self, nameservers: impl IntoIterator<Item = SocketAddr>) -> Self {
+        self.nameservers = Some(nameservers.into_iter().collect());
+        self
+    }
+
+    /// Checks once whether the name servers serve the record.
+    ///
+    /// The host may either be relative to the zone's domain or fully qualified (see [`Zone::relative_host`]).
+    pub async fn check<Z: Zone>(
+        &self,
+        zone: &Z,
+        host: &DomainName,
+        data: &RecordData,
+    ) -> Result<PropagationReport, PropagationError<Z::CustomRetrieveError>> {
+        let nameservers = self.nameservers(zone).await?;
+        Ok(self
+            .check_nameservers(&nameservers, &zone.fqdn(host), data)
+            .await)
+    }
+
+    /// Checks the name servers repeatedly until all of them serve the record or the timeout elapses.
+    ///
+    /// The host may either be relative to the zone's domain or fully qualified (see [`Zone::relative_host`]).
+    pub async fn wait<Z: Zone>(
+        &self,
+        zone: &Z,
+        host: &DomainName,
+        data: &RecordData,
+    ) -> Result<PropagationReport, PropagationError<Z::CustomRetrieveError>> {
+        let deadline = Instant::now() + self.timeout;
+        let nameservers = self.nameservers(zone).await?;
+        let name = zone.fqdn(host);
+
+        loop {
+            let report = self.check_nameservers(&nameservers, &name, data).await;
+            if report.is_propagated() {
+                return Ok(report);
+            }
+            if Instant::now() + self.polling_interval > deadline {
+                return Err(PropagationError::Timeout(report));
+            }
+            sleep(self.polling_interval).await;
+        }
+    }
+
+    async fn nameservers<Z: Zone>(
+        &self,
+        zone: &Z,
+    ) -> Result<Vec<Nameserver>, PropagationError<Z::CustomRetrieveError>> {
+        let nameservers = match &self.nameservers {
+            Some(addresses) => addresses
+                .iter()
+                .map(|&address| Nameserver::Address(address))
+                .collect::<Vec<_>>(),
+            None => zone
+                .list_records()
+                .await
+                .map_err(PropagationError::RetrieveRecord)?
+                .into_iter()
+                .filter_map(|record| match record.data {
+                    RecordData::NS(nameserver) if record.host.is_apex() => {
+                        Some(Nameserver::Name(nameserver.to_fqdn(zone.domain())))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        };
+
+        if nameservers.is_empty() {
+            return Err(PropagationError::NoNameservers);
+        }
+        Ok(nameservers)
+    }
+
+    async fn check_nameservers(
+        &self,
+        nameservers: &[Nameserver],
+        name: &DomainName,
+        data: &RecordData,
+    ) -> PropagationReport {
+        let mut servers = Vec::new();
+        for nameserver in nameservers {
+            let (nameserver, addresses) = match nameserver {
+                Nameserver::Address(address) => (None, vec![*address]),
+                Nameserver::Name(nameserver) => (
+                    Some(nameserver.clone()),
+                    query::resolve(nameserver).await.unwrap_or_default(),
+                ),
+            };
+
+            if addresses.is_empty() {
+                servers.push(ServerReport {
+                    nameserver,
+                    address: None,
+                    status: ServerStatus::Unresolved,
+                });
+                continue;
+            }
+            for address in addresses {
+                servers.push(ServerReport {
+                    nameserver: nameserver.clone(),
+                    address: Some(address),
+                    status: self.check_address(address, name, data).await,
+                });
+            }
+        }
+
+        PropagationReport { servers }
+    }
+
+    async fn check_address(
+        &self,
+        address: SocketAddr,
+        name: &DomainName,
+        data: &RecordData,
+    ) -> ServerStatus {
+        match query::query(address, name, data.get_type(), self.query_timeout).await {
+            Err(err) => ServerStatus::Unreachable(err.kind()),
+            Ok(response) if response.rcode != 0 && response.rcode != RCODE_NXDOMAIN => {
+                ServerStatus::Rcode(response.rcode)
+            }
+            Ok(response) if !response.authoritative => ServerStatus::NotAuthoritative,
+            Ok(response) if response.answers.iter().any(|(_, answer)| answer == data) => {
+                ServerStatus::Propagated
+            }
+            Ok(_) => ServerStatus::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, UdpSocket},
+    };
+
+    use super::*;
+    use crate::{wire::build_response, Record};
+
+    const TYPE_TXT: u16 = 16;
+
+    struct TestZone {
+        domain: DomainName,
+    }
+
+    impl Zone for TestZone {
+        type CustomRetrieveError = ();
+
+        fn id(&self) -> &str {
+            "test"
+        }
+
+        fn domain(&self) -> &DomainName {
+            &self.domain
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<()>> {
+            Ok(vec![Record {
+                id: "1".to_owned(),
+                host: DomainName::new("www").unwrap(),
+                data: RecordData::A("192.0.2.1".parse().unwrap()),
+                ttl: 3600,
+            }])
+        }
+
+        async fn get_record(&self, _: &str) -> Result<Record, RetrieveRecordError<()>> {
+            Err(RetrieveRecordError::NotFound)
+        }
+    }
+
+    fn zone() -> TestZone {
+        TestZone {
+            domain: DomainName::new("example.com.").unwrap(),
+        }
+    }
+
+    fn record() -> (DomainName, RecordData) {
+        (
+            DomainName::new("_acme-challenge").unwrap(),
+            RecordData::txt("token"),
+        )
+    }
+
+    /// Starts a name server responding over UDP using the given flags, which serves the record from the given query on.
+    ///
+    /// Responses containing the record are truncated over UDP, so they have to be repeated over TCP.
+    async fn start_nameserver(flags: u16, propagated_after: usize) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let listener = TcpListener::bind(address).await.unwrap();
+        let answers = [(
+            DomainName::new("_acme-challenge.example.com.").unwrap(),
+            TYPE_TXT,
+            b"\x05token".to_vec(),
+        )];
+
+        let queries = AtomicUsize::new(0);
+        tokio::spawn(async move {
+            let mut buffer = [0; 512];
+            loop {
+                let (length, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let query = &buffer[..length];
+                let response = if queries.fetch_add(1, Ordering::SeqCst) < propagated_after {
+                    build_response(query, flags, &[])
+                } else {
+                    build_response(query, flags | 0x0200, &[])
+                };
+                socket.send_to(&response, peer).await.unwrap();
+            }
+        });
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut query = vec![0; stream.read_u16().await.unwrap() as usize];
+                stream.read_exact(&mut query).await.unwrap();
+                let response = build_response(&query, flags & !0x000f, &answers);
+                stream.write_u16(response.len() as u16).await.unwrap();
+                stream.write_all(&response).await.unwrap();
+            }
+        });
+
+        address
+    }
+
+    fn statuses(report: &PropagationReport) -> Vec<ServerStatus> {
+        report.servers.iter().map(|server| server.status).collect()
+    }
+
+    #[tokio::test]
+    async fn every_nameserver_is_checked() {
+        // Authoritative NXDOMAIN until the second query.
+        let primary = start_nameserver(0x0403, 1).await;
+        let unauthoritative = start_nameserver(0x0000, 0).await;
+        let failing = start_nameserver(0x0402, usize::MAX).await;
+        let unreachable = {
+            let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            socket.local_addr().unwrap()
+        };
+
+        let (host, data) = record();
+        let checker = PropagationChecker::new()
+            .with_query_timeout(Duration::from_millis(200))
+            .with_nameservers([primary, unauthoritative, failing, unreachable]);
+
+        let report = checker.check(&zone(), &host, &data).await.unwrap();
+        assert_eq!(
+            statuses(&report)[..3],
+            [
+                ServerStatus::Pending,
+                ServerStatus::NotAuthoritative,
+                ServerStatus::Rcode(2),
+            ]
+        );
+        assert!(matches!(
+            report.servers[3].status,
+            ServerStatus::Unreachable(_)
+        ));
+        assert_eq!(report.servers[0].nameserver, None);
+        assert_eq!(report.servers[0].address, Some(primary));
+
+        let report = checker.check(&zone(), &host, &data).await.unwrap();
+        assert_eq!(report.servers[0].status, ServerStatus::Propagated);
+        assert!(!report.is_propagated());
+        assert_eq!(report.pending().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn waiting_polls_until_propagated_or_timed_out() {
+        let (host, data) = record();
+        let checker = PropagationChecker::new()
+            .with_timeout(Duration::from_secs(5))
+            .with_polling_interval(Duration::from_millis(10));
+
+        let propagated = start_nameserver(0x0403, 3).await;
+        let report = checker
+            .clone()
+            .with_nameservers([propagated])
+            .wait(&zone(), &host.to_fqdn(zone().domain()), &data)
+            .await
+            .unwrap();
+        assert!(report.is_propagated());
+
+        let pending = start_nameserver(0x0403, usize::MAX).await;
+        let result = checker
+            .with_timeout(Duration::from_millis(50))
+            .with_nameservers([propagated, pending])
+            .wait(&zone(), &host, &data)
+            .await;
+        let Err(PropagationError::Timeout(report)) = result else {
+            panic!("expected a timeout, got {:?}", result);
+        };
+        assert_eq!(
+            statuses(&report),
+            [ServerStatus::Propagated, ServerStatus::Pending]
+        );
+
+        assert!(matches!(
+            PropagationChecker::new().check(&zone(), &host, &data).await,
+            Err(PropagationError::NoNameservers)
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! Querying name servers directly, bypassing any caching resolvers.
+
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{lookup_host, TcpStream, UdpSocket},
+    time::timeout,
+};
+
+use crate::{
+    wire::{self, Message},
+    DomainName, RecordData,
+};
+
+/// The port name servers listen on.
+pub const DNS_PORT: u16 = 53;
+
+/// The maximum size of responses received over UDP without EDNS (see RFC 1035, section 2.3.4).
+const MAX_UDP_SIZE: usize = 512;
+
+/// Represents the response of a name server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub authoritative: bool,
+    pub rcode: u8,
+    /// The records of the answer section.
+    pub answers: Vec<(DomainName, RecordData)>,
+}
+
+/// Queries a name server for the records of the given name and type.
+///
+/// The query is sent over UDP and repeated over TCP if the response was truncated.
+/// Every attempt has to complete within `duration`.
+pub async fn query(
+    server: SocketAddr,
+    name: &DomainName,
+    typ: &str,
+    duration: Duration,
+) -> io::Result<Response> {
+    let typ = wire::type_code(typ).ok_or(io::ErrorKind::InvalidInput)?;
+    let name = name.to_absolute();
+
+    let id = wire::random_id();
+    let query = wire::build_query(id, &name, typ);
+    let message = timeout(duration, query_udp(server, id, &query))
+        .await
+        .map_err(|_| io::ErrorKind::TimedOut)??;
+
+    let (message, parsed) = match parse(&message, id)? {
+        parsed if parsed.truncated => {
+            let message = timeout(duration, query_tcp(server, &query))
+                .await
+                .map_err(|_| io::ErrorKind::TimedOut)??;
+            let parsed = parse(&message, id)?;
+            (message, parsed)
+        }
+        parsed => (message, parsed),
+    };
+
+    Ok(Response {
+        authoritative: parsed.authoritative,
+        rcode: parsed.rcode,
+        answers: parsed
+            .answers
+            .iter()
+            .filter(|answer| answer.name == name && answer.typ == typ)
+            .map(|answer| {
+                (
+                    answer.name.clone(),
+                    wire::decode_record_data(&message, answer),
+                )
+            })
+            .collect(),
+    })
+}
+
+/// Resolves the addresses of a name server using the system's resolver.
+pub async fn resolve(host: &DomainName) -> io::Result<Vec<SocketAddr>> {
+    let mut addresses = lookup_host((host.as_ascii(), DNS_PORT))
+        .await?
+        .collect::<Vec<_>>();
+    addresses.sort();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+fn parse(message: &[u8], id: u16) -> io::Result<Message> {
+    Message::parse(message)
+        .filter(|parsed| parsed.id == id)
+        .ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
+
+async fn query_udp(server: SocketAddr, id: u16, query: &[u8]) -> io::Result<Vec<u8>> {
+    let local: SocketAddr = match server {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    socket.send(query).await?;
+
+    let mut buffer = [0; MAX_UDP_SIZE];
+    loop {
+        let length = socket.recv(&mut buffer).await?;
+        // Datagrams with other IDs are late responses to earlier queries or spoofing attempts.
+        if length >= 2 && buffer[..2] == id.to_be_bytes() {
+            return Ok(buffer[..length].to_vec());
+        }
+    }
+}
+
+async fn query_tcp(server: SocketAddr, query: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(server).await?;
+    stream.write_u16(query.len() as u16).await?;
+    stream.write_all(query).await?;
+
+    let length = stream.read_u16().await?;
+    let mut message = vec![0; length as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(message)
+}
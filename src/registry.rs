@@ -0,0 +1,531 @@
+//! Constructing providers by name from configuration, e.g. read from a YAML or TOML file.
+//!
+//! A [`Registry`] maps provider names to constructors taking a [`ProviderConfig`] and returning a [`DynProvider`].
+//! [`Registry::new`] contains every provider enabled at compile time, named like its feature flag, e.g. `hetzner`:
+//!
+//! ```toml
+//! provider = "hetzner"
+//! token_env = "HETZNER_TOKEN"
+//! ```
+//!
+//! Providers read their secret from the token and additional settings from the options:
+//!
+//! | Provider       | Token                                | Options                                                                        |
+//! |----------------|--------------------------------------|--------------------------------------------------------------------------------|
+//! | `axfr`         | base64 encoded TSIG secret, optional | `primary` (address), `zones` (comma-separated), `tsig-name`, `tsig-algorithm`  |
+//! | `azure`        | client secret, optional              | `subscription-id`, `resource-group`, `tenant-id`, `client-id`, `authority-url` |
+//! | `desec`        | API token                            |                                                                                |
+//! | `digitalocean` | API token                            |                                                                                |
+//! | `gandi`        | personal access token                |                                                                                |
+//! | `gcloud`       | JSON key of a service account        | `token-url`                                                                    |
+//! | `hetzner`      | API key                              |                                                                                |
+//! | `linode`       | API token                            |                                                                                |
+//! | `ovh`          | application secret                   | `endpoint` (`eu`, `ca` or `us`), `application-key`, `consumer-key`             |
+//! | `porkbun`      | secret API key                       | `api-key`                                                                      |
+//! | `route53`      | secret access key, optional          | `access-key-id`, `session-token`                                               |
+//! | `vultr`        | API key                              |                                                                                |
+//! | `zonefile`     |                                      | `directory`, `locking` (`true` or `false`)                                     |
+//!
+//! Without a token, `azure` and `route53` read their credentials from the usual environment variables.
+//!
+//! This module is only available with the `registry` feature enabled.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error as StdErr,
+    fmt, fs, io,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::dynamic::DynProvider;
+
+/// Represents the configuration of a provider.
+///
+/// At most one of `token`, `token_env` and `token_file` may be set.
+/// The token and the values of options whose name contains `token` or `secret` are redacted in the [`Debug`](fmt::Debug) output.
+#[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderConfig {
+    /// The name of the provider, e.g. `hetzner`.
+    pub provider: String,
+
+    /// The token or secret used to authenticate.
+    pub token: Option<String>,
+
+    /// The name of an environment variable containing the token.
+    pub token_env: Option<String>,
+
+    /// The path of a file containing the token. Leading and trailing whitespace is removed.
+    pub token_file: Option<PathBuf>,
+
+    /// A custom API base URL, e.g. of a mock server.
+    pub base_url: Option<String>,
+
+    /// Provider-specific options.
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+}
+
+impl fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const REDACTED: &str = "<redacted>";
+
+        let options = self
+            .options
+            .iter()
+            .map(
+                |(name, value)| match name.contains("token") || name.contains("secret") {
+                    true => (name, REDACTED),
+                    false => (name, value.as_str()),
+                },
+            )
+            .collect::<BTreeMap<_, _>>();
+
+        f.debug_struct("ProviderConfig")
+            .field("provider", &self.provider)
+            .field("token", &self.token.as_ref().map(|_| REDACTED))
+            .field("token_env", &self.token_env)
+            .field("token_file", &self.token_file)
+            .field("base_url", &self.base_url)
+            .field("options", &options)
+            .finish()
+    }
+}
+
+/// Represents an error that occured when constructing a provider from its configuration.
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    /// Indicates that no provider with the name is registered, e.g. because its feature is not enabled.
+    #[error("unknown provider {0}")]
+    UnknownProvider(String),
+
+    /// Indicates that more than one of `token`, `token_env` and `token_file` is set.
+    #[error("only one of token, token_env and token_file may be set")]
+    AmbiguousToken,
+
+    /// Indicates that the provider requires a token, but none is configured.
+    #[error("the provider requires a token")]
+    MissingToken,
+
+    /// Indicates that the environment variable given by `token_env` is not set.
+    #[error("the environment variable {0} is not set")]
+    TokenEnv(String),
+
+    /// Indicates that the file given by `token_file` could not be read.
+    #[error("failed to read the token file")]
+    TokenFile(#[source] io::Error),
+
+    /// Indicates that the provider requires an option which is not configured.
+    #[error("the provider requires the option {0}")]
+    MissingOption(String),
+
+    /// Indicates that an option has an invalid value.
+    #[error("invalid value {value} for the option {key}")]
+    InvalidOption { key: String, value: String },
+
+    /// Indicates that a base URL is configured, but the provider does not support custom base URLs.
+    #[error("the provider does not support a custom base URL")]
+    UnsupportedBaseUrl,
+
+    /// Indicates that the provider could not be created, e.g. because of an invalid key.
+    #[error("failed to create the provider: {0}")]
+    Provider(Box<dyn StdErr>),
+}
+
+impl ProviderConfig {
+    /// Creates an empty configuration for the provider with the given name.
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the token, reading it from the environment or a file if configured, or [`None`] if no token is configured.
+    pub fn token(&self) -> Result<Option<String>, RegistryError> {
+        match (&self.token, &self.token_env, &self.token_file) {
+            (Some(token), None, None) => Ok(Some(token.clone())),
+            (None, Some(name), None) => env::var(name)
+                .map(Some)
+                .map_err(|_| RegistryError::TokenEnv(name.clone())),
+            (None, None, Some(path)) => fs::read_to_string(path)
+                .map(|token| Some(token.trim().to_owned()))
+                .map_err(RegistryError::TokenFile),
+            (None, None, None) => Ok(None),
+            _ => Err(RegistryError::AmbiguousToken),
+        }
+    }
+
+    /// Returns the token, failing with [`RegistryError::MissingToken`] if no token is configured.
+    pub fn required_token(&self) -> Result<String, RegistryError> {
+        self.token()?.ok_or(RegistryError::MissingToken)
+    }
+
+    /// Returns the value of an option, failing with [`RegistryError::MissingOption`] if it is not configured.
+    pub fn option(&self, key: &str) -> Result<&str, RegistryError> {
+        self.optional(key)
+            .ok_or_else(|| RegistryError::MissingOption(key.to_owned()))
+    }
+
+    /// Returns the value of an option, or [`None`] if it is not configured.
+    pub fn optional(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    /// Fails with [`RegistryError::UnsupportedBaseUrl`] if a base URL is configured.
+    pub fn reject_base_url(&self) -> Result<(), RegistryError> {
+        match self.base_url {
+            Some(_) => Err(RegistryError::UnsupportedBaseUrl),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a [`RegistryError::InvalidOption`] for the configured value of an option.
+    pub fn invalid_option(&self, key: &str) -> RegistryError {
+        RegistryError::InvalidOption {
+            key: key.to_owned(),
+            value: self.optional(key).unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+/// Constructs a provider from its configuration.
+pub type Constructor = fn(&ProviderConfig) -> Result<DynProvider, RegistryError>;
+
+/// Maps provider names to their constructors.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    constructors: BTreeMap<String, Constructor>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    /// Creates a registry containing every provider enabled at compile time.
+    pub fn new() -> Self {
+        let providers: &[(&str, Constructor)] = &[
+            #[cfg(feature = "axfr")]
+            ("axfr", axfr),
+            #[cfg(feature = "azure")]
+            ("azure", azure),
+            #[cfg(feature = "desec")]
+            ("desec", desec),
+            #[cfg(feature = "digitalocean")]
+            ("digitalocean", digitalocean),
+            #[cfg(feature = "gandi")]
+            ("gandi", gandi),
+            #[cfg(feature = "gcloud")]
+            ("gcloud", gcloud),
+            #[cfg(feature = "hetzner")]
+            ("hetzner", hetzner),
+            #[cfg(feature = "linode")]
+            ("linode", linode),
+            #[cfg(feature = "ovh")]
+            ("ovh", ovh),
+            #[cfg(feature = "porkbun")]
+            ("porkbun", porkbun),
+            #[cfg(feature = "route53")]
+            ("route53", route53),
+            #[cfg(feature = "vultr")]
+            ("vultr", vultr),
+            #[cfg(feature = "zonefile")]
+            ("zonefile", zonefile),
+        ];
+
+        Self {
+            constructors: providers
+                .iter()
+                .map(|(name, constructor)| (name.to_string(), *constructor))
+                .collect(),
+        }
+    }
+
+    /// Creates a registry without any providers.
+    pub fn empty() -> Self {
+        Self {
+            constructors: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a provider, e.g. an implementation outside of this crate, replacing any provider of the same name.
+    pub fn with_provider(mut self, name: &str, constructor: Constructor) -> Self {
+        self.constructors.insert(name.to_owned(), constructor);
+        self
+    }
+
+    /// Returns the names of the registered providers in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    /// Creates the provider named by the configuration.
+    pub fn create(&self, config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+        let constructor = self
+            .constructors
+            .get(&config.provider)
+            .ok_or_else(|| RegistryError::UnknownProvider(config.provider.clone()))?;
+        constructor(config)
+    }
+}
+
+/// Calls `with_base_url` if a base URL is configured and `new` otherwise.
+#[allow(unused_macros)]
+macro_rules! token_provider {
+    ($config:expr, $provider:ty) => {{
+        let token = $config.required_token()?;
+        match &$config.base_url {
+            Some(base_url) => <$provider>::with_base_url(&token, base_url),
+            None => <$provider>::new(&token),
+        }
+        .map_err(RegistryError::Provider)?
+    }};
+}
+
+#[cfg(feature = "axfr")]
+fn axfr(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use std::net::{IpAddr, SocketAddr};
+
+    use crate::{
+        axfr::{AxfrProvider, TsigAlgorithm, TsigKey},
+        DomainName,
+    };
+
+    config.reject_base_url()?;
+    let primary = config.option("primary")?;
+    let primary = match primary.parse::<IpAddr>() {
+        Ok(address) => SocketAddr::new(address, 53),
+        Err(_) => primary
+            .parse::<SocketAddr>()
+            .map_err(|_| config.invalid_option("primary"))?,
+    };
+
+    let provider = match config.token()? {
+        Some(secret) => {
+            let algorithm = match config.optional("tsig-algorithm").unwrap_or("hmac-sha256") {
+                "hmac-sha1" => TsigAlgorithm::HmacSha1,
+                "hmac-sha256" => TsigAlgorithm::HmacSha256,
+                "hmac-sha384" => TsigAlgorithm::HmacSha384,
+                "hmac-sha512" => TsigAlgorithm::HmacSha512,
+                _ => return Err(config.invalid_option("tsig-algorithm")),
+            };
+            let name = config.option("tsig-name")?;
+            let name = DomainName::new(name).map_err(|_| config.invalid_option("tsig-name"))?;
+            let key = TsigKey::from_base64(name, algorithm, &secret)
+                .map_err(|err| RegistryError::Provider(err.into()))?;
+            AxfrProvider::with_tsig(primary, key)
+        }
+        None => AxfrProvider::new(primary),
+    };
+
+    let zones = match config.optional("zones") {
+        Some(zones) => zones
+            .split(',')
+            .map(|zone| DomainName::new(zone.trim()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| config.invalid_option("zones"))?,
+        None => Vec::new(),
+    };
+    Ok(DynProvider::read_only(provider.with_zones(zones)))
+}
+
+#[cfg(feature = "azure")]
+fn azure(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::azure::{AzureProvider, ClientCredentials};
+
+    const AUTHORITY_URL: &str = "https://login.microsoftonline.com";
+
+    let credentials = match config.token()? {
+        Some(secret) => ClientCredentials::new(
+            config.option("tenant-id")?,
+            config.option("client-id")?,
+            &secret,
+        ),
+        None => ClientCredentials::from_env().ok_or(RegistryError::MissingToken)?,
+    };
+    let subscription_id = config.option("subscription-id")?;
+    let resource_group = config.option("resource-group")?;
+
+    let provider = match &config.base_url {
+        Some(base_url) => AzureProvider::with_endpoints(
+            credentials,
+            subscription_id,
+            resource_group,
+            config.optional("authority-url").unwrap_or(AUTHORITY_URL),
+            base_url,
+        ),
+        None => AzureProvider::new(credentials, subscription_id, resource_group),
+    }
+    .map_err(RegistryError::Provider)?;
+    Ok(DynProvider::new(provider))
+}
+
+#[cfg(feature = "desec")]
+fn desec(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::new(token_provider!(
+        config,
+        crate::desec::DesecProvider
+    )))
+}
+
+#[cfg(feature = "digitalocean")]
+fn digitalocean(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::new(token_provider!(
+        config,
+        crate::digitalocean::DigitalOceanProvider
+    )))
+}
+
+#[cfg(feature = "gandi")]
+fn gandi(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::without_zone_management(token_provider!(
+        config,
+        crate::gandi::GandiProvider
+    )))
+}
+
+#[cfg(feature = "gcloud")]
+fn gcloud(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::gcloud::{CloudDnsProvider, ServiceAccountKey};
+
+    const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+    let key = ServiceAccountKey::from_json(&config.required_token()?)
+        .map_err(|err| RegistryError::Provider(err.into()))?;
+    let provider = match &config.base_url {
+        Some(base_url) => {
+            let token_url = match config.optional("token-url") {
+                Some(token_url) => token_url.to_owned(),
+                None => key
+                    .token_uri
+                    .clone()
+                    .unwrap_or_else(|| TOKEN_URL.to_owned()),
+            };
+            CloudDnsProvider::with_endpoints(key, base_url, &token_url)
+        }
+        None => CloudDnsProvider::new(key),
+    }
+    .map_err(RegistryError::Provider)?;
+    Ok(DynProvider::new(provider))
+}
+
+#[cfg(feature = "hetzner")]
+fn hetzner(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::new(token_provider!(
+        config,
+        crate::hetzner::HetznerProvider
+    )))
+}
+
+#[cfg(feature = "linode")]
+fn linode(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::new(token_provider!(
+        config,
+        crate::linode::LinodeProvider
+    )))
+}
+
+#[cfg(feature = "ovh")]
+fn ovh(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::ovh::{Credentials, Endpoint, OvhProvider};
+
+    let endpoint = match (&config.base_url, config.optional("endpoint")) {
+        (Some(base_url), _) => Endpoint::Custom(base_url.clone()),
+        (None, None | Some("eu")) => Endpoint::Eu,
+        (None, Some("ca")) => Endpoint::Ca,
+        (None, Some("us")) => Endpoint::Us,
+        (None, Some(_)) => return Err(config.invalid_option("endpoint")),
+    };
+    let credentials = Credentials::new(
+        config.option("application-key")?,
+        &config.required_token()?,
+        config.option("consumer-key")?,
+    );
+    let provider = OvhProvider::new(endpoint, credentials).map_err(RegistryError::Provider)?;
+    Ok(DynProvider::without_zone_management(provider))
+}
+
+#[cfg(feature = "porkbun")]
+fn porkbun(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::porkbun::PorkbunProvider;
+
+    let api_key = config.option("api-key")?;
+    let secret_api_key = config.required_token()?;
+    let provider = match &config.base_url {
+        Some(base_url) => PorkbunProvider::with_base_url(api_key, &secret_api_key, base_url),
+        None => PorkbunProvider::new(api_key, &secret_api_key),
+    }
+    .map_err(RegistryError::Provider)?;
+    Ok(DynProvider::without_zone_management(provider))
+}
+
+#[cfg(feature = "route53")]
+fn route53(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::route53::{Credentials, Route53Provider};
+
+    let credentials = match config.token()? {
+        Some(secret) => Credentials {
+            session_token: config.optional("session-token").map(str::to_owned),
+            ..Credentials::new(config.option("access-key-id")?, &secret)
+        },
+        None => Credentials::from_env().ok_or(RegistryError::MissingToken)?,
+    };
+    let provider = match &config.base_url {
+        Some(base_url) => Route53Provider::with_endpoint(credentials, base_url),
+        None => Route53Provider::new(credentials),
+    }
+    .map_err(RegistryError::Provider)?;
+    Ok(DynProvider::new(provider))
+}
+
+#[cfg(feature = "vultr")]
+fn vultr(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    Ok(DynProvider::new(token_provider!(
+        config,
+        crate::vultr::VultrProvider
+    )))
+}
+
+#[cfg(feature = "zonefile")]
+fn zonefile(config: &ProviderConfig) -> Result<DynProvider, RegistryError> {
+    use crate::zonefile::ZoneFileProvider;
+
+    config.reject_base_url()?;
+    let directory = config.option("directory")?;
+    let provider = match config.optional("locking") {
+        None | Some("false") => ZoneFileProvider::new(directory),
+        Some("true") => ZoneFileProvider::with_file_locking(directory),
+        Some(_) => return Err(config.invalid_option("locking")),
+    };
+    Ok(DynProvider::new(provider))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let config = ProviderConfig {
+            provider: "route53".to_owned(),
+            token: Some("secret-access-key".to_owned()),
+            options: HashMap::from([
+                ("access-key-id".to_owned(), "AKIDEXAMPLE".to_owned()),
+                ("session-token".to_owned(), "session-token-value".to_owned()),
+            ]),
+            ..Default::default()
+        };
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("AKIDEXAMPLE"));
+        assert!(!debug.contains("secret-access-key"));
+        assert!(!debug.contains("session-token-value"));
+    }
+}
@@ -0,0 +1,475 @@
+//! Helpers for reverse DNS (`PTR` records).
+//!
+//! Reverse DNS maps IP addresses to host names using special names below `in-addr.arpa` (IPv4) and `ip6.arpa` (IPv6).
+//! This module converts addresses into these names, determines the reverse zones responsible for a network prefix
+//! (including [RFC 2317](https://www.rfc-editor.org/rfc/rfc2317) classless delegation) and provides helpers to manage
+//! the `PTR` record of an address using any [`Provider`].
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use thiserror::Error;
+
+use crate::{
+    CreateRecord, CreateRecordError, DeleteRecord, DeleteRecordError, DomainName, Provider, Record,
+    RecordData, RetrieveRecordError, RetrieveZoneError, Zone,
+};
+
+const IPV4_REVERSE_DOMAIN: &str = "in-addr.arpa";
+const IPV6_REVERSE_DOMAIN: &str = "ip6.arpa";
+
+/// Returns the `in-addr.arpa` name of an IPv4 address, e.g. `4.3.2.1.in-addr.arpa.` for `1.2.3.4`.
+pub fn ipv4_ptr_name(addr: Ipv4Addr) -> DomainName {
+    let [a, b, c, d] = addr.octets();
+    DomainName::from_raw(&format!("{}.{}.{}.{}.{}.", d, c, b, a, IPV4_REVERSE_DOMAIN))
+}
+
+/// Returns the `ip6.arpa` name of an IPv6 address, consisting of all 32 nibbles in reverse order.
+pub fn ipv6_ptr_name(addr: Ipv6Addr) -> DomainName {
+    let mut name = String::with_capacity(72);
+    for nibble in ipv6_nibbles(addr).iter().rev() {
+        name.push(char::from_digit(*nibble as u32, 16).unwrap());
+        name.push('.');
+    }
+    name.push_str(IPV6_REVERSE_DOMAIN);
+    name.push('.');
+    DomainName::from_raw(&name)
+}
+
+/// Returns the reverse DNS name of an IP address.
+///
+/// See [`ipv4_ptr_name`] and [`ipv6_ptr_name`].
+pub fn ptr_name(addr: IpAddr) -> DomainName {
+    match addr {
+        IpAddr::V4(addr) => ipv4_ptr_name(addr),
+        IpAddr::V6(addr) => ipv6_ptr_name(addr),
+    }
+}
+
+/// Represents an error that occured when determining the reverse zones of a network prefix.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Error)]
+pub enum ReverseZoneError {
+    /// Indicates that the prefix length exceeds the length of the address.
+    #[error("the prefix length {0} is out of range for the address family")]
+    InvalidPrefixLength(u8),
+
+    /// Indicates that the prefix does not fall on a label boundary and would be covered by multiple zones.
+    /// Use [`reverse_zones`] to retrieve all of them.
+    #[error("the prefix is covered by multiple reverse zones")]
+    MultipleZones,
+}
+
+/// Returns the reverse zone responsible for the network `addr/prefix_len`.
+///
+/// IPv4 prefixes between `/25` and `/31` result in an [RFC 2317](https://www.rfc-editor.org/rfc/rfc2317) classless zone
+/// of the form `<first octet>/<prefix length>.<c>.<b>.<a>.in-addr.arpa.`, e.g. `64/26.2.0.192.in-addr.arpa.` for `192.0.2.64/26`.
+/// Prefixes which are not aligned to a label boundary (octets for IPv4, nibbles for IPv6) would be covered by multiple zones
+/// and result in [`ReverseZoneError::MultipleZones`].
+pub fn reverse_zone(addr: IpAddr, prefix_len: u8) -> Result<DomainName, ReverseZoneError> {
+    let mut zones = reverse_zones(addr, prefix_len)?;
+    if zones.len() != 1 {
+        return Err(ReverseZoneError::MultipleZones);
+    }
+    Ok(zones.remove(0))
+}
+
+/// Returns all reverse zones required to cover the network `addr/prefix_len`.
+///
+/// Prefixes aligned to a label boundary and IPv4 prefixes between `/25` and `/31` result in a single zone (see [`reverse_zone`]).
+/// Other prefixes are expanded to the next label boundary, e.g. `10.0.0.0/22` results in the four zones from `0.0.10.in-addr.arpa.`
+/// to `3.0.10.in-addr.arpa.`.
+pub fn reverse_zones(addr: IpAddr, prefix_len: u8) -> Result<Vec<DomainName>, ReverseZoneError> {
+    match addr {
+        IpAddr::V4(addr) => ipv4_reverse_zones(addr, prefix_len),
+        IpAddr::V6(addr) => ipv6_reverse_zones(addr, prefix_len),
+    }
+}
+
+fn ipv4_reverse_zones(addr: Ipv4Addr, prefix_len: u8) -> Result<Vec<DomainName>, ReverseZoneError> {
+    if prefix_len > 32 {
+        return Err(ReverseZoneError::InvalidPrefixLength(prefix_len));
+    }
+
+    let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+    let network = u32::from(addr) & mask;
+
+    if (25..32).contains(&prefix_len) {
+        let [a, b, c, d] = network.to_be_bytes();
+        return Ok(vec![DomainName::from_raw(&format!(
+            "{}/{}.{}.{}.{}.{}.",
+            d, prefix_len, c, b, a, IPV4_REVERSE_DOMAIN
+        ))]);
+    }
+
+    let labels = prefix_len.div_ceil(8) as usize;
+    let count = 1u32 << (labels as u8 * 8 - prefix_len);
+
+    Ok((0..count)
+        .map(|i| {
+            let offset = i.checked_shl((32 - labels * 8) as u32).unwrap_or(0);
+            let octets = (network | offset).to_be_bytes();
+            reverse_domain(
+                octets[..labels].iter().map(u8::to_string),
+                IPV4_REVERSE_DOMAIN,
+            )
+        })
+        .collect())
+}
+
+fn ipv6_reverse_zones(addr: Ipv6Addr, prefix_len: u8) -> Result<Vec<DomainName>, ReverseZoneError> {
+    if prefix_len > 128 {
+        return Err(ReverseZoneError::InvalidPrefixLength(prefix_len));
+    }
+
+    let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+    let network = u128::from(addr) & mask;
+
+    let labels = prefix_len.div_ceil(4) as usize;
+    let count = 1u128 << (labels as u8 * 4 - prefix_len);
+
+    Ok((0..count)
+        .map(|i| {
+            let offset = i.checked_shl((128 - labels * 4) as u32).unwrap_or(0);
+            let nibbles = ipv6_nibbles(Ipv6Addr::from(network | offset));
+            reverse_domain(
+                nibbles[..labels]
+                    .iter()
+                    .map(|nibble| format!("{:x}", nibble)),
+                IPV6_REVERSE_DOMAIN,
+            )
+        })
+        .collect())
+}
+
+fn ipv6_nibbles(addr: Ipv6Addr) -> [u8; 32] {
+    let mut nibbles = [0; 32];
+    for (i, octet) in addr.octets().iter().enumerate() {
+        nibbles[i * 2] = octet >> 4;
+        nibbles[i * 2 + 1] = octet & 0x0f;
+    }
+    nibbles
+}
+
+fn reverse_domain(labels: impl DoubleEndedIterator<Item = String>, suffix: &str) -> DomainName {
+    let name = labels
+        .rev()
+        .chain([suffix.to_owned(), String::new()])
+        .collect::<Vec<String>>()
+        .join(".");
+    DomainName::from_raw(&name)
+}
+
+/// Returns the host of the `PTR` record of `addr` relative to the reverse zone `zone_domain`.
+///
+/// [`DomainName::apex`] is returned if the reverse name equals the zone's domain.
+/// [`None`] is returned if the zone is not responsible for the address.
+/// [RFC 2317](https://www.rfc-editor.org/rfc/rfc2317) classless zones are supported in both the `<first>/<prefix length>` and
+/// the `<first>-<last>` notation.
+pub fn ptr_host(addr: IpAddr, zone_domain: &DomainName) -> Option<DomainName> {
+    let zone = zone_domain.as_ascii().to_ascii_lowercase();
+    let name = ptr_name(addr);
+    let name = name.as_ascii();
+
+    if name == zone {
+        return Some(DomainName::apex());
+    }
+    if let Some(host) = name.strip_suffix(&zone).and_then(|h| h.strip_suffix('.')) {
+        return Some(DomainName::from_raw(host));
+    }
+
+    let IpAddr::V4(addr) = addr else {
+        return None;
+    };
+
+    let (range, parent) = zone.split_once('.')?;
+    let (first, second) = range.split_once(['/', '-'])?;
+    let first = first.parse::<u8>().ok()?;
+    let second = second.parse::<u8>().ok()?;
+
+    let [a, b, c, d] = addr.octets();
+    if parent != format!("{}.{}.{}.{}", c, b, a, IPV4_REVERSE_DOMAIN) {
+        return None;
+    }
+
+    let last = if range.contains('/') {
+        if !(24..=32).contains(&second) {
+            return None;
+        }
+        first.saturating_add(((1u16 << (32 - second)) - 1) as u8)
+    } else {
+        second
+    };
+
+    (first..=last)
+        .contains(&d)
+        .then(|| DomainName::from_raw(&d.to_string()))
+}
+
+/// Searches the zones of a provider for the most specific reverse zone responsible for `addr`.
+///
+/// The zone with the longest domain is the most specific one. An RFC 2317 classless zone is preferred over
+/// a zone of the same length, as the classless zone is delegated from its parent zone.
+/// Returns the zone together with the host of the address' `PTR` record relative to it (see [`ptr_host`]).
+pub async fn find_reverse_zone<P: Provider>(
+    provider: &P,
+    addr: IpAddr,
+) -> Result<Option<(P::Zone, DomainName)>, RetrieveZoneError<P::CustomRetrieveError>> {
+    let zones = provider.list_zones().await?;
+
+    Ok(zones
+        .into_iter()
+        .filter_map(|zone| ptr_host(addr, zone.domain()).map(|host| (zone, host)))
+        .max_by_key(|(zone, _)| {
+            let domain = zone.domain();
+            let classless = domain
+                .labels()
+                .next()
+                .is_some_and(|label| label.contains(['/', '-']));
+            (domain.label_count(), classless)
+        }))
+}
+
+/// Represents an error that occured when managing `PTR` records using [`create_ptr`] or [`delete_ptr`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Error)]
+pub enum PtrError<Z, R, M> {
+    /// Indicates that none of the provider's zones is responsible for the address.
+    #[error("no reverse zone is responsible for the address")]
+    NoReverseZone,
+
+    /// Indicates that the provider's zones could not be retrieved.
+    #[error("the reverse zone could not be retrieved")]
+    RetrieveZone(RetrieveZoneError<Z>),
+
+    /// Indicates that the existing records of the reverse zone could not be retrieved.
+    #[error("the records of the reverse zone could not be retrieved")]
+    RetrieveRecord(RetrieveRecordError<R>),
+
+    /// Indicates that the `PTR` record could not be created.
+    #[error("the PTR record could not be created")]
+    Create(CreateRecordError<M>),
+
+    /// Indicates that a `PTR` record could not be deleted.
+    #[error("the PTR record could not be deleted")]
+    Delete(DeleteRecordError<M>),
+}
+
+/// Creates a `PTR` record pointing `addr` to `target` in the responsible reverse zone of the provider.
+///
+/// The zone is determined using [`find_reverse_zone`].
+pub async fn create_ptr<P>(
+    provider: &P,
+    addr: IpAddr,
+    target: &DomainName,
+    ttl: u64,
+) -> Result<
+    Record,
+    PtrError<
+        P::CustomRetrieveError,
+        <P::Zone as Zone>::CustomRetrieveError,
+        <P::Zone as CreateRecord>::CustomCreateError,
+    >,
+>
+where
+    P: Provider,
+    P::Zone: CreateRecord,
+{
+    let (zone, host) = find_reverse_zone(provider, addr)
+        .await
+        .map_err(PtrError::RetrieveZone)?
+        .ok_or(PtrError::NoReverseZone)?;
+
+    zone.create_record(&host, &RecordData::PTR(target.clone()), ttl)
+        .await
+        .map_err(PtrError::Create)
+}
+
+/// Deletes the `PTR` records of `addr` in the responsible reverse zone of the provider.
+///
+/// If `target` is given, only records pointing to it are deleted.
+/// Returns the deleted records.
+pub async fn delete_ptr<P>(
+    provider: &P,
+    addr: IpAddr,
+    target: Option<&DomainName>,
+) -> Result<
+    Vec<Record>,
+    PtrError<
+        P::CustomRetrieveError,
+        <P::Zone as Zone>::CustomRetrieveError,
+        <P::Zone as DeleteRecord>::CustomDeleteError,
+    >,
+>
+where
+    P: Provider,
+    P::Zone: DeleteRecord,
+{
+    let (zone, host) = find_reverse_zone(provider, addr)
+        .await
+        .map_err(PtrError::RetrieveZone)?
+        .ok_or(PtrError::NoReverseZone)?;
+
+    let records = zone
+        .list_records()
+        .await
+        .map_err(PtrError::RetrieveRecord)?
+        .into_iter()
+        .filter(|record| record.host == host)
+        .filter(|record| match &record.data {
+            RecordData::PTR(value) => {
+                target.is_none_or(|target| value.to_absolute() == target.to_absolute())
+            }
+            _ => false,
+        })
+        .collect::<Vec<Record>>();
+
+    for record in &records {
+        zone.delete_record(&record.id)
+            .await
+            .map_err(PtrError::Delete)?;
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new(name).unwrap()
+    }
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn ptr_names() {
+        assert_eq!(ptr_name(ip("192.0.2.1")), name("1.2.0.192.in-addr.arpa."));
+        assert_eq!(
+            ptr_name(ip("2001:db8::567:89ab")),
+            name("b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa.")
+        );
+    }
+
+    #[test]
+    fn reverse_zones_of_prefixes() {
+        assert_eq!(
+            reverse_zones(ip("10.0.1.0"), 22).unwrap(),
+            vec![
+                name("0.0.10.in-addr.arpa."),
+                name("1.0.10.in-addr.arpa."),
+                name("2.0.10.in-addr.arpa."),
+                name("3.0.10.in-addr.arpa."),
+            ]
+        );
+        assert_eq!(
+            reverse_zones(ip("192.0.2.100"), 26).unwrap(),
+            vec![name("64/26.2.0.192.in-addr.arpa.")]
+        );
+        assert_eq!(
+            reverse_zones(ip("2001:db8:0:4::"), 62).unwrap(),
+            vec![
+                name("4.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."),
+                name("5.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."),
+                name("6.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."),
+                name("7.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."),
+            ]
+        );
+        assert_eq!(
+            reverse_zone(ip("10.0.0.0"), 22),
+            Err(ReverseZoneError::MultipleZones)
+        );
+        assert_eq!(
+            reverse_zones(ip("10.0.0.0"), 33),
+            Err(ReverseZoneError::InvalidPrefixLength(33))
+        );
+    }
+
+    #[test]
+    fn ptr_hosts() {
+        let addr = ip("192.0.2.100");
+        assert_eq!(
+            ptr_host(addr, &name("2.0.192.in-addr.arpa.")),
+            Some(name("100"))
+        );
+        assert_eq!(
+            ptr_host(addr, &name("100.2.0.192.in-addr.arpa.")),
+            Some(DomainName::apex())
+        );
+        assert_eq!(
+            ptr_host(addr, &name("64/26.2.0.192.in-addr.arpa.")),
+            Some(name("100"))
+        );
+        assert_eq!(
+            ptr_host(addr, &name("64-127.2.0.192.in-addr.arpa.")),
+            Some(name("100"))
+        );
+        assert_eq!(ptr_host(addr, &name("0/26.2.0.192.in-addr.arpa.")), None);
+        assert_eq!(ptr_host(addr, &name("3.0.192.in-addr.arpa.")), None);
+        assert_eq!(
+            ptr_host(ip("2001:db8::1"), &name("8.b.d.0.1.0.0.2.ip6.arpa.")),
+            Some(name("1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0"))
+        );
+    }
+
+    struct TestProvider(Vec<&'static str>);
+
+    struct TestZone(DomainName);
+
+    impl Provider for TestProvider {
+        type Zone = TestZone;
+        type CustomRetrieveError = ();
+
+        async fn list_zones(&self) -> Result<Vec<Self::Zone>, RetrieveZoneError<()>> {
+            Ok(self.0.iter().map(|zone| TestZone(name(zone))).collect())
+        }
+
+        async fn get_zone(&self, _: &str) -> Result<Self::Zone, RetrieveZoneError<()>> {
+            Err(RetrieveZoneError::NotFound)
+        }
+    }
+
+    impl Zone for TestZone {
+        type CustomRetrieveError = ();
+
+        fn id(&self) -> &str {
+            self.0.as_ascii()
+        }
+
+        fn domain(&self) -> &DomainName {
+            &self.0
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>, RetrieveRecordError<()>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_record(&self, _: &str) -> Result<Record, RetrieveRecordError<()>> {
+            Err(RetrieveRecordError::NotFound)
+        }
+    }
+
+    #[tokio::test]
+    async fn find_most_specific_reverse_zone() {
+        for zones in [
+            vec!["2.0.192.in-addr.arpa.", "64/26.2.0.192.in-addr.arpa."],
+            vec!["64/26.2.0.192.in-addr.arpa.", "2.0.192.in-addr.arpa."],
+        ] {
+            let provider = TestProvider(zones);
+            let (zone, host) = find_reverse_zone(&provider, ip("192.0.2.100"))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(zone.domain(), &name("64/26.2.0.192.in-addr.arpa."));
+            assert_eq!(host, name("100"));
+        }
+
+        let provider = TestProvider(vec!["in-addr.arpa.", "0.192.in-addr.arpa."]);
+        let (zone, host) = find_reverse_zone(&provider, ip("192.0.2.100"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(zone.domain(), &name("0.192.in-addr.arpa."));
+        assert_eq!(host, name("100.2"));
+    }
+}
@@ -0,0 +1,435 @@
+use std::{error::Error as StdErr, fmt::Write, time::SystemTime};
+
+use quick_xml::escape::escape;
+use reqwest::{Client as HttpClient, Method, StatusCode, Url};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::sigv4::{self, Signer};
+use crate::http::StatusError;
+#[cfg(feature = "retry")]
+use crate::{http::is_transient_status, layer::Transient};
+
+pub const ROUTE53_ENDPOINT: &str = "https://route53.amazonaws.com";
+pub const ROUTE53_REGION: &str = "us-east-1";
+
+const API_VERSION: &str = "2013-04-01";
+const XML_NAMESPACE: &str = "https://route53.amazonaws.com/doc/2013-04-01/";
+
+/// Represents an error returned by the Route 53 API client.
+#[derive(Debug, Error)]
+pub enum Route53Error {
+    /// Indicates that the HTTP request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// Indicates that Route 53 responded with an error.
+    #[error("Route 53 responded with {status} ({code}): {message}")]
+    Api {
+        status: StatusCode,
+        code: String,
+        message: String,
+    },
+
+    /// Indicates that a response could not be parsed.
+    #[error(transparent)]
+    Xml(#[from] quick_xml::DeError),
+}
+
+impl Route53Error {
+    /// Returns the HTTP status code of an API error.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Route53Error::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the Route 53 error code of an API error, e.g. `NoSuchHostedZone`.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Route53Error::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl StatusError for Route53Error {
+    fn status(&self) -> Option<StatusCode> {
+        Route53Error::status(self)
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Transient for Route53Error {
+    /// Route 53 responds with `400 Bad Request` when throttling, so the error code is checked as well.
+    fn is_transient(&self) -> bool {
+        match self {
+            Route53Error::Http(err) => err.is_transient(),
+            Route53Error::Api { status, code, .. } => {
+                is_transient_status(*status)
+                    || matches!(code.as_str(), "Throttling" | "PriorRequestNotComplete")
+            }
+            Route53Error::Xml(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    endpoint: Url,
+    signer: Signer,
+}
+
+impl Client {
+    pub fn new(endpoint: &str, signer: Signer) -> Result<Self, Box<dyn StdErr>> {
+        let endpoint = Url::parse(endpoint)?;
+        if endpoint.host_str().is_none() {
+            return Err("the Route 53 endpoint has no host".into());
+        }
+
+        let http_client = HttpClient::builder().build()?;
+        Ok(Self {
+            http_client,
+            endpoint,
+            signer,
+        })
+    }
+
+    async fn request<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<T, Route53Error> {
+        let path = format!("/{}{}", API_VERSION, path);
+        let query = query
+            .iter()
+            .map(|(key, value)| (sigv4::encode(key), sigv4::encode(value)))
+            .collect::<Vec<(String, String)>>();
+
+        let mut host = self.endpoint.host_str().unwrap_or_default().to_owned();
+        if let Some(port) = self.endpoint.port() {
+            write!(host, ":{}", port).unwrap();
+        }
+
+        let payload = body.unwrap_or_default();
+        let headers = self.signer.sign(
+            method.as_str(),
+            &host,
+            &path,
+            &query,
+            payload.as_bytes(),
+            SystemTime::now(),
+        );
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+        if !query.is_empty() {
+            let query = query
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<String>>()
+                .join("&");
+            url.set_query(Some(&query));
+        }
+
+        let mut request = self.http_client.request(method, url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if !payload.is_empty() {
+            request = request
+                .header("content-type", "application/xml")
+                .body(payload);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+
+        if !status.is_success() {
+            let error = quick_xml::de::from_str::<ErrorResponse>(&text)
+                .map(|response| response.error)
+                .unwrap_or_else(|_| ApiError {
+                    code: status.as_str().to_owned(),
+                    message: text,
+                });
+            return Err(Route53Error::Api {
+                status,
+                code: error.code,
+                message: error.message,
+            });
+        }
+
+        Ok(quick_xml::de::from_str(&text)?)
+    }
+
+    pub async fn list_hosted_zones(
+        &self,
+        marker: Option<&str>,
+        max_items: u32,
+    ) -> Result<ListHostedZonesResponse, Route53Error> {
+        let max_items = max_items.to_string();
+        let mut query = vec![("maxitems", max_items.as_str())];
+        if let Some(marker) = marker {
+            query.push(("marker", marker));
+        }
+
+        self.request(Method::GET, "/hostedzone", &query, None).await
+    }
+
+    pub async fn get_hosted_zone(&self, zone_id: &str) -> Result<HostedZoneResponse, Route53Error> {
+        self.request(Method::GET, &format!("/hostedzone/{}", zone_id), &[], None)
+            .await
+    }
+
+    pub async fn create_hosted_zone(
+        &self,
+        domain: &str,
+        caller_reference: &str,
+    ) -> Result<HostedZoneResponse, Route53Error> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><CreateHostedZoneRequest xmlns="{}"><Name>{}</Name><CallerReference>{}</CallerReference></CreateHostedZoneRequest>"#,
+            XML_NAMESPACE,
+            escape(domain),
+            escape(caller_reference)
+        );
+
+        self.request(Method::POST, "/hostedzone", &[], Some(body))
+            .await
+    }
+
+    pub async fn delete_hosted_zone(&self, zone_id: &str) -> Result<(), Route53Error> {
+        self.request::<ChangeInfoResponse>(
+            Method::DELETE,
+            &format!("/hostedzone/{}", zone_id),
+            &[],
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn list_resource_record_sets(
+        &self,
+        zone_id: &str,
+        start: Option<&RecordSetPosition>,
+        max_items: u32,
+    ) -> Result<ListResourceRecordSetsResponse, Route53Error> {
+        let max_items = max_items.to_string();
+        let mut query = vec![("maxitems", max_items.as_str())];
+        if let Some(start) = start {
+            query.push(("name", &start.name));
+            if let Some(typ) = &start.typ {
+                query.push(("type", typ));
+            }
+            if let Some(identifier) = &start.identifier {
+                query.push(("identifier", identifier));
+            }
+        }
+
+        self.request(
+            Method::GET,
+            &format!("/hostedzone/{}/rrset", zone_id),
+            &query,
+            None,
+        )
+        .await
+    }
+
+    pub async fn change_resource_record_sets(
+        &self,
+        zone_id: &str,
+        changes: &[Change],
+    ) -> Result<(), Route53Error> {
+        let mut body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><ChangeResourceRecordSetsRequest xmlns="{}"><ChangeBatch><Changes>"#,
+            XML_NAMESPACE
+        );
+        for change in changes {
+            change.write_xml(&mut body);
+        }
+        body.push_str("</Changes></ChangeBatch></ChangeResourceRecordSetsRequest>");
+
+        self.request::<ChangeInfoResponse>(
+            Method::POST,
+            &format!("/hostedzone/{}/rrset", zone_id),
+            &[],
+            Some(body),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RecordSetPosition {
+    pub name: String,
+    pub typ: Option<String>,
+    pub identifier: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ChangeAction {
+    Create,
+    Delete,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Change {
+    pub action: ChangeAction,
+    pub record_set: ResourceRecordSet,
+}
+
+impl Change {
+    fn write_xml(&self, out: &mut String) {
+        let action = match self.action {
+            ChangeAction::Create => "CREATE",
+            ChangeAction::Delete => "DELETE",
+        };
+        let set = &self.record_set;
+
+        write!(
+            out,
+            "<Change><Action>{}</Action><ResourceRecordSet><Name>{}</Name><Type>{}</Type>",
+            action,
+            escape(&set.name),
+            escape(&set.typ)
+        )
+        .unwrap();
+        if let Some(ttl) = set.ttl {
+            write!(out, "<TTL>{}</TTL>", ttl).unwrap();
+        }
+        out.push_str("<ResourceRecords>");
+        for record in set.records() {
+            write!(
+                out,
+                "<ResourceRecord><Value>{}</Value></ResourceRecord>",
+                escape(&record.value)
+            )
+            .unwrap();
+        }
+        out.push_str("</ResourceRecords></ResourceRecordSet></Change>");
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorResponse {
+    error: ApiError,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiError {
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChangeInfoResponse {
+    pub change_info: ChangeInfo,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChangeInfo {
+    pub id: String,
+    pub status: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HostedZone {
+    pub id: String,
+    pub name: String,
+}
+
+impl HostedZone {
+    /// Returns the ID without the `/hostedzone/` prefix Route 53 returns.
+    pub fn short_id(&self) -> &str {
+        self.id.trim_start_matches("/hostedzone/")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HostedZoneResponse {
+    pub hosted_zone: HostedZone,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListHostedZonesResponse {
+    pub hosted_zones: HostedZones,
+    pub is_truncated: bool,
+    pub next_marker: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default, Deserialize)]
+pub struct HostedZones {
+    #[serde(rename = "HostedZone", default)]
+    pub hosted_zones: Vec<HostedZone>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResourceRecordSet {
+    pub name: String,
+    #[serde(rename = "Type")]
+    pub typ: String,
+    #[serde(rename = "TTL")]
+    pub ttl: Option<u64>,
+    pub set_identifier: Option<String>,
+    pub alias_target: Option<AliasTarget>,
+    pub resource_records: Option<ResourceRecords>,
+}
+
+impl ResourceRecordSet {
+    pub fn records(&self) -> &[ResourceRecord] {
+        self.resource_records
+            .as_ref()
+            .map(|records| records.resource_records.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AliasTarget {
+    #[serde(rename = "DNSName")]
+    pub dns_name: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default, Deserialize)]
+pub struct ResourceRecords {
+    #[serde(rename = "ResourceRecord", default)]
+    pub resource_records: Vec<ResourceRecord>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResourceRecord {
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListResourceRecordSetsResponse {
+    pub resource_record_sets: ResourceRecordSets,
+    pub is_truncated: bool,
+    pub next_record_name: Option<String>,
+    pub next_record_type: Option<String>,
+    pub next_record_identifier: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default, Deserialize)]
+pub struct ResourceRecordSets {
+    #[serde(rename = "ResourceRecordSet", default)]
+    pub resource_record_sets: Vec<ResourceRecordSet>,
+}
@@ -0,0 +1,610 @@
+//! Provider implementation for [Amazon Route 53](https://aws.amazon.com/route53/).
+//!
+//! Route 53 manages records as resource record sets (RRsets) grouping all values of the same name and type without assigning IDs to them.
+//! Every value of a record set is represented as an individual [`Record`] with a [synthetic ID](crate::rrset).
+//!
+//! Creating or deleting a record replaces the whole record set in a single atomic change batch.
+//! As the TTL belongs to the record set, creating a record applies its TTL to all other values of the same set.
+//! Alias records and record sets using routing policies (i.e. having a set identifier) are not supported and skipped when listing records.
+
+use std::{
+    env,
+    error::Error as StdErr,
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    rrset::{self, parse_record_id, record_id},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+mod sigv4;
+
+pub use api::Route53Error;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 16] = &[
+    "A", "AAAA", "CAA", "CNAME", "DS", "HTTPS", "MX", "NAPTR", "NS", "PTR", "SPF", "SRV", "SSHFP",
+    "SVCB", "TLSA", "TXT",
+];
+
+/// Route 53 expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+const PAGE_SIZE: u32 = 100;
+
+const PROVIDER_NAME: &str = "route53";
+
+/// Represents the AWS credentials used to sign requests.
+///
+/// The secret access key and the session token are omitted from the [`Debug`](fmt::Debug) output.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(access_key_id: &str, secret_access_key: &str) -> Self {
+        Self {
+            access_key_id: access_key_id.to_owned(),
+            secret_access_key: secret_access_key.to_owned(),
+            session_token: None,
+        }
+    }
+
+    /// Reads the credentials from the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` environment variables.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Route53Provider {
+    api_client: Arc<api::Client>,
+}
+
+impl Route53Provider {
+    pub fn new(credentials: Credentials) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_endpoint(credentials, api::ROUTE53_ENDPOINT)
+    }
+
+    /// Creates a provider talking to a custom endpoint instead of `https://route53.amazonaws.com`, e.g. a mock server.
+    pub fn with_endpoint(
+        credentials: Credentials,
+        endpoint: &str,
+    ) -> Result<Self, Box<dyn StdErr>> {
+        let signer = sigv4::Signer {
+            access_key_id: credentials.access_key_id,
+            secret_access_key: credentials.secret_access_key,
+            session_token: credentials.session_token,
+            region: api::ROUTE53_REGION.to_owned(),
+            service: "route53".to_owned(),
+        };
+
+        let api_client = api::Client::new(endpoint, signer)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for Route53Provider {
+    type Zone = Route53Zone;
+    type CustomRetrieveError = Route53Error;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let response = self
+            .api_client
+            .get_hosted_zone(zone_id.trim_start_matches("/hostedzone/"))
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(Route53Zone::new(
+            self.api_client.clone(),
+            response.hosted_zone,
+        ))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = collect_pages(PROVIDER_NAME, |marker: Option<String>| async move {
+            self.api_client
+                .list_hosted_zones(marker.as_deref(), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.hosted_zones.hosted_zones,
+                    next: response.next_marker.filter(|_| response.is_truncated),
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(zones
+            .into_iter()
+            .map(|zone| Route53Zone::new(self.api_client.clone(), zone))
+            .collect())
+    }
+}
+
+impl CreateZone for Route53Provider {
+    type CustomCreateError = Route53Error;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        // Route 53 requires a unique caller reference to make retried requests idempotent.
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        let caller_reference = format!("libdns-{}-{}", domain.as_ascii(), nanos);
+
+        let response = self
+            .api_client
+            .create_hosted_zone(domain.as_ascii(), &caller_reference)
+            .await
+            // Route 53 also responds with 400 Bad Request when throttling, so invalid input is told apart by the error code.
+            .map_err(|err| match (err.status(), err.code()) {
+                (Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN), _) => {
+                    CreateZoneError::Unauthorized
+                }
+                (_, Some("InvalidDomainName")) => CreateZoneError::InvalidDomainName,
+                _ => CreateZoneError::Custom(err),
+            })?;
+
+        Ok(Route53Zone::new(
+            self.api_client.clone(),
+            response.hosted_zone,
+        ))
+    }
+}
+
+impl DeleteZone for Route53Provider {
+    type CustomDeleteError = Route53Error;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_hosted_zone(zone_id.trim_start_matches("/hostedzone/"))
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+/// Represents a Route 53 hosted zone.
+///
+/// The zone ID is the hosted zone ID without the `/hostedzone/` prefix, e.g. `Z1D633PJN98FT9`.
+#[derive(Debug, Clone)]
+pub struct Route53Zone {
+    api_client: Arc<api::Client>,
+    repr: api::HostedZone,
+    domain: DomainName,
+}
+
+impl Route53Zone {
+    fn new(api_client: Arc<api::Client>, repr: api::HostedZone) -> Self {
+        let domain = DomainName::from_raw(&decode_name(&repr.name)).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+
+    async fn find_record_set(
+        &self,
+        name: &DomainName,
+        typ: &str,
+    ) -> Result<Option<api::ResourceRecordSet>, Route53Error> {
+        let start = api::RecordSetPosition {
+            name: name.to_string(),
+            typ: Some(typ.to_owned()),
+            identifier: None,
+        };
+
+        let response = self
+            .api_client
+            .list_resource_record_sets(self.repr.short_id(), Some(&start), 1)
+            .await?;
+
+        Ok(response
+            .resource_record_sets
+            .resource_record_sets
+            .into_iter()
+            .next()
+            // Alias and routing policy record sets are skipped like in `list_records`, as changing them
+            // requires their alias target and set identifier.
+            .filter(|set| {
+                set.typ == typ
+                    && set.alias_target.is_none()
+                    && set.set_identifier.is_none()
+                    && DomainName::from_raw(&decode_name(&set.name)) == *name
+            }))
+    }
+
+    fn to_records(&self, set: api::ResourceRecordSet) -> Vec<Record> {
+        let name = DomainName::from_raw(&decode_name(&set.name));
+        let host = name.relative_to(&self.domain).unwrap_or(name.clone());
+
+        rrset::to_records(
+            &name,
+            &host,
+            set.records()
+                .iter()
+                .map(|record| RecordData::from_raw(&set.typ, &record.value)),
+            set.ttl.unwrap_or_default(),
+        )
+    }
+}
+
+impl Zone for Route53Zone {
+    type CustomRetrieveError = Route53Error;
+
+    fn id(&self) -> &str {
+        self.repr.short_id()
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let sets = collect_pages(
+            PROVIDER_NAME,
+            |start: Option<api::RecordSetPosition>| async move {
+                self.api_client
+                    .list_resource_record_sets(self.repr.short_id(), start.as_ref(), PAGE_SIZE)
+                    .await
+                    .map(|response| Page {
+                        items: response.resource_record_sets.resource_record_sets,
+                        next: match (response.is_truncated, response.next_record_name) {
+                            (true, Some(name)) => Some(api::RecordSetPosition {
+                                name,
+                                typ: response.next_record_type,
+                                identifier: response.next_record_identifier,
+                            }),
+                            _ => None,
+                        },
+                    })
+                    .map_err(RetrieveRecordError::from_status)
+            },
+        )
+        .await?;
+
+        Ok(sets
+            .into_iter()
+            .filter(|set| set.alias_target.is_none() && set.set_identifier.is_none())
+            .flat_map(|set| self.to_records(set))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(RetrieveRecordError::NotFound)?;
+
+        let set = self
+            .find_record_set(&name, data.get_type())
+            .await
+            .map_err(RetrieveRecordError::from_status)?
+            .ok_or(RetrieveRecordError::NotFound)?;
+
+        rrset::find_record(self.to_records(set), record_id).ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for Route53Zone {
+    type CustomCreateError = Route53Error;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let name = self.fqdn(&host);
+
+        // Like in `create_zone`, invalid input is told apart from throttling by the error code.
+        let map_err = |err: Route53Error| match (err.status(), err.code()) {
+            (Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN), _) => {
+                CreateRecordError::Unauthorized
+            }
+            (_, Some("InvalidChangeBatch" | "InvalidInput")) => CreateRecordError::InvalidRecord,
+            _ => CreateRecordError::Custom(err),
+        };
+
+        let existing = self.find_record_set(&name, typ).await.map_err(map_err)?;
+
+        let records = rrset::add_value(
+            existing
+                .as_ref()
+                .map(|set| set.records().to_vec())
+                .unwrap_or_default(),
+            data,
+            api::ResourceRecord {
+                value: data.get_value_as(TXT_FORMAT),
+            },
+            |record| RecordData::from_raw(typ, &record.value),
+        );
+
+        let mut changes = Vec::new();
+        if let Some(existing) = existing {
+            changes.push(api::Change {
+                action: api::ChangeAction::Delete,
+                record_set: existing,
+            });
+        }
+
+        changes.push(api::Change {
+            action: api::ChangeAction::Create,
+            record_set: api::ResourceRecordSet {
+                name: name.to_string(),
+                typ: typ.to_owned(),
+                ttl: Some(ttl),
+                set_identifier: None,
+                alias_target: None,
+                resource_records: Some(api::ResourceRecords {
+                    resource_records: records,
+                }),
+            },
+        });
+
+        self.api_client
+            .change_resource_record_sets(self.repr.short_id(), &changes)
+            .await
+            .map_err(map_err)?;
+
+        Ok(Record {
+            id: record_id(&name, data),
+            host,
+            data: data.clone(),
+            ttl,
+        })
+    }
+}
+
+impl DeleteRecord for Route53Zone {
+    type CustomDeleteError = Route53Error;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (name, data) = parse_record_id(record_id).ok_or(DeleteRecordError::NotFound)?;
+        let typ = data.get_type();
+
+        let existing = self
+            .find_record_set(&name, typ)
+            .await
+            .map_err(DeleteRecordError::from_status)?
+            .ok_or(DeleteRecordError::NotFound)?;
+
+        let remaining = rrset::remove_value(existing.records().to_vec(), &data, |record| {
+            RecordData::from_raw(typ, &record.value)
+        })
+        .ok_or(DeleteRecordError::NotFound)?;
+
+        let mut changes = vec![api::Change {
+            action: api::ChangeAction::Delete,
+            record_set: existing.clone(),
+        }];
+        if !remaining.is_empty() {
+            changes.push(api::Change {
+                action: api::ChangeAction::Create,
+                record_set: api::ResourceRecordSet {
+                    resource_records: Some(api::ResourceRecords {
+                        resource_records: remaining,
+                    }),
+                    ..existing
+                },
+            });
+        }
+
+        self.api_client
+            .change_resource_record_sets(self.repr.short_id(), &changes)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+/// Decodes the `\DDD` octal escapes Route 53 uses for special characters in names, e.g. `\052` for `*`.
+fn decode_name(name: &str) -> String {
+    let mut decoded = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(index) = rest.find('\\') {
+        decoded.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        let escaped = rest
+            .get(..3)
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte as char);
+                rest = &rest[3..];
+            }
+            None => decoded.push('\\'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[test]
+    fn debug_omits_secrets() {
+        let mut credentials = Credentials::new("AKIDEXAMPLE", "secret-access-key");
+        credentials.session_token = Some("session-token".to_owned());
+        let signer = sigv4::Signer {
+            access_key_id: credentials.access_key_id.clone(),
+            secret_access_key: credentials.secret_access_key.clone(),
+            session_token: credentials.session_token.clone(),
+            region: "us-east-1".to_owned(),
+            service: "route53".to_owned(),
+        };
+
+        for debug in [format!("{:?}", credentials), format!("{:?}", signer)] {
+            assert!(debug.contains("AKIDEXAMPLE"));
+            assert!(!debug.contains("secret-access-key"));
+            assert!(!debug.contains("session-token"));
+        }
+    }
+
+    #[tokio::test]
+    async fn records_are_paged_and_changed_in_batches() {
+        let server = MockServer::start(|request| {
+            let path = request.path.as_str();
+            match request.method.as_str() {
+                "GET" if path == "/2013-04-01/hostedzone/Z1" => Response::new(
+                    200,
+                    "<GetHostedZoneResponse><HostedZone><Id>/hostedzone/Z1</Id><Name>example.com.</Name></HostedZone></GetHostedZoneResponse>",
+                ),
+                "GET" if path == "/2013-04-01/hostedzone/Z1/rrset?maxitems=100" => Response::new(
+                    200,
+                    "<ListResourceRecordSetsResponse><ResourceRecordSets>\
+                     <ResourceRecordSet><Name>www.example.com.</Name><Type>A</Type><TTL>300</TTL><ResourceRecords>\
+                     <ResourceRecord><Value>192.0.2.1</Value></ResourceRecord><ResourceRecord><Value>192.0.2.2</Value></ResourceRecord>\
+                     </ResourceRecords></ResourceRecordSet>\
+                     <ResourceRecordSet><Name>alias.example.com.</Name><Type>A</Type><AliasTarget><DNSName>lb.example.net.</DNSName></AliasTarget></ResourceRecordSet>\
+                     </ResourceRecordSets><IsTruncated>true</IsTruncated><NextRecordName>example.com.</NextRecordName><NextRecordType>MX</NextRecordType></ListResourceRecordSetsResponse>",
+                ),
+                "GET" if path == "/2013-04-01/hostedzone/Z1/rrset?maxitems=100&name=example.com.&type=MX" => Response::new(
+                    200,
+                    "<ListResourceRecordSetsResponse><ResourceRecordSets>\
+                     <ResourceRecordSet><Name>example.com.</Name><Type>MX</Type><TTL>3600</TTL><ResourceRecords>\
+                     <ResourceRecord><Value>10 mail.example.com.</Value></ResourceRecord>\
+                     </ResourceRecords></ResourceRecordSet>\
+                     </ResourceRecordSets><IsTruncated>false</IsTruncated></ListResourceRecordSetsResponse>",
+                ),
+                // The record set following the requested one is returned if the requested one does not exist.
+                "GET" if path.starts_with("/2013-04-01/hostedzone/Z1/rrset?maxitems=1&name=www.example.com.") => Response::new(
+                    200,
+                    "<ListResourceRecordSetsResponse><ResourceRecordSets>\
+                     <ResourceRecordSet><Name>www.example.com.</Name><Type>A</Type><TTL>300</TTL><ResourceRecords>\
+                     <ResourceRecord><Value>192.0.2.1</Value></ResourceRecord><ResourceRecord><Value>192.0.2.2</Value></ResourceRecord>\
+                     </ResourceRecords></ResourceRecordSet>\
+                     </ResourceRecordSets><IsTruncated>true</IsTruncated></ListResourceRecordSetsResponse>",
+                ),
+                "POST" if path == "/2013-04-01/hostedzone/Z1/rrset" => Response::new(
+                    200,
+                    "<ChangeResourceRecordSetsResponse><ChangeInfo><Id>/change/C1</Id><Status>PENDING</Status></ChangeInfo></ChangeResourceRecordSetsResponse>",
+                ),
+                _ => Response::new(
+                    404,
+                    "<ErrorResponse><Error><Type>Sender</Type><Code>NoSuchHostedZone</Code><Message>not found</Message></Error></ErrorResponse>",
+                ),
+            }
+        })
+        .await;
+
+        let credentials = Credentials::new("AKIDEXAMPLE", "secret-access-key");
+        let provider = Route53Provider::with_endpoint(credentials, server.url()).unwrap();
+        assert!(matches!(
+            provider.get_zone("Z2").await,
+            Err(RetrieveZoneError::NotFound)
+        ));
+
+        let zone = provider.get_zone("/hostedzone/Z1").await.unwrap();
+        assert_eq!(zone.id(), "Z1");
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.host.to_string(), &record.data))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "www".to_owned(),
+                    &RecordData::A("192.0.2.1".parse().unwrap())
+                ),
+                (
+                    "www".to_owned(),
+                    &RecordData::A("192.0.2.2".parse().unwrap())
+                ),
+                (
+                    "@".to_owned(),
+                    &RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mail.example.com."),
+                    }
+                ),
+            ]
+        );
+
+        let data = RecordData::TXT(vec!["hello".to_owned()]);
+        let record = zone
+            .create_record(&DomainName::from_raw("www"), &data, 600)
+            .await
+            .unwrap();
+        assert_eq!(
+            record.id,
+            record_id(&DomainName::from_raw("www.example.com."), &data)
+        );
+
+        zone.delete_record(&records[0].id).await.unwrap();
+
+        let requests = server.requests();
+        let changes = requests
+            .iter()
+            .filter(|request| request.method == "POST")
+            .map(|request| request.body.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(changes.len(), 2);
+        assert!(!changes[0].contains("<Action>DELETE</Action>"));
+        assert!(changes[0].contains("<Type>TXT</Type><TTL>600</TTL><ResourceRecords><ResourceRecord><Value>&quot;hello&quot;</Value>"));
+        assert!(changes[1].contains(
+            "<Action>DELETE</Action><ResourceRecordSet><Name>www.example.com.</Name><Type>A</Type>"
+        ));
+        assert!(changes[1].contains("<Action>CREATE</Action><ResourceRecordSet><Name>www.example.com.</Name><Type>A</Type><TTL>300</TTL><ResourceRecords><ResourceRecord><Value>192.0.2.2</Value></ResourceRecord></ResourceRecords>"));
+
+        for request in &requests {
+            assert!(request.header("x-amz-date").is_some());
+            assert!(request
+                .header("authorization")
+                .unwrap()
+                .starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! A minimal implementation of the AWS Signature Version 4 request signing process.
+//!
+//! See <https://docs.aws.amazon.com/IAM/latest/UserGuide/create-signed-request.html>.
+
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::presentation::encode_hex;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Signs requests using AWS credentials, whose secrets are omitted from the [`Debug`](fmt::Debug) output.
+#[derive(Clone)]
+pub struct Signer {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+    pub service: String,
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("access_key_id", &self.access_key_id)
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer {
+    /// Signs a request and returns the headers which have to be added to it.
+    ///
+    /// `query` has to contain the already encoded (see [`encode`]) query parameters.
+    pub fn sign(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        query: &[(String, String)],
+        payload: &[u8],
+        time: SystemTime,
+    ) -> Vec<(&'static str, String)> {
+        let timestamp = format_time(time);
+        let payload_hash = encode_hex(&Sha256::digest(payload));
+
+        let mut headers = vec![
+            ("host", host.to_owned()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", timestamp.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+
+        let authorization =
+            self.authorization(method, path, query, &headers, &payload_hash, &timestamp);
+
+        headers.retain(|(name, _)| *name != "host");
+        headers.push(("authorization", authorization));
+        headers
+    }
+
+    /// Computes the value of the `authorization` header for a request, signing all of the given `headers`.
+    fn authorization(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(String, String)],
+        headers: &[(&'static str, String)],
+        payload_hash: &str,
+        timestamp: &str,
+    ) -> String {
+        let date = &timestamp[..8];
+
+        let mut sorted_query = query.to_vec();
+        sorted_query.sort();
+        let canonical_query = sorted_query
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<String>>()
+            .join("&");
+
+        let canonical_headers = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect::<String>();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<&str>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            encode_path(path),
+            canonical_query,
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            timestamp,
+            scope,
+            encode_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signature = encode_hex(&hmac(&self.signing_key(date), &string_to_sign));
+        format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, self.access_key_id, scope, signed_headers, signature
+        )
+    }
+
+    /// Derives the key used to sign requests on the given date (`YYYYMMDD`).
+    fn signing_key(&self, date: &str) -> Vec<u8> {
+        [self.region.as_str(), self.service.as_str(), "aws4_request"]
+            .iter()
+            .fold(
+                hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), date),
+                |key, part| hmac(&key, part),
+            )
+    }
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes a value as required by SigV4, leaving only unreserved characters as they are.
+pub fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(encode)
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Formats a point in time as a SigV4 timestamp (`YYYYMMDD'T'HHMMSS'Z'`), whose first eight characters are the date.
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Converts days since the epoch into a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const EMPTY_PAYLOAD_HASH: &str =
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn signer(service: &str) -> Signer {
+        Signer {
+            access_key_id: "AKIDEXAMPLE".to_owned(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_owned(),
+            session_token: None,
+            region: "us-east-1".to_owned(),
+            service: service.to_owned(),
+        }
+    }
+
+    /// Signs a request of the AWS SigV4 test suite, which only signs the `host` and `x-amz-date` headers.
+    fn sign_test_request(path: &str, query: &[(&str, &str)]) -> String {
+        let query = query
+            .iter()
+            .map(|(key, value)| (encode(key), encode(value)))
+            .collect::<Vec<(String, String)>>();
+        let headers = [
+            ("host", "example.amazonaws.com".to_owned()),
+            ("x-amz-date", "20150830T123600Z".to_owned()),
+        ];
+
+        signer("service").authorization(
+            "GET",
+            path,
+            &query,
+            &headers,
+            EMPTY_PAYLOAD_HASH,
+            "20150830T123600Z",
+        )
+    }
+
+    #[test]
+    fn aws_test_suite() {
+        let credential = "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date";
+
+        // get-vanilla
+        assert_eq!(
+            sign_test_request("/", &[]),
+            format!(
+                "{}, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+                credential
+            )
+        );
+        // get-vanilla-query-order-key-case
+        assert_eq!(
+            sign_test_request("/", &[("Param2", "value2"), ("Param1", "value1")]),
+            format!(
+                "{}, Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500",
+                credential
+            )
+        );
+        // get-utf8
+        assert_eq!(
+            sign_test_request("/\u{1234}", &[]),
+            format!(
+                "{}, Signature=8318018e0b0f223aa2bbf98705b62bb787dc9c0e678f255a891fd03141be5d85",
+                credential
+            )
+        );
+    }
+
+    #[test]
+    fn signing_key() {
+        // The example from the AWS documentation on deriving the signing key.
+        let key = signer("iam").signing_key("20120215");
+        assert_eq!(
+            encode_hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn signed_headers() {
+        let mut signer = signer("route53");
+        signer.session_token = Some("session-token".to_owned());
+
+        let time = UNIX_EPOCH + Duration::from_secs(1_440_938_160);
+        let headers = signer.sign("GET", "route53.amazonaws.com", "/", &[], b"", time);
+        let names = headers.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            [
+                "x-amz-content-sha256",
+                "x-amz-date",
+                "x-amz-security-token",
+                "authorization"
+            ]
+        );
+        assert_eq!(headers[0].1, EMPTY_PAYLOAD_HASH);
+        assert_eq!(headers[1].1, "20150830T123600Z");
+        assert!(headers[3]
+            .1
+            .contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token,"));
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_time(UNIX_EPOCH), "19700101T000000Z");
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(951_868_799)),
+            "20000229T235959Z"
+        );
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(encode("AZaz09-_.~"), "AZaz09-_.~");
+        assert_eq!(encode("a b/c=d"), "a%20b%2Fc%3Dd");
+        assert_eq!(
+            encode_path("/2013-04-01/hostedzone/Z1"),
+            "/2013-04-01/hostedzone/Z1"
+        );
+        assert_eq!(encode_path("/a b"), "/a%20b");
+    }
+}
@@ -0,0 +1,165 @@
+//! Synthetic record IDs for providers and backends which do not assign IDs to individual records.
+//!
+//! Many DNS APIs manage records as resource record sets (RRsets) grouping all values of the same name and type,
+//! and zone files and zone transfers have no notion of IDs at all. These providers represent every value as an individual
+//! [`Record`](crate::Record) with a synthetic ID of the form `<fqdn>|<type>|<hex encoded value>`, e.g. `www.example.com.|A|3139322e302e322e31`
+//! for the value `192.0.2.1`. IDs only depend on the record itself and thus stay stable between calls.
+//!
+//! Providers managing record sets create or delete a record by replacing the whole record set, so the TTL of a newly created
+//! record applies to all values of its set.
+
+use crate::{
+    presentation::{decode_hex, encode_hex},
+    DomainName, RecordData,
+};
+
+/// Builds the synthetic ID of a record from its fully qualified name and data.
+pub fn record_id(name: &DomainName, data: &RecordData) -> String {
+    format!(
+        "{}|{}|{}",
+        name.to_string().to_ascii_lowercase(),
+        data.get_type(),
+        encode_hex(data.get_value().as_bytes())
+    )
+}
+
+/// Parses a synthetic record ID into the fully qualified name and data of the record.
+pub fn parse_record_id(record_id: &str) -> Option<(DomainName, RecordData)> {
+    let mut parts = record_id.rsplitn(3, '|');
+    let value = String::from_utf8(decode_hex(parts.next()?)?).ok()?;
+    let typ = parts.next()?;
+    let name = DomainName::from_raw(parts.next()?);
+
+    Some((name, RecordData::from_raw(typ, &value)))
+}
+
+/// Represents the values of a record set as records, `name` being the fully qualified name and `host` the relative host of the set.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "route53"
+))]
+pub(crate) fn to_records(
+    name: &DomainName,
+    host: &DomainName,
+    values: impl IntoIterator<Item = RecordData>,
+    ttl: u64,
+) -> Vec<crate::Record> {
+    values
+        .into_iter()
+        .map(|data| crate::Record {
+            id: record_id(name, &data),
+            host: host.clone(),
+            data,
+            ttl,
+        })
+        .collect()
+}
+
+/// Returns the record with the given ID among the records of a record set.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "route53"
+))]
+pub(crate) fn find_record(records: Vec<crate::Record>, record_id: &str) -> Option<crate::Record> {
+    records.into_iter().find(|record| record.id == record_id)
+}
+
+/// Adds `value`, the provider-specific representation of `data`, to the values of a record set.
+///
+/// Values equal to `data` when parsed using `parse` are replaced, so a record set never contains a value twice.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "route53"
+))]
+pub(crate) fn add_value<T>(
+    mut values: Vec<T>,
+    data: &RecordData,
+    value: T,
+    parse: impl Fn(&T) -> RecordData,
+) -> Vec<T> {
+    values.retain(|existing| parse(existing) != *data);
+    values.push(value);
+    values
+}
+
+/// Removes the values equal to `data` when parsed using `parse` from the values of a record set.
+///
+/// Returns the remaining values, [`None`] if the record set does not contain `data`.
+#[cfg(any(
+    feature = "azure",
+    feature = "desec",
+    feature = "gandi",
+    feature = "gcloud",
+    feature = "route53"
+))]
+pub(crate) fn remove_value<T>(
+    mut values: Vec<T>,
+    data: &RecordData,
+    parse: impl Fn(&T) -> RecordData,
+) -> Option<Vec<T>> {
+    let count = values.len();
+    values.retain(|existing| parse(existing) != *data);
+    (values.len() != count).then_some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_ids_round_trip() {
+        let name = DomainName::from_raw("WWW.example.com.");
+        for data in [
+            RecordData::A("192.0.2.1".parse().unwrap()),
+            RecordData::TXT(vec!["v=spf1 -all|~all".to_owned()]),
+            RecordData::MX {
+                priority: 10,
+                mail_server: DomainName::from_raw("mail.example.com."),
+            },
+        ] {
+            let id = record_id(&name, &data);
+            assert_eq!(parse_record_id(&id), Some((name.clone(), data)));
+        }
+
+        assert_eq!(
+            record_id(&name, &RecordData::A("192.0.2.1".parse().unwrap())),
+            "www.example.com.|A|3139322e302e322e31"
+        );
+        assert_eq!(parse_record_id("www.example.com.|A|zz"), None);
+        assert_eq!(parse_record_id("3139322e302e322e31"), None);
+    }
+
+    #[cfg(any(
+        feature = "azure",
+        feature = "desec",
+        feature = "gandi",
+        feature = "gcloud",
+        feature = "route53"
+    ))]
+    #[test]
+    fn values_are_added_and_removed_once() {
+        let parse = |value: &String| RecordData::from_raw("A", value);
+        let data = RecordData::A("192.0.2.2".parse().unwrap());
+
+        let values = add_value(
+            vec!["192.0.2.1".to_owned(), "192.0.2.2".to_owned()],
+            &data,
+            "192.0.2.2".to_owned(),
+            parse,
+        );
+        assert_eq!(values, ["192.0.2.1", "192.0.2.2"]);
+
+        let values = remove_value(values, &data, parse).unwrap();
+        assert_eq!(values, ["192.0.2.1"]);
+        assert_eq!(remove_value(values, &data, parse), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Generation of `SSHFP` records (see [RFC 4255](https://www.rfc-editor.org/rfc/rfc4255)) from OpenSSH public keys.
+//!
+//! This module is only available with the `sshfp` feature enabled.
+
+use std::{fs, io, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::RecordData;
+
+/// The `SSHFP` fingerprint type for SHA-1 fingerprints.
+pub const FINGERPRINT_SHA1: u8 = 1;
+
+/// The `SSHFP` fingerprint type for SHA-256 fingerprints.
+pub const FINGERPRINT_SHA256: u8 = 2;
+
+const ALGORITHMS: &[(&str, u8)] = &[
+    ("ssh-rsa", 1),
+    ("ssh-dss", 2),
+    ("ecdsa-sha2-nistp256", 3),
+    ("ecdsa-sha2-nistp384", 3),
+    ("ecdsa-sha2-nistp521", 3),
+    ("ssh-ed25519", 4),
+    ("ssh-ed448", 6),
+];
+
+/// Represents an error that occured when generating `SSHFP` records.
+#[derive(Debug, Error)]
+pub enum SshfpError {
+    /// Indicates that the public key file could not be read.
+    #[error("the public key file could not be read")]
+    Io(#[from] io::Error),
+
+    /// Indicates that a line is not a valid OpenSSH public key.
+    #[error("the public key is malformed")]
+    MalformedKey,
+
+    /// Indicates that the key algorithm has no `SSHFP` algorithm number assigned.
+    #[error("the key algorithm {0} is not supported by SSHFP")]
+    UnsupportedAlgorithm(String),
+}
+
+/// Generates the SHA-1 and SHA-256 `SSHFP` records of a single OpenSSH public key, e.g. `ssh-ed25519 AAAA... comment`.
+pub fn from_public_key(public_key: &str) -> Result<Vec<RecordData>, SshfpError> {
+    let mut fields = public_key.split_whitespace();
+    let key_type = fields.next().ok_or(SshfpError::MalformedKey)?;
+    let encoded = fields.next().ok_or(SshfpError::MalformedKey)?;
+
+    let algorithm = ALGORITHMS
+        .iter()
+        .find(|(name, _)| *name == key_type)
+        .map(|(_, algorithm)| *algorithm)
+        .ok_or_else(|| SshfpError::UnsupportedAlgorithm(key_type.to_owned()))?;
+
+    let blob = STANDARD
+        .decode(encoded)
+        .map_err(|_| SshfpError::MalformedKey)?;
+
+    // The key blob starts with the length-prefixed key type which has to match the textual one.
+    let embedded_type = blob
+        .get(..4)
+        .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+        .and_then(|len| blob.get(4..4 + len))
+        .ok_or(SshfpError::MalformedKey)?;
+    if embedded_type != key_type.as_bytes() {
+        return Err(SshfpError::MalformedKey);
+    }
+
+    Ok(vec![
+        RecordData::SSHFP {
+            algorithm,
+            fingerprint_type: FINGERPRINT_SHA1,
+            fingerprint: Sha1::digest(&blob).to_vec(),
+        },
+        RecordData::SSHFP {
+            algorithm,
+            fingerprint_type: FINGERPRINT_SHA256,
+            fingerprint: Sha256::digest(&blob).to_vec(),
+        },
+    ])
+}
+
+/// Generates `SSHFP` records for every public key in the contents of an OpenSSH public key file.
+///
+/// Empty lines and comments starting with `#` are skipped.
+pub fn from_public_keys(contents: &str) -> Result<Vec<RecordData>, SshfpError> {
+    let mut records = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        records.append(&mut from_public_key(line)?);
+    }
+    Ok(records)
+}
+
+/// Reads an OpenSSH public key file (e.g. `/etc/ssh/ssh_host_ed25519_key.pub`) and generates `SSHFP` records for its keys.
+///
+/// See [`from_public_keys`].
+pub fn from_public_key_file(path: impl AsRef<Path>) -> Result<Vec<RecordData>, SshfpError> {
+    from_public_keys(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    // Both keys and their fingerprints were generated using `ssh-keygen`, the fingerprints by `ssh-keygen -r`.
+    const ED25519: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOd7ZS2tKGrTyksdsOtarT+9M7yNXko4dhKkrrJRDIoj host";
+    const RSA: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAAgQC0Tqo8CrzfqvWVqakkctSy5fk4bwfemCepxkhS3/8krkLaogzeaYicKEOG3IB8skYqbYk8AQgaXpiQhDgUPeuuTwOLxYs6o5o453nYuYPjr+xB/yOc8TkiRDBiym+0610pP9uYiZX0+5N4OurKds5dr4YmDfG6Tl4il7RGWfwjyQ== rsa";
+
+    fn values(records: &[RecordData]) -> Vec<String> {
+        records.iter().map(RecordData::get_value).collect()
+    }
+
+    #[test]
+    fn fingerprints() {
+        assert_eq!(
+            values(&from_public_key(ED25519).unwrap()),
+            [
+                "4 1 225546c46bb67ec7e91cbd164174e673b207547a",
+                "4 2 d955f4f25f784b29d3503af4162abb92618a8f9d30ae12e2403effee560acb89",
+            ]
+        );
+        assert_eq!(
+            values(&from_public_key(RSA).unwrap()),
+            [
+                "1 1 4ffb422b59e5b3e7681b0963d1aa87a2bae463ea",
+                "1 2 139a9de2ebb6b6596165faef70dbb844f425ff0972bf2091059f560a775ac15d",
+            ]
+        );
+    }
+
+    #[test]
+    fn public_key_files() {
+        let contents = format!("# host keys\n\n  {}\n{}\n", ED25519, RSA);
+        let records = from_public_keys(&contents).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[..2], from_public_key(ED25519).unwrap());
+        assert_eq!(records[2..], from_public_key(RSA).unwrap());
+
+        let path = env::temp_dir().join(format!("libdns-sshfp-{}.pub", std::process::id()));
+        fs::write(&path, &contents).unwrap();
+        let result = from_public_key_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap(), records);
+
+        assert!(matches!(
+            from_public_key_file(env::temp_dir().join("libdns-sshfp-missing.pub")),
+            Err(SshfpError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_keys() {
+        let ed25519_blob = ED25519.split_whitespace().nth(1).unwrap();
+        for key in [
+            "",
+            "ssh-ed25519",
+            "ssh-ed25519 not-base64!",
+            "ssh-ed25519 AAAA",
+            &format!("ssh-rsa {}", ed25519_blob),
+        ] {
+            assert!(
+                matches!(from_public_key(key), Err(SshfpError::MalformedKey)),
+                "{}",
+                key
+            );
+        }
+
+        assert!(matches!(
+            from_public_key(&format!("ssh-foo {}", ed25519_blob)),
+            Err(SshfpError::UnsupportedAlgorithm(algorithm)) if algorithm == "ssh-foo"
+        ));
+        assert!(matches!(
+            from_public_keys(&format!("{}\nssh-ed25519 AAAA\n", ED25519)),
+            Err(SshfpError::MalformedKey)
+        ));
+    }
+}
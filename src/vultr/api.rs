@@ -0,0 +1,213 @@
+use std::{collections::HashMap, error::Error};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client as HttpClient,
+};
+use serde::{Deserialize, Serialize};
+
+pub const VULTR_API_URL: &str = "https://api.vultr.com/v2";
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(api_key: &str, base_url: &str) -> Result<Self, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value = HeaderValue::from_str(&format!("Bearer {}", api_key))?;
+        auth_value.set_sensitive(true);
+        headers.append(AUTHORIZATION, auth_value);
+
+        let http_client = HttpClient::builder().default_headers(headers).build()?;
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    pub async fn retrieve_domains(
+        &self,
+        cursor: Option<&str>,
+        per_page: u32,
+    ) -> Result<DomainsResponse, reqwest::Error> {
+        let mut query = vec![("per_page", per_page.to_string())];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_owned()));
+        }
+
+        self.http_client
+            .get(format!("{}/domains", self.base_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_domain(&self, domain: &str) -> Result<DomainResponse, reqwest::Error> {
+        self.http_client
+            .get(format!("{}/domains/{}", self.base_url, domain))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_domain(&self, domain: &str) -> Result<DomainResponse, reqwest::Error> {
+        let mut request_body = HashMap::new();
+        request_body.insert("domain", domain);
+
+        self.http_client
+            .post(format!("{}/domains", self.base_url))
+            .json(&request_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_domain(&self, domain: &str) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!("{}/domains/{}", self.base_url, domain))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+
+    pub async fn retrieve_records(
+        &self,
+        domain: &str,
+        cursor: Option<&str>,
+        per_page: u32,
+    ) -> Result<RecordsResponse, reqwest::Error> {
+        let mut query = vec![("per_page", per_page.to_string())];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_owned()));
+        }
+
+        self.http_client
+            .get(format!("{}/domains/{}/records", self.base_url, domain))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn retrieve_record(
+        &self,
+        domain: &str,
+        record_id: &str,
+    ) -> Result<RecordResponse, reqwest::Error> {
+        self.http_client
+            .get(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn create_record(
+        &self,
+        domain: &str,
+        request: &RecordRequest<'_>,
+    ) -> Result<RecordResponse, reqwest::Error> {
+        self.http_client
+            .post(format!("{}/domains/{}/records", self.base_url, domain))
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    pub async fn delete_record(&self, domain: &str, record_id: &str) -> Result<(), reqwest::Error> {
+        self.http_client
+            .delete(format!(
+                "{}/domains/{}/records/{}",
+                self.base_url, domain, record_id
+            ))
+            .send()
+            .await?
+            .error_for_status()
+            .map(|_| ())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Domain {
+    pub domain: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct DomainResponse {
+    pub domain: Domain,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct DomainsResponse {
+    pub domains: Vec<Domain>,
+    #[serde(default)]
+    pub meta: Meta,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize, Default)]
+pub struct Meta {
+    #[serde(default)]
+    pub links: Links,
+}
+
+/// The cursors of the adjacent pages, which are empty strings if there is no such page.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize, Default)]
+pub struct Links {
+    #[serde(default)]
+    pub next: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct Record {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub name: String,
+    pub data: String,
+    #[serde(default)]
+    pub priority: i32,
+    pub ttl: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RecordResponse {
+    pub record: Record,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Deserialize)]
+pub struct RecordsResponse {
+    pub records: Vec<Record>,
+    #[serde(default)]
+    pub meta: Meta,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+pub struct RecordRequest<'a> {
+    #[serde(rename = "type")]
+    pub typ: &'a str,
+    pub name: &'a str,
+    pub data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u16>,
+    pub ttl: u64,
+}
@@ -0,0 +1,385 @@
+//! Provider implementation for [Vultr](https://www.vultr.com/products/dns/).
+//!
+//! Requests are authenticated using an API key, which Vultr only accepts from allowed IP addresses.
+//! Zones are identified by their domain, e.g. `example.com`, and records by the UUIDs Vultr assigns to them.
+
+use std::{error::Error as StdErr, sync::Arc};
+
+use crate::{
+    http::{collect_pages, FromStatus, Page},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, TxtFormat, Zone,
+};
+
+mod api;
+
+const SUPPORTED_RECORD_TYPES: &[&str; 9] = &[
+    "A", "AAAA", "CAA", "CNAME", "MX", "NS", "SRV", "SSHFP", "TXT",
+];
+
+/// Vultr expects TXT values as quoted chunks of at most 255 bytes.
+const TXT_FORMAT: TxtFormat = TxtFormat::Quoted;
+
+const PAGE_SIZE: u32 = 500;
+
+const PROVIDER_NAME: &str = "vultr";
+
+#[derive(Debug, Clone)]
+pub struct VultrProvider {
+    api_client: Arc<api::Client>,
+}
+
+impl VultrProvider {
+    pub fn new(api_key: &str) -> Result<Self, Box<dyn StdErr>> {
+        Self::with_base_url(api_key, api::VULTR_API_URL)
+    }
+
+    /// Creates a provider talking to a custom API base URL instead of `https://api.vultr.com/v2`, e.g. a mock server.
+    pub fn with_base_url(api_key: &str, base_url: &str) -> Result<Self, Box<dyn StdErr>> {
+        let api_client = api::Client::new(api_key, base_url)?;
+        Ok(Self {
+            api_client: Arc::new(api_client),
+        })
+    }
+}
+
+impl Provider for VultrProvider {
+    type Zone = VultrZone;
+    type CustomRetrieveError = reqwest::Error;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let response = self
+            .api_client
+            .retrieve_domain(zone_id)
+            .await
+            .map_err(RetrieveZoneError::from_status)?;
+
+        Ok(VultrZone::new(self.api_client.clone(), response.domain))
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domains = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_domains(cursor.as_deref(), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.domains,
+                    next: Some(response.meta.links.next).filter(|next| !next.is_empty()),
+                })
+                .map_err(RetrieveZoneError::from_status)
+        })
+        .await?;
+
+        Ok(domains
+            .into_iter()
+            .map(|domain| VultrZone::new(self.api_client.clone(), domain))
+            .collect())
+    }
+}
+
+impl CreateZone for VultrProvider {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let response = self
+            .api_client
+            .create_domain(domain.as_ascii())
+            .await
+            .map_err(CreateZoneError::from_status)?;
+
+        Ok(VultrZone::new(self.api_client.clone(), response.domain))
+    }
+}
+
+impl DeleteZone for VultrProvider {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_domain(zone_id)
+            .await
+            .map_err(DeleteZoneError::from_status)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VultrZone {
+    api_client: Arc<api::Client>,
+    repr: api::Domain,
+    domain: DomainName,
+}
+
+impl VultrZone {
+    fn new(api_client: Arc<api::Client>, repr: api::Domain) -> Self {
+        let domain = DomainName::from_raw(&repr.domain).to_absolute();
+        Self {
+            api_client,
+            repr,
+            domain,
+        }
+    }
+}
+
+impl Zone for VultrZone {
+    type CustomRetrieveError = reqwest::Error;
+
+    fn id(&self) -> &str {
+        &self.repr.domain
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let records = collect_pages(PROVIDER_NAME, |cursor: Option<String>| async move {
+            self.api_client
+                .retrieve_records(&self.repr.domain, cursor.as_deref(), PAGE_SIZE)
+                .await
+                .map(|response| Page {
+                    items: response.records,
+                    next: Some(response.meta.links.next).filter(|next| !next.is_empty()),
+                })
+                .map_err(RetrieveRecordError::from_status)
+        })
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| record.into_generic(&self.domain))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let response = self
+            .api_client
+            .retrieve_record(&self.repr.domain, record_id)
+            .await
+            .map_err(RetrieveRecordError::from_status)?;
+
+        Ok(response.record.into_generic(&self.domain))
+    }
+}
+
+impl CreateRecord for VultrZone {
+    type CustomCreateError = reqwest::Error;
+
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        let typ = data.get_type();
+        if !SUPPORTED_RECORD_TYPES.contains(&typ) {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+
+        let mut request = api::RecordRequest {
+            typ,
+            name: host.as_ascii(),
+            data: data.get_value_as(TXT_FORMAT),
+            priority: None,
+            ttl,
+        };
+
+        match data {
+            RecordData::CNAME(target) | RecordData::NS(target) => {
+                request.data = self.fqdn(target).as_ascii().to_owned();
+            }
+            RecordData::MX {
+                priority,
+                mail_server,
+            } => {
+                request.data = self.fqdn(mail_server).as_ascii().to_owned();
+                request.priority = Some(*priority);
+            }
+            RecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                request.data = format!("{} {} {}", weight, port, self.fqdn(target).as_ascii());
+                request.priority = Some(*priority);
+            }
+            _ => {}
+        }
+
+        let response = self
+            .api_client
+            .create_record(&self.repr.domain, &request)
+            .await
+            .map_err(CreateRecordError::from_status)?;
+
+        Ok(response.record.into_generic(&self.domain))
+    }
+}
+
+impl DeleteRecord for VultrZone {
+    type CustomDeleteError = reqwest::Error;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        self.api_client
+            .delete_record(&self.repr.domain, record_id)
+            .await
+            .map_err(DeleteRecordError::from_status)
+    }
+}
+
+impl api::Record {
+    pub fn into_generic(self, zone_domain: &DomainName) -> Record {
+        // Vultr returns target names without a trailing dot and the priority of MX and SRV records as a separate field.
+        let target = |name: &str| DomainName::from_raw(name).to_absolute();
+        let priority = u16::try_from(self.priority).unwrap_or_default();
+
+        let data = match self.typ.as_str() {
+            "CNAME" => RecordData::CNAME(target(&self.data)),
+            "NS" => RecordData::NS(target(&self.data)),
+            "MX" => RecordData::MX {
+                priority,
+                mail_server: target(&self.data),
+            },
+            "SRV" => {
+                let mut iter = self.data.split_whitespace();
+                match (
+                    iter.next().and_then(|raw| raw.parse::<u16>().ok()),
+                    iter.next().and_then(|raw| raw.parse::<u16>().ok()),
+                    iter.next(),
+                ) {
+                    (Some(weight), Some(port), Some(name)) => RecordData::SRV {
+                        priority,
+                        weight,
+                        port,
+                        target: target(name),
+                    },
+                    _ => RecordData::from_raw(&self.typ, &self.data),
+                }
+            }
+            _ => RecordData::from_raw(&self.typ, &self.data),
+        };
+
+        let host = DomainName::from_raw(&self.name);
+        Record {
+            id: self.id,
+            host: host.relative_to(zone_domain).unwrap_or(host),
+            data,
+            ttl: self.ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockServer, Response};
+
+    #[tokio::test]
+    async fn pages_are_followed_by_cursor_and_priorities_sent_separately() {
+        let server = MockServer::start(|request| {
+            if request.header("authorization") != Some("Bearer api-key") {
+                return Response::json(401, r#"{"error":"Invalid API token.","status":401}"#);
+            }
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/domains/example.com") => {
+                    Response::json(200, r#"{"domain":{"domain":"example.com"}}"#)
+                }
+                ("GET", "/domains/example.com/records?per_page=500") => Response::json(
+                    200,
+                    r#"{"records":[{"id":"a1","type":"A","name":"www","data":"192.0.2.1","priority":-1,"ttl":300}],"meta":{"total":2,"links":{"next":"bmV4dA==","prev":""}}}"#,
+                ),
+                ("GET", "/domains/example.com/records?per_page=500&cursor=bmV4dA%3D%3D") => Response::json(
+                    200,
+                    r#"{"records":[{"id":"b2","type":"SRV","name":"_sip._tcp","data":"20 5060 sip.example.com","priority":10,"ttl":600}],"meta":{"total":2,"links":{"next":"","prev":"cHJldg=="}}}"#,
+                ),
+                ("POST", "/domains/example.com/records") => Response::json(
+                    201,
+                    r#"{"record":{"id":"c3","type":"MX","name":"","data":"mail.example.com","priority":10,"ttl":3600}}"#,
+                ),
+                ("DELETE", "/domains/example.com/records/c3") => Response::new(204, ""),
+                _ => Response::json(404, r#"{"error":"Not found.","status":404}"#),
+            }
+        })
+        .await;
+
+        let provider = VultrProvider::with_base_url("api-key", server.url()).unwrap();
+        let zone = provider.get_zone("example.com").await.unwrap();
+
+        let records = zone.list_records().await.unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (record.id.as_str(), record.host.to_string(), &record.data))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "a1",
+                    "www".to_owned(),
+                    &RecordData::A("192.0.2.1".parse().unwrap())
+                ),
+                (
+                    "b2",
+                    "_sip._tcp".to_owned(),
+                    &RecordData::SRV {
+                        priority: 10,
+                        weight: 20,
+                        port: 5060,
+                        target: DomainName::from_raw("sip.example.com."),
+                    }
+                ),
+            ]
+        );
+
+        let data = RecordData::MX {
+            priority: 10,
+            mail_server: DomainName::from_raw("mail"),
+        };
+        let record = zone
+            .create_record(&DomainName::from_raw("@"), &data, 3600)
+            .await
+            .unwrap();
+        assert_eq!(record.id, "c3");
+        assert!(record.host.is_apex());
+
+        zone.delete_record(&record.id).await.unwrap();
+        assert!(matches!(
+            zone.delete_record("d4").await,
+            Err(DeleteRecordError::NotFound)
+        ));
+
+        let requests = server.requests();
+        let create = requests
+            .iter()
+            .find(|request| request.method == "POST")
+            .unwrap();
+        assert_eq!(
+            create.body,
+            r#"{"type":"MX","name":"","data":"mail.example.com","priority":10,"ttl":3600}"#
+        );
+    }
+}
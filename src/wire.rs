@@ -0,0 +1,487 @@
+//! Encoding and decoding of DNS messages in wire format (see RFC 1035, section 4).
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{
+    presentation::{encode_hex, format_generic, quote},
+    DomainName, RecordData,
+};
+
+pub const CLASS_IN: u16 = 1;
+#[cfg(feature = "axfr")]
+pub const CLASS_ANY: u16 = 255;
+#[cfg(feature = "axfr")]
+pub const TYPE_SOA: u16 = 6;
+pub const TYPE_TSIG: u16 = 250;
+#[cfg(feature = "axfr")]
+pub const TYPE_AXFR: u16 = 252;
+
+const RECORD_TYPES: &[(&str, u16)] = &[
+    ("A", 1),
+    ("NS", 2),
+    ("CNAME", 5),
+    ("SOA", 6),
+    ("PTR", 12),
+    ("HINFO", 13),
+    ("MX", 15),
+    ("TXT", 16),
+    ("RP", 17),
+    ("AAAA", 28),
+    ("LOC", 29),
+    ("SRV", 33),
+    ("NAPTR", 35),
+    ("CERT", 37),
+    ("DNAME", 39),
+    ("DS", 43),
+    ("SSHFP", 44),
+    ("RRSIG", 46),
+    ("NSEC", 47),
+    ("DNSKEY", 48),
+    ("NSEC3", 50),
+    ("NSEC3PARAM", 51),
+    ("TLSA", 52),
+    ("CDS", 59),
+    ("CDNSKEY", 60),
+    ("OPENPGPKEY", 61),
+    ("SVCB", 64),
+    ("HTTPS", 65),
+    ("URI", 256),
+    ("CAA", 257),
+];
+
+/// Returns the mnemonic of a record type, falling back to the generic `TYPE<number>` notation of RFC 3597.
+pub fn type_name(typ: u16) -> String {
+    RECORD_TYPES
+        .iter()
+        .find(|&&(_, code)| code == typ)
+        .map(|&(name, _)| name.to_owned())
+        .unwrap_or_else(|| format!("TYPE{}", typ))
+}
+
+/// Returns the numeric code of a record type, e.g. `16` for `TXT` or `1234` for `TYPE1234`.
+#[cfg(feature = "propagation")]
+pub fn type_code(typ: &str) -> Option<u16> {
+    RECORD_TYPES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(typ))
+        .map(|&(_, code)| code)
+        .or_else(|| {
+            typ.get(..4)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("TYPE"))
+                .and_then(|_| typ[4..].parse().ok())
+        })
+}
+
+/// Returns a random message ID, making it harder to spoof responses.
+pub fn random_id() -> u16 {
+    RandomState::new().build_hasher().finish() as u16
+}
+
+/// Encodes a name in uncompressed wire format, lowercasing it as required for TSIG computations.
+pub fn encode_name(name: &DomainName, buffer: &mut Vec<u8>) {
+    for label in name.labels() {
+        buffer.push(label.len() as u8);
+        buffer.extend(label.to_ascii_lowercase().bytes());
+    }
+    buffer.push(0);
+}
+
+/// Builds a query message with a single question.
+pub fn build_query(id: u16, name: &DomainName, typ: u16) -> Vec<u8> {
+    let mut message = Vec::with_capacity(512);
+    message.extend(id.to_be_bytes());
+    // Standard query without recursion desired, one question and no other records.
+    message.extend([0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    encode_name(name, &mut message);
+    message.extend(typ.to_be_bytes());
+    message.extend(CLASS_IN.to_be_bytes());
+    message
+}
+
+/// Builds a response repeating the question of a query, with answers whose data is already in wire format.
+///
+/// The QR flag is always set, while the other flags and the response code are taken from `flags`.
+#[cfg(test)]
+pub fn build_response(query: &[u8], flags: u16, answers: &[(DomainName, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut question_end = 12;
+    while query[question_end] != 0 {
+        question_end += query[question_end] as usize + 1;
+    }
+    question_end += 5;
+
+    let mut message = query[..2].to_vec();
+    message.extend((flags | 0x8000).to_be_bytes());
+    message.extend([0, 1]);
+    message.extend((answers.len() as u16).to_be_bytes());
+    message.extend([0, 0, 0, 0]);
+    message.extend(&query[12..question_end]);
+    for (name, typ, rdata) in answers {
+        encode_name(name, &mut message);
+        message.extend(typ.to_be_bytes());
+        message.extend(CLASS_IN.to_be_bytes());
+        message.extend(3600u32.to_be_bytes());
+        message.extend((rdata.len() as u16).to_be_bytes());
+        message.extend(rdata);
+    }
+    message
+}
+
+/// Represents a resource record of a parsed message, with its data still in wire format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRecord {
+    pub name: DomainName,
+    pub typ: u16,
+    pub class: u16,
+    pub ttl: u32,
+    /// The offset of the record within the message.
+    pub offset: usize,
+    pub rdata: std::ops::Range<usize>,
+}
+
+/// Represents a parsed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub id: u16,
+    /// Whether the server is authoritative for the queried name (AA flag).
+    pub authoritative: bool,
+    /// Whether the message was truncated to fit into a UDP datagram (TC flag).
+    pub truncated: bool,
+    pub rcode: u8,
+    pub answers: Vec<ResourceRecord>,
+    /// The TSIG record, which has to be the last record of the additional section.
+    pub tsig: Option<ResourceRecord>,
+}
+
+impl Message {
+    pub fn parse(message: &[u8]) -> Option<Message> {
+        let mut reader = Reader::new(message);
+        let id = reader.u16()?;
+        let flags = reader.u16()?;
+        let counts = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+
+        for _ in 0..counts[0] {
+            reader.name()?;
+            reader.bytes(4)?;
+        }
+
+        let mut answers = Vec::with_capacity(counts[1] as usize);
+        for _ in 0..counts[1] {
+            answers.push(reader.record()?);
+        }
+        for _ in 0..counts[2] {
+            reader.record()?;
+        }
+
+        let mut tsig = None;
+        for i in 0..counts[3] {
+            let record = reader.record()?;
+            if record.typ == TYPE_TSIG {
+                // A TSIG record anywhere but at the end of the message is invalid.
+                if i + 1 != counts[3] {
+                    return None;
+                }
+                tsig = Some(record);
+            }
+        }
+
+        Some(Message {
+            id,
+            authoritative: flags & 0x0400 != 0,
+            truncated: flags & 0x0200 != 0,
+            rcode: (flags & 0x000f) as u8,
+            answers,
+            tsig,
+        })
+    }
+}
+
+/// Represents the data of a TSIG record (see RFC 8945, section 4.2).
+#[cfg(feature = "axfr")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TsigData {
+    pub algorithm: DomainName,
+    pub time_signed: u64,
+    pub fudge: u16,
+    pub mac: Vec<u8>,
+    pub original_id: u16,
+    pub error: u16,
+    pub other: Vec<u8>,
+}
+
+#[cfg(feature = "axfr")]
+impl TsigData {
+    pub fn parse(message: &[u8], record: &ResourceRecord) -> Option<TsigData> {
+        let mut reader = Reader::new(message);
+        reader.position = record.rdata.start;
+
+        let algorithm = reader.name()?;
+        let time_signed = reader.u48()?;
+        let fudge = reader.u16()?;
+        let mac_size = reader.u16()? as usize;
+        let mac = reader.bytes(mac_size)?.to_vec();
+        let original_id = reader.u16()?;
+        let error = reader.u16()?;
+        let other_size = reader.u16()? as usize;
+        let other = reader.bytes(other_size)?.to_vec();
+
+        Some(TsigData {
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error,
+            other,
+        })
+    }
+
+    /// Encodes the record data in wire format.
+    pub fn encode(&self, buffer: &mut Vec<u8>) {
+        encode_name(&self.algorithm, buffer);
+        buffer.extend(&self.time_signed.to_be_bytes()[2..]);
+        buffer.extend(self.fudge.to_be_bytes());
+        buffer.extend((self.mac.len() as u16).to_be_bytes());
+        buffer.extend(&self.mac);
+        buffer.extend(self.original_id.to_be_bytes());
+        buffer.extend(self.error.to_be_bytes());
+        buffer.extend((self.other.len() as u16).to_be_bytes());
+        buffer.extend(&self.other);
+    }
+}
+
+/// Converts the data of a record to [`RecordData`].
+///
+/// Domain names within the data may be compressed, so they are decoded using the whole message.
+/// Data of types without a dedicated decoder is represented using the generic representation of RFC 3597.
+pub fn decode_record_data(message: &[u8], record: &ResourceRecord) -> RecordData {
+    let typ = type_name(record.typ);
+    let rdata = &message[record.rdata.clone()];
+
+    match format_record_data(message, record) {
+        Some(value) => RecordData::from_raw(&typ, &value),
+        None => RecordData::from_raw(&typ, &format_generic(rdata)),
+    }
+}
+
+/// Formats the data of a record in presentation format, returning [`None`] for unsupported types or malformed data.
+fn format_record_data(message: &[u8], record: &ResourceRecord) -> Option<String> {
+    let mut reader = Reader::new(message);
+    reader.position = record.rdata.start;
+    let end = record.rdata.end;
+
+    let value = match type_name(record.typ).as_str() {
+        "A" => Ipv4Addr::from(<[u8; 4]>::try_from(reader.bytes(4)?).ok()?).to_string(),
+        "AAAA" => Ipv6Addr::from(<[u8; 16]>::try_from(reader.bytes(16)?).ok()?).to_string(),
+        "NS" | "CNAME" | "PTR" | "DNAME" => reader.name()?.to_string(),
+        "MX" => format!("{} {}", reader.u16()?, reader.name()?),
+        "SOA" => format!(
+            "{} {} {} {} {} {} {}",
+            reader.name()?,
+            reader.name()?,
+            reader.u32()?,
+            reader.u32()?,
+            reader.u32()?,
+            reader.u32()?,
+            reader.u32()?
+        ),
+        "SRV" => format!(
+            "{} {} {} {}",
+            reader.u16()?,
+            reader.u16()?,
+            reader.u16()?,
+            reader.name()?
+        ),
+        "TXT" => {
+            let mut strings = Vec::new();
+            while reader.position < end {
+                strings.push(quote(&String::from_utf8_lossy(reader.character_string()?)));
+            }
+            strings.join(" ")
+        }
+        "HINFO" => format!(
+            "{} {}",
+            quote(&String::from_utf8_lossy(reader.character_string()?)),
+            quote(&String::from_utf8_lossy(reader.character_string()?))
+        ),
+        "RP" => format!("{} {}", reader.name()?, reader.name()?),
+        "NAPTR" => format!(
+            "{} {} {} {} {} {}",
+            reader.u16()?,
+            reader.u16()?,
+            quote(&String::from_utf8_lossy(reader.character_string()?)),
+            quote(&String::from_utf8_lossy(reader.character_string()?)),
+            quote(&String::from_utf8_lossy(reader.character_string()?)),
+            reader.name()?
+        ),
+        "DS" | "CDS" => format!(
+            "{} {} {} {}",
+            reader.u16()?,
+            reader.u8()?,
+            reader.u8()?,
+            encode_hex(reader.remaining(end)?)
+        ),
+        "DNSKEY" | "CDNSKEY" => format!(
+            "{} {} {} {}",
+            reader.u16()?,
+            reader.u8()?,
+            reader.u8()?,
+            STANDARD.encode(reader.remaining(end)?)
+        ),
+        "SSHFP" => format!(
+            "{} {} {}",
+            reader.u8()?,
+            reader.u8()?,
+            encode_hex(reader.remaining(end)?)
+        ),
+        "TLSA" => format!(
+            "{} {} {} {}",
+            reader.u8()?,
+            reader.u8()?,
+            reader.u8()?,
+            encode_hex(reader.remaining(end)?)
+        ),
+        "OPENPGPKEY" => STANDARD.encode(reader.remaining(end)?),
+        "URI" => format!(
+            "{} {} {}",
+            reader.u16()?,
+            reader.u16()?,
+            quote(&String::from_utf8_lossy(reader.remaining(end)?))
+        ),
+        "CAA" => {
+            let flags = reader.u8()?;
+            let tag = String::from_utf8_lossy(reader.character_string()?).into_owned();
+            let value = reader.remaining(end)?;
+            format!(
+                "{} {} {}",
+                flags,
+                tag,
+                quote(&String::from_utf8_lossy(value))
+            )
+        }
+        _ => return None,
+    };
+
+    // Data not matching the length of the record is considered malformed.
+    (reader.position == end).then_some(value)
+}
+
+/// Reads values from a message in network byte order.
+struct Reader<'a> {
+    message: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(message: &'a [u8]) -> Self {
+        Self {
+            message,
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .message
+            .get(self.position..self.position.checked_add(count)?)?;
+        self.position += count;
+        Some(bytes)
+    }
+
+    /// Reads all bytes up to the given end offset.
+    fn remaining(&mut self, end: usize) -> Option<&'a [u8]> {
+        self.bytes(end.checked_sub(self.position)?)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    #[cfg(feature = "axfr")]
+    fn u48(&mut self) -> Option<u64> {
+        let bytes = self.bytes(6)?;
+        Some(
+            bytes
+                .iter()
+                .fold(0u64, |value, &byte| (value << 8) | byte as u64),
+        )
+    }
+
+    fn character_string(&mut self) -> Option<&'a [u8]> {
+        let length = self.u8()? as usize;
+        self.bytes(length)
+    }
+
+    /// Reads a possibly compressed domain name, returning it as an absolute name.
+    fn name(&mut self) -> Option<DomainName> {
+        let mut labels = Vec::new();
+        let mut position = self.position;
+        let mut end = None;
+        // Every pointer has to point backwards, which rules out loops.
+        let mut limit = position;
+
+        loop {
+            let length = *self.message.get(position)? as usize;
+            match length {
+                0 => {
+                    end.get_or_insert(position + 1);
+                    break;
+                }
+                0xc0.. => {
+                    let pointer =
+                        ((length & 0x3f) << 8) | *self.message.get(position + 1)? as usize;
+                    if pointer >= limit {
+                        return None;
+                    }
+                    end.get_or_insert(position + 2);
+                    limit = pointer;
+                    position = pointer;
+                }
+                0x40.. => return None,
+                _ => {
+                    let label = self.message.get(position + 1..position + 1 + length)?;
+                    labels.push(String::from_utf8_lossy(label).into_owned());
+                    position += 1 + length;
+                }
+            }
+        }
+
+        self.position = end?;
+        Some(DomainName::from_raw(&format!("{}.", labels.join("."))))
+    }
+
+    fn record(&mut self) -> Option<ResourceRecord> {
+        let offset = self.position;
+        let name = self.name()?;
+        let typ = self.u16()?;
+        let class = self.u16()?;
+        let ttl = self.u32()?;
+        let length = self.u16()? as usize;
+        let start = self.position;
+        self.bytes(length)?;
+
+        Some(ResourceRecord {
+            name,
+            typ,
+            class,
+            ttl,
+            offset,
+            rdata: start..start + length,
+        })
+    }
+}
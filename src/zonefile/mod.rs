@@ -0,0 +1,381 @@
+//! Provider implementation using a local directory of zone files as its backend, e.g. for air-gapped environments.
+//!
+//! Every file named `<domain>.zone` in the directory is a zone in the master file format (see RFC 1035, section 5),
+//! identified by its domain, e.g. `example.com`.
+//! Records are represented with [synthetic IDs](crate::rrset) as zone files do not assign IDs.
+//!
+//! Changes rewrite the whole zone file atomically, bumping the serial of its SOA record.
+//! Comments, directives other than `$TTL` and the original formatting are not preserved.
+//! Changes made through a provider are serialized; enable file locking with [`ZoneFileProvider::with_file_locking`]
+//! if other processes modify the files concurrently.
+//!
+//! The file system is accessed using blocking I/O, see [`ZoneFileProvider`].
+
+use std::{io, path::Path, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{
+    rrset::{parse_record_id, record_id},
+    CreateRecord, CreateRecordError, CreateZone, CreateZoneError, DeleteRecord, DeleteRecordError,
+    DeleteZone, DeleteZoneError, DomainName, Provider, Record, RecordData, RetrieveRecordError,
+    RetrieveZoneError, Zone,
+};
+
+mod parser;
+mod store;
+
+/// The SOA timers (refresh, retry, expire and minimum) used for newly created zones.
+const SOA_TIMERS: &str = "7200 3600 1209600 3600";
+
+/// Represents an error that occured when accessing zone files.
+#[derive(Debug, Error)]
+pub enum ZoneFileError {
+    /// Indicates that reading or writing a file failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Indicates that a zone file could not be parsed.
+    #[error("invalid zone file, line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    /// Indicates that a zone file for the domain already exists.
+    #[error("the zone already exists")]
+    ZoneExists,
+
+    /// Indicates that the SOA record of a zone was attempted to be deleted.
+    #[error("the SOA record of a zone cannot be deleted")]
+    SoaDeletion,
+}
+
+impl ZoneFileError {
+    fn is_not_found(&self) -> bool {
+        matches!(self, ZoneFileError::Io(err) if err.kind() == io::ErrorKind::NotFound)
+    }
+
+    fn is_permission_denied(&self) -> bool {
+        matches!(self, ZoneFileError::Io(err) if err.kind() == io::ErrorKind::PermissionDenied)
+    }
+}
+
+/// Manages the zone files in a local directory.
+///
+/// # Blocking
+///
+/// The futures returned by the provider and its zones read and write files using blocking I/O,
+/// and changes wait for concurrent changes (and for file locks, if enabled) by blocking the thread.
+/// On an async runtime, they block the executor thread while they run. Avoid polling them on threads shared with latency-sensitive tasks,
+/// e.g. by running them on a separate runtime or within `tokio::task::spawn_blocking` using `Handle::block_on`.
+#[derive(Debug, Clone)]
+pub struct ZoneFileProvider {
+    store: Arc<store::Store>,
+}
+
+impl ZoneFileProvider {
+    /// Creates a provider managing the zone files in the given directory.
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            store: Arc::new(store::Store::new(directory.as_ref(), false)),
+        }
+    }
+
+    /// Creates a provider which additionally holds an exclusive lock on `<domain>.zone.lock` while changing a zone file.
+    pub fn with_file_locking(directory: impl AsRef<Path>) -> Self {
+        Self {
+            store: Arc::new(store::Store::new(directory.as_ref(), true)),
+        }
+    }
+}
+
+/// Returns the validated domain of a zone ID, which may optionally end with a dot.
+///
+/// IDs containing path separators (e.g. RFC 2317 reverse zones like `64/26.2.0.192.in-addr.arpa`) are rejected,
+/// as the ID is used as the name of the zone file.
+fn parse_zone_id(zone_id: &str) -> Option<DomainName> {
+    if zone_id.contains(['/', '\\']) {
+        return None;
+    }
+    let domain = DomainName::new(zone_id.trim_end_matches('.')).ok()?;
+    (!domain.is_apex()).then(|| domain.to_absolute())
+}
+
+impl Provider for ZoneFileProvider {
+    type Zone = ZoneFileZone;
+    type CustomRetrieveError = ZoneFileError;
+
+    async fn get_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<Self::Zone, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let domain = parse_zone_id(zone_id).ok_or(RetrieveZoneError::NotFound)?;
+        let zone = ZoneFileZone::new(self.store.clone(), domain);
+
+        self.store
+            .check_zone(&zone.id)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => RetrieveZoneError::NotFound,
+                io::ErrorKind::PermissionDenied => RetrieveZoneError::Unauthorized,
+                _ => RetrieveZoneError::Custom(err.into()),
+            })?;
+
+        Ok(zone)
+    }
+
+    async fn list_zones(
+        &self,
+    ) -> Result<Vec<Self::Zone>, RetrieveZoneError<Self::CustomRetrieveError>> {
+        let zones = self.store.list_zones().map_err(|err| match err.kind() {
+            io::ErrorKind::PermissionDenied => RetrieveZoneError::Unauthorized,
+            _ => RetrieveZoneError::Custom(err.into()),
+        })?;
+
+        Ok(zones
+            .iter()
+            .filter_map(|zone_id| parse_zone_id(zone_id))
+            .map(|domain| ZoneFileZone::new(self.store.clone(), domain))
+            .collect())
+    }
+}
+
+impl CreateZone for ZoneFileProvider {
+    type CustomCreateError = ZoneFileError;
+
+    /// Creates a zone file containing only a SOA record.
+    ///
+    /// The SOA record names `ns1.<domain>` as the primary name server and `hostmaster@<domain>` as the responsible mailbox.
+    async fn create_zone(
+        &self,
+        domain: &DomainName,
+    ) -> Result<Self::Zone, CreateZoneError<Self::CustomCreateError>> {
+        let domain = parse_zone_id(domain.as_ascii()).ok_or(CreateZoneError::InvalidDomainName)?;
+        let zone = ZoneFileZone::new(self.store.clone(), domain);
+
+        let soa = RecordData::Other {
+            typ: "SOA".to_owned(),
+            value: format!(
+                "ns1.{} hostmaster.{} 1 {}",
+                zone.domain, zone.domain, SOA_TIMERS
+            ),
+        };
+        let contents = parser::ZoneContents {
+            default_ttl: Some(parser::DEFAULT_TTL),
+            entries: vec![parser::Entry {
+                name: zone.domain.clone(),
+                ttl: parser::DEFAULT_TTL,
+                data: soa,
+            }],
+        };
+
+        self.store
+            .create_zone(&zone.id, &zone.domain, &contents)
+            .map_err(|err| match err {
+                _ if err.is_permission_denied() => CreateZoneError::Unauthorized,
+                _ => CreateZoneError::Custom(err),
+            })?;
+
+        Ok(zone)
+    }
+}
+
+impl DeleteZone for ZoneFileProvider {
+    type CustomDeleteError = ZoneFileError;
+
+    async fn delete_zone(
+        &self,
+        zone_id: &str,
+    ) -> Result<(), DeleteZoneError<Self::CustomDeleteError>> {
+        let domain = parse_zone_id(zone_id).ok_or(DeleteZoneError::NotFound)?;
+
+        self.store
+            .delete_zone(domain.as_ascii())
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => DeleteZoneError::NotFound,
+                io::ErrorKind::PermissionDenied => DeleteZoneError::Unauthorized,
+                _ => DeleteZoneError::Custom(err.into()),
+            })
+    }
+}
+
+/// Parses the records of a zone file for the given domain, e.g. to import them into another provider.
+///
+/// The records use synthetic IDs and hosts relative to `domain`, like the records of a [`ZoneFileZone`].
+pub fn parse_records(content: &str, domain: &DomainName) -> Result<Vec<Record>, ZoneFileError> {
+    let contents = parser::parse(content, domain)?;
+    Ok(contents
+        .entries
+        .into_iter()
+        .map(|entry| to_record(entry, domain))
+        .collect())
+}
+
+/// Formats records of the given domain as a zone file, e.g. to export them from another provider.
+///
+/// Relative names within the record data are made fully qualified using `domain`.
+pub fn format_records(records: &[Record], domain: &DomainName) -> String {
+    let contents = parser::ZoneContents {
+        default_ttl: None,
+        entries: records
+            .iter()
+            .map(|record| parser::Entry {
+                name: record.host.to_fqdn(domain),
+                ttl: record.ttl,
+                data: parser::qualify(record.data.clone(), domain),
+            })
+            .collect(),
+    };
+    parser::format(&contents, domain)
+}
+
+fn to_record(entry: parser::Entry, domain: &DomainName) -> Record {
+    Record {
+        id: record_id(&entry.name, &entry.data),
+        host: entry
+            .name
+            .relative_to(domain)
+            .unwrap_or_else(|| entry.name.clone()),
+        data: entry.data,
+        ttl: entry.ttl,
+    }
+}
+
+/// Represents a zone backed by a zone file.
+///
+/// The zone ID is the domain of the zone, e.g. `example.com`, which is also the name of the file without its extension.
+#[derive(Debug, Clone)]
+pub struct ZoneFileZone {
+    store: Arc<store::Store>,
+    id: String,
+    domain: DomainName,
+}
+
+impl ZoneFileZone {
+    fn new(store: Arc<store::Store>, domain: DomainName) -> Self {
+        Self {
+            store,
+            id: domain.as_ascii().to_owned(),
+            domain,
+        }
+    }
+
+    fn to_record(&self, entry: parser::Entry) -> Record {
+        to_record(entry, &self.domain)
+    }
+}
+
+impl Zone for ZoneFileZone {
+    type CustomRetrieveError = ZoneFileError;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn domain(&self) -> &DomainName {
+        &self.domain
+    }
+
+    async fn list_records(
+        &self,
+    ) -> Result<Vec<Record>, RetrieveRecordError<Self::CustomRetrieveError>> {
+        let contents = self
+            .store
+            .read_zone(&self.id, &self.domain)
+            .map_err(|err| match err {
+                _ if err.is_not_found() => RetrieveRecordError::NotFound,
+                _ if err.is_permission_denied() => RetrieveRecordError::Unauthorized,
+                _ => RetrieveRecordError::Custom(err),
+            })?;
+
+        Ok(contents
+            .entries
+            .into_iter()
+            .map(|entry| self.to_record(entry))
+            .collect())
+    }
+
+    async fn get_record(
+        &self,
+        record_id: &str,
+    ) -> Result<Record, RetrieveRecordError<Self::CustomRetrieveError>> {
+        self.list_records()
+            .await?
+            .into_iter()
+            .find(|record| record.id == record_id)
+            .ok_or(RetrieveRecordError::NotFound)
+    }
+}
+
+impl CreateRecord for ZoneFileZone {
+    type CustomCreateError = ZoneFileError;
+
+    /// Adds a record to the zone file.
+    ///
+    /// If an identical record already exists, only its TTL is updated.
+    /// Relative names within the record data are made fully qualified using the zone's domain.
+    async fn create_record(
+        &self,
+        host: &DomainName,
+        data: &RecordData,
+        ttl: u64,
+    ) -> Result<Record, CreateRecordError<Self::CustomCreateError>> {
+        if data.get_type() == "SOA" {
+            return Err(CreateRecordError::UnsupportedType);
+        }
+
+        let host = self
+            .relative_host(host)
+            .ok_or(CreateRecordError::InvalidRecord)?;
+        let entry = parser::Entry {
+            name: self.fqdn(&host),
+            ttl,
+            data: parser::qualify(data.clone(), &self.domain),
+        };
+
+        self.store
+            .update_zone(&self.id, &self.domain, |contents| {
+                match contents
+                    .entries
+                    .iter_mut()
+                    .find(|existing| existing.name == entry.name && existing.data == entry.data)
+                {
+                    Some(existing) => existing.ttl = ttl,
+                    None => contents.entries.push(entry.clone()),
+                }
+                Some(())
+            })
+            .map_err(|err| match err {
+                _ if err.is_permission_denied() => CreateRecordError::Unauthorized,
+                _ => CreateRecordError::Custom(err),
+            })?;
+
+        Ok(self.to_record(entry))
+    }
+}
+
+impl DeleteRecord for ZoneFileZone {
+    type CustomDeleteError = ZoneFileError;
+
+    async fn delete_record(
+        &self,
+        record_id: &str,
+    ) -> Result<(), DeleteRecordError<Self::CustomDeleteError>> {
+        let (_, data) = parse_record_id(record_id).ok_or(DeleteRecordError::NotFound)?;
+        if data.get_type() == "SOA" {
+            return Err(DeleteRecordError::Custom(ZoneFileError::SoaDeletion));
+        }
+
+        self.store
+            .update_zone(&self.id, &self.domain, |contents| {
+                let count = contents.entries.len();
+                contents
+                    .entries
+                    .retain(|entry| crate::rrset::record_id(&entry.name, &entry.data) != record_id);
+                (contents.entries.len() < count).then_some(())
+            })
+            .map_err(|err| match err {
+                _ if err.is_not_found() => DeleteRecordError::NotFound,
+                _ if err.is_permission_denied() => DeleteRecordError::Unauthorized,
+                _ => DeleteRecordError::Custom(err),
+            })?
+            .ok_or(DeleteRecordError::NotFound)
+    }
+}
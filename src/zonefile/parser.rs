@@ -0,0 +1,535 @@
+//! Parsing and formatting of master files as described in RFC 1035, section 5.
+//!
+//! Only the subset needed to represent a single zone is supported: the `$ORIGIN` and `$TTL` directives,
+//! comments, parentheses spanning multiple lines, omitted owners, TTLs and classes as well as the class `IN`.
+
+use std::fmt::Write;
+
+use super::ZoneFileError;
+use crate::{presentation, DomainName, RecordData};
+
+/// The TTL used for records without an explicit TTL if the file neither contains a `$TTL` directive nor a previous TTL.
+pub(super) const DEFAULT_TTL: u64 = 3600;
+
+/// Represents a single resource record of a zone file with its owner being fully qualified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Entry {
+    pub name: DomainName,
+    pub ttl: u64,
+    pub data: RecordData,
+}
+
+/// Represents the contents of a zone file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(super) struct ZoneContents {
+    /// The value of the `$TTL` directive, if any.
+    pub default_ttl: Option<u64>,
+    pub entries: Vec<Entry>,
+}
+
+impl ZoneContents {
+    /// Increments the serial of the zone's SOA record, if there is one, using serial number arithmetic (see RFC 1982).
+    pub fn bump_serial(&mut self) {
+        for entry in &mut self.entries {
+            let RecordData::Other { typ, value } = &mut entry.data else {
+                continue;
+            };
+            if typ != "SOA" {
+                continue;
+            }
+
+            let mut fields = value
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<String>>();
+            if let Some(serial) = fields.get_mut(2) {
+                if let Ok(number) = serial.parse::<u32>() {
+                    *serial = number.wrapping_add(1).to_string();
+                    *value = fields.join(" ");
+                }
+            }
+        }
+    }
+}
+
+/// Represents a logical line, i.e. a line with comments removed and parenthesized continuations joined.
+struct Line {
+    number: usize,
+    /// Whether the line starts with whitespace, meaning the owner of the previous record is used.
+    blank_owner: bool,
+    text: String,
+}
+
+/// Parses the contents of a zone file, resolving relative names against `origin`.
+pub(super) fn parse(content: &str, origin: &DomainName) -> Result<ZoneContents, ZoneFileError> {
+    let mut contents = ZoneContents::default();
+    let mut origin = origin.to_absolute();
+    let mut last_name = None;
+    let mut last_ttl = None;
+
+    for line in logical_lines(content)? {
+        let fields = split_fields(&line.text);
+        let error = |message: &str| ZoneFileError::Parse {
+            line: line.number,
+            message: message.to_owned(),
+        };
+
+        let Some(&(_, first)) = fields.first() else {
+            continue;
+        };
+        if first.starts_with('$') {
+            let argument = fields.get(1).map(|&(_, field)| field);
+            match (first.to_ascii_uppercase().as_str(), argument) {
+                ("$ORIGIN", Some(name)) => {
+                    origin = parse_name(name, &origin).ok_or_else(|| error("invalid origin"))?;
+                }
+                ("$TTL", Some(ttl)) => {
+                    contents.default_ttl =
+                        Some(parse_ttl(ttl).ok_or_else(|| error("invalid TTL"))?);
+                }
+                ("$ORIGIN" | "$TTL", None) => return Err(error("missing directive argument")),
+                _ => return Err(error("unsupported directive")),
+            }
+            continue;
+        }
+
+        let mut fields = fields.into_iter().peekable();
+        let name = if line.blank_owner {
+            last_name
+                .clone()
+                .ok_or_else(|| error("missing owner name"))?
+        } else {
+            let (_, owner) = fields.next().ok_or_else(|| error("missing owner name"))?;
+            parse_name(owner, &origin).ok_or_else(|| error("invalid owner name"))?
+        };
+
+        let mut ttl = None;
+        let mut class = None;
+        while let Some(&(_, field)) = fields.peek() {
+            if ttl.is_none() {
+                if let Some(value) = parse_ttl(field) {
+                    ttl = Some(value);
+                    fields.next();
+                    continue;
+                }
+            }
+            if class.is_none()
+                && matches!(
+                    field.to_ascii_uppercase().as_str(),
+                    "IN" | "CH" | "HS" | "CS"
+                )
+            {
+                class = Some(field.to_ascii_uppercase());
+                fields.next();
+                continue;
+            }
+            break;
+        }
+        if class.is_some_and(|class| class != "IN") {
+            return Err(error("only the class IN is supported"));
+        }
+
+        let (_, typ) = fields.next().ok_or_else(|| error("missing record type"))?;
+        let typ = typ.to_ascii_uppercase();
+        let (offset, _) = fields.next().ok_or_else(|| error("missing record data"))?;
+
+        let ttl = ttl
+            .or(contents.default_ttl)
+            .or(last_ttl)
+            .unwrap_or(DEFAULT_TTL);
+        let data = qualify(RecordData::from_raw(&typ, &line.text[offset..]), &origin);
+
+        last_name = Some(name.clone());
+        last_ttl = Some(ttl);
+        contents.entries.push(Entry { name, ttl, data });
+    }
+
+    Ok(contents)
+}
+
+/// Formats the contents of a zone file, writing owner names relative to `origin`.
+pub(super) fn format(contents: &ZoneContents, origin: &DomainName) -> String {
+    let origin = origin.to_absolute();
+    let mut output = format!("$ORIGIN {}\n", origin);
+    if let Some(ttl) = contents.default_ttl {
+        let _ = writeln!(output, "$TTL {}", ttl);
+    }
+
+    for entry in &contents.entries {
+        let _ = writeln!(
+            output,
+            "{}\t{}\tIN\t{}\t{}",
+            entry
+                .name
+                .relative_to(&origin)
+                .unwrap_or_else(|| entry.name.clone()),
+            entry.ttl,
+            entry.data.get_type(),
+            entry.data.get_value()
+        );
+    }
+
+    output
+}
+
+/// Makes the domain names contained in record data fully qualified by appending `origin` to relative ones.
+pub(super) fn qualify(data: RecordData, origin: &DomainName) -> RecordData {
+    match data {
+        RecordData::CNAME(target) => RecordData::CNAME(target.to_fqdn(origin)),
+        RecordData::NS(target) => RecordData::NS(target.to_fqdn(origin)),
+        RecordData::PTR(target) => RecordData::PTR(target.to_fqdn(origin)),
+        RecordData::MX {
+            priority,
+            mail_server,
+        } => RecordData::MX {
+            priority,
+            mail_server: mail_server.to_fqdn(origin),
+        },
+        RecordData::SRV {
+            priority,
+            weight,
+            port,
+            target,
+        } => RecordData::SRV {
+            priority,
+            weight,
+            port,
+            target: target.to_fqdn(origin),
+        },
+        RecordData::Other { typ, value } if typ == "SOA" => {
+            let mut fields = value
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<String>>();
+            for field in fields.iter_mut().take(2) {
+                *field = DomainName::from_raw(field).to_fqdn(origin).to_string();
+            }
+            RecordData::Other {
+                typ,
+                value: fields.join(" "),
+            }
+        }
+        data => data,
+    }
+}
+
+/// Splits the content into logical lines, removing comments and joining lines continued using parentheses.
+///
+/// Whitespace outside of quoted strings is collapsed into single spaces.
+fn logical_lines(content: &str) -> Result<Vec<Line>, ZoneFileError> {
+    let mut lines = Vec::new();
+    let mut current: Option<Line> = None;
+    let mut depth = 0usize;
+
+    for (index, raw) in content.lines().enumerate() {
+        let number = index + 1;
+        let line = current.get_or_insert_with(|| Line {
+            number,
+            blank_owner: raw.starts_with([' ', '\t']),
+            text: String::new(),
+        });
+
+        let mut quoted = false;
+        let mut escaped = false;
+        for c in raw.chars() {
+            if escaped {
+                line.text.push(c);
+                escaped = false;
+                continue;
+            }
+
+            match c {
+                '\\' => {
+                    escaped = true;
+                    line.text.push(c);
+                }
+                '"' => {
+                    quoted = !quoted;
+                    line.text.push(c);
+                }
+                _ if quoted => line.text.push(c),
+                ';' => break,
+                '(' => {
+                    depth += 1;
+                    line.text.push(' ');
+                }
+                ')' => {
+                    depth = depth.checked_sub(1).ok_or(ZoneFileError::Parse {
+                        line: number,
+                        message: "unbalanced parentheses".to_owned(),
+                    })?;
+                    line.text.push(' ');
+                }
+                c if c.is_whitespace() => {
+                    if !line.text.ends_with(' ') {
+                        line.text.push(' ');
+                    }
+                }
+                c => line.text.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(ZoneFileError::Parse {
+                line: number,
+                message: "unterminated quoted string".to_owned(),
+            });
+        }
+
+        if depth == 0 {
+            if let Some(mut line) = current.take() {
+                line.text = line.text.trim().to_owned();
+                if !line.text.is_empty() {
+                    lines.push(line);
+                }
+            }
+        } else {
+            line.text.push(' ');
+        }
+    }
+
+    if let Some(line) = current {
+        return Err(ZoneFileError::Parse {
+            line: line.number,
+            message: "unbalanced parentheses".to_owned(),
+        });
+    }
+
+    Ok(lines)
+}
+
+/// Splits a logical line into its fields together with their byte offsets, keeping quoted strings intact.
+fn split_fields(text: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (offset, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match c {
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if let Some(start) = start.take() {
+                    fields.push((start, &text[start..offset]));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(offset);
+    }
+    if let Some(start) = start {
+        fields.push((start, &text[start..]));
+    }
+
+    fields
+}
+
+/// Parses an owner name or `$ORIGIN` argument, resolving relative names and `@` against `origin`.
+fn parse_name(name: &str, origin: &DomainName) -> Option<DomainName> {
+    let name = presentation::tokenize(name)?.into_iter().next()?;
+    DomainName::new(&name).ok().map(|name| name.to_fqdn(origin))
+}
+
+/// Parses a TTL given either in seconds or using the BIND unit syntax, e.g. `1h30m`.
+fn parse_ttl(value: &str) -> Option<u64> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+
+    let mut total = 0u64;
+    let mut number = None;
+    for c in value.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            number = Some(
+                number
+                    .unwrap_or(0u64)
+                    .checked_mul(10)?
+                    .checked_add(digit as u64)?,
+            );
+            continue;
+        }
+
+        let factor = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return None,
+        };
+        total = total.checked_add(number.take()?.checked_mul(factor)?)?;
+    }
+
+    if number.is_some() || value.is_empty() {
+        return None;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONE: &str = r#"$ORIGIN example.com.
+$TTL 1h
+@   IN  SOA ns1 hostmaster (
+            2024010101 ; serial
+            7200 3600 1209600 300 )
+    IN  NS  ns1
+ns1 300 IN A 192.0.2.1
+www     CNAME @
+txt     TXT "v=spf1 -all; not a comment" "second chunk" ; a comment
+$ORIGIN sub.example.com.
+mail    1d  MX  10 mx.example.net.
+"#;
+
+    fn origin() -> DomainName {
+        DomainName::new("example.com").unwrap()
+    }
+
+    #[test]
+    fn directives_continuations_and_omitted_fields() {
+        let contents = parse(ZONE, &origin()).unwrap();
+        assert_eq!(contents.default_ttl, Some(3600));
+
+        let entries = contents
+            .entries
+            .iter()
+            .map(|entry| (entry.name.to_string(), entry.ttl, entry.data.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "example.com.".to_owned(),
+                    3600,
+                    RecordData::from_raw(
+                        "SOA",
+                        "ns1.example.com. hostmaster.example.com. 2024010101 7200 3600 1209600 300"
+                    )
+                ),
+                (
+                    "example.com.".to_owned(),
+                    3600,
+                    RecordData::NS(DomainName::from_raw("ns1.example.com."))
+                ),
+                (
+                    "ns1.example.com.".to_owned(),
+                    300,
+                    RecordData::A("192.0.2.1".parse().unwrap())
+                ),
+                (
+                    "www.example.com.".to_owned(),
+                    3600,
+                    RecordData::CNAME(DomainName::from_raw("example.com."))
+                ),
+                (
+                    "txt.example.com.".to_owned(),
+                    3600,
+                    RecordData::TXT(vec![
+                        "v=spf1 -all; not a comment".to_owned(),
+                        "second chunk".to_owned()
+                    ])
+                ),
+                (
+                    "mail.sub.example.com.".to_owned(),
+                    86400,
+                    RecordData::MX {
+                        priority: 10,
+                        mail_server: DomainName::from_raw("mx.example.net."),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn formatted_contents_parse_to_the_same_entries() {
+        let contents = parse(ZONE, &origin()).unwrap();
+        let formatted = format(&contents, &origin());
+        assert!(formatted.starts_with("$ORIGIN example.com.\n$TTL 3600\n"));
+        assert!(formatted.contains("ns1\t300\tIN\tA\t192.0.2.1\n"));
+        assert!(formatted.contains("mail.sub\t86400\tIN\tMX\t10 mx.example.net.\n"));
+
+        assert_eq!(parse(&formatted, &origin()).unwrap(), contents);
+    }
+
+    #[test]
+    fn ttls() {
+        assert_eq!(parse_ttl("300"), Some(300));
+        assert_eq!(parse_ttl("1h30m"), Some(5400));
+        assert_eq!(parse_ttl("1W2D"), Some(777600));
+        assert_eq!(parse_ttl("h"), None);
+        assert_eq!(parse_ttl("1h30"), None);
+        assert_eq!(parse_ttl("IN"), None);
+        assert_eq!(parse_ttl(""), None);
+    }
+
+    #[test]
+    fn serials_are_bumped_with_wraparound() {
+        let mut contents = parse(ZONE, &origin()).unwrap();
+        contents.bump_serial();
+        assert_eq!(
+            contents.entries[0].data.get_value(),
+            "ns1.example.com. hostmaster.example.com. 2024010102 7200 3600 1209600 300"
+        );
+
+        let mut contents = parse(
+            "@ SOA ns1 hostmaster 4294967295 7200 3600 1209600 300",
+            &origin(),
+        )
+        .unwrap();
+        contents.bump_serial();
+        assert_eq!(
+            contents.entries[0].data.get_value(),
+            "ns1.example.com. hostmaster.example.com. 0 7200 3600 1209600 300"
+        );
+
+        // Zones without an SOA record are left untouched.
+        let mut contents = parse("www A 192.0.2.1", &origin()).unwrap();
+        let unchanged = contents.clone();
+        contents.bump_serial();
+        assert_eq!(contents, unchanged);
+    }
+
+    #[test]
+    fn errors_carry_line_numbers() {
+        for (content, line, message) in [
+            (
+                "www A 192.0.2.1\n@ SOA ns1 hostmaster (\n1 2 3 4 5",
+                2,
+                "unbalanced parentheses",
+            ),
+            ("www A 192.0.2.1 )", 1, "unbalanced parentheses"),
+            ("\ntxt TXT \"unterminated", 2, "unterminated quoted string"),
+            ("www CH A 192.0.2.1", 1, "only the class IN is supported"),
+            ("$INCLUDE other.zone", 1, "unsupported directive"),
+            ("$TTL", 1, "missing directive argument"),
+            ("  A 192.0.2.1", 1, "missing owner name"),
+            ("www 300 IN", 1, "missing record type"),
+            ("www A", 1, "missing record data"),
+        ] {
+            match parse(content, &origin()) {
+                Err(ZoneFileError::Parse {
+                    line: actual_line,
+                    message: actual_message,
+                }) => {
+                    assert_eq!(
+                        (actual_line, actual_message.as_str()),
+                        (line, message),
+                        "{}",
+                        content
+                    )
+                }
+                result => panic!("{:?} for {}", result, content),
+            }
+        }
+    }
+}
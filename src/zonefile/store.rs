@@ -0,0 +1,186 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use super::{
+    parser::{self, ZoneContents},
+    ZoneFileError,
+};
+use crate::{fs::write_atomic, DomainName};
+
+/// The extension of files in the directory which are treated as zones.
+pub const ZONE_FILE_EXTENSION: &str = "zone";
+
+#[derive(Debug)]
+pub struct Store {
+    directory: PathBuf,
+    file_locking: bool,
+    /// Serializes changes made through this store, which file locks do not do within a single process.
+    write_lock: Mutex<()>,
+}
+
+impl Store {
+    pub fn new(directory: &Path, file_locking: bool) -> Self {
+        Self {
+            directory: directory.to_owned(),
+            file_locking,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn zone_path(&self, zone: &str) -> io::Result<PathBuf> {
+        self.file_path(zone, ZONE_FILE_EXTENSION)
+    }
+
+    fn lock_path(&self, zone: &str) -> io::Result<PathBuf> {
+        self.file_path(zone, &format!("{}.lock", ZONE_FILE_EXTENSION))
+    }
+
+    /// Returns the path of the zone's file with the given extension, failing for zones which are not a plain file name
+    /// and could therefore resolve to a path outside of the directory.
+    fn file_path(&self, zone: &str, extension: &str) -> io::Result<PathBuf> {
+        if zone.is_empty() || zone.starts_with('.') || zone.contains(['/', '\\']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the zone {} is not a valid file name", zone),
+            ));
+        }
+        Ok(self.directory.join(format!("{}.{}", zone, extension)))
+    }
+
+    /// Lists the names of all zone files in the directory without their extension.
+    pub fn list_zones(&self) -> io::Result<Vec<String>> {
+        let mut zones = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if !path.is_file()
+                || path.extension().and_then(|ext| ext.to_str()) != Some(ZONE_FILE_EXTENSION)
+            {
+                continue;
+            }
+            if let Some(zone) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if !zone.starts_with('.') {
+                    zones.push(zone.to_owned());
+                }
+            }
+        }
+
+        zones.sort();
+        Ok(zones)
+    }
+
+    /// Checks that the zone file exists, returning an error of the kind [`io::ErrorKind::NotFound`] otherwise.
+    pub fn check_zone(&self, zone: &str) -> io::Result<()> {
+        let metadata = fs::metadata(self.zone_path(zone)?)?;
+        if !metadata.is_file() {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        Ok(())
+    }
+
+    pub fn read_zone(
+        &self,
+        zone: &str,
+        origin: &DomainName,
+    ) -> Result<ZoneContents, ZoneFileError> {
+        let content = fs::read_to_string(self.zone_path(zone)?)?;
+        parser::parse(&content, origin)
+    }
+
+    /// Creates a new zone file, failing if it already exists.
+    pub fn create_zone(
+        &self,
+        zone: &str,
+        origin: &DomainName,
+        contents: &ZoneContents,
+    ) -> Result<(), ZoneFileError> {
+        let _guard = self.write_lock.lock().unwrap();
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.zone_path(zone)?)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::AlreadyExists => ZoneFileError::ZoneExists,
+                _ => ZoneFileError::Io(err),
+            })?;
+        file.write_all(parser::format(contents, origin).as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn delete_zone(&self, zone: &str) -> io::Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        let _file_lock = self.lock_file(zone)?;
+
+        fs::remove_file(self.zone_path(zone)?)?;
+        if self.file_locking {
+            let _ = fs::remove_file(self.lock_path(zone)?);
+        }
+        Ok(())
+    }
+
+    /// Applies a change to the contents of a zone file.
+    ///
+    /// The file is read, passed to `update` and, if a value is returned, written back with the SOA serial being bumped.
+    /// The zone file is replaced atomically (see [`write_atomic`]), so readers never see a partially written file.
+    pub fn update_zone<T>(
+        &self,
+        zone: &str,
+        origin: &DomainName,
+        update: impl FnOnce(&mut ZoneContents) -> Option<T>,
+    ) -> Result<Option<T>, ZoneFileError> {
+        let _guard = self.write_lock.lock().unwrap();
+        let _file_lock = self.lock_file(zone)?;
+
+        let mut contents = self.read_zone(zone, origin)?;
+        let Some(result) = update(&mut contents) else {
+            return Ok(None);
+        };
+        contents.bump_serial();
+
+        let path = self.zone_path(zone)?;
+        write_atomic(&path, parser::format(&contents, origin).as_bytes())?;
+
+        Ok(Some(result))
+    }
+
+    /// Acquires an exclusive lock on the zone's lock file if file locking is enabled, released when the file is dropped.
+    ///
+    /// A separate lock file is used as the zone file itself is replaced on every change.
+    fn lock_file(&self, zone: &str) -> io::Result<Option<File>> {
+        if !self.file_locking {
+            return Ok(None);
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.lock_path(zone)?)?;
+        file.lock()?;
+        Ok(Some(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_path_rejects_names_outside_of_directory() {
+        let store = Store::new(Path::new("/srv/zones"), false);
+
+        assert_eq!(
+            store.zone_path("example.com").unwrap(),
+            Path::new("/srv/zones/example.com.zone")
+        );
+        for zone in ["/tmp/evil", "a/b", "..", ".hidden", "a\\b", ""] {
+            let err = store.zone_path(zone).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", zone);
+        }
+    }
+}